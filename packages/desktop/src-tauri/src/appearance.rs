@@ -0,0 +1,165 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{window::Color, Manager, Theme};
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    settings,
+};
+
+/// Settings-store key: `"light"`, `"dark"` or `"system"` (the default).
+pub(crate) const THEME_PREFERENCE_KEY: &str = "themePreference";
+
+// Window backgrounds shown before the page paints, matched to the UI's base colors.
+const LIGHT_BACKGROUND: Color = Color(0xff, 0xff, 0xff, 0xff);
+const DARK_BACKGROUND: Color = Color(0x15, 0x15, 0x15, 0xff);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ThemePreference {
+    Light,
+    Dark,
+    System,
+}
+
+impl ThemePreference {
+    pub(crate) const ALL: [ThemePreference; 3] = [
+        ThemePreference::Light,
+        ThemePreference::Dark,
+        ThemePreference::System,
+    ];
+
+    pub(crate) fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "light" => ThemePreference::Light,
+            "dark" => ThemePreference::Dark,
+            _ => ThemePreference::System,
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ThemePreference::Light => "light",
+            ThemePreference::Dark => "dark",
+            ThemePreference::System => "system",
+        }
+    }
+
+    /// Explicit window theme; `None` lets windows follow the OS.
+    fn window_theme(self) -> Option<Theme> {
+        match self {
+            ThemePreference::Light => Some(Theme::Light),
+            ThemePreference::Dark => Some(Theme::Dark),
+            ThemePreference::System => None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OsThemeChangedEvent {
+    theme: &'static str,
+}
+
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+pub(crate) fn theme_preference<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> ThemePreference {
+    settings::get_setting(app, THEME_PREFERENCE_KEY)
+        .and_then(|value| value.as_str().map(ThemePreference::parse))
+        .unwrap_or(ThemePreference::System)
+}
+
+fn apply_background<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>, theme: Theme) {
+    let color = match theme {
+        Theme::Dark => DARK_BACKGROUND,
+        _ => LIGHT_BACKGROUND,
+    };
+    let _ = window.set_background_color(Some(color));
+}
+
+fn apply_preference<R: tauri::Runtime>(app: &tauri::AppHandle<R>, preference: ThemePreference) {
+    // Setting the native theme also switches the macOS traffic lights and the
+    // Windows title bar between their light and dark variants.
+    app.set_theme(preference.window_theme());
+    for window in app.webview_windows().values() {
+        let effective = preference
+            .window_theme()
+            .or_else(|| window.theme().ok())
+            .unwrap_or(Theme::Light);
+        apply_background(window, effective);
+    }
+}
+
+/// Settings side effect for `themePreference`.
+pub(crate) fn apply_theme_setting<R: tauri::Runtime>(app: &tauri::AppHandle<R>, value: &Value) {
+    let preference = value
+        .as_str()
+        .map(ThemePreference::parse)
+        .unwrap_or(ThemePreference::System);
+    apply_preference(app, preference);
+    crate::menu::sync_theme_menu(app, preference);
+}
+
+/// Applies the saved preference to a freshly created window.
+pub(crate) fn restore_theme<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
+    let preference = theme_preference(window.app_handle());
+    if let Some(theme) = preference.window_theme() {
+        let _ = window.set_theme(Some(theme));
+    }
+    if let Ok(theme) = window.theme() {
+        apply_background(window, theme);
+    }
+}
+
+/// Called from the Theme menu items.
+pub(crate) fn set_theme_preference<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    preference: ThemePreference,
+) {
+    if let Err(err) = settings::set_setting(
+        app,
+        THEME_PREFERENCE_KEY,
+        Value::String(preference.as_str().to_string()),
+        None,
+    ) {
+        log::warn!("[appearance] failed to save theme preference: {err}");
+    }
+}
+
+/// `WindowEvent::ThemeChanged` handler. Windows only report OS changes while they
+/// follow the system theme, so the event is forwarded only in that mode.
+pub(crate) fn handle_theme_changed<R: tauri::Runtime>(window: &tauri::Window<R>, theme: Theme) {
+    let app = window.app_handle();
+    if theme_preference(app) != ThemePreference::System {
+        return;
+    }
+    if let Some(webview_window) = app.get_webview_window(window.label()) {
+        apply_background(&webview_window, theme);
+    }
+    if window.label() == "main" {
+        log::info!("[appearance] OS theme changed to {}", theme_name(theme));
+        events::emit(
+            app,
+            DesktopEvent::OsThemeChanged(OsThemeChangedEvent {
+                theme: theme_name(theme),
+            }),
+        );
+    }
+}
+
+/// Current OS appearance as seen by the main window: `"light"` or `"dark"`.
+#[tauri::command]
+pub(crate) fn desktop_get_os_theme(app: tauri::AppHandle) -> Result<&'static str, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    window
+        .theme()
+        .map(theme_name)
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,618 @@
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    audit::{self, AuditCategory},
+    events::{self, DesktopEvent},
+    fs_ops,
+    path_policy::{PathError, PathErrorKind, PathPolicy},
+    progress::{set_progress, TaskProgress},
+    watcher::{self, FsChangeKind},
+};
+
+/// Minimum time between progress events for one archive.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+enum ArchiveOperation {
+    Extract,
+    Create,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveProgressEvent {
+    archive: String,
+    operation: ArchiveOperation,
+    /// The entry being read or written.
+    entry: Option<String>,
+    /// Bytes so far, of the uncompressed contents except when extracting a
+    /// `.tar.gz`, which is only measured by how much of the archive was read.
+    processed: u64,
+    total: Option<u64>,
+    /// Sent once at the end, whether the operation succeeded or not.
+    done: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveSummary {
+    /// The destination directory when extracting, the new archive when creating.
+    path: String,
+    entries: u64,
+    /// Uncompressed size of the files written or archived.
+    bytes: u64,
+}
+
+/// Progress of one operation, reported as `openchamber:archive-progress` and on the
+/// taskbar/dock icon.
+struct Progress<'a> {
+    app: &'a tauri::AppHandle,
+    archive: String,
+    operation: ArchiveOperation,
+    total: Option<u64>,
+    processed: Cell<u64>,
+    entry: RefCell<Option<String>>,
+    last_emit: Cell<Option<Instant>>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(
+        app: &'a tauri::AppHandle,
+        archive: &Path,
+        operation: ArchiveOperation,
+        total: Option<u64>,
+    ) -> Self {
+        set_progress(app, TaskProgress::from_bytes(0, total));
+        Self {
+            app,
+            archive: archive.to_string_lossy().to_string(),
+            operation,
+            total,
+            processed: Cell::new(0),
+            entry: RefCell::new(None),
+            last_emit: Cell::new(None),
+        }
+    }
+
+    fn emit(&self, done: bool) {
+        events::emit(
+            self.app,
+            DesktopEvent::ArchiveProgress(ArchiveProgressEvent {
+                archive: self.archive.clone(),
+                operation: self.operation,
+                entry: self.entry.borrow().clone(),
+                processed: self.processed.get(),
+                total: self.total,
+                done,
+            }),
+        );
+        self.last_emit.set(Some(Instant::now()));
+    }
+
+    fn entry(&self, name: &str) {
+        *self.entry.borrow_mut() = Some(name.to_string());
+    }
+
+    fn advance(&self, bytes: u64) {
+        self.processed.set(self.processed.get() + bytes);
+        if self
+            .last_emit
+            .get()
+            .is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL)
+        {
+            set_progress(
+                self.app,
+                TaskProgress::from_bytes(self.processed.get(), self.total),
+            );
+            self.emit(false);
+        }
+    }
+
+    fn finish(&self) {
+        set_progress(self.app, TaskProgress::None);
+        self.emit(true);
+    }
+}
+
+/// Counts the bytes passing through a reader into `Progress`.
+struct Counted<'p, 'a, R> {
+    inner: R,
+    progress: &'p Progress<'a>,
+}
+
+impl<R: Read> Read for Counted<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.advance(read as u64);
+        Ok(read)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn archive_error(err: io::Error, path: &Path) -> PathError {
+    let kind = match err.kind() {
+        io::ErrorKind::NotFound => PathErrorKind::NotFound,
+        io::ErrorKind::AlreadyExists => PathErrorKind::AlreadyExists,
+        io::ErrorKind::InvalidData => PathErrorKind::InvalidPath,
+        _ => PathErrorKind::Io,
+    };
+    PathError::new(kind, format!("{}: {err}", path.display()))
+}
+
+/// The format of an existing archive, by its magic bytes.
+fn detect_format(path: &Path) -> io::Result<ArchiveFormat> {
+    let mut magic = [0u8; 4];
+    let read = fs::File::open(path)?.read(&mut magic)?;
+    match &magic[..read] {
+        [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6] => Ok(ArchiveFormat::Zip),
+        [0x1f, 0x8b, ..] => Ok(ArchiveFormat::TarGz),
+        _ => Err(invalid("Not a zip or tar.gz archive".to_string())),
+    }
+}
+
+/// Creates `relative` below `dest` one directory at a time, refusing to go through
+/// a symlink already on disk that resolves outside `dest`. Nothing is created until
+/// every existing directory on the way has been checked.
+fn create_contained_dir(dest: &Path, relative: &Path, name: &Path) -> io::Result<()> {
+    let mut current = dest.to_path_buf();
+    for part in relative.components() {
+        current.push(part);
+        match fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                if !fs::canonicalize(&current)?.starts_with(dest) {
+                    return Err(invalid(format!(
+                        "Entry escapes the destination: {}",
+                        name.display()
+                    )));
+                }
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => fs::create_dir(&current)?,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Where an archive entry goes below `dest`. Entries with absolute paths or `..`
+/// are rejected ("zip slip"), and so is any parent directory that resolves outside
+/// `dest` through a symlink already on disk.
+fn entry_target(dest: &Path, name: &Path) -> io::Result<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(invalid(format!(
+                    "Entry escapes the destination: {}",
+                    name.display()
+                )))
+            }
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        return Ok(dest.to_path_buf());
+    }
+    if let Some(parent) = relative.parent() {
+        create_contained_dir(dest, parent, name)?;
+    }
+    Ok(dest.join(&relative))
+}
+
+/// Writes one file entry, replacing whatever was there without following it if it
+/// was a symlink.
+fn write_entry(target: &Path, reader: &mut impl Read, mode: Option<u32>) -> io::Result<u64> {
+    if target
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+    {
+        fs::remove_file(target)?;
+    }
+    let mut file = fs::File::create(target)?;
+    let written = io::copy(reader, &mut file)?;
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(mode & 0o777))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    Ok(written)
+}
+
+fn extract_zip(archive: &Path, dest: &Path, progress: &Progress) -> io::Result<(u64, u64)> {
+    let mut zip = ZipArchive::new(fs::File::open(archive)?).map_err(io::Error::other)?;
+    let (mut entries, mut bytes) = (0, 0);
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(io::Error::other)?;
+        let name = entry.name().to_string();
+        // Links could point anywhere; nothing the app extracts needs them.
+        if entry.is_symlink() {
+            log::warn!("[archive] skipped symlink {name}");
+            continue;
+        }
+        let target = entry_target(dest, Path::new(&name))?;
+        progress.entry(&name);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            let mode = entry.unix_mode();
+            let written = write_entry(
+                &target,
+                &mut Counted {
+                    inner: &mut entry,
+                    progress,
+                },
+                mode,
+            )?;
+            bytes += written;
+        }
+        entries += 1;
+    }
+    Ok((entries, bytes))
+}
+
+fn extract_tar_gz(archive: &Path, dest: &Path, progress: &Progress) -> io::Result<(u64, u64)> {
+    let reader = Counted {
+        inner: fs::File::open(archive)?,
+        progress,
+    };
+    let mut tar = tar::Archive::new(GzDecoder::new(reader));
+    let (mut entries, mut bytes) = (0, 0);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.into_owned();
+        let kind = entry.header().entry_type();
+        if !(kind.is_file() || kind.is_dir()) {
+            log::warn!("[archive] skipped {:?} entry {}", kind, name.display());
+            continue;
+        }
+        let target = entry_target(dest, &name)?;
+        progress.entry(&name.to_string_lossy());
+        if kind.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            let mode = entry.header().mode().ok();
+            bytes += write_entry(&target, &mut entry, mode)?;
+        }
+        entries += 1;
+    }
+    Ok((entries, bytes))
+}
+
+/// Files and directories below `path`, with their archive names relative to
+/// `base`. Symlinks are left out, as when extracting.
+fn collect_entries(
+    base: &Path,
+    path: &Path,
+    entries: &mut Vec<(PathBuf, String, fs::Metadata)>,
+) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    let name = path
+        .strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let is_dir = metadata.is_dir();
+    entries.push((path.to_path_buf(), name, metadata));
+    if is_dir {
+        let mut children: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        children.sort();
+        for child in children {
+            collect_entries(base, &child, entries)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+fn create_zip(
+    target: &Path,
+    entries: &[(PathBuf, String, fs::Metadata)],
+    progress: &Progress,
+) -> io::Result<u64> {
+    let mut zip = ZipWriter::new(fs::File::create(target)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, name, metadata) in entries {
+        progress.entry(name);
+        let options = options.unix_permissions(file_mode(metadata));
+        if metadata.is_dir() {
+            zip.add_directory(format!("{name}/"), options)
+                .map_err(io::Error::other)?;
+        } else {
+            zip.start_file(name.as_str(), options)
+                .map_err(io::Error::other)?;
+            io::copy(
+                &mut Counted {
+                    inner: fs::File::open(path)?,
+                    progress,
+                },
+                &mut zip,
+            )?;
+        }
+    }
+    zip.finish().map_err(io::Error::other)?.flush()?;
+    Ok(entries.len() as u64)
+}
+
+fn create_tar_gz(
+    target: &Path,
+    entries: &[(PathBuf, String, fs::Metadata)],
+    progress: &Progress,
+) -> io::Result<u64> {
+    let encoder = GzEncoder::new(fs::File::create(target)?, Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    for (path, name, metadata) in entries {
+        progress.entry(name);
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(metadata);
+        header.set_mode(file_mode(metadata));
+        if metadata.is_dir() {
+            tar.append_data(&mut header, name, io::empty())?;
+        } else {
+            let file = Counted {
+                inner: fs::File::open(path)?,
+                progress,
+            };
+            tar.append_data(&mut header, name, file)?;
+        }
+    }
+    tar.into_inner()?.finish()?.flush()?;
+    Ok(entries.len() as u64)
+}
+
+/// Extracts a zip or tar.gz archive into `dest`, creating it if needed. Entries
+/// that would land outside `dest` fail the extraction; links are skipped.
+#[tauri::command]
+pub(crate) async fn desktop_archive_extract(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    archive: String,
+    dest: String,
+) -> Result<ArchiveSummary, PathError> {
+    crate::origin::require_local(&webview, "extract archives")?;
+    let policy = PathPolicy::for_app(&app);
+    let archive = policy.check("extract", &archive)?;
+    let dest = fs_ops::check_new(&policy, "extract", &dest)?;
+
+    let task_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = (|| {
+            fs::create_dir_all(&dest)?;
+            let dest = fs::canonicalize(&dest)?;
+            let format = detect_format(&archive)?;
+            let before: Vec<PathBuf> = fs::read_dir(&dest)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect();
+            let total = match format {
+                ArchiveFormat::Zip => ZipArchive::new(fs::File::open(&archive)?)
+                    .ok()
+                    .and_then(|zip| zip.decompressed_size())
+                    .and_then(|size| u64::try_from(size).ok()),
+                ArchiveFormat::TarGz => fs::metadata(&archive).ok().map(|m| m.len()),
+            };
+            let progress = Progress::new(&task_app, &archive, ArchiveOperation::Extract, total);
+            let extracted = match format {
+                ArchiveFormat::Zip => extract_zip(&archive, &dest, &progress),
+                ArchiveFormat::TarGz => extract_tar_gz(&archive, &dest, &progress),
+            };
+            progress.finish();
+            // Whatever was written before a failure is reported too.
+            let created = fs::read_dir(&dest)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| !before.contains(path))
+                .map(|path| (path, FsChangeKind::Created));
+            watcher::emit_changes(&task_app, created);
+            let (entries, bytes) = extracted?;
+            Ok::<_, io::Error>(ArchiveSummary {
+                path: dest.to_string_lossy().to_string(),
+                entries,
+                bytes,
+            })
+        })();
+        audit::record(
+            &task_app,
+            AuditCategory::FileWrite,
+            "archive extract",
+            archive.to_string_lossy(),
+            &result,
+        );
+        if let Ok(summary) = &result {
+            log::info!(
+                "[archive] extracted {} entries from {} to {}",
+                summary.entries,
+                archive.display(),
+                summary.path
+            );
+        }
+        result.map_err(|err| archive_error(err, &archive))
+    })
+    .await
+    .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))?
+}
+
+/// Packs `paths` into a new zip or tar.gz at `dest`, replacing any file there.
+/// Each path is stored under its own name, with directories included recursively.
+#[tauri::command]
+pub(crate) async fn desktop_archive_create(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    paths: Vec<String>,
+    dest: String,
+    format: ArchiveFormat,
+) -> Result<ArchiveSummary, PathError> {
+    crate::origin::require_local(&webview, "create archives")?;
+    let policy = PathPolicy::for_app(&app);
+    let sources = paths
+        .iter()
+        .map(|path| policy.check("archive", path))
+        .collect::<Result<Vec<_>, _>>()?;
+    if sources.is_empty() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            "Nothing to archive",
+        ));
+    }
+    let target = fs_ops::check_new(&policy, "archive", &dest)?;
+    if target.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::AlreadyExists,
+            format!("{} is a directory", target.display()),
+        ));
+    }
+
+    let task_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = (|| {
+            let mut entries = Vec::new();
+            for source in &sources {
+                let base = source.parent().unwrap_or(source);
+                collect_entries(base, source, &mut entries)?;
+            }
+            let total = entries
+                .iter()
+                .filter(|(_, _, metadata)| metadata.is_file())
+                .map(|(_, _, metadata)| metadata.len())
+                .sum();
+            let progress = Progress::new(&task_app, &target, ArchiveOperation::Create, Some(total));
+            // Written next to the target and renamed at the end, so a failure never
+            // leaves a truncated archive behind.
+            let partial = target.with_extension("partial");
+            let created = match format {
+                ArchiveFormat::Zip => create_zip(&partial, &entries, &progress),
+                ArchiveFormat::TarGz => create_tar_gz(&partial, &entries, &progress),
+            }
+            .and_then(|count| fs::rename(&partial, &target).map(|()| count));
+            progress.finish();
+            if created.is_err() {
+                let _ = fs::remove_file(&partial);
+            }
+            let count = created?;
+            watcher::emit_changes(&task_app, [(target.clone(), FsChangeKind::Created)]);
+            Ok::<_, io::Error>(ArchiveSummary {
+                path: target.to_string_lossy().to_string(),
+                entries: count,
+                bytes: total,
+            })
+        })();
+        audit::record(
+            &task_app,
+            AuditCategory::FileWrite,
+            "archive create",
+            target.to_string_lossy(),
+            &result,
+        );
+        if let Ok(summary) = &result {
+            log::info!(
+                "[archive] archived {} entries into {}",
+                summary.entries,
+                summary.path
+            );
+        }
+        result.map_err(|err| archive_error(err, &target))
+    })
+    .await
+    .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, canonical scratch directory for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("openchamber-archive-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(&dir).unwrap()
+    }
+
+    #[test]
+    fn entry_target_creates_parents_below_dest() {
+        let dest = scratch("nested");
+        let target = entry_target(&dest, Path::new("./a/b/file.txt")).unwrap();
+        assert_eq!(target, dest.join("a/b/file.txt"));
+        assert!(dest.join("a/b").is_dir());
+        assert_eq!(entry_target(&dest, Path::new(".")).unwrap(), dest);
+        let _ = fs::remove_dir_all(&dest);
+    }
+
+    #[test]
+    fn entry_target_rejects_zip_slip_names() {
+        let dest = scratch("slip");
+        for name in ["../evil.txt", "a/../../evil.txt", "/etc/evil.txt"] {
+            let err = entry_target(&dest, Path::new(name)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{name}");
+        }
+        // Rejected before anything was created.
+        assert!(!dest.join("a").exists());
+        let _ = fs::remove_dir_all(&dest);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_contained_dir_refuses_symlinks_out_of_dest() {
+        let base = scratch("symlink");
+        let dest = base.join("dest");
+        let outside = base.join("outside");
+        fs::create_dir_all(&dest).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, dest.join("link")).unwrap();
+
+        let name = Path::new("link/sub/file.txt");
+        let err = create_contained_dir(&dest, Path::new("link/sub"), name).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(entry_target(&dest, name).is_err());
+        assert!(!outside.join("sub").exists());
+
+        // A symlink that stays inside `dest` is fine.
+        fs::create_dir_all(dest.join("real")).unwrap();
+        std::os::unix::fs::symlink(dest.join("real"), dest.join("alias")).unwrap();
+        create_contained_dir(&dest, Path::new("alias/sub"), Path::new("alias/sub/x")).unwrap();
+        assert!(dest.join("real/sub").is_dir());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::{profiles, sidecar_log::rotated_path};
+
+const AUDIT_LOG_FILE_NAME: &str = "audit.log";
+const AUDIT_LOG_MAX_BYTES: u64 = 1024 * 1024;
+const AUDIT_LOG_MAX_ROTATED: usize = 3;
+/// Entries returned by `desktop_get_audit_log` when no limit is given.
+const DEFAULT_AUDIT_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AuditCategory {
+    /// An external program was started on the user's behalf.
+    Process,
+    /// A file was written somewhere the user picked, outside the app's own dirs.
+    FileWrite,
+    Secret,
+    /// The local server became reachable from, or stopped being reachable from,
+    /// other machines.
+    NetworkExposure,
+    /// A setting that can run programs or send data elsewhere was changed.
+    Settings,
+}
+
+/// One line of `audit.log`. Targets never include secret values.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditEntry {
+    /// Milliseconds since the Unix epoch.
+    timestamp: u64,
+    category: AuditCategory,
+    action: String,
+    target: String,
+    succeeded: bool,
+    /// The error, for failed actions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Which entries `desktop_get_audit_log` returns; every bound is optional.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditRange {
+    /// Inclusive lower bound, in milliseconds since the Unix epoch.
+    since: Option<u64>,
+    /// Exclusive upper bound, in milliseconds since the Unix epoch.
+    until: Option<u64>,
+    /// Keep only the newest `limit` entries.
+    limit: Option<usize>,
+}
+
+/// Append-only JSON-lines file: `audit.log`, `audit.log.1` … `audit.log.N`.
+struct AuditLogFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl AuditLogFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file,
+            written,
+        })
+    }
+
+    fn append(&mut self, entry: &AuditEntry) -> io::Result<()> {
+        if self.written >= AUDIT_LOG_MAX_BYTES {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        self.written = self.written.saturating_add(line.len() as u64);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for idx in (1..AUDIT_LOG_MAX_ROTATED).rev() {
+            let from = rotated_path(&self.path, idx);
+            if from.exists() {
+                let _ = fs::rename(&from, rotated_path(&self.path, idx + 1));
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AuditState {
+    file: Mutex<Option<AuditLogFile>>,
+}
+
+fn audit_log_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(AUDIT_LOG_FILE_NAME))
+}
+
+/// The current file and its rotations that exist, oldest first.
+pub(crate) fn audit_log_files<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Vec<PathBuf> {
+    let Some(path) = audit_log_path(app) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = (1..=AUDIT_LOG_MAX_ROTATED)
+        .rev()
+        .map(|idx| rotated_path(&path, idx))
+        .collect();
+    files.push(path);
+    files.retain(|path| path.is_file());
+    files
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Appends the outcome of a privileged action to `audit.log`.
+pub(crate) fn record<R: tauri::Runtime, T, E: fmt::Display>(
+    app: &tauri::AppHandle<R>,
+    category: AuditCategory,
+    action: &str,
+    target: impl Into<String>,
+    outcome: &Result<T, E>,
+) {
+    let entry = AuditEntry {
+        timestamp: now_millis(),
+        category,
+        action: action.to_string(),
+        target: target.into(),
+        succeeded: outcome.is_ok(),
+        error: outcome.as_ref().err().map(ToString::to_string),
+    };
+
+    let Some(state) = app.try_state::<AuditState>() else {
+        return;
+    };
+    let mut guard = state.file.lock().expect("audit log mutex");
+    if guard.is_none() {
+        let Some(path) = audit_log_path(app) else {
+            return;
+        };
+        match AuditLogFile::open(path) {
+            Ok(file) => *guard = Some(file),
+            Err(err) => {
+                log::warn!("[audit] failed to open {AUDIT_LOG_FILE_NAME}: {err}");
+                return;
+            }
+        }
+    }
+    if let Some(file) = guard.as_mut() {
+        if let Err(err) = file.append(&entry) {
+            log::warn!("[audit] failed to write {AUDIT_LOG_FILE_NAME}: {err}");
+            *guard = None;
+        }
+    }
+}
+
+/// Audit entries within `range`, oldest first. Lines that fail to parse (e.g. cut
+/// off by a crash) are skipped.
+#[tauri::command]
+pub(crate) fn desktop_get_audit_log(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    range: Option<AuditRange>,
+) -> Result<Vec<AuditEntry>, String> {
+    crate::origin::require_local(&webview, "read the audit log")?;
+    let range = range.unwrap_or_default();
+    // Hold the writer lock so a rotation cannot move files mid-read.
+    let state = app.state::<AuditState>();
+    let _writer = state.file.lock().expect("audit log mutex");
+
+    let mut entries: Vec<AuditEntry> = audit_log_files(&app)
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|raw| {
+            raw.lines()
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+                .collect::<Vec<_>>()
+        })
+        .filter(|entry| range.since.is_none_or(|since| entry.timestamp >= since))
+        .filter(|entry| range.until.is_none_or(|until| entry.timestamp < until))
+        .collect();
+    let limit = range.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,284 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::{i18n, settings};
+
+/// Settings-store key holding when to ask for the user; see `OsAuthConfig`. Only
+/// `desktop_set_os_auth` writes it.
+pub(crate) const OS_AUTH_KEY: &str = "osAuthentication";
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct OsAuthConfig {
+    /// Ask before handing a stored secret to the frontend.
+    reveal_secrets: bool,
+    /// Ask before connecting to a remote server with saved credentials.
+    connect_servers: bool,
+    /// How long a successful check covers further requests; 0 asks every time.
+    grace_seconds: u64,
+}
+
+impl Default for OsAuthConfig {
+    fn default() -> Self {
+        Self {
+            reveal_secrets: false,
+            connect_servers: false,
+            grace_seconds: 300,
+        }
+    }
+}
+
+fn load_config(app: &tauri::AppHandle) -> OsAuthConfig {
+    settings::get_setting(app, OS_AUTH_KEY)
+        .and_then(|value: Value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// What a native gate is about to do.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum AuthPurpose {
+    RevealSecret,
+    ConnectServer,
+}
+
+#[derive(Default)]
+pub(crate) struct AuthState {
+    last_success: std::sync::Mutex<Option<Instant>>,
+    /// Held while the OS prompt is up, so concurrent requests share one prompt.
+    prompting: tokio::sync::Mutex<()>,
+}
+
+fn within_grace(state: &AuthState, grace: Duration) -> bool {
+    state
+        .last_success
+        .lock()
+        .expect("auth mutex")
+        .is_some_and(|at| at.elapsed() < grace)
+}
+
+fn unavailable() -> String {
+    "OS authentication is not available on this system".to_string()
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+/// Touch ID, falling back to the login password.
+#[cfg(target_os = "macos")]
+async fn prompt(_app: &tauri::AppHandle, reason: &str) -> Result<bool, String> {
+    use std::sync::Mutex;
+
+    use block2::RcBlock;
+    use objc2::{
+        msg_send,
+        rc::Retained,
+        runtime::{AnyClass, AnyObject, Bool},
+    };
+    use objc2_foundation::NSString;
+    use tokio::sync::oneshot;
+
+    /// `LAPolicyDeviceOwnerAuthentication`.
+    const POLICY: isize = 2;
+
+    let (done, finished) = oneshot::channel();
+    {
+        let class = AnyClass::get(c"LAContext").ok_or_else(unavailable)?;
+        // SAFETY: `+new` returns a retained `LAContext`.
+        let context: Retained<AnyObject> = unsafe { msg_send![class, new] };
+        // SAFETY: `canEvaluatePolicy:error:` accepts a NULL error pointer.
+        let available: Bool = unsafe {
+            msg_send![
+                &context,
+                canEvaluatePolicy: POLICY,
+                error: std::ptr::null_mut::<*mut AnyObject>()
+            ]
+        };
+        if !available.as_bool() {
+            return Err(unavailable());
+        }
+        let done = Mutex::new(Some(done));
+        // Evaluation is cancelled when the context goes away, so the reply keeps it.
+        let keep_alive = context.clone();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut AnyObject| {
+            let _ = &keep_alive;
+            if let Some(done) = done.lock().expect("auth reply mutex").take() {
+                let _ = done.send(success.as_bool());
+            }
+        });
+        let reason = NSString::from_str(reason);
+        // SAFETY: the reply block matches `void (^)(BOOL, NSError *)`.
+        let _: () = unsafe {
+            msg_send![
+                &context,
+                evaluatePolicy: POLICY,
+                localizedReason: &*reason,
+                reply: &*reply
+            ]
+        };
+    }
+    Ok(finished.await.unwrap_or(false))
+}
+
+/// Windows Hello, parented to the main window so the prompt shows in front of it.
+#[cfg(windows)]
+async fn prompt(app: &tauri::AppHandle, reason: &str) -> Result<bool, String> {
+    use windows::{
+        core::{factory, HSTRING},
+        Security::Credentials::UI::{
+            UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+        },
+        Win32::{Foundation::HWND, System::WinRT::IUserConsentVerifierInterop},
+    };
+    use windows_future::IAsyncOperation;
+
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    let hwnd = window.hwnd().map_err(|err| err.to_string())?.0 as isize;
+    let reason = HSTRING::from(reason);
+    tauri::async_runtime::spawn_blocking(move || -> windows::core::Result<Option<bool>> {
+        if UserConsentVerifier::CheckAvailabilityAsync()?.get()?
+            != UserConsentVerifierAvailability::Available
+        {
+            return Ok(None);
+        }
+        let interop = factory::<UserConsentVerifier, IUserConsentVerifierInterop>()?;
+        // SAFETY: `hwnd` is the live main window.
+        let operation: IAsyncOperation<UserConsentVerificationResult> = unsafe {
+            interop.RequestVerificationForWindowAsync(HWND(hwnd as *mut std::ffi::c_void), &reason)
+        }?;
+        Ok(Some(
+            operation.get()? == UserConsentVerificationResult::Verified,
+        ))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())?
+    .ok_or_else(unavailable)
+}
+
+/// polkit, through whichever authentication agent the desktop runs. The agent
+/// shows its own message; pkcheck cannot pass `reason` along.
+#[cfg(not(any(target_os = "macos", windows)))]
+async fn prompt(_app: &tauri::AppHandle, _reason: &str) -> Result<bool, String> {
+    use std::process::{Command, Stdio};
+
+    let status = tauri::async_runtime::spawn_blocking(|| {
+        Command::new("pkcheck")
+            .args(["--action-id", "org.freedesktop.policykit.exec", "--process"])
+            .arg(std::process::id().to_string())
+            .arg("--allow-user-interaction")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|_| unavailable())?;
+    // 1: not authorized, 2: the user dismissed the agent's dialog.
+    match status.code() {
+        Some(0) => Ok(true),
+        Some(1 | 2) => Ok(false),
+        _ => Err(unavailable()),
+    }
+}
+
+/// Asks the OS to confirm the user is present, unless a check succeeded within the
+/// configured grace period. `Ok(false)` means the user cancelled or failed.
+pub(crate) async fn authenticate(app: &tauri::AppHandle, reason: &str) -> Result<bool, String> {
+    let state = app.state::<AuthState>();
+    let grace = Duration::from_secs(load_config(app).grace_seconds);
+    if within_grace(&state, grace) {
+        return Ok(true);
+    }
+    let _prompting = state.prompting.lock().await;
+    // Another request may have succeeded while this one waited.
+    if within_grace(&state, grace) {
+        return Ok(true);
+    }
+
+    let authenticated = prompt(app, reason).await?;
+    log::info!(
+        "[auth] OS authentication {}",
+        if authenticated {
+            "succeeded"
+        } else {
+            "declined"
+        }
+    );
+    if authenticated {
+        *state.last_success.lock().expect("auth mutex") = Some(Instant::now());
+    }
+    Ok(authenticated)
+}
+
+/// Gate for native commands; passes straight through unless the settings ask
+/// for user presence before `purpose`.
+pub(crate) async fn require_presence(
+    app: &tauri::AppHandle,
+    purpose: AuthPurpose,
+    subject: &str,
+) -> Result<(), String> {
+    let config = load_config(app);
+    let (required, key) = match purpose {
+        AuthPurpose::RevealSecret => (config.reveal_secrets, "auth.reason.revealSecret"),
+        AuthPurpose::ConnectServer => (config.connect_servers, "auth.reason.connectServer"),
+    };
+    if !required {
+        return Ok(());
+    }
+    let reason = i18n::t_args(app, key, &[("name", subject)]);
+    if authenticate(app, &reason).await? {
+        Ok(())
+    } else {
+        Err("Authentication was cancelled".to_string())
+    }
+}
+
+/// Changes when the OS check is required. Anything that asks less often than now
+/// (a switch turned off, a longer grace period) needs a fresh check first, outside
+/// any grace period, so the gate cannot be lifted without the user.
+#[tauri::command]
+pub(crate) async fn desktop_set_os_auth(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    config: OsAuthConfig,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change authentication settings")?;
+    let current = load_config(&app);
+    let weakens = (current.reveal_secrets && !config.reveal_secrets)
+        || (current.connect_servers && !config.connect_servers)
+        || config.grace_seconds > current.grace_seconds;
+    if weakens {
+        let state = app.state::<AuthState>();
+        let _prompting = state.prompting.lock().await;
+        let reason = i18n::t(&app, "auth.reason.changeSettings");
+        if !prompt(&app, &reason).await? {
+            return Err("Authentication was cancelled".to_string());
+        }
+        *state.last_success.lock().expect("auth mutex") = Some(Instant::now());
+    }
+    let value = serde_json::to_value(&config).map_err(|err| err.to_string())?;
+    settings::set_setting(&app, OS_AUTH_KEY, value, Some(webview.label()))
+}
+
+/// Touch ID / Windows Hello / polkit check for the frontend, e.g. before showing a
+/// stored API key. Honors the grace period but not the per-purpose switches.
+#[tauri::command]
+pub(crate) async fn desktop_authenticate(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    reason: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "ask for authentication")?;
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("Authentication reason must not be empty".to_string());
+    }
+    authenticate(&app, reason).await
+}
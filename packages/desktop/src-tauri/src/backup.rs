@@ -0,0 +1,488 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+use ts_rs::TS;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    audit::{self, AuditCategory},
+    events::{self, DesktopEvent},
+    i18n, kill_sidecar,
+    notifications::{NotificationCategory, NotificationPrefsState},
+    openchamber_data_dir, path_policy, profiles, safe_mode, secrets, servers, settings,
+    window_registry,
+};
+
+/// Settings-store key holding `{ "enabled", "intervalHours", "keep", "includeServerData" }`.
+const BACKUP_SETTINGS_KEY: &str = "backups";
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Lets startup settle before the first scheduled backup.
+const BACKUP_STARTUP_DELAY: Duration = Duration::from_secs(5 * 60);
+const BACKUP_DIR_NAME: &str = "backups";
+const BACKUP_FILE_PREFIX: &str = "openchamber-backup-";
+const MANIFEST_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u64 = 1;
+const CONFIG_PREFIX: &str = "config/";
+const SERVER_DATA_PREFIX: &str = "server/";
+/// Server data files larger than this are left out; they are caches, not state.
+const MAX_SERVER_FILE_SIZE: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct BackupConfig {
+    enabled: bool,
+    interval_hours: u64,
+    /// Backups kept; older ones are deleted after each new one.
+    keep: usize,
+    /// Include the local server's data directory, not just the desktop config.
+    include_server_data: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+            include_server_data: true,
+        }
+    }
+}
+
+fn load_config(app: &tauri::AppHandle) -> BackupConfig {
+    settings::get_setting(app, BACKUP_SETTINGS_KEY)
+        .and_then(|value: Value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Serializes backups and restores, so the scheduler never races a manual run.
+#[derive(Default)]
+pub(crate) struct BackupState {
+    running: Mutex<()>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u64,
+    /// Milliseconds since the Unix epoch.
+    created_at: u64,
+    app_version: String,
+    profile: Option<String>,
+    reason: BackupReason,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BackupReason {
+    Scheduled,
+    Manual,
+    /// Taken automatically before a restore replaced the data.
+    PreRestore,
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupInfo {
+    path: String,
+    /// Milliseconds since the Unix epoch.
+    created_at: u64,
+    size: u64,
+    reason: Option<BackupReason>,
+}
+
+/// Emitted as `openchamber:backup` after every backup, successful or not.
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupEvent {
+    backup: Option<BackupInfo>,
+    error: Option<String>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn backup_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(BACKUP_DIR_NAME))
+        .map_err(|err| err.to_string())
+}
+
+fn config_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(profiles::scoped)
+        .map_err(|err| err.to_string())
+}
+
+/// Desktop config files worth restoring. The portable secrets file is left out on
+/// purpose: backups are not encrypted.
+fn config_files() -> [&'static str; 3] {
+    [
+        settings::SETTINGS_FILE_NAME,
+        servers::SERVERS_FILE_NAME,
+        secrets::SECRETS_INDEX_FILE_NAME,
+    ]
+}
+
+fn read_manifest<R: Read + io::Seek>(zip: &mut ZipArchive<R>) -> Option<Manifest> {
+    let mut contents = String::new();
+    zip.by_name(MANIFEST_NAME)
+        .ok()?
+        .read_to_string(&mut contents)
+        .ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let metadata = fs::metadata(path).ok()?;
+    let manifest = fs::File::open(path)
+        .ok()
+        .and_then(|file| ZipArchive::new(file).ok())
+        .and_then(|mut zip| read_manifest(&mut zip));
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    Some(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        created_at: manifest.as_ref().map_or(modified, |m| m.created_at),
+        size: metadata.len(),
+        reason: manifest.map(|m| m.reason),
+    })
+}
+
+/// Backups in `dir`, newest first.
+fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(BACKUP_FILE_PREFIX) && name.ends_with(".zip"))
+        })
+        .filter_map(|path| backup_info(&path))
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    backups
+}
+
+fn add_tree(
+    zip: &mut ZipWriter<fs::File>,
+    options: SimpleFileOptions,
+    root: &Path,
+    dir: &Path,
+    skip: &[PathBuf],
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() || skip.iter().any(|skipped| path.starts_with(skipped)) {
+            continue;
+        }
+        let name = format!(
+            "{SERVER_DATA_PREFIX}{}",
+            path.strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/")
+        );
+        if file_type.is_dir() {
+            add_tree(zip, options, root, &path, skip)?;
+            continue;
+        }
+        let size = entry.metadata().map_or(0, |metadata| metadata.len());
+        if size > MAX_SERVER_FILE_SIZE {
+            log::info!("[backup] skipped {} ({size} bytes)", path.display());
+            continue;
+        }
+        // Files the server is rewriting right now may vanish; skip them.
+        match fs::read(&path) {
+            Ok(bytes) => {
+                zip.start_file(name, options).map_err(io::Error::other)?;
+                zip.write_all(&bytes)?;
+            }
+            Err(err) => log::warn!("[backup] skipped {}: {err}", path.display()),
+        }
+    }
+    Ok(())
+}
+
+fn write_backup(
+    app: &tauri::AppHandle,
+    target: &Path,
+    reason: BackupReason,
+    include_server_data: bool,
+) -> Result<(), String> {
+    let config_dir = config_dir(app)?;
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created_at: now_millis(),
+        app_version: app.package_info().version.to_string(),
+        profile: profiles::active().map(str::to_string),
+        reason,
+    };
+    let write = || -> anyhow::Result<()> {
+        let mut zip = ZipWriter::new(fs::File::create(target)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(MANIFEST_NAME, options)?;
+        zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+        for name in config_files() {
+            if let Ok(bytes) = fs::read(config_dir.join(name)) {
+                zip.start_file(format!("{CONFIG_PREFIX}{name}"), options)?;
+                zip.write_all(&bytes)?;
+            }
+        }
+        if include_server_data {
+            let data_dir = openchamber_data_dir();
+            if data_dir.is_dir() {
+                // Other profiles live below the default data dir and back up themselves.
+                let skip = [
+                    data_dir.join(profiles::PROFILES_DIR_NAME),
+                    backup_dir(app).map_err(anyhow::Error::msg)?,
+                ];
+                add_tree(&mut zip, options, &data_dir, &data_dir, &skip)?;
+            }
+        }
+        zip.finish()?;
+        Ok(())
+    };
+    write().map_err(|err| {
+        let _ = fs::remove_file(target);
+        err.to_string()
+    })
+}
+
+/// Deletes all but the newest `keep` backups.
+fn apply_retention(dir: &Path, keep: usize) {
+    for old in list_backups(dir).into_iter().skip(keep.max(1)) {
+        match fs::remove_file(&old.path) {
+            Ok(()) => log::info!("[backup] removed old backup {}", old.path),
+            Err(err) => log::warn!("[backup] failed to remove {}: {err}", old.path),
+        }
+    }
+}
+
+fn create_backup(app: &tauri::AppHandle, reason: BackupReason) -> Result<BackupInfo, String> {
+    let config = load_config(app);
+    let dir = backup_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let mut name = format!(
+        "{BACKUP_FILE_PREFIX}{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    if matches!(reason, BackupReason::PreRestore) {
+        name.push_str("-pre-restore");
+    }
+    let target = dir.join(format!("{name}.zip"));
+    write_backup(app, &target, reason, config.include_server_data)?;
+    apply_retention(&dir, config.keep);
+    let info = backup_info(&target).ok_or_else(|| "The backup disappeared".to_string())?;
+    log::info!("[backup] wrote {} ({} bytes)", info.path, info.size);
+    Ok(info)
+}
+
+fn run_backup(app: &tauri::AppHandle, reason: BackupReason) -> Result<BackupInfo, String> {
+    let state = app.state::<BackupState>();
+    let _running = state.running.lock().expect("backup mutex");
+    let result = create_backup(app, reason);
+    events::emit(
+        app,
+        DesktopEvent::Backup(BackupEvent {
+            backup: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().cloned(),
+        }),
+    );
+    result
+}
+
+fn notify_failure(app: &tauri::AppHandle, error: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    let focused = window_registry::is_app_focused(app);
+    let allowed = app
+        .try_state::<NotificationPrefsState>()
+        .is_none_or(|prefs| prefs.allows(NotificationCategory::Error, focused));
+    if !allowed {
+        return;
+    }
+    let _ = app
+        .notification()
+        .builder()
+        .title(i18n::t(app, "backup.failedTitle"))
+        .body(i18n::t_args(app, "backup.failedBody", &[("error", error)]))
+        .show();
+}
+
+/// Backs up on the configured interval, counted from the newest existing backup so
+/// restarts don't reset the clock. Failures are reported as a notification.
+pub(crate) fn spawn_backup_scheduler(app: tauri::AppHandle) {
+    if safe_mode::is_enabled() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(BACKUP_STARTUP_DELAY).await;
+        loop {
+            let config = load_config(&app);
+            let due = config.enabled
+                && backup_dir(&app).is_ok_and(|dir| {
+                    list_backups(&dir).first().is_none_or(|newest| {
+                        now_millis().saturating_sub(newest.created_at)
+                            >= config.interval_hours.max(1) * 60 * 60 * 1000
+                    })
+                });
+            if due {
+                let task_app = app.clone();
+                let result = tauri::async_runtime::spawn_blocking(move || {
+                    run_backup(&task_app, BackupReason::Scheduled)
+                })
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|result| result);
+                if let Err(err) = result {
+                    log::warn!("[backup] scheduled backup failed: {err}");
+                    notify_failure(&app, &err);
+                }
+            }
+            tokio::time::sleep(BACKUP_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Puts the files of a backup back in place. Entries other than the known config
+/// files and the server data are ignored, and so are paths that would leave the
+/// data directory.
+fn restore_files(app: &tauri::AppHandle, source: &Path) -> Result<(), String> {
+    let mut zip = fs::File::open(source)
+        .map_err(|err| err.to_string())
+        .and_then(|file| ZipArchive::new(file).map_err(|err| err.to_string()))?;
+    let manifest =
+        read_manifest(&mut zip).ok_or_else(|| "Not an OpenChamber backup".to_string())?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "The backup was made by a newer OpenChamber ({})",
+            manifest.app_version
+        ));
+    }
+    let config_dir = config_dir(app)?;
+    let data_dir = openchamber_data_dir();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|err| err.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let target = if let Some(file) = name.strip_prefix(CONFIG_PREFIX) {
+            if !config_files().contains(&file) {
+                continue;
+            }
+            config_dir.join(file)
+        } else if name.starts_with(SERVER_DATA_PREFIX) {
+            let Some(relative) = entry.enclosed_name().and_then(|path| {
+                path.strip_prefix(SERVER_DATA_PREFIX)
+                    .ok()
+                    .map(Path::to_path_buf)
+            }) else {
+                log::warn!("[backup] skipped unsafe entry {name}");
+                continue;
+            };
+            data_dir.join(relative)
+        } else {
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let mut file = fs::File::create(&target).map_err(|err| err.to_string())?;
+        io::copy(&mut entry, &mut file).map_err(|err| format!("{}: {err}", target.display()))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_backup_list(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<Vec<BackupInfo>, String> {
+    crate::origin::require_local(&webview, "list backups")?;
+    let dir = backup_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || list_backups(&dir))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Backs up the desktop config and the local server's data right away.
+#[tauri::command]
+pub(crate) async fn desktop_backup_now(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<BackupInfo, String> {
+    crate::origin::require_local(&webview, "back up app data")?;
+    let task_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || run_backup(&task_app, BackupReason::Manual))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// Replaces the current config and server data with a backup, then restarts the
+/// app so everything loads it. The current state is backed up first, so a restore
+/// can be undone by restoring that.
+#[tauri::command]
+pub(crate) async fn desktop_restore_backup(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "restore a backup")?;
+    let dir = backup_dir(&app)?;
+    let source = match fs::canonicalize(path.trim()) {
+        Ok(source) if fs::canonicalize(&dir).is_ok_and(|dir| source.starts_with(dir)) => source,
+        _ => path_policy::check(&app, "restore backup", &path).map_err(|err| err.to_string())?,
+    };
+
+    let task_app = app.clone();
+    let restore_source = source.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let state = task_app.state::<BackupState>();
+        let _running = state.running.lock().expect("backup mutex");
+        create_backup(&task_app, BackupReason::PreRestore)
+            .map_err(|err| format!("Could not back up the current data first: {err}"))?;
+        // The server must not write its data while it is being replaced.
+        kill_sidecar(task_app.clone());
+        restore_files(&task_app, &restore_source)
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    audit::record(
+        &app,
+        AuditCategory::FileWrite,
+        "restore backup",
+        source.to_string_lossy(),
+        &result,
+    );
+    result?;
+    log::info!("[backup] restored {}; restarting", source.display());
+    app.restart();
+}
@@ -0,0 +1,86 @@
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+/// Count shown on the dock icon (macOS, Linux launchers that support it) or as a
+/// taskbar overlay (Windows).
+#[derive(Default)]
+pub(crate) struct BadgeState {
+    count: Mutex<u32>,
+}
+
+#[cfg(target_os = "windows")]
+fn overlay_icon() -> tauri::image::Image<'static> {
+    // Windows overlays can't render text, so a pending count is shown as a red dot.
+    const SIZE: u32 = 16;
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    let radius = SIZE as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            let alpha = ((radius - distance).clamp(0.0, 1.0) * 255.0) as u8;
+            rgba.extend_from_slice(&[0xE5, 0x48, 0x4D, alpha]);
+        }
+    }
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+fn apply_badge<R: tauri::Runtime>(app: &tauri::AppHandle<R>, count: u32) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let icon = (count > 0).then(overlay_icon);
+        if let Err(err) = window.set_overlay_icon(icon) {
+            log::warn!("[badge] failed to set overlay icon: {err}");
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let badge = (count > 0).then_some(i64::from(count));
+        if let Err(err) = window.set_badge_count(badge) {
+            log::debug!("[badge] failed to set badge count: {err}");
+        }
+    }
+}
+
+pub(crate) fn set_badge<R: tauri::Runtime>(app: &tauri::AppHandle<R>, count: u32) {
+    let Some(state) = app.try_state::<BadgeState>() else {
+        return;
+    };
+    {
+        let mut current = state.count.lock().expect("badge mutex");
+        if *current == count {
+            return;
+        }
+        *current = count;
+    }
+    apply_badge(app, count);
+}
+
+/// Bumps the badge for a notification shown while the window is in the background.
+pub(crate) fn increment_badge<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<BadgeState>() else {
+        return;
+    };
+    let count = {
+        let mut current = state.count.lock().expect("badge mutex");
+        *current = current.saturating_add(1);
+        *current
+    };
+    apply_badge(app, count);
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_badge(app: tauri::AppHandle, count: u32) {
+    set_badge(&app, count);
+}
+
+#[tauri::command]
+pub(crate) fn desktop_clear_badge(app: tauri::AppHandle) {
+    set_badge(&app, 0);
+}
@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::Engine;
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{
+    boot, i18n, kill_sidecar, portable, profiles, reveal, sidecar_log::sidecar_log_path, splash,
+};
+
+/// Links on the error page use this scheme; navigations to it are intercepted and
+/// run the matching action instead, so the page needs no IPC access.
+const ACTION_SCHEME: &str = "openchamber-action";
+const LOG_TAIL_LINES: usize = 40;
+
+static RETRYING: AtomicBool = AtomicBool::new(false);
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn sidecar_log_tail<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> String {
+    let Some(contents) = sidecar_log_path(app).and_then(|path| std::fs::read_to_string(path).ok())
+    else {
+        return String::new();
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+}
+
+fn error_page_html<R: tauri::Runtime>(app: &tauri::AppHandle<R>, reason: &str) -> String {
+    let tail = sidecar_log_tail(app);
+    let log_section = if tail.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<h2>{}</h2><pre>{}</pre>",
+            i18n::t(app, "bootError.logHeading"),
+            escape_html(&tail)
+        )
+    };
+    format!(
+        r#"<!doctype html>
+<html><head><meta charset="utf-8"><style>
+:root {{ color-scheme: light dark; }}
+body {{ margin: 0; padding: 56px 48px; box-sizing: border-box; font: 14px -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif;
+  background: #f5f5f4; color: #1c1917; }}
+@media (prefers-color-scheme: dark) {{ body {{ background: #1c1917; color: #e7e5e4; }} pre {{ background: #292524; }} }}
+h1 {{ font-size: 20px; margin: 0 0 8px; }}
+h2 {{ font-size: 13px; margin: 28px 0 8px; opacity: .7; }}
+p {{ margin: 0 0 20px; opacity: .85; }}
+code {{ font: 12px ui-monospace, SFMono-Regular, Menlo, monospace; }}
+pre {{ font: 11px/1.5 ui-monospace, SFMono-Regular, Menlo, monospace; background: #e7e5e4; padding: 12px;
+  border-radius: 6px; max-height: 50vh; overflow: auto; white-space: pre-wrap; word-break: break-all; }}
+a.button {{ display: inline-block; padding: 7px 14px; margin-right: 8px; border-radius: 6px; text-decoration: none;
+  border: 1px solid currentColor; color: inherit; }}
+a.primary {{ background: #1c1917; color: #f5f5f4; border-color: #1c1917; }}
+@media (prefers-color-scheme: dark) {{ a.primary {{ background: #e7e5e4; color: #1c1917; border-color: #e7e5e4; }} }}
+</style></head>
+<body>
+<h1>{title}</h1>
+<p>{message}</p>
+<p><code>{reason}</code></p>
+<a class="button primary" href="{ACTION_SCHEME}://retry">{retry}</a><a class="button" href="{ACTION_SCHEME}://diagnostics">{diagnostics}</a>
+{log_section}
+</body></html>"#,
+        title = i18n::t(app, "bootError.title"),
+        message = i18n::t(app, "bootError.message"),
+        reason = escape_html(reason),
+        retry = i18n::t(app, "bootError.retry"),
+        diagnostics = i18n::t(app, "bootError.diagnostics"),
+    )
+}
+
+fn handle_action(app: &tauri::AppHandle, url: &url::Url) {
+    match url.host_str() {
+        Some("retry") => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = desktop_retry_local_server(app).await {
+                    log::warn!("[boot] retry failed: {err}");
+                }
+            });
+        }
+        Some("diagnostics") => {
+            if let Err(err) = desktop_open_diagnostics(app.clone()) {
+                log::warn!("[boot] failed to open diagnostics: {err}");
+            }
+        }
+        other => log::warn!("[boot] unknown error page action: {other:?}"),
+    }
+}
+
+/// Points the main window at the offline error page, creating the window if the
+/// app failed before it existed.
+pub(crate) fn show(app: &tauri::AppHandle, reason: &str) {
+    let data_url = format!(
+        "data:text/html;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(error_page_html(app, reason))
+    );
+    let Ok(url) = url::Url::parse(&data_url) else {
+        return;
+    };
+
+    let window = match app.get_webview_window("main") {
+        Some(window) => {
+            let _ = window.navigate(url);
+            window
+        }
+        None => {
+            let action_app = app.clone();
+            let builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(url))
+                .incognito(crate::safe_mode::is_enabled());
+            // Later navigations reuse this window, so it needs the main window's storage.
+            let builder = match portable::webview_data_dir() {
+                Some(dir) => builder.data_directory(dir),
+                None => builder,
+            };
+            let built = builder
+                .title(profiles::window_title())
+                .inner_size(900.0, 640.0)
+                .on_navigation(move |target| {
+                    if target.scheme() != ACTION_SCHEME {
+                        return true;
+                    }
+                    handle_action(&action_app, target);
+                    false
+                })
+                .build();
+            match built {
+                Ok(window) => window,
+                Err(err) => {
+                    log::error!("[boot] failed to create error window: {err}");
+                    return;
+                }
+            }
+        }
+    };
+    let _ = window.show();
+    let _ = window.set_focus();
+    // Close the splash only once another window exists, or the app would exit.
+    splash::close(app);
+}
+
+/// Tries to start the local server again after a failed launch, replacing the
+/// offline error page with the app on success.
+#[tauri::command]
+pub(crate) async fn desktop_retry_local_server(app: tauri::AppHandle) -> Result<(), String> {
+    if RETRYING.swap(true, Ordering::SeqCst) {
+        return Err("A retry is already in progress".to_string());
+    }
+    log::info!("[boot] retrying local server start");
+    let kill_app = app.clone();
+    let _ = tauri::async_runtime::spawn_blocking(move || kill_sidecar(kill_app)).await;
+    boot(app).await;
+    RETRYING.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Reveals the sidecar log in the file manager.
+#[tauri::command]
+pub(crate) fn desktop_open_diagnostics(app: tauri::AppHandle) -> Result<(), String> {
+    let path = sidecar_log_path(&app).ok_or_else(|| "Log directory unavailable".to_string())?;
+    reveal::reveal_path(&app, &path.to_string_lossy()).map_err(|err| err.to_string())
+}
@@ -0,0 +1,483 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{i18n, menu, profiles, proxy, settings};
+
+const CHANGELOG_URL: &str =
+    "https://raw.githubusercontent.com/btriapitsyn/openchamber/main/CHANGELOG.md";
+const CHANGELOG_CACHE_FILE_NAME: &str = "changelog.md";
+const CHANGELOG_CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+const CHANGELOG_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Remembers the last version that ran, so the first start after an update knows
+/// which releases it skipped.
+const VERSION_HISTORY_FILE_NAME: &str = "version-history.json";
+/// Settings-store key; `false` stops the window from opening by itself after updates.
+const WHATS_NEW_AFTER_UPDATE_KEY: &str = "showWhatsNewAfterUpdate";
+const WHATS_NEW_LABEL: &str = "whats-new";
+const WHATS_NEW_WIDTH: f64 = 560.0;
+const WHATS_NEW_HEIGHT: f64 = 640.0;
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionHistory {
+    last_version: Option<String>,
+    /// The version the most recent update replaced.
+    updated_from: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChangelogSection {
+    version: String,
+    /// The release's heading line without the `## `, e.g. `[1.4.0] - 2025-06-01`.
+    heading: String,
+    /// Markdown below the heading.
+    body: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Changelog {
+    /// Releases after this one are included; `None` shows the current release only.
+    since_version: Option<String>,
+    current_version: String,
+    /// Newest first.
+    sections: Vec<ChangelogSection>,
+}
+
+fn parse_semver_num(value: &str) -> Option<u32> {
+    let trimmed = value.trim().trim_start_matches('v');
+    let mut parts = trimmed.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    let patch: u32 = parts.next()?.parse().ok()?;
+    Some(major.saturating_mul(10_000) + minor.saturating_mul(100) + patch)
+}
+
+fn cache_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_cache_dir()
+        .ok()
+        .map(|dir| dir.join(CHANGELOG_CACHE_FILE_NAME))
+}
+
+async fn fetch_changelog<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    let client = proxy::load(app)
+        .apply(reqwest::Client::builder())
+        .timeout(CHANGELOG_FETCH_TIMEOUT)
+        .build()
+        .ok()?;
+    let response = client.get(CHANGELOG_URL).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let changelog = response.text().await.ok()?;
+    (!changelog.trim().is_empty()).then_some(changelog)
+}
+
+/// CHANGELOG.md from the repository, cached for a few hours. A stale copy is
+/// better than nothing when offline.
+async fn load_changelog<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    let path = cache_path(app);
+    let cached = path.as_ref().and_then(|path| {
+        let age = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        Some((fs::read_to_string(path).ok()?, age))
+    });
+    if let Some((changelog, age)) = &cached {
+        if *age < CHANGELOG_CACHE_TTL {
+            return Some(changelog.clone());
+        }
+    }
+
+    match fetch_changelog(app).await {
+        Some(changelog) => {
+            if let Some(path) = path {
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::write(&path, &changelog));
+                if let Err(err) = written {
+                    log::warn!("[changelog] failed to cache changelog: {err}");
+                }
+            }
+            Some(changelog)
+        }
+        None => cached.map(|(changelog, _)| changelog),
+    }
+}
+
+/// Releases in `(from, to]`, newest first as they appear in the file.
+fn sections_between(changelog: &str, from_num: u32, to_num: u32) -> Vec<ChangelogSection> {
+    let mut sections = Vec::new();
+    let mut current: Option<(String, String, Vec<&str>)> = None;
+    let mut finish = |current: Option<(String, String, Vec<&str>)>| {
+        if let Some((version, heading, lines)) = current {
+            sections.push(ChangelogSection {
+                version,
+                heading,
+                body: lines.join("\n").trim().to_string(),
+            });
+        }
+    };
+    for line in changelog.lines() {
+        let line = line.trim_end_matches('\r');
+        if let Some(heading) = line.strip_prefix("## ") {
+            finish(current.take());
+            let version = heading
+                .strip_prefix('[')
+                .and_then(|rest| rest.split(']').next())
+                .unwrap_or("");
+            current = parse_semver_num(version)
+                .filter(|num| *num > from_num && *num <= to_num)
+                .map(|_| (version.to_string(), heading.to_string(), Vec::new()));
+        } else if let Some((_, _, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    finish(current);
+    sections
+}
+
+/// Release notes for the versions after `from_version` up to `to_version`, as one
+/// Markdown string. Used when an update's own notes are a placeholder.
+pub(crate) async fn notes_between(
+    app: &tauri::AppHandle,
+    from_version: &str,
+    to_version: &str,
+) -> Option<String> {
+    let from_num = parse_semver_num(from_version)?;
+    let to_num = parse_semver_num(to_version)?;
+    if to_num <= from_num {
+        return None;
+    }
+    let changelog = load_changelog(app).await?;
+    let sections = sections_between(&changelog, from_num, to_num);
+    if sections.is_empty() {
+        return None;
+    }
+    Some(
+        sections
+            .iter()
+            .map(|section| format!("## {}\n\n{}", section.heading, section.body))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    )
+}
+
+fn history_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(VERSION_HISTORY_FILE_NAME))
+}
+
+fn read_history<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> VersionHistory {
+    history_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+async fn changelog_since<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    since_version: Option<String>,
+) -> Changelog {
+    let current_version = app.package_info().version.to_string();
+    let since_version = since_version
+        .map(|version| version.trim().to_string())
+        .filter(|version| !version.is_empty())
+        .or_else(|| read_history(app).updated_from);
+    let to_num = parse_semver_num(&current_version);
+    let from_num = match (&since_version, to_num) {
+        (Some(since), _) => parse_semver_num(since),
+        (None, to_num) => to_num.map(|num| num.saturating_sub(1)),
+    };
+    let sections = match (from_num, to_num) {
+        (Some(from_num), Some(to_num)) if from_num < to_num => load_changelog(app)
+            .await
+            .map(|changelog| sections_between(&changelog, from_num, to_num))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    Changelog {
+        since_version,
+        current_version,
+        sections,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Inline Markdown for changelog lines: `code`, **bold** and [links](https://...).
+/// Everything else is escaped.
+fn render_inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = after.find('`') {
+                html.push_str(&format!("<code>{}</code>", escape_html(&after[..end])));
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        if let Some(after) = rest.strip_prefix("**") {
+            if let Some(end) = after.find("**") {
+                html.push_str(&format!(
+                    "<strong>{}</strong>",
+                    render_inline(&after[..end])
+                ));
+                rest = &after[end + 2..];
+                continue;
+            }
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            let link = after.find("](").and_then(|label_end| {
+                let target = &after[label_end + 2..];
+                let target_end = target.find(')')?;
+                Some((
+                    label_end,
+                    &target[..target_end],
+                    label_end + 2 + target_end + 1,
+                ))
+            });
+            if let Some((label_end, target, consumed)) = link {
+                let label = render_inline(&after[..label_end]);
+                if target.starts_with("https://") || target.starts_with("http://") {
+                    html.push_str(&format!(r#"<a href="{}">{label}</a>"#, escape_html(target)));
+                } else {
+                    html.push_str(&label);
+                }
+                rest = &after[consumed..];
+                continue;
+            }
+        }
+        let next = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| matches!(c, '`' | '*' | '['))
+            .map_or(rest.len(), |(index, _)| index);
+        html.push_str(&escape_html(&rest[..next]));
+        rest = &rest[next..];
+    }
+    html
+}
+
+/// The subset of Markdown CHANGELOG.md uses: headings, bullet lists and paragraphs.
+fn render_markdown(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut in_list = false;
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush_paragraph = |html: &mut String, paragraph: &mut Vec<&str>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>", render_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "));
+        if item.is_none() && in_list && (trimmed.is_empty() || !line.starts_with(' ')) {
+            html.push_str("</ul>");
+            in_list = false;
+        }
+        if let Some(item) = item {
+            flush_paragraph(&mut html, &mut paragraph);
+            if !in_list {
+                html.push_str("<ul>");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>", render_inline(item)));
+        } else if let Some(heading) = trimmed.strip_prefix("### ") {
+            flush_paragraph(&mut html, &mut paragraph);
+            html.push_str(&format!("<h3>{}</h3>", render_inline(heading)));
+        } else if trimmed.is_empty() || trimmed.starts_with('#') {
+            flush_paragraph(&mut html, &mut paragraph);
+        } else if in_list {
+            // A wrapped list item continues on an indented line.
+            if let Some(open) = html.rfind("</li>") {
+                html.insert_str(open, &format!(" {}", render_inline(trimmed)));
+            }
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush_paragraph(&mut html, &mut paragraph);
+    if in_list {
+        html.push_str("</ul>");
+    }
+    html
+}
+
+fn whats_new_html<R: tauri::Runtime>(app: &tauri::AppHandle<R>, changelog: &Changelog) -> String {
+    let title = i18n::t_args(app, "whatsNew.title", &[("name", &app.package_info().name)]);
+    let content = if changelog.sections.is_empty() {
+        format!(r#"<p class="empty">{}</p>"#, i18n::t(app, "whatsNew.empty"))
+    } else {
+        changelog
+            .sections
+            .iter()
+            .map(|section| {
+                format!(
+                    "<section><h2>{}</h2>{}</section>",
+                    escape_html(&section.heading.replace(['[', ']'], "")),
+                    render_markdown(&section.body)
+                )
+            })
+            .collect()
+    };
+    format!(
+        r#"<!doctype html>
+<html><head><meta charset="utf-8"><style>
+:root {{ color-scheme: light dark; }}
+body {{ margin: 0; padding: 32px 40px; box-sizing: border-box; font: 14px/1.55 -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif;
+  background: #f5f5f4; color: #1c1917; }}
+@media (prefers-color-scheme: dark) {{ body {{ background: #1c1917; color: #e7e5e4; }} code {{ background: #292524; }} section {{ border-color: #44403c; }} }}
+h1 {{ font-size: 20px; margin: 0 0 20px; }}
+h2 {{ font-size: 15px; margin: 0 0 8px; }}
+h3 {{ font-size: 13px; margin: 16px 0 4px; opacity: .75; text-transform: uppercase; letter-spacing: .04em; }}
+section {{ padding: 16px 0; border-top: 1px solid #e7e5e4; }}
+ul {{ margin: 4px 0; padding-left: 20px; }}
+li {{ margin: 2px 0; }}
+p {{ margin: 6px 0; }}
+p.empty {{ opacity: .7; }}
+code {{ font: 12px ui-monospace, SFMono-Regular, Menlo, monospace; background: #e7e5e4; padding: 1px 4px; border-radius: 4px; }}
+a {{ color: inherit; }}
+</style></head>
+<body><h1>{title}</h1>{content}</body></html>"#,
+        title = escape_html(&title),
+    )
+}
+
+/// Opens (or refreshes and focuses) the What's New window with the notes for the
+/// releases after `since_version`.
+async fn open_whats_new<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    since_version: Option<String>,
+) -> Result<(), String> {
+    let changelog = changelog_since(app, since_version).await;
+    let data_url = format!(
+        "data:text/html;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(whats_new_html(app, &changelog))
+    );
+    let url = url::Url::parse(&data_url).map_err(|err| err.to_string())?;
+
+    if let Some(window) = app.get_webview_window(WHATS_NEW_LABEL) {
+        window.navigate(url).map_err(|err| err.to_string())?;
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+    let link_app = app.clone();
+    let title = i18n::t_args(app, "whatsNew.title", &[("name", &app.package_info().name)]);
+    WebviewWindowBuilder::new(app, WHATS_NEW_LABEL, WebviewUrl::External(url))
+        .title(title)
+        .inner_size(WHATS_NEW_WIDTH, WHATS_NEW_HEIGHT)
+        .min_inner_size(360.0, 320.0)
+        .center()
+        .incognito(true)
+        // The page is static; links open in the browser instead of replacing it.
+        .on_navigation(move |target| {
+            if target.scheme() == "data" {
+                return true;
+            }
+            if matches!(target.scheme(), "http" | "https") {
+                menu::open_external_url(&link_app, target.as_str());
+            }
+            false
+        })
+        .build()
+        .map_err(|err| err.to_string())?;
+    log::info!("[changelog] opened What's New");
+    Ok(())
+}
+
+/// Menu entry point; errors only go to the log.
+pub(crate) fn show_whats_new<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = open_whats_new(&app, None).await {
+            log::warn!("[changelog] failed to open What's New: {err}");
+        }
+    });
+}
+
+/// Records the running version. On the first start after an update, caches the
+/// changelog and opens What's New for the releases that were skipped.
+pub(crate) fn check_after_update(app: &tauri::AppHandle) {
+    let current = app.package_info().version.to_string();
+    let mut history = read_history(app);
+    let previous = history.last_version.replace(current.clone());
+    let updated = previous.as_deref().is_some_and(|previous| {
+        previous != current
+            && parse_semver_num(previous)
+                .zip(parse_semver_num(&current))
+                .is_some_and(|(previous, current)| previous < current)
+    });
+    if !updated && previous.is_some() {
+        return;
+    }
+    if updated {
+        history.updated_from = previous;
+    }
+    if let Some(path) = history_path(app) {
+        let written = serde_json::to_string_pretty(&history)
+            .map_err(std::io::Error::other)
+            .and_then(|raw| {
+                path.parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|()| fs::write(&path, raw))
+            });
+        if let Err(err) = written {
+            log::warn!("[changelog] failed to record version: {err}");
+        }
+    }
+    let show = updated
+        && settings::get_setting(app, WHATS_NEW_AFTER_UPDATE_KEY)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(true);
+    if show {
+        log::info!(
+            "[changelog] updated from {} to {current}",
+            history.updated_from.as_deref().unwrap_or("?")
+        );
+        show_whats_new(app);
+    }
+}
+
+/// Release notes for the versions after `since_version` up to the running one.
+/// Without `since_version`, the version the last update replaced is used.
+#[tauri::command]
+pub(crate) async fn desktop_get_changelog(
+    app: tauri::AppHandle,
+    since_version: Option<String>,
+) -> Result<Changelog, String> {
+    Ok(changelog_since(&app, since_version).await)
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_open_whats_new(
+    app: tauri::AppHandle,
+    since_version: Option<String>,
+) -> Result<(), String> {
+    open_whats_new(&app, since_version).await
+}
@@ -0,0 +1,84 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, RgbaImage};
+use tauri::image::Image;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Largest decoded image accepted for writing, to keep a bad paste from exhausting memory.
+const MAX_IMAGE_PIXELS: u64 = 64 * 1024 * 1024;
+
+fn decode_png(png_bytes: &[u8]) -> Result<RgbaImage, String> {
+    let decoded = image::load_from_memory_with_format(png_bytes, ImageFormat::Png)
+        .map_err(|err| format!("Invalid PNG: {err}"))?;
+    if u64::from(decoded.width()) * u64::from(decoded.height()) > MAX_IMAGE_PIXELS {
+        return Err("Image is too large for the clipboard".to_string());
+    }
+    Ok(decoded.to_rgba8())
+}
+
+fn encode_png(image: &Image<'_>) -> Result<Vec<u8>, String> {
+    let rgba = RgbaImage::from_raw(image.width(), image.height(), image.rgba().to_vec())
+        .ok_or_else(|| "Clipboard image has an unexpected size".to_string())?;
+    let mut out = Cursor::new(Vec::new());
+    rgba.write_to(&mut out, ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(out.into_inner())
+}
+
+// Clipboard access must stay off the main thread (it can deadlock on Linux), so every
+// command here is async and therefore runs on the async runtime.
+
+/// Writes plain text, or rich text when `html` is given (with `text` as the plain fallback).
+#[tauri::command]
+pub(crate) async fn desktop_clipboard_write_text(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    text: String,
+    html: Option<String>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "write the clipboard")?;
+    let result = match html {
+        Some(html) => app.clipboard().write_html(html, Some(text)),
+        None => app.clipboard().write_text(text),
+    };
+    result.map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_clipboard_read_text(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<Option<String>, String> {
+    crate::origin::require_local(&webview, "read the clipboard")?;
+    // An empty clipboard or non-text content is not an error for callers.
+    Ok(app.clipboard().read_text().ok())
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_clipboard_write_image(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    png_bytes: Vec<u8>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "write the clipboard")?;
+    let rgba = decode_png(&png_bytes)?;
+    let (width, height) = rgba.dimensions();
+    let image = Image::new_owned(rgba.into_raw(), width, height);
+    app.clipboard()
+        .write_image(&image)
+        .map_err(|err| err.to_string())
+}
+
+/// Returns the clipboard image as PNG bytes (an `ArrayBuffer` on the JS side), or an
+/// empty buffer when the clipboard holds no image.
+#[tauri::command]
+pub(crate) async fn desktop_clipboard_read_image(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<tauri::ipc::Response, String> {
+    crate::origin::require_local(&webview, "read the clipboard")?;
+    let Ok(image) = app.clipboard().read_image() else {
+        return Ok(tauri::ipc::Response::new(Vec::new()));
+    };
+    Ok(tauri::ipc::Response::new(encode_png(&image)?))
+}
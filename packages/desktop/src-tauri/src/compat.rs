@@ -0,0 +1,200 @@
+use std::{
+    collections::HashSet,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    i18n,
+};
+
+/// Server HTTP API versions this build of the desktop app can talk to.
+const SUPPORTED_API_VERSIONS: RangeInclusive<u32> = 1..=1;
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Mismatches the user chose to ignore this session, keyed by origin and API version.
+#[derive(Default)]
+pub(crate) struct CompatState {
+    overridden: Mutex<HashSet<(String, u32)>>,
+    /// Set when the user asked to update before the main window existed.
+    pending_update_check: AtomicBool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerVersion {
+    version: Option<String>,
+    api_version: u32,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VersionMismatch {
+    url: String,
+    server_version: Option<String>,
+    server_api_version: u32,
+    desktop_version: String,
+    supported_api_versions: [u32; 2],
+}
+
+/// What the caller should do after [`check`].
+pub(crate) enum Compatibility {
+    /// Compatible, unknown (pre-handshake server) or overridden.
+    Proceed,
+    /// The user chose to look for an app update instead.
+    UpdateRequested,
+}
+
+async fn fetch_version(
+    client: &reqwest::Client,
+    url: &str,
+    authorization: Option<&str>,
+) -> Result<Option<ServerVersion>, String> {
+    let mut request = client
+        .get(format!("{}/version", url.trim_end_matches('/')))
+        .timeout(VERSION_TIMEOUT);
+    if let Some(header) = authorization {
+        request = request.header(reqwest::header::AUTHORIZATION, header);
+    }
+    let response = request.send().await.map_err(|err| err.to_string())?;
+    // Servers from before the handshake existed have no `/version`.
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let response = response.error_for_status().map_err(|err| err.to_string())?;
+    response
+        .json()
+        .await
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+fn confirm_override(app: &tauri::AppHandle, mismatch: &VersionMismatch) -> bool {
+    let server_version = mismatch
+        .server_version
+        .clone()
+        .unwrap_or_else(|| mismatch.server_api_version.to_string());
+    app.dialog()
+        .message(i18n::t_args(
+            app,
+            "dialog.versionMismatch.message",
+            &[
+                ("url", &mismatch.url),
+                ("serverVersion", &server_version),
+                ("desktopVersion", &mismatch.desktop_version),
+            ],
+        ))
+        .title(i18n::t(app, "dialog.versionMismatch.title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.versionMismatch.override"),
+            i18n::t(app, "dialog.versionMismatch.update"),
+        ))
+        .blocking_show()
+}
+
+/// Compares the API version of the server at `url` with what this build supports.
+/// On a mismatch, emits `openchamber:version-mismatch` and asks the user to either
+/// continue anyway (remembered for the session) or look for an app update.
+/// Unreachable or pre-handshake servers are let through; the health check already
+/// covers reachability.
+pub(crate) async fn check(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    url: &str,
+    authorization: Option<&str>,
+) -> Compatibility {
+    let server = match fetch_version(client, url, authorization).await {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            log::info!("[compat] {url} predates the version handshake");
+            return Compatibility::Proceed;
+        }
+        Err(err) => {
+            log::warn!("[compat] version check for {url} failed: {err}");
+            return Compatibility::Proceed;
+        }
+    };
+    if SUPPORTED_API_VERSIONS.contains(&server.api_version) {
+        return Compatibility::Proceed;
+    }
+
+    let origin = url::Url::parse(url)
+        .map(|parsed| parsed.origin().ascii_serialization())
+        .unwrap_or_else(|_| url.to_string());
+    let key = (origin, server.api_version);
+    let state = app.state::<CompatState>();
+    if state
+        .overridden
+        .lock()
+        .expect("compat overrides mutex")
+        .contains(&key)
+    {
+        return Compatibility::Proceed;
+    }
+
+    let mismatch = VersionMismatch {
+        url: url.to_string(),
+        server_version: server.version,
+        server_api_version: server.api_version,
+        desktop_version: app.package_info().version.to_string(),
+        supported_api_versions: [
+            *SUPPORTED_API_VERSIONS.start(),
+            *SUPPORTED_API_VERSIONS.end(),
+        ],
+    };
+    log::warn!(
+        "[compat] {url} speaks API v{} but this app supports v{}..=v{}",
+        mismatch.server_api_version,
+        mismatch.supported_api_versions[0],
+        mismatch.supported_api_versions[1]
+    );
+    events::emit(app, DesktopEvent::VersionMismatch(mismatch.clone()));
+
+    let dialog_app = app.clone();
+    let proceed =
+        tauri::async_runtime::spawn_blocking(move || confirm_override(&dialog_app, &mismatch))
+            .await
+            .unwrap_or(true);
+    if proceed {
+        state
+            .overridden
+            .lock()
+            .expect("compat overrides mutex")
+            .insert(key);
+        return Compatibility::Proceed;
+    }
+    Compatibility::UpdateRequested
+}
+
+/// Starts the update check now, or once the main window has loaded if it does not
+/// exist yet.
+pub(crate) fn request_update_check(app: &tauri::AppHandle) {
+    if app.get_webview_window("main").is_some() {
+        events::emit(app, DesktopEvent::CheckForUpdates);
+    } else {
+        app.state::<CompatState>()
+            .pending_update_check
+            .store(true, Ordering::SeqCst);
+    }
+}
+
+/// Main-window page-load hook for update checks requested during boot.
+pub(crate) fn flush_pending_update_check<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<CompatState>() else {
+        return;
+    };
+    if state.pending_update_check.swap(false, Ordering::SeqCst) {
+        events::emit(app, DesktopEvent::CheckForUpdates);
+    }
+}
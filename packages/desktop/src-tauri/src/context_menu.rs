@@ -0,0 +1,178 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu};
+use tauri::Manager;
+use tokio::sync::oneshot;
+
+/// Item ids are namespaced per popup so a late click on a stale menu is ignored.
+pub(crate) const CONTEXT_MENU_ID_PREFIX: &str = "context:";
+
+/// macOS and Windows block in `popup_menu` until the menu closes, but the click
+/// event is delivered afterwards through the event loop; wait this long for it
+/// before treating the menu as dismissed. GTK returns immediately, so there this
+/// only bounds how long an unanswered menu stays pending.
+#[cfg(not(target_os = "linux"))]
+const DISMISS_GRACE: Duration = Duration::from_millis(250);
+#[cfg(target_os = "linux")]
+const DISMISS_GRACE: Duration = Duration::from_secs(120);
+
+/// One entry of the item tree sent by the web UI.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ContextMenuItem {
+    #[serde(rename_all = "camelCase")]
+    Item {
+        id: String,
+        label: String,
+        #[serde(default)]
+        disabled: bool,
+        /// Rendered as a check item when present.
+        checked: Option<bool>,
+        accelerator: Option<String>,
+    },
+    Separator,
+    #[serde(rename_all = "camelCase")]
+    Submenu {
+        label: String,
+        #[serde(default)]
+        disabled: bool,
+        items: Vec<ContextMenuItem>,
+    },
+}
+
+/// The popup currently awaiting a choice; opening another one dismisses it.
+#[derive(Default)]
+pub(crate) struct ContextMenuState {
+    next_popup: AtomicU64,
+    pending: Mutex<Option<(u64, oneshot::Sender<Option<String>>)>>,
+}
+
+fn build_items<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    popup: u64,
+    items: &[ContextMenuItem],
+) -> tauri::Result<Vec<MenuItemKind<R>>> {
+    items
+        .iter()
+        .map(|item| {
+            Ok(match item {
+                ContextMenuItem::Item {
+                    id,
+                    label,
+                    disabled,
+                    checked,
+                    accelerator,
+                } => {
+                    let id = format!("{CONTEXT_MENU_ID_PREFIX}{popup}:{id}");
+                    match checked {
+                        Some(checked) => MenuItemKind::Check(CheckMenuItem::with_id(
+                            app,
+                            id,
+                            label,
+                            !disabled,
+                            *checked,
+                            accelerator.as_deref(),
+                        )?),
+                        None => MenuItemKind::MenuItem(MenuItem::with_id(
+                            app,
+                            id,
+                            label,
+                            !disabled,
+                            accelerator.as_deref(),
+                        )?),
+                    }
+                }
+                ContextMenuItem::Separator => {
+                    MenuItemKind::Predefined(PredefinedMenuItem::separator(app)?)
+                }
+                ContextMenuItem::Submenu {
+                    label,
+                    disabled,
+                    items,
+                } => {
+                    let submenu = Submenu::new(app, label, !disabled)?;
+                    for child in build_items(app, popup, items)? {
+                        submenu.append(&child)?;
+                    }
+                    MenuItemKind::Submenu(submenu)
+                }
+            })
+        })
+        .collect()
+}
+
+fn resolve<R: tauri::Runtime>(app: &tauri::AppHandle<R>, popup: u64, choice: Option<String>) {
+    let Some(state) = app.try_state::<ContextMenuState>() else {
+        return;
+    };
+    let mut pending = state.pending.lock().expect("context menu mutex");
+    if pending
+        .as_ref()
+        .is_some_and(|(current, _)| *current == popup)
+    {
+        if let Some((_, sender)) = pending.take() {
+            let _ = sender.send(choice);
+        }
+    }
+}
+
+/// Menu-event hook for ids under [`CONTEXT_MENU_ID_PREFIX`].
+pub(crate) fn handle_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str) {
+    let Some((popup, item_id)) = id
+        .strip_prefix(CONTEXT_MENU_ID_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return;
+    };
+    if let Ok(popup) = popup.parse() {
+        resolve(app, popup, Some(item_id.to_string()));
+    }
+}
+
+/// Shows a native context menu at the cursor over the calling window and resolves
+/// with the chosen item id, or `None` if the menu was dismissed.
+#[tauri::command]
+pub(crate) async fn desktop_show_context_menu(
+    window: tauri::WebviewWindow,
+    items: Vec<ContextMenuItem>,
+) -> Result<Option<String>, String> {
+    let app = window.app_handle().clone();
+    let state = app.state::<ContextMenuState>();
+    let popup = state.next_popup.fetch_add(1, Ordering::SeqCst);
+
+    let menu = Menu::new(&app).map_err(|err| err.to_string())?;
+    for item in build_items(&app, popup, &items).map_err(|err| err.to_string())? {
+        menu.append(&item).map_err(|err| err.to_string())?;
+    }
+
+    let (sender, receiver) = oneshot::channel();
+    if let Some((_, previous)) = state
+        .pending
+        .lock()
+        .expect("context menu mutex")
+        .replace((popup, sender))
+    {
+        let _ = previous.send(None);
+    }
+
+    let popup_window = window.as_ref().window();
+    tauri::async_runtime::spawn_blocking(move || popup_window.popup_menu(&menu))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())?;
+
+    let dismiss_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(DISMISS_GRACE).await;
+        resolve(&dismiss_app, popup, None);
+    });
+
+    Ok(receiver.await.unwrap_or(None))
+}
@@ -0,0 +1,323 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{
+    i18n, menu::open_external_url, profiles, read_desktop_setting_from_disk,
+    sidecar_log::sidecar_log_path, write_desktop_setting_to_disk,
+};
+
+const CRASHES_DIR_NAME: &str = "crashes";
+const MAX_CRASH_REPORTS: usize = 50;
+const SIDECAR_LOG_TAIL_LINES: usize = 200;
+const LAST_CRASH_PROMPT_KEY: &str = "desktopLastCrashPromptAt";
+const GITHUB_NEW_ISSUE_URL: &str = "https://github.com/btriapitsyn/openchamber/issues/new";
+// GitHub rejects very long prefilled URLs; the full report stays on disk.
+const ISSUE_BODY_MAX_CHARS: usize = 6000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CrashKind {
+    Panic,
+    Sidecar,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CrashReport {
+    id: String,
+    kind: CrashKind,
+    created_at: String,
+    app_version: String,
+    os: String,
+    arch: String,
+    message: String,
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(default)]
+    thread: Option<String>,
+    #[serde(default)]
+    backtrace: Option<String>,
+    #[serde(default)]
+    exit_code: Option<i32>,
+    #[serde(default)]
+    signal: Option<i32>,
+    #[serde(default)]
+    uptime_secs: Option<u64>,
+    #[serde(default)]
+    log_tail: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CrashReportSummary {
+    id: String,
+    kind: CrashKind,
+    created_at: String,
+    app_version: String,
+    message: String,
+    path: String,
+}
+
+fn crashes_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(CRASHES_DIR_NAME))
+}
+
+fn new_report(kind: CrashKind, app_version: &str, message: String) -> CrashReport {
+    let now = chrono::Utc::now();
+    let suffix = match kind {
+        CrashKind::Panic => "panic",
+        CrashKind::Sidecar => "sidecar",
+    };
+    CrashReport {
+        id: format!("{}-{suffix}", now.format("%Y%m%d-%H%M%S-%3f")),
+        kind,
+        created_at: now.to_rfc3339(),
+        app_version: app_version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        message,
+        location: None,
+        thread: None,
+        backtrace: None,
+        exit_code: None,
+        signal: None,
+        uptime_secs: None,
+        log_tail: None,
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let json = serde_json::to_string_pretty(report).map_err(std::io::Error::other)?;
+    fs::write(dir.join(format!("{}.json", report.id)), json)?;
+    prune_reports(dir);
+    Ok(())
+}
+
+fn report_paths(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Ids start with a timestamp, so name order is chronological.
+    paths.sort();
+    paths
+}
+
+fn prune_reports(dir: &Path) {
+    let paths = report_paths(dir);
+    if paths.len() > MAX_CRASH_REPORTS {
+        for path in &paths[..paths.len() - MAX_CRASH_REPORTS] {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn read_reports(dir: &Path) -> Vec<(PathBuf, CrashReport)> {
+    report_paths(dir)
+        .into_iter()
+        .rev()
+        .filter_map(|path| {
+            let raw = fs::read_to_string(&path).ok()?;
+            let report = serde_json::from_str::<CrashReport>(&raw).ok()?;
+            Some((path, report))
+        })
+        .collect()
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Writes every Rust panic to `crashes/` before running the default hook.
+pub(crate) fn install_panic_hook<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(dir) = crashes_dir(app) else {
+        return;
+    };
+    let app_version = app.package_info().version.to_string();
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let mut report = new_report(CrashKind::Panic, &app_version, panic_message(info));
+        report.location = info
+            .location()
+            .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column()));
+        report.thread = std::thread::current().name().map(str::to_string);
+        report.backtrace = Some(Backtrace::force_capture().to_string());
+        if let Err(err) = write_report(&dir, &report) {
+            eprintln!("[crash] failed to write panic report: {err}");
+        }
+        previous(info);
+    }));
+}
+
+fn sidecar_log_tail<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    let raw = fs::read_to_string(sidecar_log_path(app)?).ok()?;
+    let lines: Vec<&str> = raw.lines().collect();
+    let start = lines.len().saturating_sub(SIDECAR_LOG_TAIL_LINES);
+    Some(lines[start..].join("\n"))
+}
+
+/// Records an abnormal sidecar exit together with the tail of its log.
+pub(crate) fn record_sidecar_crash<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    code: Option<i32>,
+    signal: Option<i32>,
+    uptime: Option<Duration>,
+) {
+    let Some(dir) = crashes_dir(app) else {
+        return;
+    };
+    let mut report = new_report(
+        CrashKind::Sidecar,
+        &app.package_info().version.to_string(),
+        format!("Sidecar exited unexpectedly (code={code:?}, signal={signal:?})"),
+    );
+    report.exit_code = code;
+    report.signal = signal;
+    report.uptime_secs = uptime.map(|uptime| uptime.as_secs());
+    report.log_tail = sidecar_log_tail(app);
+    if let Err(err) = write_report(&dir, &report) {
+        log::warn!("[crash] failed to write sidecar crash report: {err}");
+    }
+}
+
+fn issue_url(report: &CrashReport) -> Option<String> {
+    let mut body = format!(
+        "**Version:** {}\n**OS:** {} ({})\n**Time:** {}\n\n**Message:** {}\n",
+        report.app_version, report.os, report.arch, report.created_at, report.message
+    );
+    if let Some(location) = report.location.as_deref() {
+        body.push_str(&format!("**Location:** {location}\n"));
+    }
+    if let Some(backtrace) = report.backtrace.as_deref() {
+        body.push_str(&format!("\n```\n{backtrace}\n```\n"));
+    }
+    if body.chars().count() > ISSUE_BODY_MAX_CHARS {
+        body = body.chars().take(ISSUE_BODY_MAX_CHARS).collect();
+        body.push_str("\n```\n(truncated; full report in the crashes folder)\n");
+    }
+
+    let title = format!(
+        "Crash: {}",
+        report.message.lines().next().unwrap_or_default()
+    );
+    url::Url::parse_with_params(
+        GITHUB_NEW_ISSUE_URL,
+        &[
+            ("labels", "bug"),
+            ("title", title.as_str()),
+            ("body", body.as_str()),
+        ],
+    )
+    .ok()
+    .map(String::from)
+}
+
+/// Offers to open a prefilled GitHub issue if the app panicked since the last prompt.
+pub(crate) fn prompt_for_unseen_crash(app: &tauri::AppHandle) {
+    let Some(dir) = crashes_dir(app) else {
+        return;
+    };
+    let last_prompted = read_desktop_setting_from_disk(LAST_CRASH_PROMPT_KEY)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    // RFC 3339 UTC timestamps compare correctly as strings.
+    let Some((_, report)) = read_reports(&dir)
+        .into_iter()
+        .find(|(_, report)| report.kind == CrashKind::Panic)
+        .filter(|(_, report)| report.created_at > last_prompted)
+    else {
+        return;
+    };
+
+    let _ = write_desktop_setting_to_disk(
+        LAST_CRASH_PROMPT_KEY,
+        serde_json::Value::String(report.created_at.clone()),
+    );
+
+    let name = app.package_info().name.clone();
+    let app_handle = app.clone();
+    app.dialog()
+        .message(i18n::t_args(
+            app,
+            "dialog.crash.message",
+            &[("name", &name), ("message", &report.message)],
+        ))
+        .title(i18n::t_args(app, "dialog.crash.title", &[("name", &name)]))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.crash.report"),
+            i18n::t(app, "dialog.crash.notNow"),
+        ))
+        .show(move |report_it| {
+            if report_it {
+                if let Some(url) = issue_url(&report) {
+                    open_external_url(&app_handle, &url);
+                }
+            }
+        });
+}
+
+#[tauri::command]
+pub(crate) fn desktop_list_crash_reports(
+    app: tauri::AppHandle,
+) -> Result<Vec<CrashReportSummary>, String> {
+    let Some(dir) = crashes_dir(&app) else {
+        return Ok(Vec::new());
+    };
+    Ok(read_reports(&dir)
+        .into_iter()
+        .map(|(path, report)| CrashReportSummary {
+            id: report.id,
+            kind: report.kind,
+            created_at: report.created_at,
+            app_version: report.app_version,
+            message: report.message,
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_delete_crash_report(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<bool, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report id: {id}"));
+    }
+    let Some(dir) = crashes_dir(&app) else {
+        return Ok(false);
+    };
+    match fs::remove_file(dir.join(format!("{id}.json"))) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.to_string()),
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
+use ts_rs::TS;
+
+use crate::events::{self, DesktopEvent};
+use crate::tray::show_main_window;
+
+pub(crate) const DEEP_LINK_SCHEME: &str = "openchamber";
+const MAX_SESSION_ID_LEN: usize = 128;
+
+/// Navigation target decoded from an `openchamber://` URL.
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum DeepLinkTarget {
+    /// `openchamber://session/<id>`
+    Session { id: String },
+    /// `openchamber://workspace?path=/repo`
+    Workspace { path: String },
+}
+
+/// Links received before the main window exists (cold start), delivered once it loads.
+#[derive(Default)]
+pub(crate) struct DeepLinkState {
+    pending: Mutex<Vec<DeepLinkTarget>>,
+}
+
+pub(crate) fn is_valid_session_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_SESSION_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub(crate) fn parse_deep_link(raw: &str) -> Option<DeepLinkTarget> {
+    let parsed = url::Url::parse(raw.trim()).ok()?;
+    if parsed.scheme() != DEEP_LINK_SCHEME {
+        return None;
+    }
+
+    match parsed.host_str()? {
+        "session" => {
+            let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+            let id = segments.next()?;
+            if segments.next().is_some() || !is_valid_session_id(id) {
+                return None;
+            }
+            Some(DeepLinkTarget::Session { id: id.to_string() })
+        }
+        "workspace" => {
+            let path = parsed
+                .query_pairs()
+                .find(|(key, _)| key == "path")
+                .map(|(_, value)| value.trim().to_string())?;
+            if path.is_empty() || !std::path::Path::new(&path).is_absolute() {
+                return None;
+            }
+            Some(DeepLinkTarget::Workspace { path })
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn handle_deep_links<R: tauri::Runtime, S: AsRef<str>>(
+    app: &tauri::AppHandle<R>,
+    urls: &[S],
+) {
+    let targets: Vec<DeepLinkTarget> = urls
+        .iter()
+        .filter_map(|raw| {
+            let raw = raw.as_ref();
+            let target = parse_deep_link(raw);
+            if target.is_none() {
+                log::warn!("[deeplink] ignoring invalid link: {raw}");
+            }
+            target
+        })
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    if app.get_webview_window("main").is_none() {
+        if let Some(state) = app.try_state::<DeepLinkState>() {
+            state
+                .pending
+                .lock()
+                .expect("deep link mutex")
+                .extend(targets);
+        }
+        return;
+    }
+
+    show_main_window(app);
+    for target in targets {
+        log::info!("[deeplink] dispatching {target:?}");
+        events::emit(app, DesktopEvent::DeepLink(target));
+    }
+}
+
+/// Delivers links queued during cold start. Called once the main window finished loading.
+pub(crate) fn flush_pending_deep_links<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<DeepLinkState>() else {
+        return;
+    };
+    let pending = std::mem::take(&mut *state.pending.lock().expect("deep link mutex"));
+    for target in pending {
+        log::info!("[deeplink] dispatching queued {target:?}");
+        events::emit(app, DesktopEvent::DeepLink(target));
+    }
+}
+
+pub(crate) fn setup_deep_links<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    // Installed bundles register the scheme themselves; this covers dev builds and
+    // AppImages that were never integrated with the desktop.
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(err) = app.deep_link().register_all() {
+        log::warn!("[deeplink] failed to register {DEEP_LINK_SCHEME}:// scheme: {err}");
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        let urls: Vec<String> = event.urls().into_iter().map(String::from).collect();
+        handle_deep_links(&handle, &urls);
+    });
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            let urls: Vec<String> = urls.into_iter().map(String::from).collect();
+            handle_deep_links(app, &urls);
+        }
+        Ok(None) => {}
+        Err(err) => log::warn!("[deeplink] failed to read launch URLs: {err}"),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_take_pending_deep_links(
+    state: tauri::State<'_, DeepLinkState>,
+) -> Vec<DeepLinkTarget> {
+    std::mem::take(&mut *state.pending.lock().expect("deep link mutex"))
+}
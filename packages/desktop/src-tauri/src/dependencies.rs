@@ -0,0 +1,173 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::Serialize;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    i18n,
+};
+
+/// Environment variables that point the sidecar at a specific `opencode` binary.
+const OPENCODE_OVERRIDE_VARS: &[&str] = &[
+    "OPENCHAMBER_OPENCODE_PATH",
+    "OPENCHAMBER_OPENCODE_BIN",
+    "OPENCODE_PATH",
+    "OPENCODE_BINARY",
+];
+
+/// The missing-dependency dialog is shown once per launch, not on every sidecar restart.
+static DIALOG_SHOWN: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct MissingDependency {
+    name: &'static str,
+    /// Whether the local server cannot work without it.
+    required: bool,
+    guidance: String,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DependencyMissingEvent {
+    missing: Vec<MissingDependency>,
+    searched_path: Vec<String>,
+}
+
+pub(crate) fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+fn find_binary(name: &str, path_segments: &[String]) -> Option<PathBuf> {
+    let names: Vec<String> = if cfg!(windows) {
+        ["exe", "cmd", "bat"]
+            .iter()
+            .map(|ext| format!("{name}.{ext}"))
+            .collect()
+    } else {
+        vec![name.to_string()]
+    };
+    path_segments.iter().find_map(|dir| {
+        names
+            .iter()
+            .map(|name| Path::new(dir).join(name))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+fn find_opencode(path_segments: &[String]) -> Option<PathBuf> {
+    OPENCODE_OVERRIDE_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|value| PathBuf::from(value.trim()))
+        .find(|path| is_executable(path))
+        .or_else(|| find_binary("opencode", path_segments))
+}
+
+/// npm-installed `opencode` is a script; returns the interpreter its shebang needs.
+fn script_interpreter(path: &Path) -> Option<&'static str> {
+    let mut head = [0u8; 128];
+    let read = std::fs::File::open(path).ok()?.read(&mut head).ok()?;
+    let head = String::from_utf8_lossy(&head[..read]);
+    let shebang = head.strip_prefix("#!")?.lines().next()?;
+    if shebang.contains("bun") {
+        Some("bun")
+    } else if shebang.contains("node") {
+        Some("node")
+    } else {
+        None
+    }
+}
+
+fn missing<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    name: &'static str,
+    required: bool,
+) -> MissingDependency {
+    MissingDependency {
+        name,
+        required,
+        guidance: i18n::t(app, &format!("dependency.guidance.{name}")),
+    }
+}
+
+fn show_dialog<R: tauri::Runtime>(app: &tauri::AppHandle<R>, missing: &[MissingDependency]) {
+    let guidance = missing
+        .iter()
+        .map(|dependency| format!("• {}: {}", dependency.name, dependency.guidance))
+        .collect::<Vec<_>>()
+        .join("\n");
+    app.dialog()
+        .message(i18n::t_args(
+            app,
+            "dependency.message",
+            &[("guidance", &guidance)],
+        ))
+        .title(i18n::t(app, "dependency.title"))
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+}
+
+/// Looks for the external tools the local server shells out to across the PATH
+/// the sidecar will get. Anything missing is reported as
+/// `openchamber:dependency-missing`; missing required tools also get a native
+/// dialog with install guidance. The sidecar itself is a compiled binary, so Node
+/// or Bun are only needed when `opencode` is an npm-installed script.
+pub(crate) fn check<R: tauri::Runtime>(app: &tauri::AppHandle<R>, path_segments: &[String]) {
+    let mut found = Vec::new();
+
+    match find_opencode(path_segments) {
+        Some(opencode) => {
+            if let Some(interpreter) = script_interpreter(&opencode) {
+                if find_binary(interpreter, path_segments).is_none() {
+                    found.push(missing(app, interpreter, true));
+                }
+            }
+        }
+        None => found.push(missing(app, "opencode", true)),
+    }
+    if find_binary("git", path_segments).is_none() {
+        found.push(missing(app, "git", true));
+    }
+    // Only needed for SSH tunnels to remote servers.
+    if find_binary("ssh", path_segments).is_none() {
+        found.push(missing(app, "ssh", false));
+    }
+
+    if found.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = found.iter().map(|dependency| dependency.name).collect();
+    log::warn!("[dependencies] missing: {}", names.join(", "));
+
+    let required: Vec<MissingDependency> = found
+        .iter()
+        .filter(|dependency| dependency.required)
+        .cloned()
+        .collect();
+    if !required.is_empty() && !DIALOG_SHOWN.swap(true, Ordering::SeqCst) {
+        show_dialog(app, &required);
+    }
+    events::emit(
+        app,
+        DesktopEvent::DependencyMissing(DependencyMissingEvent {
+            missing: found,
+            searched_path: path_segments.to_vec(),
+        }),
+    );
+}
@@ -0,0 +1,56 @@
+use tauri::Manager;
+
+use crate::settings;
+
+/// Hidden settings-store key; `true` lets release builds open the inspector. Only
+/// changed through [`desktop_set_devtools_enabled`].
+pub(crate) const DEVTOOLS_ENABLED_KEY: &str = "devtoolsEnabled";
+
+/// Debug builds and `--features devtools` builds always allow the inspector.
+fn allowed(app: &tauri::AppHandle) -> bool {
+    cfg!(any(debug_assertions, feature = "devtools"))
+        || settings::get_bool_setting(app, DEVTOOLS_ENABLED_KEY)
+}
+
+/// Opens or closes the web inspector of the window with `label`, or the calling
+/// one. Release builds refuse unless the hidden `devtoolsEnabled` setting is on.
+#[tauri::command]
+pub(crate) fn desktop_toggle_devtools(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    label: Option<String>,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "open devtools")?;
+    if !allowed(&app) {
+        return Err("Developer tools are disabled".to_string());
+    }
+    let label = label.unwrap_or_else(|| webview.label().to_string());
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("No window labelled {label}"))?;
+    let open = !window.is_devtools_open();
+    if open {
+        log::info!("[devtools] opening inspector for {label}");
+        window.open_devtools();
+    } else {
+        window.close_devtools();
+    }
+    Ok(open)
+}
+
+/// Lets release builds open the inspector (or stops them). The inspector can read
+/// and run anything in the page, so the user confirms the change first.
+#[tauri::command]
+pub(crate) async fn desktop_set_devtools_enabled(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    enabled: bool,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change devtools access")?;
+    let value = if enabled {
+        serde_json::Value::Bool(true)
+    } else {
+        serde_json::Value::Null
+    };
+    settings::set_protected_setting(&app, &webview, DEVTOOLS_ENABLED_KEY, value).await
+}
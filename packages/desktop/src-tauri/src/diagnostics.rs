@@ -0,0 +1,271 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    audit::{self, AuditCategory},
+    local_sidecar, logging, screenshot,
+    sidecar_log::{sidecar_log_path, SIDECAR_LOG_MAX_ROTATED},
+    startup::{self, StartupReport},
+    watchdog::SidecarResourceIncident,
+    SidecarCrashRecord,
+};
+
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticsSummary {
+    generated_at: String,
+    app_name: String,
+    app_version: String,
+    identifier: String,
+    tauri_version: &'static str,
+    webview_version: Option<String>,
+    os: &'static str,
+    os_version: Option<String>,
+    arch: &'static str,
+    server_url: Option<String>,
+    local_server_url: Option<String>,
+    sidecar_running: bool,
+    sidecar_health: &'static str,
+    sidecar_restart_attempts: u32,
+    recent_sidecar_crashes: Vec<SidecarCrashRecord>,
+    recent_sidecar_resource_incidents: Vec<SidecarResourceIncident>,
+    startup: Option<StartupReport>,
+}
+
+fn os_version() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let raw = fs::read_to_string("/etc/os-release").ok()?;
+        raw.lines()
+            .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|value| value.trim_matches('"').to_string())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("/usr/bin/sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let output = std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+async fn probe_health(url: &str) -> &'static str {
+    let Ok(client) = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(HEALTH_PROBE_TIMEOUT)
+        .build()
+    else {
+        return "unknown";
+    };
+    let health_url = format!("{}/health", url.trim_end_matches('/'));
+    match client.get(&health_url).send().await {
+        Ok(resp) if resp.status().is_success() => "ok",
+        Ok(_) => "unhealthy",
+        Err(_) => "unreachable",
+    }
+}
+
+async fn collect_summary(app: &tauri::AppHandle) -> DiagnosticsSummary {
+    let state = local_sidecar(app).unwrap_or_default();
+    let local_server_url = state.url.lock().expect("sidecar url mutex").clone();
+    let sidecar_running = state.child.lock().expect("sidecar mutex").is_some();
+    let sidecar_restart_attempts = *state
+        .restart_attempts
+        .lock()
+        .expect("sidecar restart mutex");
+    let recent_sidecar_crashes = state
+        .recent_crashes
+        .lock()
+        .expect("sidecar crashes mutex")
+        .clone();
+    let recent_sidecar_resource_incidents = state
+        .recent_resource_incidents
+        .lock()
+        .expect("sidecar incidents mutex")
+        .clone();
+
+    let sidecar_health = match local_server_url.as_deref() {
+        Some(url) => probe_health(url).await,
+        None => "not started",
+    };
+
+    let server_url = app
+        .get_webview_window("main")
+        .and_then(|window| window.url().ok())
+        .map(|url| url.origin().ascii_serialization());
+
+    let package = app.package_info();
+    DiagnosticsSummary {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        app_name: package.name.clone(),
+        app_version: package.version.to_string(),
+        identifier: app.config().identifier.clone(),
+        tauri_version: tauri::VERSION,
+        webview_version: tauri::webview_version().ok(),
+        os: std::env::consts::OS,
+        os_version: os_version(),
+        arch: std::env::consts::ARCH,
+        server_url,
+        local_server_url,
+        sidecar_running,
+        sidecar_health,
+        sidecar_restart_attempts,
+        recent_sidecar_crashes,
+        recent_sidecar_resource_incidents,
+        startup: startup::report(app),
+    }
+}
+
+fn log_files(app: &tauri::AppHandle) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+
+    if let Some(dir) = logging::log_dir(app) {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    files.push((format!("logs/app/{name}"), path));
+                }
+            }
+        }
+    }
+
+    if let Some(path) = sidecar_log_path(app) {
+        if path.is_file() {
+            files.push(("logs/sidecar/sidecar.log".to_string(), path.clone()));
+        }
+        for idx in 1..=SIDECAR_LOG_MAX_ROTATED {
+            let mut rotated = path.as_os_str().to_os_string();
+            rotated.push(format!(".{idx}"));
+            let rotated = PathBuf::from(rotated);
+            if rotated.is_file() {
+                files.push((format!("logs/sidecar/sidecar.log.{idx}"), rotated));
+            }
+        }
+    }
+
+    for path in audit::audit_log_files(app) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        files.push((format!("logs/audit/{name}"), path));
+    }
+
+    files
+}
+
+fn write_bundle(
+    target: &Path,
+    summary: &DiagnosticsSummary,
+    screenshot: Option<&[u8]>,
+    files: &[(String, PathBuf)],
+) -> anyhow::Result<()> {
+    let mut zip = ZipWriter::new(fs::File::create(target)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("summary.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(summary)?.as_bytes())?;
+
+    if let Some(screenshot) = screenshot {
+        // Already compressed; deflating a PNG again only costs time.
+        zip.start_file(
+            "screenshot.png",
+            options.compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(screenshot)?;
+    }
+
+    for (name, path) in files {
+        // Log files may be rotated away or locked mid-export; skip rather than fail the bundle.
+        match fs::read(path) {
+            Ok(bytes) => {
+                zip.start_file(name.as_str(), options)?;
+                zip.write_all(&bytes)?;
+            }
+            Err(err) => log::warn!("[diagnostics] skipped {}: {err}", path.display()),
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Builds a zip with logs and environment details, asking the user where to save it.
+/// Returns the saved path, or `None` if the save dialog was cancelled.
+#[tauri::command]
+pub(crate) async fn desktop_export_diagnostics(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<Option<String>, String> {
+    crate::origin::require_local(&webview, "export diagnostics")?;
+    let summary = collect_summary(&app).await;
+    // Taken before the save dialog covers the window.
+    let screenshot = screenshot::capture_main_window_png(&app).await;
+
+    let default_name = format!(
+        "openchamber-diagnostics-{}.zip",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let dialog_app = app.clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .set_file_name(default_name)
+            .add_filter("Zip archive", &["zip"])
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let target = picked.into_path().map_err(|err| err.to_string())?;
+
+    let files = log_files(&app);
+    let bundle_target = target.clone();
+    let written = tauri::async_runtime::spawn_blocking(move || {
+        write_bundle(&bundle_target, &summary, screenshot.as_deref(), &files)
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result.map_err(|err| err.to_string()));
+    audit::record(
+        &app,
+        AuditCategory::FileWrite,
+        "export diagnostics",
+        target.to_string_lossy(),
+        &written,
+    );
+    written?;
+
+    log::info!("[diagnostics] exported to {}", target.display());
+    Ok(Some(target.to_string_lossy().to_string()))
+}
@@ -0,0 +1,277 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::{
+    audit::{self, AuditCategory},
+    path_policy::{self, PathError},
+    settings,
+};
+
+/// Settings-store key for a custom launch command, e.g. `code --goto {path}:{line}:{column}`.
+pub(crate) const EDITOR_COMMAND_KEY: &str = "editorCommand";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum EditorKind {
+    Vscode,
+    Cursor,
+    Zed,
+    Jetbrains,
+    Sublime,
+}
+
+impl EditorKind {
+    fn label(self) -> &'static str {
+        match self {
+            EditorKind::Vscode => "VS Code",
+            EditorKind::Cursor => "Cursor",
+            EditorKind::Zed => "Zed",
+            EditorKind::Jetbrains => "JetBrains",
+            EditorKind::Sublime => "Sublime Text",
+        }
+    }
+
+    fn args(self, path: &str, line: u32, column: u32) -> Vec<String> {
+        match self {
+            EditorKind::Vscode | EditorKind::Cursor => {
+                vec!["--goto".into(), format!("{path}:{line}:{column}")]
+            }
+            EditorKind::Zed | EditorKind::Sublime => vec![format!("{path}:{line}:{column}")],
+            EditorKind::Jetbrains => vec![
+                "--line".into(),
+                line.to_string(),
+                "--column".into(),
+                column.to_string(),
+                path.to_string(),
+            ],
+        }
+    }
+}
+
+/// Probe order; the first installed editor wins when no command is configured.
+const EDITOR_BINARIES: &[(EditorKind, &[&str])] = &[
+    (EditorKind::Cursor, &["cursor"]),
+    (EditorKind::Vscode, &["code", "code-insiders"]),
+    (EditorKind::Zed, &["zed", "zeditor"]),
+    (
+        EditorKind::Jetbrains,
+        &[
+            "idea",
+            "webstorm",
+            "pycharm",
+            "goland",
+            "rustrover",
+            "clion",
+        ],
+    ),
+    (EditorKind::Sublime, &["subl"]),
+];
+
+/// CLI shims inside app bundles, for when the app was launched without a login shell PATH.
+#[cfg(target_os = "macos")]
+const MACOS_BUNDLE_BINARIES: &[(EditorKind, &str)] = &[
+    (
+        EditorKind::Cursor,
+        "/Applications/Cursor.app/Contents/Resources/app/bin/cursor",
+    ),
+    (
+        EditorKind::Vscode,
+        "/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code",
+    ),
+    (EditorKind::Zed, "/Applications/Zed.app/Contents/MacOS/cli"),
+    (
+        EditorKind::Sublime,
+        "/Applications/Sublime Text.app/Contents/SharedSupport/bin/subl",
+    ),
+];
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DetectedEditor {
+    kind: EditorKind,
+    name: &'static str,
+    command: PathBuf,
+}
+
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    dirs.extend(["/usr/local/bin", "/opt/homebrew/bin"].map(PathBuf::from));
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        dirs.push(home.join(".local/bin"));
+        dirs.push(home.join(".local/share/JetBrains/Toolbox/scripts"));
+        dirs.push(home.join("Library/Application Support/JetBrains/Toolbox/scripts"));
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+        dirs.push(local.join("Programs/Microsoft VS Code/bin"));
+        dirs.push(local.join("Programs/cursor/resources/app/bin"));
+        dirs.push(local.join("JetBrains/Toolbox/scripts"));
+    }
+    dirs
+}
+
+fn find_binary(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    let candidates: &[&str] = if cfg!(windows) {
+        &[".cmd", ".exe", ".bat"]
+    } else {
+        &[""]
+    };
+    dirs.iter().find_map(|dir| {
+        candidates
+            .iter()
+            .map(|ext| dir.join(format!("{name}{ext}")))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+pub(crate) fn detect_editors() -> Vec<DetectedEditor> {
+    let dirs = search_dirs();
+    let mut found: Vec<DetectedEditor> = Vec::new();
+    for (kind, names) in EDITOR_BINARIES {
+        if let Some(command) = names.iter().find_map(|name| find_binary(&dirs, name)) {
+            found.push(DetectedEditor {
+                kind: *kind,
+                name: kind.label(),
+                command,
+            });
+            continue;
+        }
+        #[cfg(target_os = "macos")]
+        if let Some((_, bundle)) = MACOS_BUNDLE_BINARIES
+            .iter()
+            .find(|(bundle_kind, bundle)| bundle_kind == kind && Path::new(bundle).is_file())
+        {
+            found.push(DetectedEditor {
+                kind: *kind,
+                name: kind.label(),
+                command: PathBuf::from(bundle),
+            });
+        }
+    }
+    found
+}
+
+/// Expands `{path}`, `{line}` and `{column}` in a configured command. Placeholders are
+/// substituted after splitting so paths containing spaces stay a single argument.
+fn expand_template(template: &str, path: &str, line: u32, column: u32) -> Option<Vec<String>> {
+    let parts: Vec<String> = template
+        .split_whitespace()
+        .map(|part| {
+            part.replace("{path}", path)
+                .replace("{line}", &line.to_string())
+                .replace("{column}", &column.to_string())
+        })
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts)
+}
+
+fn launch(app: &tauri::AppHandle, program: &Path, args: &[String]) -> Result<(), String> {
+    let result = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to launch {}: {err}", program.display()));
+    audit::record(
+        app,
+        AuditCategory::Process,
+        "open in editor",
+        format!("{} {}", program.display(), args.join(" ")),
+        &result,
+    );
+    result
+}
+
+#[tauri::command]
+pub(crate) fn desktop_detect_editors() -> Vec<DetectedEditor> {
+    detect_editors()
+}
+
+/// Opens `path` at `line`/`column` in the configured editor, or the first one detected.
+/// Sets (or clears, with `None`) the custom launch command. It names a program to
+/// run, so the user confirms the change first.
+#[tauri::command]
+pub(crate) async fn desktop_set_editor_command(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    command: Option<String>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change the editor command")?;
+    let value = command
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .map_or(serde_json::Value::Null, serde_json::Value::String);
+    settings::set_protected_setting(&app, &webview, EDITOR_COMMAND_KEY, value).await
+}
+
+#[tauri::command]
+pub(crate) fn desktop_open_in_editor(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> Result<(), PathError> {
+    crate::origin::require_local(&webview, "open an editor")?;
+    let target = path_policy::check(&app, "open in editor", &path)?;
+    let target = target.to_string_lossy().into_owned();
+    let line = line.unwrap_or(1).max(1);
+    let column = column.unwrap_or(1).max(1);
+
+    let template = settings::get_setting(&app, EDITOR_COMMAND_KEY)
+        .and_then(|value| value.as_str().map(|s| s.trim().to_string()))
+        .filter(|value| !value.is_empty());
+    if let Some(template) = template {
+        let mut parts = expand_template(&template, &target, line, column)
+            .ok_or_else(|| "Editor command is empty".to_string())?;
+        let program = PathBuf::from(parts.remove(0));
+        log::info!("[editor] opening {target}:{line} with custom command");
+        return Ok(launch(&app, &program, &parts)?);
+    }
+
+    let editor = detect_editors()
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No supported editor found".to_string())?;
+    log::info!("[editor] opening {target}:{line} with {}", editor.name);
+    Ok(launch(
+        &app,
+        &editor.command,
+        &editor.kind.args(&target, line, column),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_template_substitutes_after_splitting() {
+        assert_eq!(
+            expand_template("code -g {path}:{line}:{column}", "/tmp/my file.rs", 3, 7),
+            Some(vec![
+                "code".to_string(),
+                "-g".to_string(),
+                "/tmp/my file.rs:3:7".to_string(),
+            ])
+        );
+        assert_eq!(
+            expand_template("  subl  {path}  ", "/a", 1, 1),
+            Some(vec!["subl".to_string(), "/a".to_string()])
+        );
+    }
+
+    #[test]
+    fn expand_template_rejects_blank_commands() {
+        assert_eq!(expand_template("", "/a", 1, 1), None);
+        assert_eq!(expand_template(" \t ", "/a", 1, 1), None);
+    }
+}
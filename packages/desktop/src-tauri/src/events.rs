@@ -0,0 +1,159 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{Emitter, EventTarget};
+use ts_rs::TS;
+
+use crate::{
+    appearance::OsThemeChangedEvent, archive::ArchiveProgressEvent, backup::BackupEvent,
+    compat::VersionMismatch, deeplink::DeepLinkTarget, dependencies::DependencyMissingEvent,
+    file_drop::FilesDroppedEvent, git::GitStatusSummary, git::WorktreePruneReport,
+    git_transfer::GitTransferEvent, health::ReconnectBannerEvent, health::ServerHealthEvent,
+    idle::IdleSnapshot, keep_awake::SleepPreventionEvent, lan::LanAccessInfo, launch::LaunchAction,
+    metrics::ProcessMetrics, power::NetworkInfo, power::PowerInfo, quit_guard::QuitCheckEvent,
+    recents::OpenWorkspaceEvent, search::SearchResultsEvent, servers::ServerConnectedEvent,
+    settings::SettingsChangedEvent, trust::WorkspaceTrustEvent, tunnel::TunnelStatusEvent,
+    updates::UpdateChannel, watchdog::SidecarResourceIncident, watcher::FsChangedEvent,
+    DesktopUpdateInfo, HealthProgressEvent, SidecarCrashedEvent, SidecarPortDecision,
+    UpdateProgressEvent,
+};
+
+/// Commands from the native menu, tray and global shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum MenuAction {
+    About,
+    Settings,
+    CommandPalette,
+    NewSession,
+    NewWorktreeSession,
+    ChangeWorkspace,
+    OpenGitTab,
+    OpenDiffTab,
+    OpenFilesTab,
+    OpenTerminalTab,
+    ThemeLight,
+    ThemeDark,
+    ThemeSystem,
+    ToggleSidebar,
+    Find,
+    ToggleMemoryDebug,
+    HelpDialog,
+    DownloadLogs,
+}
+
+/// Every event the desktop shell sends to the frontend, keyed by its Tauri event
+/// name. `cargo test` exports the TypeScript side to `packages/ui`, so listeners
+/// can narrow the payload with `Extract<DesktopEvent, { event: ... }>`.
+///
+/// PTY output is not listed: it goes to a per-session event name.
+#[derive(Serialize, TS)]
+#[serde(tag = "event", content = "payload")]
+#[ts(export)]
+pub(crate) enum DesktopEvent {
+    #[serde(rename = "openchamber:menu-action")]
+    MenuAction(MenuAction),
+    #[serde(rename = "openchamber:check-for-updates")]
+    CheckForUpdates,
+    #[serde(rename = "openchamber:update-available")]
+    UpdateAvailable(DesktopUpdateInfo),
+    #[serde(rename = "openchamber:update-progress")]
+    UpdateProgress(UpdateProgressEvent),
+    #[serde(rename = "openchamber:update-channel-changed")]
+    UpdateChannelChanged(UpdateChannel),
+    #[serde(rename = "openchamber:health-progress")]
+    HealthProgress(HealthProgressEvent),
+    #[serde(rename = "openchamber:server-info")]
+    ServerInfo(SidecarPortDecision),
+    #[serde(rename = "openchamber:server-health")]
+    ServerHealth(ServerHealthEvent),
+    #[serde(rename = "openchamber:reconnect-banner")]
+    ReconnectBanner(ReconnectBannerEvent),
+    #[serde(rename = "openchamber:server-connected")]
+    ServerConnected(ServerConnectedEvent),
+    #[serde(rename = "openchamber:version-mismatch")]
+    VersionMismatch(VersionMismatch),
+    #[serde(rename = "openchamber:sidecar-crashed")]
+    SidecarCrashed(SidecarCrashedEvent),
+    /// Carries the new server URL.
+    #[serde(rename = "openchamber:sidecar-restarted")]
+    SidecarRestarted(String),
+    #[serde(rename = "openchamber:sidecar-resource-warning")]
+    SidecarResourceWarning(SidecarResourceIncident),
+    #[serde(rename = "openchamber:lan-access")]
+    LanAccess(LanAccessInfo),
+    #[serde(rename = "openchamber:tunnel-status")]
+    TunnelStatus(TunnelStatusEvent),
+    #[serde(rename = "openchamber:dependency-missing")]
+    DependencyMissing(DependencyMissingEvent),
+    #[serde(rename = "openchamber:deep-link")]
+    DeepLink(DeepLinkTarget),
+    #[serde(rename = "openchamber:launch-action")]
+    LaunchAction(LaunchAction),
+    #[serde(rename = "openchamber:workspace-trust")]
+    WorkspaceTrust(WorkspaceTrustEvent),
+    #[serde(rename = "openchamber:open-workspace")]
+    OpenWorkspace(OpenWorkspaceEvent),
+    #[serde(rename = "openchamber:files-dropped")]
+    FilesDropped(FilesDroppedEvent),
+    #[serde(rename = "openchamber:fs-changed")]
+    FsChanged(FsChangedEvent),
+    #[serde(rename = "openchamber:search-results")]
+    SearchResults(SearchResultsEvent),
+    #[serde(rename = "openchamber:archive-progress")]
+    ArchiveProgress(ArchiveProgressEvent),
+    #[serde(rename = "openchamber:backup")]
+    Backup(BackupEvent),
+    #[serde(rename = "openchamber:git-status")]
+    GitStatus(GitStatusSummary),
+    #[serde(rename = "openchamber:git-transfer")]
+    GitTransfer(GitTransferEvent),
+    #[serde(rename = "openchamber:worktree-prune")]
+    WorktreePrune(WorktreePruneReport),
+    #[serde(rename = "openchamber:settings-changed")]
+    SettingsChanged(SettingsChangedEvent),
+    #[serde(rename = "openchamber:os-theme-changed")]
+    OsThemeChanged(OsThemeChangedEvent),
+    #[serde(rename = "openchamber:quit-check")]
+    QuitCheck(QuitCheckEvent),
+    #[serde(rename = "openchamber:sleep-prevention")]
+    SleepPrevention(SleepPreventionEvent),
+    #[serde(rename = "openchamber:idle-state")]
+    IdleState(IdleSnapshot),
+    #[serde(rename = "openchamber:power")]
+    Power(PowerInfo),
+    #[serde(rename = "openchamber:network")]
+    Network(NetworkInfo),
+    #[serde(rename = "openchamber:process-metrics")]
+    ProcessMetrics(ProcessMetrics),
+}
+
+/// Splits the serialized event into its name and payload.
+fn split(event: &DesktopEvent) -> Option<(String, Value)> {
+    let Value::Object(mut fields) = serde_json::to_value(event).ok()? else {
+        return None;
+    };
+    let Some(Value::String(name)) = fields.remove("event") else {
+        return None;
+    };
+    Some((name, fields.remove("payload").unwrap_or(Value::Null)))
+}
+
+/// Sends `event` to every window.
+pub(crate) fn emit<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: DesktopEvent) {
+    emit_to(app, EventTarget::Any, event);
+}
+
+/// Sends `event` to `target`, e.g. a single window label.
+pub(crate) fn emit_to<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    target: impl Into<EventTarget>,
+    event: DesktopEvent,
+) {
+    let Some((name, payload)) = split(&event) else {
+        log::warn!("[events] failed to serialize desktop event");
+        return;
+    };
+    if let Err(err) = app.emit_to(target, &name, payload) {
+        log::warn!("[events] failed to emit {name}: {err}");
+    }
+}
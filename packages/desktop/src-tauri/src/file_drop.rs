@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine;
+use serde::Serialize;
+use tauri::{DragDropEvent, Manager};
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    git, path_policy,
+};
+
+/// Files larger than this are described but not read.
+const MAX_INLINE_FILE_BYTES: u64 = 256 * 1024;
+/// Upper bound on inlined content across one drop.
+const MAX_INLINE_TOTAL_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_DROPPED_FILES: usize = 100;
+
+#[derive(Clone, Copy, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+enum ContentEncoding {
+    Utf8,
+    Base64,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct DroppedFile {
+    path: String,
+    name: String,
+    /// Path relative to the active workspace, when the file lives inside it.
+    relative_path: Option<String>,
+    is_dir: bool,
+    size: u64,
+    content: Option<String>,
+    encoding: Option<ContentEncoding>,
+    /// Why `content` is missing for a regular file (too large, unreadable, ...).
+    skipped_reason: Option<String>,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FilesDroppedEvent {
+    files: Vec<DroppedFile>,
+    workspace: Option<String>,
+    x: f64,
+    y: f64,
+    /// More paths were dropped than `MAX_DROPPED_FILES`; the rest were ignored.
+    truncated: bool,
+}
+
+fn relative_to(path: &Path, workspace: Option<&Path>) -> Option<String> {
+    let relative = path.strip_prefix(workspace?).ok()?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn describe<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    path: &Path,
+    workspace: Option<&Path>,
+    budget: &mut u64,
+) -> Option<DroppedFile> {
+    let canonical = fs::canonicalize(path).ok()?;
+    // Dropping a file is the user's consent to the app using it.
+    path_policy::grant(app, &canonical);
+    let metadata = fs::metadata(&canonical).ok()?;
+    let mut file = DroppedFile {
+        path: canonical.to_string_lossy().to_string(),
+        name: canonical
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        relative_path: relative_to(&canonical, workspace),
+        is_dir: metadata.is_dir(),
+        size: metadata.len(),
+        content: None,
+        encoding: None,
+        skipped_reason: None,
+    };
+    if !metadata.is_file() {
+        return Some(file);
+    }
+
+    if metadata.len() > MAX_INLINE_FILE_BYTES {
+        file.skipped_reason = Some(format!("larger than {} KiB", MAX_INLINE_FILE_BYTES / 1024));
+        return Some(file);
+    }
+    if metadata.len() > *budget {
+        file.skipped_reason = Some("drop size limit reached".to_string());
+        return Some(file);
+    }
+
+    match fs::read(&canonical) {
+        Ok(bytes) => {
+            *budget -= bytes.len() as u64;
+            match String::from_utf8(bytes) {
+                Ok(text) => {
+                    file.content = Some(text);
+                    file.encoding = Some(ContentEncoding::Utf8);
+                }
+                Err(err) => {
+                    file.content =
+                        Some(base64::engine::general_purpose::STANDARD.encode(err.into_bytes()));
+                    file.encoding = Some(ContentEncoding::Base64);
+                }
+            }
+        }
+        Err(err) => file.skipped_reason = Some(err.to_string()),
+    }
+    Some(file)
+}
+
+fn collect<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    paths: &[PathBuf],
+    workspace: Option<&Path>,
+) -> Vec<DroppedFile> {
+    let mut budget = MAX_INLINE_TOTAL_BYTES;
+    paths
+        .iter()
+        .take(MAX_DROPPED_FILES)
+        .filter_map(|path| describe(app, path, workspace, &mut budget))
+        .collect()
+}
+
+/// Reads dropped files off the main thread and emits `openchamber:files-dropped`.
+pub(crate) fn handle_drag_drop(window: &tauri::Window, event: &DragDropEvent) {
+    let DragDropEvent::Drop { paths, position } = event else {
+        return;
+    };
+    if paths.is_empty() {
+        return;
+    }
+
+    let app = window.app_handle().clone();
+    let paths = paths.clone();
+    let (x, y) = (position.x, position.y);
+    tauri::async_runtime::spawn_blocking(move || {
+        let workspace = git::watched_workspace(&app);
+        let workspace_root = workspace
+            .as_deref()
+            .and_then(|root| fs::canonicalize(root).ok());
+        let files = collect(&app, &paths, workspace_root.as_deref());
+        log::info!(
+            "[file-drop] {} of {} dropped paths accepted",
+            files.len(),
+            paths.len()
+        );
+        events::emit(
+            &app,
+            DesktopEvent::FilesDropped(FilesDroppedEvent {
+                files,
+                workspace,
+                x,
+                y,
+                truncated: paths.len() > MAX_DROPPED_FILES,
+            }),
+        );
+    });
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::Instant,
+};
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::{
+    path_policy::{self, PathError, PathErrorKind},
+    watcher::{self, IgnoreFilter},
+};
+
+/// Indexing stops here; quick-open beyond this many files is not useful anyway.
+const MAX_INDEXED_FILES: usize = 500_000;
+const DEFAULT_QUICK_OPEN_LIMIT: usize = 50;
+/// Characters after which a match starts a new word.
+const WORD_SEPARATORS: &[char] = &['/', '\\', '_', '-', '.', ' '];
+
+/// Relative paths of one workspace, with their lowercase form for matching.
+#[derive(Default)]
+struct WorkspaceIndex {
+    built: OnceLock<()>,
+    files: RwLock<HashMap<String, String>>,
+}
+
+/// File indexes keyed by canonical workspace root. Built on the first quick-open
+/// and kept current by the workspace's file watcher.
+#[derive(Default)]
+pub(crate) struct FileIndexState {
+    indexes: Mutex<HashMap<PathBuf, Arc<WorkspaceIndex>>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuickOpenMatch {
+    /// Relative to the workspace, with `/` separators.
+    path: String,
+    score: i64,
+    /// Matched characters, as UTF-16 offsets into `path` for highlighting.
+    positions: Vec<usize>,
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    (!relative.as_os_str().is_empty()).then(|| relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Adds every file at or below `path` that the gitignore doesn't exclude.
+fn index_tree(
+    root: &Path,
+    path: &Path,
+    filter: &IgnoreFilter,
+    files: &mut HashMap<String, String>,
+) {
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        if files.len() >= MAX_INDEXED_FILES {
+            return;
+        }
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.file_type().is_symlink()
+            || path.file_name().is_some_and(|name| name == ".git")
+            || (path != root && filter.is_ignored(&path))
+        {
+            continue;
+        }
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&path) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        } else if let Some(relative) = relative_path(root, &path) {
+            let lower = relative.to_lowercase();
+            files.insert(relative, lower);
+        }
+    }
+}
+
+fn index_for<R: tauri::Runtime>(app: &tauri::AppHandle<R>, root: &Path) -> Arc<WorkspaceIndex> {
+    let state = app.state::<FileIndexState>();
+    let mut indexes = state.indexes.lock().expect("file index mutex");
+    indexes.entry(root.to_path_buf()).or_default().clone()
+}
+
+/// Updates the index of `root`, if one was built, for paths the watcher saw change.
+/// Each path is looked at as it is now, so creates, deletes and both halves of a
+/// rename are handled alike.
+pub(crate) fn apply_changes<'a, R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    root: &Path,
+    paths: impl IntoIterator<Item = &'a PathBuf>,
+) {
+    let Some(state) = app.try_state::<FileIndexState>() else {
+        return;
+    };
+    let Some(index) = state
+        .indexes
+        .lock()
+        .expect("file index mutex")
+        .get(root)
+        .cloned()
+    else {
+        return;
+    };
+    if index.built.get().is_none() {
+        return;
+    }
+    let filter = IgnoreFilter::new(root);
+    let mut files = index.files.write().expect("file index lock");
+    for path in paths {
+        let Some(relative) = relative_path(root, path) else {
+            continue;
+        };
+        if path.is_dir() {
+            index_tree(root, path, &filter, &mut files);
+        } else if path.is_file() {
+            if !filter.is_ignored(path) {
+                let lower = relative.to_lowercase();
+                files.insert(relative, lower);
+            }
+        } else {
+            let prefix = format!("{relative}/");
+            files.retain(|file, _| file != &relative && !file.starts_with(&prefix));
+        }
+    }
+}
+
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    index == 0
+        || WORD_SEPARATORS.contains(&chars[index - 1])
+        || (chars[index].is_uppercase() && chars[index - 1].is_lowercase())
+}
+
+/// fzf-style match of `query` against `path`: the first left-to-right match is
+/// tightened by walking back from its end, then scored. Higher is better; `None`
+/// when `query` is not a subsequence of `path`.
+fn fuzzy_match(path: &str, lower: &str, query: &[char]) -> Option<(i64, Vec<usize>)> {
+    let lower_chars: Vec<char> = lower.chars().collect();
+    let chars: Vec<char> = path.chars().collect();
+    // `to_lowercase` can change the length of some characters; fall back to the
+    // original then, which only loses case-insensitivity for those paths.
+    let haystack = if lower_chars.len() == chars.len() {
+        &lower_chars
+    } else {
+        &chars
+    };
+
+    let mut end = 0;
+    let mut next = 0;
+    for &wanted in query {
+        let found = haystack[next..].iter().position(|&c| c == wanted)?;
+        end = next + found;
+        next = end + 1;
+    }
+    let mut positions = vec![0; query.len()];
+    let mut cursor = end + 1;
+    for (slot, &wanted) in positions.iter_mut().zip(query).rev() {
+        let found = haystack[..cursor].iter().rposition(|&c| c == wanted)?;
+        *slot = found;
+        cursor = found;
+    }
+
+    let basename_start = chars
+        .iter()
+        .rposition(|&c| c == '/')
+        .map_or(0, |index| index + 1);
+    let mut score: i64 = 0;
+    let mut previous: Option<usize> = None;
+    for &position in &positions {
+        score += 1;
+        if is_word_start(&chars, position) {
+            score += 8;
+        }
+        if position >= basename_start {
+            score += 2;
+        }
+        match previous {
+            Some(previous) if position == previous + 1 => score += 5,
+            Some(previous) => score -= ((position - previous - 1) as i64).min(5),
+            None => {}
+        }
+        previous = Some(position);
+    }
+    let basename: String = haystack[basename_start..].iter().collect();
+    let query_text: String = query.iter().collect();
+    if basename == query_text {
+        score += 100;
+    } else if basename.starts_with(&query_text) {
+        score += 30;
+    }
+    // Among equal matches, shallower and shorter paths first.
+    score -= (chars.len() / 16) as i64;
+
+    let positions = positions
+        .into_iter()
+        .map(|position| chars[..position].iter().map(|c| c.len_utf16()).sum())
+        .collect();
+    Some((score, positions))
+}
+
+fn quick_open(index: &WorkspaceIndex, query: &str, limit: usize) -> Vec<QuickOpenMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let files = index.files.read().expect("file index lock");
+    let mut matches: Vec<QuickOpenMatch> = if query.is_empty() {
+        files
+            .keys()
+            .map(|path| QuickOpenMatch {
+                path: path.clone(),
+                score: 0,
+                positions: Vec::new(),
+            })
+            .collect()
+    } else {
+        files
+            .iter()
+            .filter_map(|(path, lower)| {
+                let (score, positions) = fuzzy_match(path, lower, &query)?;
+                Some(QuickOpenMatch {
+                    path: path.clone(),
+                    score,
+                    positions,
+                })
+            })
+            .collect()
+    };
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.path.len().cmp(&b.path.len()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// Fuzzy file-name search for Cmd+P. The first call for a workspace indexes it and
+/// starts watching it, so later calls only score the in-memory list.
+#[tauri::command]
+pub(crate) async fn desktop_quick_open(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    workspace: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenMatch>, PathError> {
+    crate::origin::require_local(&webview, "list files")?;
+    let root = path_policy::check(&app, "quick open", &workspace)?;
+    if !root.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a directory: {}", workspace.trim()),
+        ));
+    }
+    let index = index_for(&app, &root);
+    if index.built.get().is_none() {
+        watcher::watch(&app, &workspace)?;
+    }
+    let limit = limit.unwrap_or(DEFAULT_QUICK_OPEN_LIMIT).max(1);
+    tauri::async_runtime::spawn_blocking(move || {
+        index.built.get_or_init(|| {
+            let started = Instant::now();
+            let mut files = HashMap::new();
+            index_tree(&root, &root, &IgnoreFilter::new(&root), &mut files);
+            log::info!(
+                "[file-index] indexed {} files of {} in {:?}",
+                files.len(),
+                root.display(),
+                started.elapsed()
+            );
+            index.files.write().expect("file index lock").extend(files);
+        });
+        quick_open(&index, &query, limit)
+    })
+    .await
+    .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))
+}
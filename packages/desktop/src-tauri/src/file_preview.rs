@@ -0,0 +1,222 @@
+use std::{fs, io::Read, path::Path, time::UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::path_policy::{self, PathError, PathErrorKind};
+
+const DEFAULT_PREVIEW_BYTES: usize = 256 * 1024;
+const MAX_PREVIEW_BYTES: usize = 4 * 1024 * 1024;
+/// Share of control characters above which undecodable content counts as binary.
+const MAX_CONTROL_RATIO: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+    Mixed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FilePreview {
+    path: String,
+    size: u64,
+    /// Milliseconds since the Unix epoch.
+    modified_at: Option<u64>,
+    binary: bool,
+    /// `None` for binary files.
+    encoding: Option<TextEncoding>,
+    /// The file starts with a byte order mark, which `content` leaves out.
+    bom: bool,
+    /// `None` when the previewed part has no line breaks.
+    line_ending: Option<LineEnding>,
+    /// The decoded start of the file; `None` for binary files.
+    content: Option<String>,
+    /// Only the first `max_bytes` were read.
+    truncated: bool,
+}
+
+fn utf16_units(bytes: &[u8], little_endian: bool) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if little_endian {
+                u16::from_le_bytes(pair)
+            } else {
+                u16::from_be_bytes(pair)
+            }
+        })
+        .collect()
+}
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> String {
+    char::decode_utf16(utf16_units(bytes, little_endian))
+        .map(|unit| unit.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// UTF-16 without a BOM shows up as NULs in every other byte for mostly-ASCII
+/// text. Returns whether it is little-endian.
+fn sniff_bomless_utf16(bytes: &[u8]) -> Option<bool> {
+    let pairs = bytes.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (mut even, mut odd) = (0, 0);
+    for pair in bytes.chunks_exact(2) {
+        even += usize::from(pair[0] == 0);
+        odd += usize::from(pair[1] == 0);
+    }
+    let mostly = |count: usize| count * 10 >= pairs * 7;
+    let rarely = |count: usize| count * 10 <= pairs;
+    if mostly(odd) && rarely(even) {
+        Some(true)
+    } else if mostly(even) && rarely(odd) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// The longest prefix of `bytes` that is valid UTF-8, allowing the read to have cut
+/// the last character short. `None` when the bytes aren't UTF-8.
+fn utf8_prefix(bytes: &[u8], truncated: bool) -> Option<&str> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(err) if truncated && err.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..err.valid_up_to()]).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+fn looks_binary(text: &str) -> bool {
+    let total = text.chars().count();
+    let control = text
+        .chars()
+        .filter(|&c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b'))
+        .count();
+    total > 0 && control as f64 / total as f64 > MAX_CONTROL_RATIO
+}
+
+fn line_ending(text: &str) -> Option<LineEnding> {
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {
+                chars.next();
+                crlf += 1;
+            }
+            '\r' => cr += 1,
+            '\n' => lf += 1,
+            _ => {}
+        }
+    }
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => None,
+        (true, false, false) => Some(LineEnding::Lf),
+        (false, true, false) => Some(LineEnding::Crlf),
+        (false, false, true) => Some(LineEnding::Cr),
+        _ => Some(LineEnding::Mixed),
+    }
+}
+
+/// Works out how `bytes` is encoded and decodes it, or `None` for binary content.
+/// Returns the encoding, whether a BOM was stripped, and the text.
+fn decode(bytes: &[u8], truncated: bool) -> Option<(TextEncoding, bool, String)> {
+    if let Some(rest) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        let text = utf8_prefix(rest, truncated)
+            .map(str::to_string)
+            .unwrap_or_else(|| String::from_utf8_lossy(rest).into_owned());
+        return Some((TextEncoding::Utf8, true, text));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xff\xfe") {
+        return Some((TextEncoding::Utf16Le, true, decode_utf16(rest, true)));
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xfe\xff") {
+        return Some((TextEncoding::Utf16Be, true, decode_utf16(rest, false)));
+    }
+    if bytes.contains(&0) {
+        let little_endian = sniff_bomless_utf16(bytes)?;
+        let text = decode_utf16(bytes, little_endian);
+        let encoding = if little_endian {
+            TextEncoding::Utf16Le
+        } else {
+            TextEncoding::Utf16Be
+        };
+        return (!looks_binary(&text)).then_some((encoding, false, text));
+    }
+    if let Some(text) = utf8_prefix(bytes, truncated) {
+        return (!looks_binary(text)).then(|| (TextEncoding::Utf8, false, text.to_string()));
+    }
+    // Every byte is a Latin-1 character, so this only fails on control-heavy data.
+    let text: String = bytes.iter().map(|&byte| char::from(byte)).collect();
+    (!looks_binary(&text)).then_some((TextEncoding::Latin1, false, text))
+}
+
+fn preview(path: &Path, max_bytes: usize) -> Result<FilePreview, PathError> {
+    let io_error = |err: std::io::Error| {
+        PathError::new(PathErrorKind::Io, format!("{}: {err}", path.display()))
+    };
+    let metadata = fs::metadata(path).map_err(io_error)?;
+    if !metadata.is_file() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a file: {}", path.display()),
+        ));
+    }
+    let mut bytes = Vec::with_capacity(max_bytes.min(metadata.len() as usize));
+    fs::File::open(path)
+        .and_then(|file| file.take(max_bytes as u64).read_to_end(&mut bytes))
+        .map_err(io_error)?;
+    let truncated = (bytes.len() as u64) < metadata.len();
+
+    let decoded = decode(&bytes, truncated);
+    Ok(FilePreview {
+        path: path.to_string_lossy().to_string(),
+        size: metadata.len(),
+        modified_at: metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_millis() as u64),
+        binary: decoded.is_none(),
+        encoding: decoded.as_ref().map(|(encoding, _, _)| *encoding),
+        bom: decoded.as_ref().is_some_and(|(_, bom, _)| *bom),
+        line_ending: decoded.as_ref().and_then(|(_, _, text)| line_ending(text)),
+        content: decoded.map(|(_, _, text)| text),
+        truncated,
+    })
+}
+
+/// The start of a file for the Files tab, decoded as text when it is text. Binary
+/// files come back with metadata only, so the webview never receives raw bytes.
+#[tauri::command]
+pub(crate) async fn desktop_read_file_preview(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    max_bytes: Option<usize>,
+) -> Result<FilePreview, PathError> {
+    crate::origin::require_local(&webview, "read files")?;
+    let path = path_policy::check(&app, "preview", &path)?;
+    let max_bytes = max_bytes
+        .unwrap_or(DEFAULT_PREVIEW_BYTES)
+        .clamp(1, MAX_PREVIEW_BYTES);
+    tauri::async_runtime::spawn_blocking(move || preview(&path, max_bytes))
+        .await
+        .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))?
+}
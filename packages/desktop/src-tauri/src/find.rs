@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::Manager;
+
+use crate::{events::MenuAction, window_registry};
+
+const FIND_RESULT_EVENT: &str = "openchamber:find-result";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct FindOptions {
+    case_sensitive: bool,
+    whole_word: bool,
+}
+
+#[derive(Clone, Debug)]
+struct FindQuery {
+    text: String,
+    options: FindOptions,
+}
+
+/// The active search in the main window, reused by Find Next/Previous.
+#[derive(Default)]
+pub(crate) struct FindState {
+    query: Mutex<Option<FindQuery>>,
+}
+
+/// Runs the engine's own `window.find` (WebKit and Chromium both implement it),
+/// which selects and scrolls to the match, then reports the outcome and the
+/// number of matches in the visible text as a DOM `openchamber:find-result`.
+fn find_script(query: &FindQuery, backwards: bool, restart: bool) -> String {
+    let text = serde_json::to_string(&query.text).unwrap_or_else(|_| "\"\"".into());
+    let event = serde_json::to_string(FIND_RESULT_EVENT).unwrap_or_default();
+    format!(
+        r#"(function(q,cs,back,ww,restart){{
+try{{
+if(restart){{var s=window.getSelection();if(s)s.removeAllRanges();}}
+var found=window.find(q,cs,back,true,ww,false,false);
+var hay=document.body?document.body.innerText:'';var needle=q;
+if(!cs){{hay=hay.toLowerCase();needle=needle.toLowerCase();}}
+var matches=0,i=0;if(needle){{while((i=hay.indexOf(needle,i))!==-1){{matches++;i+=needle.length;}}}}
+window.dispatchEvent(new CustomEvent({event},{{detail:{{query:q,found:!!found,matches:matches}}}}));
+}}catch(_e){{}}
+}})({text},{cs},{backwards},{ww},{restart});"#,
+        cs = query.options.case_sensitive,
+        ww = query.options.whole_word,
+    )
+}
+
+fn run<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    backwards: bool,
+    restart: bool,
+) -> Result<(), String> {
+    let query = app
+        .state::<FindState>()
+        .query
+        .lock()
+        .expect("find mutex")
+        .clone();
+    let Some(query) = query else {
+        // Nothing to repeat yet; let the web UI open its find bar instead.
+        window_registry::dispatch_menu_action(app, MenuAction::Find);
+        return Ok(());
+    };
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window unavailable".to_string())?;
+    window
+        .eval(find_script(&query, backwards, restart))
+        .map_err(|err| err.to_string())
+}
+
+/// Menu hooks for Find Next / Find Previous.
+pub(crate) fn find_next<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Err(err) = run(app, false, false) {
+        log::warn!("[find] find next failed: {err}");
+    }
+}
+
+pub(crate) fn find_previous<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Err(err) = run(app, true, false) {
+        log::warn!("[find] find previous failed: {err}");
+    }
+}
+
+/// Starts a new search from the top of the page. An empty query stops searching.
+#[tauri::command]
+pub(crate) fn desktop_find_in_page(
+    app: tauri::AppHandle,
+    query: String,
+    options: Option<FindOptions>,
+) -> Result<(), String> {
+    if query.is_empty() {
+        return desktop_find_stop(app);
+    }
+    *app.state::<FindState>().query.lock().expect("find mutex") = Some(FindQuery {
+        text: query,
+        options: options.unwrap_or_default(),
+    });
+    run(&app, false, true)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_find_next(app: tauri::AppHandle) -> Result<(), String> {
+    run(&app, false, false)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_find_previous(app: tauri::AppHandle) -> Result<(), String> {
+    run(&app, true, false)
+}
+
+/// Forgets the search and clears the highlighted match.
+#[tauri::command]
+pub(crate) fn desktop_find_stop(app: tauri::AppHandle) -> Result<(), String> {
+    *app.state::<FindState>().query.lock().expect("find mutex") = None;
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .eval("(function(){var s=window.getSelection();if(s)s.removeAllRanges();})();")
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
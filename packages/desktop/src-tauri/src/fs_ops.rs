@@ -0,0 +1,336 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    audit::{self, AuditCategory},
+    path_policy::{PathError, PathErrorKind, PathPolicy},
+    watcher::{self, FsChangeKind},
+};
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FsEntryKind {
+    File,
+    Directory,
+}
+
+fn io_error(err: io::Error, path: &Path) -> PathError {
+    let kind = match err.kind() {
+        io::ErrorKind::NotFound => PathErrorKind::NotFound,
+        io::ErrorKind::AlreadyExists => PathErrorKind::AlreadyExists,
+        _ => PathErrorKind::Io,
+    };
+    PathError::new(kind, format!("{}: {err}", path.display()))
+}
+
+fn already_exists(path: &Path) -> PathError {
+    PathError::new(
+        PathErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+}
+
+/// An existing path the policy allows, other than a workspace root itself, which
+/// is never renamed or trashed from inside the app. Only the parent is resolved,
+/// so a symlink is acted on as a link rather than as the file it points to.
+fn check_existing(policy: &PathPolicy, operation: &str, path: &str) -> Result<PathBuf, PathError> {
+    let resolved = check_new(policy, operation, path)?;
+    fs::symlink_metadata(&resolved).map_err(|err| io_error(err, &resolved))?;
+    if policy.is_root(&resolved) {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Cannot {operation} a workspace root: {}", path.trim()),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// A path that may not exist yet, resolved through its parent directory, which
+/// must exist and be in scope.
+pub(crate) fn check_new(
+    policy: &PathPolicy,
+    operation: &str,
+    path: &str,
+) -> Result<PathBuf, PathError> {
+    let trimmed = path.trim();
+    let requested = Path::new(trimmed);
+    let (Some(parent), Some(name)) = (requested.parent(), requested.file_name()) else {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a file or directory path: {trimmed}"),
+        ));
+    };
+    let parent = policy.check(operation, &parent.to_string_lossy())?;
+    if !parent.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a directory: {}", parent.display()),
+        ));
+    }
+    Ok(parent.join(name))
+}
+
+#[cfg(target_os = "macos")]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use objc2_foundation::{NSFileManager, NSString, NSURL};
+
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    NSFileManager::defaultManager()
+        .trashItemAtURL_resultingItemURL_error(&url, None)
+        .map_err(|err| io::Error::other(err.localizedDescription().to_string()))
+}
+
+#[cfg(windows)]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT, FO_DELETE,
+        SHFILEOPSTRUCTW,
+    };
+
+    // `pFrom` is a list of paths, so it ends with two NULs.
+    let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+    let mut operation = SHFILEOPSTRUCTW {
+        hwnd: std::ptr::null_mut(),
+        wFunc: FO_DELETE,
+        pFrom: from.as_ptr(),
+        pTo: std::ptr::null(),
+        fFlags: (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT) as u16,
+        fAnyOperationsAborted: 0,
+        hNameMappings: std::ptr::null_mut(),
+        lpszProgressTitle: std::ptr::null(),
+    };
+    // SAFETY: `from` is a double-NUL-terminated UTF-16 list that outlives the call,
+    // and no other pointer in `operation` is read for FO_DELETE.
+    let code = unsafe { SHFileOperationW(&mut operation) };
+    if code != 0 || operation.fAnyOperationsAborted != 0 {
+        return Err(io::Error::other(format!(
+            "Failed to move to the Recycle Bin (error {code})"
+        )));
+    }
+    Ok(())
+}
+
+/// `Path=` values in `.trashinfo` files are percent-encoded.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn trash_info_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    path.as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|&byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Moves `path` into the home trash from the freedesktop.org spec, so file
+/// managers can list and restore it. Other filesystems can't be renamed into it;
+/// `gio trash` knows their per-volume trash directories.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    use std::io::Write;
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| io::Error::other("Could not find the home directory"))?;
+    let trash = data_home.join("Trash");
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("Path has no file name"))?
+        .to_string_lossy()
+        .to_string();
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        trash_info_path(path),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    for attempt in 0.. {
+        let candidate = if attempt == 0 {
+            name.clone()
+        } else {
+            format!("{name}.{attempt}")
+        };
+        // Creating the info file first reserves the name against other trashers.
+        let info_path = info.join(format!("{candidate}.trashinfo"));
+        let mut info_file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        };
+        let target = files.join(&candidate);
+        let moved = if target.symlink_metadata().is_ok() {
+            Err(io::Error::from(io::ErrorKind::AlreadyExists))
+        } else {
+            info_file
+                .write_all(contents.as_bytes())
+                .and_then(|()| fs::rename(path, &target))
+        };
+        match moved {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                let _ = fs::remove_file(&info_path);
+                match err.kind() {
+                    io::ErrorKind::AlreadyExists => continue,
+                    io::ErrorKind::CrossesDevices => return gio_trash(path),
+                    _ => return Err(err),
+                }
+            }
+        }
+    }
+    unreachable!("trash name attempts are unbounded")
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn gio_trash(path: &Path) -> io::Result<()> {
+    let output = std::process::Command::new("gio")
+        .arg("trash")
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(io::Error::other(
+        stderr
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .unwrap_or("gio trash failed")
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// Moves files and directories to the OS trash, so deletions from the file tree can
+/// be undone. Every path is checked before anything is moved; on a failure the
+/// paths before it stay trashed and the error names the one that failed.
+#[tauri::command]
+pub(crate) async fn desktop_fs_trash(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    paths: Vec<String>,
+) -> Result<(), PathError> {
+    crate::origin::require_local(&webview, "trash files")?;
+    let policy = PathPolicy::for_app(&app);
+    let mut resolved = paths
+        .iter()
+        .map(|path| check_existing(&policy, "trash", path))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Trashing a directory takes everything in it along.
+    resolved.sort();
+    resolved.dedup();
+    let mut targets: Vec<PathBuf> = Vec::with_capacity(resolved.len());
+    for path in resolved {
+        if !targets.iter().any(|parent| path.starts_with(parent)) {
+            targets.push(path);
+        }
+    }
+
+    let task_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut trashed = Vec::new();
+        let mut result = Ok(());
+        for path in targets {
+            let outcome = move_to_trash(&path);
+            audit::record(
+                &task_app,
+                AuditCategory::FileWrite,
+                "trash",
+                path.to_string_lossy(),
+                &outcome,
+            );
+            if let Err(err) = outcome {
+                result = Err(io_error(err, &path));
+                break;
+            }
+            log::info!("[fs] trashed {}", path.display());
+            trashed.push((path, FsChangeKind::Removed));
+        }
+        watcher::emit_changes(&task_app, trashed);
+        result
+    })
+    .await
+    .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))?
+}
+
+/// Renames or moves `from` to `to`, refusing to replace an existing entry. Returns
+/// the resolved new path.
+#[tauri::command]
+pub(crate) fn desktop_fs_rename(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    from: String,
+    to: String,
+) -> Result<String, PathError> {
+    crate::origin::require_local(&webview, "rename files")?;
+    let policy = PathPolicy::for_app(&app);
+    let source = check_existing(&policy, "rename", &from)?;
+    let target = check_new(&policy, "rename", &to)?;
+    if target.symlink_metadata().is_ok() {
+        // A case-only rename on a case-insensitive filesystem finds itself.
+        let same_entry = target.parent() == source.parent()
+            && target.file_name() != source.file_name()
+            && target
+                .file_name()
+                .zip(source.file_name())
+                .is_some_and(|(a, b)| a.eq_ignore_ascii_case(b));
+        if !same_entry {
+            return Err(already_exists(&target));
+        }
+    }
+    fs::rename(&source, &target).map_err(|err| io_error(err, &source))?;
+    log::info!("[fs] renamed {} to {}", source.display(), target.display());
+    watcher::emit_changes(
+        &app,
+        [
+            (source, FsChangeKind::Renamed),
+            (target.clone(), FsChangeKind::Renamed),
+        ],
+    );
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Creates an empty file or a directory at `path`, whose parent must exist.
+/// Returns the resolved path.
+#[tauri::command]
+pub(crate) fn desktop_fs_create(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    kind: FsEntryKind,
+) -> Result<String, PathError> {
+    crate::origin::require_local(&webview, "create files")?;
+    let policy = PathPolicy::for_app(&app);
+    let target = check_new(&policy, "create", &path)?;
+    let created = match kind {
+        FsEntryKind::File => fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&target)
+            .map(drop),
+        FsEntryKind::Directory => fs::create_dir(&target),
+    };
+    created.map_err(|err| io_error(err, &target))?;
+    log::info!("[fs] created {:?} {}", kind, target.display());
+    watcher::emit_changes(&app, [(target.clone(), FsChangeKind::Created)]);
+    Ok(target.to_string_lossy().to_string())
+}
@@ -0,0 +1,1919 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use git2::{
+    BranchType, ErrorCode, Repository, Status, StatusOptions, WorktreeAddOptions,
+    WorktreeLockStatus, WorktreePruneOptions,
+};
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::{
+    audit::{self, AuditCategory},
+    dependencies,
+    events::{self, DesktopEvent},
+    git_signing, i18n, recents, trust,
+};
+
+const GIT_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum GitErrorKind {
+    NotARepository,
+    NotAWorktree,
+    NotFound,
+    AlreadyExists,
+    BranchCheckedOut,
+    DirtyWorktree,
+    Locked,
+    InvalidPath,
+    /// The workspace is open in restricted mode.
+    Untrusted,
+    /// The index matches HEAD, so a commit would be empty, or there are no local
+    /// changes to stash.
+    NothingToCommit,
+    /// Unresolved merge conflicts block the operation.
+    Conflict,
+    /// A commit hook exited non-zero; `message` carries its output.
+    HookFailed,
+    /// The branch has commits found neither on HEAD nor on its upstream.
+    NotMerged,
+    /// The remote rejected or never got credentials.
+    AuthFailed,
+    /// The remote has commits the local branch lacks, or the branches diverged.
+    NonFastForward,
+    /// The remote could not be reached.
+    Network,
+    /// gpg or ssh-keygen could not sign the commit; `message` says why.
+    SigningFailed,
+    /// Called from a page other than the app's own UI, such as a remote server.
+    Forbidden,
+    Io,
+    Git,
+}
+
+/// Error returned by the native git commands. Serialized as `{ kind, message }` so the
+/// UI can branch on `kind` instead of matching message text; `paths` lists the files
+/// involved when there are any (e.g. those a checkout would overwrite).
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitError {
+    kind: GitErrorKind,
+    message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<String>,
+}
+
+impl GitError {
+    pub(crate) fn new(kind: GitErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            paths: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_paths(mut self, paths: Vec<String>) -> Self {
+        self.paths = paths;
+        self
+    }
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<git2::Error> for GitError {
+    fn from(err: git2::Error) -> Self {
+        let kind = match err.code() {
+            ErrorCode::NotFound => GitErrorKind::NotFound,
+            ErrorCode::Exists => GitErrorKind::AlreadyExists,
+            ErrorCode::Locked => GitErrorKind::Locked,
+            _ => GitErrorKind::Git,
+        };
+        Self::new(kind, err.message())
+    }
+}
+
+impl From<std::io::Error> for GitError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(GitErrorKind::Io, err.to_string())
+    }
+}
+
+type GitResult<T> = Result<T, GitError>;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeInfo {
+    /// Worktree name as known to git; `None` for the main worktree.
+    name: Option<String>,
+    path: String,
+    branch: Option<String>,
+    head: Option<String>,
+    is_main: bool,
+    locked: bool,
+    /// The worktree directory is gone or broken and `git worktree prune` would remove it.
+    prunable: bool,
+}
+
+pub(crate) fn open_repository(path: &str) -> GitResult<Repository> {
+    Repository::open(path).map_err(|err| match err.code() {
+        ErrorCode::NotFound => GitError::new(
+            GitErrorKind::NotARepository,
+            format!("Not a git repository: {path}"),
+        ),
+        _ => err.into(),
+    })
+}
+
+/// Opens the repository that owns all worktrees, even when `path` is a linked worktree.
+fn open_main_repository(path: &str) -> GitResult<Repository> {
+    let repo = open_repository(path)?;
+    if repo.is_worktree() {
+        return Ok(Repository::open(repo.commondir())?);
+    }
+    Ok(repo)
+}
+
+fn head_info(repo: &Repository) -> (Option<String>, Option<String>) {
+    let Ok(head) = repo.head() else {
+        return (None, None);
+    };
+    let branch = head
+        .is_branch()
+        .then(|| head.shorthand().map(str::to_string))
+        .flatten();
+    let oid = head.target().map(|oid| oid.to_string());
+    (branch, oid)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.components().eq(b.components()),
+    }
+}
+
+fn is_dirty(repo: &Repository) -> GitResult<bool> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).include_ignored(false);
+    Ok(!repo.statuses(Some(&mut opts))?.is_empty())
+}
+
+fn list_worktrees(repo: &Repository) -> GitResult<Vec<WorktreeInfo>> {
+    let mut worktrees = Vec::new();
+
+    if let Some(workdir) = repo.workdir() {
+        let (branch, head) = head_info(repo);
+        worktrees.push(WorktreeInfo {
+            name: None,
+            path: workdir.to_string_lossy().trim_end_matches('/').to_string(),
+            branch,
+            head,
+            is_main: true,
+            locked: false,
+            prunable: false,
+        });
+    }
+
+    for name in repo.worktrees()?.iter().flatten() {
+        let worktree = repo.find_worktree(name)?;
+        let (branch, head) = Repository::open_from_worktree(&worktree)
+            .map(|wt_repo| head_info(&wt_repo))
+            .unwrap_or((None, None));
+        worktrees.push(WorktreeInfo {
+            name: Some(name.to_string()),
+            path: worktree.path().to_string_lossy().to_string(),
+            branch,
+            head,
+            is_main: false,
+            locked: matches!(worktree.is_locked(), Ok(WorktreeLockStatus::Locked(_))),
+            prunable: worktree.validate().is_err(),
+        });
+    }
+
+    Ok(worktrees)
+}
+
+fn checked_out_branches(repo: &Repository) -> GitResult<HashSet<String>> {
+    Ok(list_worktrees(repo)?
+        .into_iter()
+        .filter(|wt| !wt.prunable)
+        .filter_map(|wt| wt.branch)
+        .collect())
+}
+
+/// Derives a unique git worktree name from the target directory name.
+fn worktree_name(repo: &Repository, path: &Path) -> GitResult<String> {
+    let base: String = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let base = match base.trim_matches(|c| c == '-' || c == '.') {
+        "" => "worktree".to_string(),
+        trimmed => trimmed.to_string(),
+    };
+
+    let existing: HashSet<String> = repo
+        .worktrees()?
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while existing.contains(&name) {
+        name = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    Ok(name)
+}
+
+fn create_worktree(repo_path: &str, branch: &str, path: &str) -> GitResult<WorktreeInfo> {
+    let repo = open_main_repository(repo_path)?;
+    let branch = branch.trim();
+    if branch.is_empty() || !git2::Branch::name_is_valid(branch)? {
+        return Err(GitError::new(
+            GitErrorKind::Git,
+            format!("Invalid branch name: {branch}"),
+        ));
+    }
+
+    let target = PathBuf::from(path.trim());
+    if !target.is_absolute() {
+        return Err(GitError::new(
+            GitErrorKind::InvalidPath,
+            format!("Worktree path must be absolute: {}", target.display()),
+        ));
+    }
+    if target.exists() && target.read_dir()?.next().is_some() {
+        return Err(GitError::new(
+            GitErrorKind::AlreadyExists,
+            format!("Worktree path is not empty: {}", target.display()),
+        ));
+    }
+
+    if checked_out_branches(&repo)?.contains(branch) {
+        return Err(GitError::new(
+            GitErrorKind::BranchCheckedOut,
+            format!("Branch '{branch}' is already checked out in another worktree"),
+        ));
+    }
+
+    let branch_ref = match repo.find_branch(branch, BranchType::Local) {
+        Ok(existing) => existing,
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            let head = repo.head()?.peel_to_commit()?;
+            repo.branch(branch, &head, false)?
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let name = worktree_name(&repo, &target)?;
+    let mut opts = WorktreeAddOptions::new();
+    opts.reference(Some(branch_ref.get()));
+    let worktree = repo.worktree(&name, &target, Some(&opts))?;
+
+    let wt_repo = Repository::open_from_worktree(&worktree)?;
+    let (branch, head) = head_info(&wt_repo);
+    log::info!("[git] created worktree {name} at {}", target.display());
+    Ok(WorktreeInfo {
+        name: Some(name),
+        path: worktree.path().to_string_lossy().to_string(),
+        branch,
+        head,
+        is_main: false,
+        locked: false,
+        prunable: false,
+    })
+}
+
+fn remove_worktree(path: &str, force: bool) -> GitResult<()> {
+    let target = Path::new(path.trim());
+    let wt_repo = open_repository(path.trim())?;
+    if !wt_repo.is_worktree() {
+        return Err(GitError::new(
+            GitErrorKind::NotAWorktree,
+            format!("Not a linked worktree: {}", target.display()),
+        ));
+    }
+    if !force && is_dirty(&wt_repo)? {
+        return Err(GitError::new(
+            GitErrorKind::DirtyWorktree,
+            format!("Worktree has uncommitted changes: {}", target.display()),
+        ));
+    }
+
+    let repo = Repository::open(wt_repo.commondir())?;
+    let worktree = repo
+        .worktrees()?
+        .iter()
+        .flatten()
+        .filter_map(|name| repo.find_worktree(name).ok())
+        .find(|wt| same_path(wt.path(), target))
+        .ok_or_else(|| {
+            GitError::new(
+                GitErrorKind::NotFound,
+                format!("Worktree not registered: {}", target.display()),
+            )
+        })?;
+
+    if !force && matches!(worktree.is_locked()?, WorktreeLockStatus::Locked(_)) {
+        return Err(GitError::new(
+            GitErrorKind::Locked,
+            format!("Worktree is locked: {}", target.display()),
+        ));
+    }
+
+    let mut opts = WorktreePruneOptions::new();
+    opts.valid(true).locked(force).working_tree(true);
+    worktree.prune(Some(&mut opts))?;
+    log::info!("[git] removed worktree at {}", target.display());
+    Ok(())
+}
+
+/// Where OpenChamber and opencode put agent worktrees, relative to the main worktree.
+const MANAGED_WORKTREE_DIRS: &[&str] = &[".openchamber", ".worktrees"];
+/// Branch prefixes of agent worktrees created elsewhere.
+const MANAGED_BRANCH_PREFIXES: &[&str] = &["opencode/", "openchamber/"];
+/// Younger worktrees are never pruned, so a fresh checkout whose session has not
+/// started yet survives.
+const WORKTREE_MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const WORKTREE_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StaleReason {
+    /// The worktree directory is gone.
+    Missing,
+    /// Everything on the branch is on the default branch.
+    Merged,
+    /// No session uses the worktree any more.
+    NoSession,
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StaleWorktree {
+    path: String,
+    branch: Option<String>,
+    reasons: Vec<StaleReason>,
+    /// Why the worktree stays despite being stale, e.g. uncommitted changes or a
+    /// failed removal.
+    kept_because: Option<String>,
+    removed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreePruneReport {
+    repo: String,
+    dry_run: bool,
+    /// The branch merges were checked against.
+    base_branch: Option<String>,
+    worktrees: Vec<StaleWorktree>,
+}
+
+fn is_managed_worktree(main_workdir: &Path, info: &WorktreeInfo) -> bool {
+    let path = Path::new(&info.path);
+    MANAGED_WORKTREE_DIRS
+        .iter()
+        .any(|dir| path.starts_with(main_workdir.join(dir)))
+        || info.branch.as_deref().is_some_and(|branch| {
+            MANAGED_BRANCH_PREFIXES
+                .iter()
+                .any(|prefix| branch.starts_with(prefix))
+        })
+}
+
+/// Time since `git worktree add`, from the worktree's admin directory.
+fn worktree_age(repo: &Repository, name: &str) -> Option<Duration> {
+    let admin = repo.commondir().join("worktrees").join(name);
+    let metadata = std::fs::metadata(admin).ok()?;
+    metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .ok()?
+        .elapsed()
+        .ok()
+}
+
+fn is_branch_merged(repo: &Repository, branch: &str, base: git2::Oid) -> bool {
+    repo.find_branch(branch, BranchType::Local)
+        .ok()
+        .and_then(|branch| branch.get().target())
+        .is_some_and(|tip| tip != base && repo.graph_descendant_of(base, tip).unwrap_or(false))
+}
+
+/// Why a stale worktree must stay, if it must.
+fn keep_reason(repo: &Repository, info: &WorktreeInfo) -> Option<String> {
+    if info.locked {
+        return Some("locked".to_string());
+    }
+    if info.prunable {
+        return None;
+    }
+    let name = info.name.as_deref().unwrap_or_default();
+    if worktree_age(repo, name).is_none_or(|age| age < WORKTREE_MIN_AGE) {
+        return Some("created less than a day ago".to_string());
+    }
+    match open_repository(&info.path).and_then(|wt_repo| is_dirty(&wt_repo)) {
+        Ok(false) => None,
+        Ok(true) => Some("has uncommitted changes".to_string()),
+        Err(err) => Some(err.message),
+    }
+}
+
+/// Removes a stale worktree; a merged branch goes with it, other branches stay so
+/// no commits are lost.
+fn remove_stale_worktree(repo: &Repository, info: &WorktreeInfo, merged: bool) -> GitResult<()> {
+    if info.prunable {
+        let name = info.name.as_deref().unwrap_or_default();
+        repo.find_worktree(name)?.prune(None)?;
+        log::info!("[git] pruned missing worktree {name}");
+    } else {
+        remove_worktree(&info.path, false)?;
+    }
+    if let (true, Some(branch)) = (merged, info.branch.as_deref()) {
+        if let Err(err) = repo
+            .find_branch(branch, BranchType::Local)
+            .and_then(|mut branch| branch.delete())
+        {
+            log::warn!("[git] kept merged branch {branch}: {}", err.message());
+        }
+    }
+    Ok(())
+}
+
+/// Finds agent worktrees that are missing, merged into the default branch or, when
+/// `sessions` lists the worktree paths sessions still use, orphaned; and removes
+/// them unless `dry_run`. Worktrees that are locked, new or dirty are reported but
+/// kept.
+fn prune_worktrees(
+    repo_path: &str,
+    dry_run: bool,
+    sessions: Option<&[String]>,
+) -> GitResult<WorktreePruneReport> {
+    let repo = open_main_repository(repo_path)?;
+    let main_workdir = workdir(&repo)?;
+    let base_branch = default_branch(&repo);
+    let base = base_branch
+        .as_deref()
+        .and_then(|branch| repo.find_branch(branch, BranchType::Local).ok())
+        .and_then(|branch| branch.get().target());
+
+    let mut worktrees = Vec::new();
+    for info in list_worktrees(&repo)? {
+        if info.is_main || !is_managed_worktree(&main_workdir, &info) {
+            continue;
+        }
+        let mut reasons = Vec::new();
+        if info.prunable {
+            reasons.push(StaleReason::Missing);
+        } else {
+            if let (Some(branch), Some(base)) = (info.branch.as_deref(), base) {
+                if is_branch_merged(&repo, branch, base) {
+                    reasons.push(StaleReason::Merged);
+                }
+            }
+            if sessions.is_some_and(|sessions| {
+                !sessions
+                    .iter()
+                    .any(|session| same_path(Path::new(session), Path::new(&info.path)))
+            }) {
+                reasons.push(StaleReason::NoSession);
+            }
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+
+        let mut kept_because = keep_reason(&repo, &info);
+        let mut removed = false;
+        if kept_because.is_none() && !dry_run {
+            match remove_stale_worktree(&repo, &info, reasons.contains(&StaleReason::Merged)) {
+                Ok(()) => removed = true,
+                Err(err) => kept_because = Some(err.message),
+            }
+        }
+        worktrees.push(StaleWorktree {
+            path: info.path,
+            branch: info.branch,
+            reasons,
+            kept_because,
+            removed,
+        });
+    }
+
+    if !dry_run {
+        let removed = worktrees.iter().filter(|worktree| worktree.removed).count();
+        log::info!("[git] pruned {removed} stale worktrees of {repo_path}");
+    }
+    Ok(WorktreePruneReport {
+        repo: repo_path.to_string(),
+        dry_run,
+        base_branch,
+        worktrees,
+    })
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BranchInfo {
+    /// Short name, e.g. `main` or `origin/main`.
+    name: String,
+    is_remote: bool,
+    /// Checked out in this worktree.
+    is_head: bool,
+    /// Another worktree has the branch checked out, so it cannot be checked out here.
+    worktree: Option<String>,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    commit: Option<String>,
+    summary: Option<String>,
+    /// Seconds since the Unix epoch.
+    committed_at: Option<i64>,
+}
+
+fn branch_info(
+    repo: &Repository,
+    branch: &git2::Branch<'_>,
+    kind: BranchType,
+    worktrees: &[WorktreeInfo],
+) -> GitResult<Option<BranchInfo>> {
+    let Some(name) = branch.name()?.map(str::to_string) else {
+        return Ok(None);
+    };
+    // `origin/HEAD` only points at another remote branch.
+    if kind == BranchType::Remote && branch.get().symbolic_target().is_some() {
+        return Ok(None);
+    }
+    let commit = branch.get().peel_to_commit().ok();
+    let upstream = branch.upstream().ok();
+    let (ahead, behind) = match (
+        branch.get().target(),
+        upstream
+            .as_ref()
+            .and_then(|upstream| upstream.get().target()),
+    ) {
+        (Some(local), Some(remote)) => repo.graph_ahead_behind(local, remote)?,
+        _ => (0, 0),
+    };
+    let current = repo.workdir().map(Path::to_path_buf);
+    let worktree = (kind == BranchType::Local)
+        .then(|| {
+            worktrees.iter().find(|worktree| {
+                !worktree.prunable
+                    && worktree.branch.as_deref() == Some(name.as_str())
+                    && current
+                        .as_deref()
+                        .is_none_or(|current| !same_path(Path::new(&worktree.path), current))
+            })
+        })
+        .flatten()
+        .map(|worktree| worktree.path.clone());
+    Ok(Some(BranchInfo {
+        is_remote: kind == BranchType::Remote,
+        is_head: branch.is_head(),
+        worktree,
+        upstream: upstream.and_then(|upstream| upstream.name().ok().flatten().map(str::to_string)),
+        ahead,
+        behind,
+        commit: commit.as_ref().map(|commit| commit.id().to_string()),
+        summary: commit
+            .as_ref()
+            .and_then(|commit| commit.summary().map(str::to_string)),
+        committed_at: commit.as_ref().map(|commit| commit.time().seconds()),
+        name,
+    }))
+}
+
+/// Local branches by name, then remote-tracking ones.
+fn list_branches(repo_path: &str) -> GitResult<Vec<BranchInfo>> {
+    let repo = open_repository(repo_path)?;
+    let worktrees = list_worktrees(&open_main_repository(repo_path)?)?;
+    let mut branches = Vec::new();
+    for entry in repo.branches(None)? {
+        let (branch, kind) = entry?;
+        if let Some(info) = branch_info(&repo, &branch, kind, &worktrees)? {
+            branches.push(info);
+        }
+    }
+    branches.sort_by(|a, b| a.is_remote.cmp(&b.is_remote).then(a.name.cmp(&b.name)));
+    Ok(branches)
+}
+
+fn validate_branch_name(name: &str) -> GitResult<&str> {
+    let name = name.trim();
+    if name.is_empty() || !git2::Branch::name_is_valid(name)? {
+        return Err(GitError::new(
+            GitErrorKind::Git,
+            format!("Invalid branch name: {name}"),
+        ));
+    }
+    Ok(name)
+}
+
+/// Creates `name` at `from` (default HEAD). Starting from a remote branch makes it
+/// the new branch's upstream, like `git branch --track`.
+fn create_branch(repo_path: &str, name: &str, from: Option<&str>) -> GitResult<BranchInfo> {
+    let repo = open_repository(repo_path)?;
+    let name = validate_branch_name(name)?;
+    if repo.find_branch(name, BranchType::Local).is_ok() {
+        return Err(GitError::new(
+            GitErrorKind::AlreadyExists,
+            format!("Branch '{name}' already exists"),
+        ));
+    }
+    let from = from.map(str::trim).filter(|from| !from.is_empty());
+    let start = repo
+        .revparse_single(from.unwrap_or("HEAD"))?
+        .peel_to_commit()?;
+    let mut branch = repo.branch(name, &start, false)?;
+    if let Some(remote) = from.filter(|from| repo.find_branch(from, BranchType::Remote).is_ok()) {
+        branch.set_upstream(Some(remote))?;
+    }
+    log::info!("[git] created branch {name} in {repo_path}");
+    let worktrees = list_worktrees(&open_main_repository(repo_path)?)?;
+    branch_info(&repo, &branch, BranchType::Local, &worktrees)?
+        .ok_or_else(|| GitError::new(GitErrorKind::Git, "Branch name is not valid UTF-8"))
+}
+
+/// Safe checkout of `tree`: local changes are carried over unless the target
+/// changes the same files, in which case nothing is touched and the files are listed.
+fn checkout_tree_safely(repo: &Repository, tree: &git2::Object<'_>) -> GitResult<()> {
+    let mut conflicts: Vec<String> = Vec::new();
+    let result = {
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout
+            .safe()
+            .notify_on(git2::CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, _, _, _| {
+                if let Some(path) = path {
+                    conflicts.push(path.to_string_lossy().replace('\\', "/"));
+                }
+                true
+            });
+        repo.checkout_tree(tree, Some(&mut checkout))
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) if err.code() == ErrorCode::Conflict || !conflicts.is_empty() => {
+            Err(GitError::new(
+                GitErrorKind::DirtyWorktree,
+                "Local changes would be overwritten by the checkout; commit or stash them first",
+            )
+            .with_paths(conflicts))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Checks out a local branch, a remote branch (through a local tracking branch of
+/// the same name, created if needed) or any other revision as a detached HEAD.
+fn checkout(repo_path: &str, reference: &str) -> GitResult<Option<String>> {
+    let repo = open_repository(repo_path)?;
+    let reference = reference.trim();
+    if repo.index()?.has_conflicts() {
+        return Err(GitError::new(
+            GitErrorKind::Conflict,
+            "Resolve the merge conflicts before switching branches",
+        ));
+    }
+
+    let local = match repo.find_branch(reference, BranchType::Local) {
+        Ok(branch) => Some(branch),
+        Err(_) => match repo.find_branch(reference, BranchType::Remote) {
+            Ok(remote) => {
+                let local_name = reference
+                    .split_once('/')
+                    .map_or(reference, |(_, name)| name)
+                    .to_string();
+                match repo.find_branch(&local_name, BranchType::Local) {
+                    Ok(existing) => Some(existing),
+                    Err(_) => {
+                        let mut branch =
+                            repo.branch(&local_name, &remote.get().peel_to_commit()?, false)?;
+                        branch.set_upstream(Some(reference))?;
+                        Some(branch)
+                    }
+                }
+            }
+            Err(_) => None,
+        },
+    };
+
+    if let Some(branch) = local {
+        let name = branch.name()?.unwrap_or(reference).to_string();
+        if !branch.is_head() {
+            let worktrees = list_worktrees(&open_main_repository(repo_path)?)?;
+            if let Some(info) = branch_info(&repo, &branch, BranchType::Local, &worktrees)? {
+                if let Some(worktree) = info.worktree {
+                    return Err(GitError::new(
+                        GitErrorKind::BranchCheckedOut,
+                        format!("Branch '{name}' is already checked out at {worktree}"),
+                    ));
+                }
+            }
+        }
+        let refname = branch
+            .get()
+            .name()
+            .ok_or_else(|| GitError::new(GitErrorKind::Git, "Branch name is not valid UTF-8"))?
+            .to_string();
+        checkout_tree_safely(&repo, &branch.get().peel(git2::ObjectType::Tree)?)?;
+        repo.set_head(&refname)?;
+        log::info!("[git] checked out {name} in {repo_path}");
+        return Ok(Some(name));
+    }
+
+    let commit = repo.revparse_single(reference)?.peel_to_commit()?;
+    checkout_tree_safely(&repo, commit.as_object())?;
+    repo.set_head_detached(commit.id())?;
+    log::info!(
+        "[git] checked out {} (detached) in {repo_path}",
+        commit.id()
+    );
+    Ok(None)
+}
+
+/// Whether everything on `branch` is also on HEAD or on the branch's upstream.
+fn is_merged(repo: &Repository, branch: &git2::Branch<'_>) -> GitResult<bool> {
+    let Some(tip) = branch.get().target() else {
+        return Ok(true);
+    };
+    let bases = [
+        repo.head().ok().and_then(|head| head.target()),
+        branch
+            .upstream()
+            .ok()
+            .and_then(|upstream| upstream.get().target()),
+    ];
+    for base in bases.into_iter().flatten() {
+        if base == tip || repo.graph_descendant_of(base, tip)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn delete_branch(repo_path: &str, name: &str, force: bool) -> GitResult<()> {
+    let repo = open_repository(repo_path)?;
+    let name = name.trim();
+    let mut branch = repo.find_branch(name, BranchType::Local)?;
+    if branch.is_head() {
+        return Err(GitError::new(
+            GitErrorKind::BranchCheckedOut,
+            format!("Cannot delete '{name}' while it is checked out"),
+        ));
+    }
+    if checked_out_branches(&open_main_repository(repo_path)?)?.contains(name) {
+        return Err(GitError::new(
+            GitErrorKind::BranchCheckedOut,
+            format!("Branch '{name}' is checked out in another worktree"),
+        ));
+    }
+    if !force && !is_merged(&repo, &branch)? {
+        return Err(GitError::new(
+            GitErrorKind::NotMerged,
+            format!("Branch '{name}' has commits that are not merged"),
+        ));
+    }
+    branch.delete()?;
+    log::info!("[git] deleted branch {name} in {repo_path}");
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StashInfo {
+    /// Position in the stash list; 0 is the newest (`stash@{0}`).
+    index: usize,
+    message: String,
+    commit: String,
+    /// Seconds since the Unix epoch.
+    created_at: i64,
+}
+
+/// Stashes shared by every worktree of the repository, newest first.
+fn list_stashes(repo_path: &str) -> GitResult<Vec<StashInfo>> {
+    let mut repo = open_repository(repo_path)?;
+    let mut entries = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        entries.push((index, message.to_string(), *oid));
+        true
+    })?;
+    Ok(entries
+        .into_iter()
+        .map(|(index, message, oid)| StashInfo {
+            index,
+            message,
+            commit: oid.to_string(),
+            created_at: repo
+                .find_commit(oid)
+                .map(|commit| commit.time().seconds())
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+fn save_stash(
+    repo_path: &str,
+    message: Option<&str>,
+    include_untracked: bool,
+) -> GitResult<StashInfo> {
+    let mut repo = open_repository(repo_path)?;
+    let signature = repo.signature().map_err(|err| {
+        GitError::new(
+            GitErrorKind::Git,
+            format!("Set user.name and user.email to stash: {}", err.message()),
+        )
+    })?;
+    let mut flags = git2::StashFlags::DEFAULT;
+    if include_untracked {
+        flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+    }
+    let message = message.map(str::trim).filter(|message| !message.is_empty());
+    let oid = match repo.stash_save2(&signature, message, Some(flags)) {
+        Ok(oid) => oid,
+        Err(err) if err.code() == ErrorCode::NotFound => {
+            return Err(GitError::new(
+                GitErrorKind::NothingToCommit,
+                "No local changes to stash",
+            ));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    log::info!("[git] stashed changes in {repo_path}");
+    list_stashes(repo_path)?
+        .into_iter()
+        .find(|stash| stash.commit == oid.to_string())
+        .ok_or_else(|| GitError::new(GitErrorKind::NotFound, "Stash was not recorded"))
+}
+
+/// Applies `stash@{index}`, dropping it afterwards when `pop` is set. Like
+/// [`checkout_tree_safely`], local changes the stash touches are never overwritten;
+/// the stash is kept whenever applying it fails.
+fn apply_stash(repo_path: &str, index: usize, pop: bool) -> GitResult<()> {
+    let mut repo = open_repository(repo_path)?;
+    if repo.index()?.has_conflicts() {
+        return Err(GitError::new(
+            GitErrorKind::Conflict,
+            "Resolve the merge conflicts before applying a stash",
+        ));
+    }
+    let mut conflicts: Vec<String> = Vec::new();
+    let result = {
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout
+            .safe()
+            .notify_on(git2::CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, _, _, _| {
+                if let Some(path) = path {
+                    conflicts.push(path.to_string_lossy().replace('\\', "/"));
+                }
+                true
+            });
+        let mut options = git2::StashApplyOptions::new();
+        options.checkout_options(checkout);
+        if pop {
+            repo.stash_pop(index, Some(&mut options))
+        } else {
+            repo.stash_apply(index, Some(&mut options))
+        }
+    };
+    match result {
+        Ok(()) => {
+            log::info!(
+                "[git] {} stash@{{{index}}} in {repo_path}",
+                if pop { "popped" } else { "applied" }
+            );
+            Ok(())
+        }
+        Err(err) if err.code() == ErrorCode::MergeConflict => Err(GitError::new(
+            GitErrorKind::Conflict,
+            format!("stash@{{{index}}} conflicts with the current changes; it was kept"),
+        )
+        .with_paths(conflicts)),
+        Err(err) if err.code() == ErrorCode::Conflict || !conflicts.is_empty() => {
+            Err(GitError::new(
+                GitErrorKind::DirtyWorktree,
+                format!(
+                    "Local changes would be overwritten by stash@{{{index}}}; commit or stash them first"
+                ),
+            )
+            .with_paths(conflicts))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn drop_stash(repo_path: &str, index: usize) -> GitResult<()> {
+    open_repository(repo_path)?.stash_drop(index)?;
+    log::info!("[git] dropped stash@{{{index}}} in {repo_path}");
+    Ok(())
+}
+
+/// `create_worktree`, taking the uncommitted changes of `repo` along: they are
+/// stashed, the worktree is created and the stash is popped there. If the worktree
+/// cannot be created the changes are restored where they came from.
+fn create_worktree_with_changes(
+    repo_path: &str,
+    branch: &str,
+    path: &str,
+) -> GitResult<WorktreeInfo> {
+    let stash = match save_stash(
+        repo_path,
+        Some(&format!("Moved to worktree {}", branch.trim())),
+        true,
+    ) {
+        Ok(stash) => Some(stash),
+        Err(err) if err.kind == GitErrorKind::NothingToCommit => None,
+        Err(err) => return Err(err),
+    };
+    let worktree = match create_worktree(repo_path, branch, path) {
+        Ok(worktree) => worktree,
+        Err(err) => {
+            if let Some(stash) = &stash {
+                if let Err(restore) = apply_stash(repo_path, stash.index, true) {
+                    log::warn!("[git] failed to restore stashed changes: {restore}");
+                }
+            }
+            return Err(err);
+        }
+    };
+    if let Some(stash) = stash {
+        apply_stash(&worktree.path, stash.index, true).map_err(|err| {
+            GitError::new(
+                err.kind,
+                format!(
+                    "Worktree created at {}, but the changes stayed in stash@{{{}}}: {}",
+                    worktree.path, stash.index, err.message
+                ),
+            )
+            .with_paths(err.paths)
+        })?;
+    }
+    Ok(worktree)
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PickedWorkspace {
+    path: String,
+    default_branch: Option<String>,
+    /// The folder was not a repository and `git init` was run on it.
+    initialized: bool,
+}
+
+/// Best guess at the branch new work should start from: `origin/HEAD`, then a local
+/// `main`/`master`, then whatever HEAD points at (which also covers unborn branches).
+pub(crate) fn default_branch(repo: &Repository) -> Option<String> {
+    if let Some(remote_head) = repo
+        .find_reference("refs/remotes/origin/HEAD")
+        .ok()
+        .and_then(|reference| reference.symbolic_target().map(str::to_string))
+    {
+        if let Some(branch) = remote_head.strip_prefix("refs/remotes/origin/") {
+            return Some(branch.to_string());
+        }
+    }
+
+    for candidate in ["main", "master"] {
+        if repo.find_branch(candidate, BranchType::Local).is_ok() {
+            return Some(candidate.to_string());
+        }
+    }
+
+    repo.find_reference("HEAD")
+        .ok()
+        .and_then(|head| head.symbolic_target().map(str::to_string))
+        .and_then(|target| target.strip_prefix("refs/heads/").map(str::to_string))
+}
+
+fn inspect_workspace(path: &Path, initialized: bool) -> GitResult<PickedWorkspace> {
+    let repo = open_repository(&path.to_string_lossy())?;
+    let root = repo.workdir().unwrap_or(path);
+    Ok(PickedWorkspace {
+        path: root.to_string_lossy().trim_end_matches('/').to_string(),
+        default_branch: default_branch(&repo),
+        initialized,
+    })
+}
+
+pub(crate) async fn require_trusted(app: &tauri::AppHandle, path: &str) -> GitResult<()> {
+    trust::require_trusted(app, path)
+        .await
+        .map_err(|message| GitError::new(GitErrorKind::Untrusted, message))
+}
+
+/// Shows the native folder picker and validates the choice as a git workspace, offering
+/// to `git init` plain folders. Returns `None` if the user cancels either dialog.
+fn pick_workspace(app: &tauri::AppHandle) -> GitResult<Option<PickedWorkspace>> {
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_title(i18n::t(app, "dialog.addWorkspace.title"))
+        .blocking_pick_folder()
+    else {
+        return Ok(None);
+    };
+    let picked = picked
+        .into_path()
+        .map_err(|err| GitError::new(GitErrorKind::InvalidPath, err.to_string()))?;
+    let path = picked.canonicalize()?;
+    trust::ensure_decided_blocking(app, &path.to_string_lossy());
+
+    match inspect_workspace(&path, false) {
+        Ok(workspace) => {
+            recents::record_workspace(app, &workspace.path);
+            return Ok(Some(workspace));
+        }
+        Err(err) if err.kind == GitErrorKind::NotARepository => {}
+        Err(err) => return Err(err),
+    }
+
+    let init = app
+        .dialog()
+        .message(i18n::t_args(
+            app,
+            "dialog.addWorkspace.notRepository",
+            &[("path", &path.display().to_string())],
+        ))
+        .title(i18n::t(app, "dialog.addWorkspace.title"))
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.addWorkspace.initialize"),
+            i18n::t(app, "dialog.cancel"),
+        ))
+        .blocking_show();
+    if !init {
+        return Ok(None);
+    }
+
+    Repository::init(&path)?;
+    log::info!("[git] initialized repository at {}", path.display());
+    let workspace = inspect_workspace(&path, true)?;
+    recents::record_workspace(app, &workspace.path);
+    Ok(Some(workspace))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStatusSummary {
+    repo_path: String,
+    branch: Option<String>,
+    detached: bool,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    staged: usize,
+    unstaged: usize,
+    untracked: usize,
+    conflicted: usize,
+    has_staged: bool,
+    has_unstaged: bool,
+    has_untracked: bool,
+    has_conflicts: bool,
+    /// Tracked changes exist (staged, unstaged or conflicted); untracked files alone don't count.
+    is_dirty: bool,
+}
+
+/// Workspace whose status is pushed as `openchamber:git-status` events.
+#[derive(Default)]
+pub(crate) struct GitStatusWatchState {
+    repo_path: Mutex<Option<String>>,
+    last: Mutex<Option<GitStatusSummary>>,
+}
+
+fn git_status(repo_path: &str) -> GitResult<GitStatusSummary> {
+    let repo = open_repository(repo_path)?;
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .include_ignored(false)
+        .renames_head_to_index(true);
+    let statuses = repo.statuses(Some(&mut opts))?;
+
+    let staged_mask = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+    let unstaged_mask =
+        Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE;
+
+    let (mut staged, mut unstaged, mut untracked, mut conflicted) = (0, 0, 0, 0);
+    for entry in statuses.iter() {
+        let status = entry.status();
+        if status.is_conflicted() {
+            conflicted += 1;
+            continue;
+        }
+        if status.intersects(staged_mask) {
+            staged += 1;
+        }
+        if status.intersects(unstaged_mask) {
+            unstaged += 1;
+        }
+        if status.is_wt_new() {
+            untracked += 1;
+        }
+    }
+
+    let detached = repo.head_detached().unwrap_or(false);
+    let (branch, _) = head_info(&repo);
+
+    let mut upstream = None;
+    let (mut ahead, mut behind) = (0, 0);
+    if let Some(name) = branch.as_deref() {
+        if let Ok(upstream_branch) = repo
+            .find_branch(name, BranchType::Local)
+            .and_then(|local| local.upstream())
+        {
+            upstream = upstream_branch.name().ok().flatten().map(str::to_string);
+            if let (Ok(local_oid), Some(upstream_oid)) =
+                (repo.refname_to_id("HEAD"), upstream_branch.get().target())
+            {
+                (ahead, behind) = repo.graph_ahead_behind(local_oid, upstream_oid)?;
+            }
+        }
+    }
+
+    Ok(GitStatusSummary {
+        repo_path: repo_path.to_string(),
+        branch,
+        detached,
+        upstream,
+        ahead,
+        behind,
+        staged,
+        unstaged,
+        untracked,
+        conflicted,
+        has_staged: staged > 0,
+        has_unstaged: unstaged > 0,
+        has_untracked: untracked > 0,
+        has_conflicts: conflicted > 0,
+        is_dirty: staged + unstaged + conflicted > 0,
+    })
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommitInfo {
+    oid: String,
+    summary: String,
+}
+
+/// Hooks `git commit` runs; libgit2 runs none of them.
+const COMMIT_HOOKS: &[&str] = &[
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+];
+
+pub(crate) fn workdir(repo: &Repository) -> GitResult<PathBuf> {
+    repo.workdir().map(Path::to_path_buf).ok_or_else(|| {
+        GitError::new(
+            GitErrorKind::NotARepository,
+            "Bare repositories have no working tree",
+        )
+    })
+}
+
+/// `path` relative to the worktree root. Absolute paths must lie inside it, and
+/// relative ones may not climb out of it.
+pub(crate) fn repo_relative(workdir: &Path, path: &str) -> GitResult<PathBuf> {
+    let trimmed = path.trim();
+    let requested = Path::new(trimmed);
+    let relative = if requested.is_absolute() {
+        requested
+            .strip_prefix(workdir)
+            .ok()
+            .map(Path::to_path_buf)
+            .or_else(|| {
+                let canonical = requested.canonicalize().ok()?;
+                let root = workdir.canonicalize().ok()?;
+                canonical.strip_prefix(root).ok().map(Path::to_path_buf)
+            })
+    } else {
+        Some(requested.to_path_buf())
+    };
+    match relative {
+        Some(relative)
+            if !relative.as_os_str().is_empty()
+                && relative
+                    .components()
+                    .all(|component| matches!(component, std::path::Component::Normal(_))) =>
+        {
+            Ok(relative)
+        }
+        _ => Err(GitError::new(
+            GitErrorKind::InvalidPath,
+            format!("Not a path inside the repository: {trimmed}"),
+        )),
+    }
+}
+
+fn relative_paths(repo: &Repository, paths: &[String]) -> GitResult<Vec<PathBuf>> {
+    let workdir = workdir(repo)?;
+    paths
+        .iter()
+        .map(|path| repo_relative(&workdir, path))
+        .collect()
+}
+
+/// `git add` for files and folders, including deletions.
+fn stage_paths(repo_path: &str, paths: &[String]) -> GitResult<()> {
+    let repo = open_repository(repo_path)?;
+    let workdir = workdir(&repo)?;
+    let relative = relative_paths(&repo, paths)?;
+    let mut index = repo.index()?;
+    for path in &relative {
+        match workdir.join(path).symlink_metadata() {
+            Ok(meta) if meta.is_dir() => {
+                index.add_all(
+                    [path],
+                    git2::IndexAddOption::DEFAULT | git2::IndexAddOption::DISABLE_PATHSPEC_MATCH,
+                    None,
+                )?;
+                // Picks up files deleted inside the folder.
+                index.update_all([path], None)?;
+            }
+            Ok(_) => index.add_path(path)?,
+            Err(_) => index
+                .remove_path(path)
+                .or_else(|_| index.remove_dir(path, 0))?,
+        }
+    }
+    index.write()?;
+    log::info!("[git] staged {} path(s) in {repo_path}", relative.len());
+    Ok(())
+}
+
+/// `git restore --staged`: resets the index entries to HEAD, or drops them before
+/// the first commit.
+fn unstage_paths(repo_path: &str, paths: &[String]) -> GitResult<()> {
+    let repo = open_repository(repo_path)?;
+    let relative = relative_paths(&repo, paths)?;
+    match repo
+        .head()
+        .and_then(|head| head.peel(git2::ObjectType::Commit))
+    {
+        Ok(head) => repo.reset_default(Some(&head), &relative)?,
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+            let mut index = repo.index()?;
+            for path in &relative {
+                index
+                    .remove_path(path)
+                    .or_else(|_| index.remove_dir(path, 0))?;
+            }
+            index.write()?;
+        }
+        Err(err) => return Err(err.into()),
+    }
+    log::info!("[git] unstaged {} path(s) in {repo_path}", relative.len());
+    Ok(())
+}
+
+/// `core.hooksPath` if set (relative to the worktree root, like git), else `.git/hooks`.
+fn hooks_dir(repo: &Repository) -> PathBuf {
+    let configured = repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_path("core.hooksPath").ok());
+    match configured {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => repo.workdir().unwrap_or_else(|| repo.path()).join(dir),
+        None => repo.commondir().join("hooks"),
+    }
+}
+
+fn has_commit_hooks(repo: &Repository) -> bool {
+    let dir = hooks_dir(repo);
+    COMMIT_HOOKS
+        .iter()
+        .any(|hook| dependencies::is_executable(&dir.join(hook)))
+}
+
+/// Commits libgit2 cannot make faithfully: hooks to run, a merge or rebase to
+/// conclude, or signing to do.
+fn needs_git_cli(repo: &Repository) -> bool {
+    has_commit_hooks(repo) || repo.state() != git2::RepositoryState::Clean
+}
+
+/// Appends a `Signed-off-by` trailer unless the message already carries it.
+fn add_signoff(message: &str, signature: &git2::Signature<'_>) -> String {
+    let trailer = format!(
+        "Signed-off-by: {} <{}>",
+        signature.name().unwrap_or_default(),
+        signature.email().unwrap_or_default()
+    );
+    let body = message.trim_end();
+    if body.lines().any(|line| line.trim() == trailer) {
+        return message.to_string();
+    }
+    let last_paragraph = body.rsplit("\n\n").next().unwrap_or_default();
+    let ends_with_trailers = body.contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+        });
+    let separator = if ends_with_trailers { "\n" } else { "\n\n" };
+    format!("{body}{separator}{trailer}\n")
+}
+
+fn commit_info(repo: &Repository, oid: git2::Oid) -> GitResult<CommitInfo> {
+    let commit = repo.find_commit(oid)?;
+    Ok(CommitInfo {
+        oid: oid.to_string(),
+        summary: commit.summary().unwrap_or_default().to_string(),
+    })
+}
+
+/// Points HEAD, or the branch it refers to, at `oid`, logging `reflog` like git does.
+fn advance_head(repo: &Repository, oid: git2::Oid, reflog: &str) -> GitResult<()> {
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target() {
+        Some(branch) => {
+            repo.reference(branch, oid, true, reflog)?;
+        }
+        None => repo.set_head_detached(oid)?,
+    }
+    Ok(())
+}
+
+fn commit_with_libgit2(
+    repo: &Repository,
+    message: &str,
+    amend: bool,
+    signoff: bool,
+    signing: Option<&git_signing::SigningConfig>,
+) -> GitResult<CommitInfo> {
+    let signature = repo.signature().map_err(|err| {
+        GitError::new(
+            GitErrorKind::Git,
+            format!("Set user.name and user.email to commit: {}", err.message()),
+        )
+    })?;
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Err(GitError::new(
+            GitErrorKind::Conflict,
+            "Resolve the merge conflicts before committing",
+        ));
+    }
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let (author, message, parents, reflog_kind) = if amend {
+        let head = head.ok_or_else(|| {
+            GitError::new(GitErrorKind::NotFound, "There is no commit to amend yet")
+        })?;
+        let message = if message.is_empty() {
+            head.message().unwrap_or_default().to_string()
+        } else {
+            message.to_string()
+        };
+        let author = head.author().to_owned();
+        let parents = head.parents().collect::<Vec<_>>();
+        (author, message, parents, "commit (amend)")
+    } else {
+        if message.is_empty() {
+            return Err(GitError::new(
+                GitErrorKind::Git,
+                "Commit message must not be empty",
+            ));
+        }
+        let unchanged = match &head {
+            Some(head) => head.tree_id() == tree.id(),
+            None => index.is_empty(),
+        };
+        if unchanged {
+            return Err(GitError::new(
+                GitErrorKind::NothingToCommit,
+                "No staged changes to commit",
+            ));
+        }
+        let reflog_kind = if head.is_some() {
+            "commit"
+        } else {
+            "commit (initial)"
+        };
+        (
+            signature.to_owned(),
+            message.to_string(),
+            head.into_iter().collect(),
+            reflog_kind,
+        )
+    };
+    let message = if signoff {
+        add_signoff(&message, &signature)
+    } else {
+        message
+    };
+    let parents: Vec<&git2::Commit<'_>> = parents.iter().collect();
+
+    // The commit is written first and HEAD moved after, so amending (whose parents
+    // are not HEAD) and signing go through the same path.
+    let oid = match signing {
+        Some(signing) => {
+            let buffer =
+                repo.commit_create_buffer(&author, &signature, &message, &tree, &parents)?;
+            let buffer = buffer
+                .as_str()
+                .ok_or_else(|| GitError::new(GitErrorKind::Git, "Commit is not valid UTF-8"))?;
+            let gpgsig = git_signing::sign(signing, &signature, buffer)?;
+            repo.commit_signed(buffer, &gpgsig, None)?
+        }
+        None => repo.commit(None, &author, &signature, &message, &tree, &parents)?,
+    };
+    let summary = message.lines().next().unwrap_or_default();
+    advance_head(repo, oid, &format!("{reflog_kind}: {summary}"))?;
+    commit_info(repo, oid)
+}
+
+/// Runs `git commit` so hooks, merges and signing behave exactly as on the command line.
+fn commit_with_cli(
+    repo: &Repository,
+    message: &str,
+    amend: bool,
+    signoff: bool,
+    sign: bool,
+) -> GitResult<CommitInfo> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new("git");
+    command.current_dir(workdir(repo)?).arg("commit");
+    if message.is_empty() && amend {
+        command.arg("--no-edit");
+    } else {
+        command.args(["--file", "-"]);
+    }
+    if amend {
+        command.arg("--amend");
+    }
+    if signoff {
+        command.arg("--signoff");
+    }
+    command.arg(if sign { "--gpg-sign" } else { "--no-gpg-sign" });
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| GitError::new(GitErrorKind::Io, format!("Failed to run git: {err}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let details = format!("{}\n{}", stderr.trim(), stdout.trim())
+            .trim()
+            .to_string();
+        let kind = if details.contains("nothing to commit") || details.contains("no changes added")
+        {
+            GitErrorKind::NothingToCommit
+        } else if sign && details.contains("failed to sign") {
+            GitErrorKind::SigningFailed
+        } else if has_commit_hooks(repo) {
+            GitErrorKind::HookFailed
+        } else {
+            GitErrorKind::Git
+        };
+        return Err(GitError::new(kind, details));
+    }
+    commit_info(repo, repo.refname_to_id("HEAD")?)
+}
+
+fn commit(
+    repo_path: &str,
+    message: &str,
+    amend: bool,
+    signoff: bool,
+    sign: bool,
+) -> GitResult<CommitInfo> {
+    let repo = open_repository(repo_path)?;
+    let message = git2::message_prettify(message, None)?;
+    let info = if needs_git_cli(&repo) {
+        commit_with_cli(&repo, &message, amend, signoff, sign)?
+    } else {
+        let signing = if sign {
+            Some(git_signing::signing_config(&repo)?)
+        } else {
+            None
+        };
+        commit_with_libgit2(&repo, &message, amend, signoff, signing.as_ref())?
+    };
+    log::info!("[git] committed {} in {repo_path}", info.oid);
+    Ok(info)
+}
+
+/// The workspace the UI currently has open, as last set via `desktop_git_watch_status`.
+pub(crate) fn watched_workspace<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    app.try_state::<GitStatusWatchState>()?
+        .repo_path
+        .lock()
+        .expect("git watch mutex")
+        .clone()
+}
+
+/// Polls the watched workspace and emits `openchamber:git-status` whenever it changes.
+pub(crate) fn spawn_git_status_watcher(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(GIT_STATUS_POLL_INTERVAL).await;
+
+            let Some(state) = app.try_state::<GitStatusWatchState>() else {
+                continue;
+            };
+            let Some(repo_path) = state.repo_path.lock().expect("git watch mutex").clone() else {
+                continue;
+            };
+
+            let summary = match tauri::async_runtime::spawn_blocking({
+                let repo_path = repo_path.clone();
+                move || git_status(&repo_path)
+            })
+            .await
+            {
+                Ok(Ok(summary)) => summary,
+                Ok(Err(err)) => {
+                    log::debug!("[git] status failed for {repo_path}: {err}");
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            // The watched workspace may have changed while the status was computed.
+            if state.repo_path.lock().expect("git watch mutex").as_deref() != Some(&repo_path) {
+                continue;
+            }
+
+            let mut last = state.last.lock().expect("git status mutex");
+            if last.as_ref() != Some(&summary) {
+                events::emit(&app, DesktopEvent::GitStatus(summary.clone()));
+                *last = Some(summary);
+            }
+        }
+    });
+}
+
+/// Looks for stale agent worktrees of the open workspace every few hours and
+/// reports them as `openchamber:worktree-prune`, so the UI can offer to remove them.
+/// Only a dry run: nothing is deleted without the user asking.
+pub(crate) fn spawn_worktree_maintenance(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WORKTREE_MAINTENANCE_INTERVAL).await;
+
+            let Some(repo_path) = watched_workspace(&app) else {
+                continue;
+            };
+            let report = match tauri::async_runtime::spawn_blocking({
+                let repo_path = repo_path.clone();
+                move || prune_worktrees(&repo_path, true, None)
+            })
+            .await
+            {
+                Ok(Ok(report)) => report,
+                Ok(Err(err)) => {
+                    log::debug!("[git] worktree maintenance failed for {repo_path}: {err}");
+                    continue;
+                }
+                Err(_) => continue,
+            };
+            if report
+                .worktrees
+                .iter()
+                .any(|worktree| worktree.kept_because.is_none())
+            {
+                events::emit(&app, DesktopEvent::WorktreePrune(report));
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_status(
+    webview: tauri::Webview,
+    repo_path: String,
+) -> Result<GitStatusSummary, GitError> {
+    crate::origin::require_local(&webview, "read git status")?;
+    tauri::async_runtime::spawn_blocking(move || git_status(&repo_path))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+/// Sets (or clears, with `None`) the workspace watched for `openchamber:git-status` events.
+#[tauri::command]
+pub(crate) fn desktop_git_watch_status(
+    webview: tauri::Webview,
+    state: tauri::State<'_, GitStatusWatchState>,
+    repo_path: Option<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "watch git status")?;
+    let repo_path = repo_path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    *state.repo_path.lock().expect("git watch mutex") = repo_path;
+    *state.last.lock().expect("git status mutex") = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stage(
+    webview: tauri::Webview,
+    repo: String,
+    paths: Vec<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "git stage")?;
+    tauri::async_runtime::spawn_blocking(move || stage_paths(&repo, &paths))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_unstage(
+    webview: tauri::Webview,
+    repo: String,
+    paths: Vec<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "git unstage")?;
+    tauri::async_runtime::spawn_blocking(move || unstage_paths(&repo, &paths))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+/// Commits the index. An empty `message` with `amend` keeps the previous message.
+/// `sign` defaults to `commit.gpgsign`. Goes through the `git` CLI when hooks or an
+/// unfinished merge need it; that and signing run programs named by the
+/// repository's config, so they require a trusted workspace.
+#[tauri::command]
+pub(crate) async fn desktop_git_commit(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    message: String,
+    amend: bool,
+    signoff: bool,
+    sign: Option<bool>,
+) -> Result<CommitInfo, GitError> {
+    crate::origin::require_local(&webview, "git commit")?;
+    let (uses_cli, sign) = {
+        let handle = open_repository(&repo)?;
+        let sign = match sign {
+            Some(sign) => sign,
+            None => git_signing::signing_config(&handle)?.enabled(),
+        };
+        (needs_git_cli(&handle), sign)
+    };
+    let uses_cli = uses_cli || sign;
+    if uses_cli {
+        require_trusted(&app, &repo).await?;
+    }
+    let commit_repo = repo.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        commit(&commit_repo, &message, amend, signoff, sign)
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?;
+    if uses_cli {
+        audit::record(&app, AuditCategory::Process, "git commit", repo, &result);
+    }
+    result
+}
+
+/// Creates a worktree for `branch` at `path`. With `carry_changes`, the current
+/// uncommitted changes of `repo` (untracked files included) move to the new worktree.
+#[tauri::command]
+pub(crate) async fn desktop_worktree_create(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    branch: String,
+    path: String,
+    carry_changes: Option<bool>,
+) -> Result<WorktreeInfo, GitError> {
+    crate::origin::require_local(&webview, "create a worktree")?;
+    require_trusted(&app, &repo).await?;
+    tauri::async_runtime::spawn_blocking(move || {
+        if carry_changes.unwrap_or(false) {
+            create_worktree_with_changes(&repo, &branch, &path)
+        } else {
+            create_worktree(&repo, &branch, &path)
+        }
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_worktree_list(
+    webview: tauri::Webview,
+    repo: String,
+) -> Result<Vec<WorktreeInfo>, GitError> {
+    crate::origin::require_local(&webview, "list worktrees")?;
+    tauri::async_runtime::spawn_blocking(move || list_worktrees(&open_main_repository(&repo)?))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_worktree_remove(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    force: bool,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "remove a worktree")?;
+    require_trusted(&app, &path).await?;
+    tauri::async_runtime::spawn_blocking(move || remove_worktree(&path, force))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+/// Reports stale agent worktrees of `repo` and, unless `dry_run`, removes them.
+/// `sessions` lists the worktree paths sessions still use; without it, worktrees are
+/// not judged by their sessions. The report is also sent as
+/// `openchamber:worktree-prune`.
+#[tauri::command]
+pub(crate) async fn desktop_worktree_prune(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    dry_run: bool,
+    sessions: Option<Vec<String>>,
+) -> Result<WorktreePruneReport, GitError> {
+    crate::origin::require_local(&webview, "prune worktrees")?;
+    if !dry_run {
+        require_trusted(&app, &repo).await?;
+    }
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        prune_worktrees(&repo, dry_run, sessions.as_deref())
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))??;
+    events::emit(&app, DesktopEvent::WorktreePrune(report.clone()));
+    Ok(report)
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_branches(
+    webview: tauri::Webview,
+    repo: String,
+) -> Result<Vec<BranchInfo>, GitError> {
+    crate::origin::require_local(&webview, "list branches")?;
+    tauri::async_runtime::spawn_blocking(move || list_branches(&repo))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_create_branch(
+    webview: tauri::Webview,
+    repo: String,
+    name: String,
+    from: Option<String>,
+) -> Result<BranchInfo, GitError> {
+    crate::origin::require_local(&webview, "create a branch")?;
+    tauri::async_runtime::spawn_blocking(move || create_branch(&repo, &name, from.as_deref()))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+/// Switches the worktree to `reference`. Returns the checked-out branch, or `None`
+/// for a detached HEAD. Fails with `dirtyWorktree` (listing the files) rather than
+/// overwrite local changes.
+#[tauri::command]
+pub(crate) async fn desktop_git_checkout(
+    webview: tauri::Webview,
+    repo: String,
+    reference: String,
+) -> Result<Option<String>, GitError> {
+    crate::origin::require_local(&webview, "git checkout")?;
+    tauri::async_runtime::spawn_blocking(move || checkout(&repo, &reference))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+/// Deletes a local branch; unmerged ones only with `force`.
+#[tauri::command]
+pub(crate) async fn desktop_git_delete_branch(
+    webview: tauri::Webview,
+    repo: String,
+    name: String,
+    force: bool,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "delete a branch")?;
+    tauri::async_runtime::spawn_blocking(move || delete_branch(&repo, &name, force))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stash_list(
+    webview: tauri::Webview,
+    repo: String,
+) -> Result<Vec<StashInfo>, GitError> {
+    crate::origin::require_local(&webview, "list stashes")?;
+    tauri::async_runtime::spawn_blocking(move || list_stashes(&repo))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stash_save(
+    webview: tauri::Webview,
+    repo: String,
+    message: Option<String>,
+    include_untracked: bool,
+) -> Result<StashInfo, GitError> {
+    crate::origin::require_local(&webview, "git stash")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        save_stash(&repo, message.as_deref(), include_untracked)
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stash_apply(
+    webview: tauri::Webview,
+    repo: String,
+    index: usize,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "apply a stash")?;
+    tauri::async_runtime::spawn_blocking(move || apply_stash(&repo, index, false))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stash_pop(
+    webview: tauri::Webview,
+    repo: String,
+    index: usize,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "pop a stash")?;
+    tauri::async_runtime::spawn_blocking(move || apply_stash(&repo, index, true))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_stash_drop(
+    webview: tauri::Webview,
+    repo: String,
+    index: usize,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "drop a stash")?;
+    tauri::async_runtime::spawn_blocking(move || drop_stash(&repo, index))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_pick_workspace(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<Option<PickedWorkspace>, GitError> {
+    crate::origin::require_local(&webview, "pick a workspace")?;
+    tauri::async_runtime::spawn_blocking(move || pick_workspace(&app))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
@@ -0,0 +1,418 @@
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Patch, Repository, Tree};
+use serde::{Deserialize, Serialize};
+
+use crate::git::{open_repository, GitError, GitErrorKind};
+
+/// `from`/`to` value meaning the index rather than a revision.
+const INDEX_SIDE: &str = "INDEX";
+/// `from`/`to` value meaning the working tree rather than a revision.
+const WORKTREE_SIDE: &str = "WORKTREE";
+/// Word diffs are skipped for line pairs whose token grid exceeds this, to keep
+/// minified or generated lines from stalling the diff.
+const MAX_WORD_DIFF_CELLS: usize = 250_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum DiffFileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+    Typechange,
+    Untracked,
+    Conflicted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum DiffLineKind {
+    Context,
+    Addition,
+    Deletion,
+}
+
+/// A changed span within a line, in UTF-16 code units so it indexes JS strings.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WordChange {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffLine {
+    kind: DiffLineKind,
+    old_line: Option<u32>,
+    new_line: Option<u32>,
+    /// Without the trailing newline.
+    content: String,
+    /// The file ends on this line without a newline.
+    no_newline: bool,
+    /// Spans that differ from the paired line on the other side, when word diffs
+    /// were requested and the line has a counterpart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    word_changes: Option<Vec<WordChange>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffHunk {
+    header: String,
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<DiffLine>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffFile {
+    status: DiffFileStatus,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    /// Binary files come without hunks.
+    binary: bool,
+    additions: usize,
+    deletions: usize,
+    hunks: Vec<DiffHunk>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitDiff {
+    files: Vec<DiffFile>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct GitDiffOptions {
+    /// Unchanged lines around each hunk; git's default is 3.
+    context_lines: Option<u32>,
+    ignore_whitespace: bool,
+    /// Mark the changed words of paired deletion/addition lines.
+    word_diff: bool,
+}
+
+enum DiffSide<'repo> {
+    Tree(Option<Tree<'repo>>),
+    Index,
+    Worktree,
+}
+
+/// A revision, `INDEX` or `WORKTREE`. Revisions on an unborn branch resolve to
+/// the empty tree.
+fn resolve_side<'repo>(repo: &'repo Repository, spec: &str) -> Result<DiffSide<'repo>, GitError> {
+    match spec {
+        INDEX_SIDE => Ok(DiffSide::Index),
+        WORKTREE_SIDE => Ok(DiffSide::Worktree),
+        "HEAD" if repo.head().is_err() => Ok(DiffSide::Tree(None)),
+        revision => Ok(DiffSide::Tree(Some(
+            repo.revparse_single(revision)?.peel_to_tree()?,
+        ))),
+    }
+}
+
+fn build_diff<'repo>(
+    repo: &'repo Repository,
+    from: &str,
+    to: &str,
+    opts: &mut DiffOptions,
+) -> Result<Diff<'repo>, GitError> {
+    let diff = match (resolve_side(repo, from)?, resolve_side(repo, to)?) {
+        (DiffSide::Tree(old), DiffSide::Tree(new)) => {
+            repo.diff_tree_to_tree(old.as_ref(), new.as_ref(), Some(opts))?
+        }
+        (DiffSide::Tree(old), DiffSide::Index) => {
+            repo.diff_tree_to_index(old.as_ref(), None, Some(opts))?
+        }
+        (DiffSide::Tree(old), DiffSide::Worktree) => {
+            repo.diff_tree_to_workdir_with_index(old.as_ref(), Some(opts))?
+        }
+        (DiffSide::Index, DiffSide::Worktree) => repo.diff_index_to_workdir(None, Some(opts))?,
+        _ => {
+            return Err(GitError::new(
+                GitErrorKind::Git,
+                format!("Cannot diff {from} against {to}; the older side must come first"),
+            ))
+        }
+    };
+    Ok(diff)
+}
+
+fn file_status(status: Delta) -> DiffFileStatus {
+    match status {
+        Delta::Added => DiffFileStatus::Added,
+        Delta::Deleted => DiffFileStatus::Deleted,
+        Delta::Renamed => DiffFileStatus::Renamed,
+        Delta::Copied => DiffFileStatus::Copied,
+        Delta::Typechange => DiffFileStatus::Typechange,
+        Delta::Untracked => DiffFileStatus::Untracked,
+        Delta::Conflicted => DiffFileStatus::Conflicted,
+        _ => DiffFileStatus::Modified,
+    }
+}
+
+/// Words, whitespace runs and single punctuation characters, as byte ranges.
+fn tokenize(line: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let class = |c: char| {
+            if c.is_alphanumeric() || c == '_' {
+                0
+            } else if c.is_whitespace() {
+                1
+            } else {
+                2
+            }
+        };
+        let kind = class(c);
+        let mut end = start + c.len_utf8();
+        if kind != 2 {
+            while let Some(&(index, next)) = chars.peek() {
+                if class(next) != kind {
+                    break;
+                }
+                end = index + next.len_utf8();
+                chars.next();
+            }
+        }
+        tokens.push((start, end));
+    }
+    tokens
+}
+
+fn slice(text: &str, (start, end): (usize, usize)) -> &str {
+    &text[start..end]
+}
+
+/// Byte ranges of `line` not shared with `other`, via a longest common
+/// subsequence over tokens. `None` when the lines are too long to compare.
+fn changed_ranges(line: &str, other: &str) -> Option<Vec<(usize, usize)>> {
+    let ours = tokenize(line);
+    let theirs = tokenize(other);
+    if ours.len().saturating_mul(theirs.len()) > MAX_WORD_DIFF_CELLS {
+        return None;
+    }
+    let (n, m) = (ours.len(), theirs.len());
+    // lcs[i][j]: common tokens of ours[i..] and theirs[j..].
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if slice(line, ours[i]) == slice(other, theirs[j]) {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n {
+        if j < m && slice(line, ours[i]) == slice(other, theirs[j]) {
+            i += 1;
+            j += 1;
+        } else if j < m && lcs[i * (m + 1) + j + 1] >= lcs[(i + 1) * (m + 1) + j] {
+            j += 1;
+        } else {
+            let (start, end) = ours[i];
+            match ranges.last_mut() {
+                Some(last) if last.1 == start => last.1 = end,
+                _ => ranges.push((start, end)),
+            }
+            i += 1;
+        }
+    }
+    Some(ranges)
+}
+
+fn utf16_offset(line: &str, byte: usize) -> usize {
+    line[..byte].encode_utf16().count()
+}
+
+fn word_changes(line: &str, other: &str) -> Option<Vec<WordChange>> {
+    let ranges = changed_ranges(line, other)?;
+    Some(
+        ranges
+            .into_iter()
+            .map(|(start, end)| WordChange {
+                start: utf16_offset(line, start),
+                end: utf16_offset(line, end),
+            })
+            .collect(),
+    )
+}
+
+/// Pairs each run of deletions with the additions right after it, line by line,
+/// and marks the words that changed between the two.
+fn add_word_changes(lines: &mut [DiffLine]) {
+    let mut index = 0;
+    while index < lines.len() {
+        let deletions_start = index;
+        while index < lines.len() && lines[index].kind == DiffLineKind::Deletion {
+            index += 1;
+        }
+        let additions_start = index;
+        while index < lines.len() && lines[index].kind == DiffLineKind::Addition {
+            index += 1;
+        }
+        let pairs = (additions_start - deletions_start).min(index - additions_start);
+        for offset in 0..pairs {
+            let (deleted, added) = (deletions_start + offset, additions_start + offset);
+            let old = lines[deleted].content.clone();
+            let new = lines[added].content.clone();
+            lines[deleted].word_changes = word_changes(&old, &new);
+            lines[added].word_changes = word_changes(&new, &old);
+        }
+        if index == deletions_start {
+            index += 1;
+        }
+    }
+}
+
+fn collect_hunks(patch: &Patch<'_>, word_diff: bool) -> Result<Vec<DiffHunk>, GitError> {
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index)?;
+        let mut lines: Vec<DiffLine> = Vec::with_capacity(line_count);
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index)?;
+            let kind = match line.origin() {
+                ' ' => DiffLineKind::Context,
+                '+' => DiffLineKind::Addition,
+                '-' => DiffLineKind::Deletion,
+                // "\ No newline at end of file" belongs to the line before it.
+                '=' | '>' | '<' => {
+                    if let Some(last) = lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let content = String::from_utf8_lossy(line.content());
+            lines.push(DiffLine {
+                kind,
+                old_line: line.old_lineno(),
+                new_line: line.new_lineno(),
+                content: content.trim_end_matches(['\n', '\r']).to_string(),
+                no_newline: false,
+                word_changes: None,
+            });
+        }
+        if word_diff {
+            add_word_changes(&mut lines);
+        }
+        hunks.push(DiffHunk {
+            header: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
+    Ok(hunks)
+}
+
+fn git_diff(
+    repo_path: &str,
+    from: &str,
+    to: &str,
+    path: Option<&str>,
+    options: &GitDiffOptions,
+) -> Result<GitDiff, GitError> {
+    let repo = open_repository(repo_path)?;
+
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true)
+        .include_typechange(true)
+        .ignore_whitespace(options.ignore_whitespace)
+        .context_lines(options.context_lines.unwrap_or(3));
+    if let Some(path) = path.map(str::trim).filter(|path| !path.is_empty()) {
+        opts.pathspec(path).disable_pathspec_match(true);
+    }
+    let mut diff = build_diff(&repo, from, to, &mut opts)?;
+
+    let mut find = DiffFindOptions::new();
+    find.renames(true)
+        .copies(true)
+        .for_untracked(true)
+        .renames_from_rewrites(true);
+    diff.find_similar(Some(&mut find))?;
+
+    let mut files = Vec::with_capacity(diff.deltas().len());
+    for index in 0..diff.deltas().len() {
+        let patch = Patch::from_diff(&diff, index)?;
+        let Some(delta) = diff.get_delta(index) else {
+            continue;
+        };
+        let binary = patch
+            .as_ref()
+            .is_none_or(|patch| patch.delta().flags().is_binary());
+        let (additions, deletions, hunks) = match patch.as_ref().filter(|_| !binary) {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (
+                    additions,
+                    deletions,
+                    collect_hunks(patch, options.word_diff)?,
+                )
+            }
+            None => (0, 0, Vec::new()),
+        };
+        let path_of = |file: git2::DiffFile<'_>| {
+            file.path()
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+        };
+        let status = file_status(delta.status());
+        files.push(DiffFile {
+            status,
+            old_path: (status != DiffFileStatus::Added && status != DiffFileStatus::Untracked)
+                .then(|| path_of(delta.old_file()))
+                .flatten(),
+            new_path: (status != DiffFileStatus::Deleted)
+                .then(|| path_of(delta.new_file()))
+                .flatten(),
+            binary,
+            additions,
+            deletions,
+            hunks,
+        });
+    }
+    Ok(GitDiff { files })
+}
+
+/// Structured diff between two sides of `repo`: revisions, `INDEX` or `WORKTREE`.
+/// `from` defaults to `HEAD` and `to` to `WORKTREE`; `path` limits it to one file
+/// or folder. Renames and copies are detected, binary files come without hunks.
+#[tauri::command]
+pub(crate) async fn desktop_git_diff(
+    webview: tauri::Webview,
+    repo: String,
+    from: Option<String>,
+    to: Option<String>,
+    path: Option<String>,
+    options: Option<GitDiffOptions>,
+) -> Result<GitDiff, GitError> {
+    crate::origin::require_local(&webview, "read git diffs")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        git_diff(
+            &repo,
+            from.as_deref().unwrap_or("HEAD"),
+            to.as_deref().unwrap_or(WORKTREE_SIDE),
+            path.as_deref(),
+            &options.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
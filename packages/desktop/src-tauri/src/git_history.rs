@@ -0,0 +1,279 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use git2::{BlameOptions, Commit, Oid, Repository, Sort};
+use serde::Serialize;
+
+use crate::git::{self, open_repository, GitError, GitErrorKind};
+
+const DEFAULT_LOG_LIMIT: usize = 50;
+const MAX_LOG_LIMIT: usize = 500;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LogCommit {
+    oid: String,
+    parents: Vec<String>,
+    summary: String,
+    message: String,
+    author_name: String,
+    author_email: String,
+    /// Seconds since the Unix epoch.
+    authored_at: i64,
+    committed_at: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitLogPage {
+    commits: Vec<LogCommit>,
+    /// Pass back as `cursor` for the next page; `None` on the last one.
+    next_cursor: Option<String>,
+}
+
+/// Who last changed a blamed line. Shared by all lines from the same commit.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlameCommit {
+    summary: String,
+    author_name: String,
+    author_email: String,
+    /// Seconds since the Unix epoch.
+    authored_at: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlameLine {
+    /// 1-based line number in the blamed version.
+    line: usize,
+    /// Key into `GitBlame::commits`; `None` for uncommitted lines.
+    commit: Option<String>,
+    /// Where the line was in that commit, which differs after moves and edits above it.
+    original_line: usize,
+    /// Path in that commit, for lines that came from before a rename.
+    original_path: Option<String>,
+    content: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitBlame {
+    lines: Vec<BlameLine>,
+    commits: HashMap<String, BlameCommit>,
+}
+
+/// Pages start from a fixed commit so new commits on HEAD don't shift them:
+/// `<start oid>:<commits already returned>`.
+fn parse_cursor(cursor: &str) -> Result<(Oid, usize), GitError> {
+    cursor
+        .split_once(':')
+        .and_then(|(oid, offset)| Some((Oid::from_str(oid).ok()?, offset.parse().ok()?)))
+        .ok_or_else(|| GitError::new(GitErrorKind::Git, format!("Invalid log cursor: {cursor}")))
+}
+
+fn relative_path(repo: &Repository, path: &str) -> Result<PathBuf, GitError> {
+    git::repo_relative(&git::workdir(repo)?, path)
+}
+
+/// The tree entry at `path`, so a commit touched `path` when it differs from its
+/// parents' entries.
+fn entry_at(commit: &Commit<'_>, path: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()?
+        .get_path(path)
+        .ok()
+        .map(|entry| entry.id())
+}
+
+/// Like `git log -- path` without `--full-history`: merges only count when the
+/// path differs from every parent.
+fn touches(commit: &Commit<'_>, path: &Path) -> bool {
+    let entry = entry_at(commit, path);
+    if commit.parent_count() == 0 {
+        return entry.is_some();
+    }
+    commit
+        .parents()
+        .all(|parent| entry_at(&parent, path) != entry)
+}
+
+fn log_commit(commit: &Commit<'_>) -> LogCommit {
+    let author = commit.author();
+    LogCommit {
+        oid: commit.id().to_string(),
+        parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+        summary: commit.summary().unwrap_or_default().to_string(),
+        message: commit.message().unwrap_or_default().to_string(),
+        author_name: author.name().unwrap_or_default().to_string(),
+        author_email: author.email().unwrap_or_default().to_string(),
+        authored_at: author.when().seconds(),
+        committed_at: commit.time().seconds(),
+    }
+}
+
+fn log(
+    repo_path: &str,
+    path: Option<&str>,
+    limit: Option<usize>,
+    cursor: Option<&str>,
+) -> Result<GitLogPage, GitError> {
+    let repo = open_repository(repo_path)?;
+    let path = path
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| relative_path(&repo, path))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+    let (start, offset) = match cursor {
+        Some(cursor) => parse_cursor(cursor)?,
+        None => match repo.head() {
+            Ok(head) => (head.peel_to_commit()?.id(), 0),
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => {
+                return Ok(GitLogPage {
+                    commits: Vec::new(),
+                    next_cursor: None,
+                });
+            }
+            Err(err) => return Err(err.into()),
+        },
+    };
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push(start)?;
+    let mut matching = walk
+        .filter_map(Result::ok)
+        .filter_map(|oid| repo.find_commit(oid).ok())
+        .filter(|commit| path.as_deref().is_none_or(|path| touches(commit, path)))
+        .skip(offset);
+    let commits: Vec<LogCommit> = matching
+        .by_ref()
+        .take(limit)
+        .map(|commit| log_commit(&commit))
+        .collect();
+    let more = commits.len() == limit && matching.next().is_some();
+    Ok(GitLogPage {
+        next_cursor: more.then(|| format!("{start}:{}", offset + commits.len())),
+        commits,
+    })
+}
+
+fn blame_commit(repo: &Repository, oid: Oid) -> Option<BlameCommit> {
+    let commit = repo.find_commit(oid).ok()?;
+    let author = commit.author();
+    Some(BlameCommit {
+        summary: commit.summary().unwrap_or_default().to_string(),
+        author_name: author.name().unwrap_or_default().to_string(),
+        author_email: author.email().unwrap_or_default().to_string(),
+        authored_at: author.when().seconds(),
+    })
+}
+
+/// Blames `path` at `rev`, or the working copy (uncommitted lines included) when
+/// `rev` is `None`.
+fn blame(repo_path: &str, path: &str, rev: Option<&str>) -> Result<GitBlame, GitError> {
+    let repo = open_repository(repo_path)?;
+    let relative = relative_path(&repo, path)?;
+    let rev = rev.map(str::trim).filter(|rev| !rev.is_empty());
+
+    let mut options = BlameOptions::new();
+    let content = match rev {
+        Some(rev) => {
+            let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+            options.newest_commit(commit.id());
+            let entry = commit.tree()?.get_path(&relative)?;
+            repo.find_blob(entry.id())?.content().to_vec()
+        }
+        None => std::fs::read(git::workdir(&repo)?.join(&relative))?,
+    };
+    let committed = match repo.blame_file(&relative, Some(&mut options)) {
+        Ok(blame) => Some(blame),
+        // A file that was never committed is all uncommitted lines.
+        Err(err) if rev.is_none() && err.code() == git2::ErrorCode::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    let blame = match (&committed, rev) {
+        (Some(committed), None) => Some(committed.blame_buffer(&content)?),
+        _ => None,
+    };
+    let blame = blame.as_ref().or(committed.as_ref());
+
+    let text = String::from_utf8_lossy(&content);
+    let mut commits = HashMap::new();
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, content)| {
+            let line = index + 1;
+            let hunk = blame.and_then(|blame| blame.get_line(line));
+            let commit = hunk
+                .as_ref()
+                .map(|hunk| hunk.final_commit_id())
+                .filter(|oid| !oid.is_zero());
+            if let Some(oid) = commit {
+                commits
+                    .entry(oid.to_string())
+                    .or_insert_with(|| blame_commit(&repo, oid));
+            }
+            let original_path = hunk
+                .as_ref()
+                .and_then(|hunk| hunk.path())
+                .filter(|original| *original != relative.as_path())
+                .map(|original| original.to_string_lossy().replace('\\', "/"));
+            let original_line = hunk
+                .as_ref()
+                .filter(|_| commit.is_some())
+                .map(|hunk| hunk.orig_start_line() + (line - hunk.final_start_line()))
+                .unwrap_or(line);
+            BlameLine {
+                line,
+                commit: commit.map(|oid| oid.to_string()),
+                original_line,
+                original_path,
+                content: content.to_string(),
+            }
+        })
+        .collect();
+    Ok(GitBlame {
+        lines,
+        commits: commits
+            .into_iter()
+            .filter_map(|(oid, commit)| Some((oid, commit?)))
+            .collect(),
+    })
+}
+
+/// History of the repository, or of `path` (a file or directory) within it, newest
+/// first. Pages hold `limit` commits (default 50).
+#[tauri::command]
+pub(crate) async fn desktop_git_log(
+    webview: tauri::Webview,
+    repo: String,
+    path: Option<String>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+) -> Result<GitLogPage, GitError> {
+    crate::origin::require_local(&webview, "read git history")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        log(&repo, path.as_deref(), limit, cursor.as_deref())
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_git_blame(
+    webview: tauri::Webview,
+    repo: String,
+    path: String,
+    rev: Option<String>,
+) -> Result<GitBlame, GitError> {
+    crate::origin::require_local(&webview, "read git blame")?;
+    tauri::async_runtime::spawn_blocking(move || blame(&repo, &path, rev.as_deref()))
+        .await
+        .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?
+}
@@ -0,0 +1,293 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use git2::Repository;
+use serde::Serialize;
+
+use crate::{
+    dependencies,
+    git::{open_repository, GitError, GitErrorKind},
+    sidecar_env,
+};
+
+/// `user.signingkey` values with these prefixes are the public key itself rather
+/// than a path to it.
+const LITERAL_SSH_KEY_PREFIXES: &[&str] = &["ssh-", "ecdsa-", "sk-ssh-", "sk-ecdsa-", "key::"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SigningFormat {
+    Openpgp,
+    X509,
+    Ssh,
+}
+
+/// How commits in a repository are signed, from `commit.gpgsign`, `gpg.format`,
+/// `user.signingkey` and `gpg.<format>.program`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SigningConfig {
+    /// `commit.gpgsign`: commits are signed unless the caller opts out.
+    enabled: bool,
+    format: SigningFormat,
+    key: Option<String>,
+    program: String,
+}
+
+impl SigningConfig {
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SigningStatus {
+    #[serde(flatten)]
+    config: SigningConfig,
+    /// Where the signing program was found, if it was.
+    program_path: Option<String>,
+    /// Why signing would fail, when that is known up front.
+    problem: Option<String>,
+}
+
+pub(crate) fn signing_config(repo: &Repository) -> Result<SigningConfig, GitError> {
+    let config = repo.config()?;
+    let string = |name: &str| {
+        config
+            .get_string(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let format = match string("gpg.format").as_deref() {
+        Some("ssh") => SigningFormat::Ssh,
+        Some("x509") => SigningFormat::X509,
+        _ => SigningFormat::Openpgp,
+    };
+    let program = match format {
+        SigningFormat::Openpgp => string("gpg.openpgp.program")
+            .or_else(|| string("gpg.program"))
+            .unwrap_or_else(|| "gpg".to_string()),
+        SigningFormat::X509 => string("gpg.x509.program").unwrap_or_else(|| "gpgsm".to_string()),
+        SigningFormat::Ssh => string("gpg.ssh.program").unwrap_or_else(|| "ssh-keygen".to_string()),
+    };
+    Ok(SigningConfig {
+        enabled: config.get_bool("commit.gpgsign").unwrap_or(false),
+        format,
+        key: string("user.signingkey"),
+        program,
+    })
+}
+
+/// `program` as configured, or looked up on PATH and the usual Homebrew prefixes,
+/// which a GUI launch on macOS leaves off PATH.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let program = sidecar_env::expand_home(program);
+    let path = Path::new(&program);
+    if path.components().count() > 1 {
+        return dependencies::is_executable(path).then(|| path.to_path_buf());
+    }
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    dirs.extend(["/usr/local/bin", "/opt/homebrew/bin"].map(PathBuf::from));
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{program}.exe"), program.clone()]
+    } else {
+        vec![program.clone()]
+    };
+    dirs.iter().find_map(|dir| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| dependencies::is_executable(candidate))
+    })
+}
+
+fn problem(config: &SigningConfig, program_path: Option<&Path>) -> Option<String> {
+    if program_path.is_none() {
+        return Some(format!("{} was not found", config.program));
+    }
+    if config.format == SigningFormat::Ssh && config.key.is_none() {
+        return Some("Set user.signingkey to the SSH key to sign with".to_string());
+    }
+    None
+}
+
+pub(crate) fn signing_status(repo_path: &str) -> Result<SigningStatus, GitError> {
+    let config = signing_config(&open_repository(repo_path)?)?;
+    let program_path = find_program(&config.program);
+    Ok(SigningStatus {
+        problem: problem(&config, program_path.as_deref()),
+        program_path: program_path.map(|path| path.to_string_lossy().to_string()),
+        config,
+    })
+}
+
+/// Turns gpg's stderr into something a user can act on. Pinentry failures are the
+/// common case: gpg started from a GUI app has no terminal to ask on.
+fn gpg_failure(stderr: &str, key: &str) -> String {
+    if stderr.contains("Operation cancelled") || stderr.contains("canceled") {
+        "Signing was cancelled in the passphrase prompt".to_string()
+    } else if stderr.contains("Inappropriate ioctl")
+        || stderr.contains("No pinentry")
+        || stderr.contains("pinentry")
+    {
+        "gpg could not ask for the key's passphrase; configure a graphical pinentry \
+         (e.g. pinentry-mac) or unlock the key in gpg-agent first"
+            .to_string()
+    } else if stderr.contains("No secret key") || stderr.contains("unusable secret key") {
+        format!("No usable secret key for {key}")
+    } else {
+        stderr
+            .lines()
+            .rfind(|line| !line.starts_with("[GNUPG:]") && !line.trim().is_empty())
+            .unwrap_or("gpg failed to sign the commit")
+            .trim()
+            .to_string()
+    }
+}
+
+fn ssh_failure(stderr: &str) -> String {
+    if stderr.contains("incorrect passphrase") || stderr.contains("passphrase") {
+        "The SSH key needs its passphrase; add it to ssh-agent first".to_string()
+    } else if stderr.contains("agent") && stderr.contains("refused") {
+        "ssh-agent refused to sign with the key".to_string()
+    } else {
+        stderr
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .unwrap_or("ssh-keygen failed to sign the commit")
+            .trim()
+            .to_string()
+    }
+}
+
+/// Runs `program` with `buffer` on stdin, returning stdout and stderr.
+fn run(program: &Path, args: &[&str], buffer: &str) -> Result<(bool, String, String), GitError> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn().map_err(|err| {
+        GitError::new(
+            GitErrorKind::SigningFailed,
+            format!("Failed to run {}: {err}", program.display()),
+        )
+    })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(buffer.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+/// A literal public key is written to a temporary file for `ssh-keygen -f`, which
+/// then signs through ssh-agent.
+struct TempKeyFile(PathBuf);
+
+impl Drop for TempKeyFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Signs a commit buffer the way `git commit -S` would, returning the armored
+/// signature for the `gpgsig` header.
+pub(crate) fn sign(
+    config: &SigningConfig,
+    committer: &git2::Signature<'_>,
+    buffer: &str,
+) -> Result<String, GitError> {
+    let program_path = find_program(&config.program);
+    if let Some(problem) = problem(config, program_path.as_deref()) {
+        return Err(GitError::new(GitErrorKind::SigningFailed, problem));
+    }
+    let program = program_path.unwrap_or_default();
+
+    let signature = match config.format {
+        SigningFormat::Openpgp | SigningFormat::X509 => {
+            // Without a configured key git signs as the committer.
+            let key = config.key.clone().unwrap_or_else(|| {
+                format!(
+                    "{} <{}>",
+                    committer.name().unwrap_or_default(),
+                    committer.email().unwrap_or_default()
+                )
+            });
+            let (succeeded, stdout, stderr) =
+                run(&program, &["--status-fd=2", "-bsau", &key], buffer)?;
+            if !succeeded || !stderr.contains("[GNUPG:] SIG_CREATED") {
+                return Err(GitError::new(
+                    GitErrorKind::SigningFailed,
+                    gpg_failure(&stderr, &key),
+                ));
+            }
+            stdout
+        }
+        SigningFormat::Ssh => {
+            let key = config.key.as_deref().unwrap_or_default();
+            let literal = LITERAL_SSH_KEY_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix));
+            let (key_file, _temp) = if literal {
+                let path = std::env::temp_dir().join(format!(
+                    "openchamber-signing-{}-{}.pub",
+                    std::process::id(),
+                    committer.when().seconds()
+                ));
+                std::fs::write(&path, key.trim_start_matches("key::"))?;
+                (path.clone(), Some(TempKeyFile(path)))
+            } else {
+                (PathBuf::from(sidecar_env::expand_home(key)), None)
+            };
+            let key_file = key_file.to_string_lossy().to_string();
+            let mut args = vec!["-Y", "sign", "-n", "git", "-f", &key_file];
+            if literal {
+                args.push("-U");
+            }
+            let (succeeded, stdout, stderr) = run(&program, &args, buffer)?;
+            if !succeeded {
+                return Err(GitError::new(
+                    GitErrorKind::SigningFailed,
+                    ssh_failure(&stderr),
+                ));
+            }
+            stdout
+        }
+    };
+    if signature.trim().is_empty() {
+        return Err(GitError::new(
+            GitErrorKind::SigningFailed,
+            format!("{} produced no signature", config.program),
+        ));
+    }
+    Ok(signature)
+}
+
+/// Whether commits in `repo` will be signed, with which key and program, and what
+/// would stop that from working.
+#[tauri::command]
+pub(crate) async fn desktop_git_signing_status(
+    webview: tauri::Webview,
+    repo: String,
+) -> Result<SigningStatus, GitError> {
+    crate::origin::require_local(&webview, "read signing settings")?;
+    signing_status(&repo)
+}
@@ -0,0 +1,385 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    audit::{self, AuditCategory},
+    events::{self, DesktopEvent},
+    git::{self, open_repository, GitError, GitErrorKind},
+};
+
+/// Output that means the remote refused us, checked before the network markers
+/// because ssh reports a refused key as "Could not read from remote repository" too.
+const AUTH_MARKERS: &[&str] = &[
+    "Authentication failed",
+    "Permission denied (publickey",
+    "could not read Username",
+    "could not read Password",
+    "terminal prompts disabled",
+    "Invalid username or password",
+    "Host key verification failed",
+    "returned error: 401",
+    "returned error: 403",
+];
+const DIRTY_MARKERS: &[&str] = &[
+    "would be overwritten by merge",
+    "Please commit your changes or stash them",
+    "cannot pull with rebase: You have unstaged changes",
+];
+const CONFLICT_MARKERS: &[&str] = &["CONFLICT (", "Automatic merge failed", "could not apply"];
+const NON_FAST_FORWARD_MARKERS: &[&str] = &[
+    "non-fast-forward",
+    "[rejected]",
+    "fetch first",
+    "Not possible to fast-forward",
+    "divergent branches",
+];
+const NETWORK_MARKERS: &[&str] = &[
+    "Could not resolve host",
+    "Connection refused",
+    "Connection timed out",
+    "Operation timed out",
+    "Network is unreachable",
+    "unable to access",
+    "early EOF",
+    "The remote end hung up",
+    "Could not read from remote repository",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum GitTransferOperation {
+    Fetch,
+    Pull,
+    Push,
+}
+
+impl GitTransferOperation {
+    fn subcommand(self) -> &'static str {
+        match self {
+            Self::Fetch => "fetch",
+            Self::Pull => "pull",
+            Self::Push => "push",
+        }
+    }
+}
+
+/// Progress of a fetch, pull or push, as git reports it on stderr.
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitTransferEvent {
+    repo: String,
+    operation: GitTransferOperation,
+    /// git's label for the current step, e.g. `Receiving objects`.
+    phase: Option<String>,
+    percent: Option<u8>,
+    current: Option<u64>,
+    total: Option<u64>,
+    /// Sent once at the end, whether the transfer succeeded or not.
+    done: bool,
+}
+
+/// `remote: Counting objects:  45% (450/1000)` → (`Counting objects`, 45, 450, 1000).
+fn parse_progress(line: &str) -> Option<(String, u8, Option<u64>, Option<u64>)> {
+    let line = line.trim().trim_start_matches("remote:").trim_start();
+    let (phase, rest) = line.split_once(':')?;
+    let (percent, rest) = rest.trim_start().split_once('%')?;
+    let percent = percent.trim().parse::<u8>().ok()?;
+    let counts = rest
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .and_then(|(counts, _)| counts.split_once('/'));
+    let (current, total) = match counts {
+        Some((current, total)) => (current.parse().ok(), total.parse().ok()),
+        None => (None, None),
+    };
+    Some((phase.trim().to_string(), percent, current, total))
+}
+
+fn classify(details: &str) -> GitErrorKind {
+    let matches = |markers: &[&str]| markers.iter().any(|marker| details.contains(marker));
+    if matches(AUTH_MARKERS) {
+        GitErrorKind::AuthFailed
+    } else if matches(DIRTY_MARKERS) {
+        GitErrorKind::DirtyWorktree
+    } else if matches(CONFLICT_MARKERS) {
+        GitErrorKind::Conflict
+    } else if matches(NON_FAST_FORWARD_MARKERS) {
+        GitErrorKind::NonFastForward
+    } else if matches(NETWORK_MARKERS) {
+        GitErrorKind::Network
+    } else {
+        GitErrorKind::Git
+    }
+}
+
+/// Remote names and refspecs are passed as arguments, so they may not look like options.
+fn validate_argument(label: &str, value: Option<String>) -> Result<Option<String>, GitError> {
+    let Some(value) = value.map(|value| value.trim().to_string()) else {
+        return Ok(None);
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if value.starts_with('-') || value.chars().any(char::is_whitespace) {
+        return Err(GitError::new(
+            GitErrorKind::Git,
+            format!("Invalid {label}: {value}"),
+        ));
+    }
+    Ok(Some(value))
+}
+
+/// The current branch's upstream remote, else `origin`.
+fn default_remote(repo: &git2::Repository) -> String {
+    repo.head()
+        .ok()
+        .and_then(|head| head.name().map(str::to_string))
+        .and_then(|name| repo.branch_upstream_remote(&name).ok())
+        .and_then(|remote| remote.as_str().map(str::to_string))
+        .unwrap_or_else(|| "origin".to_string())
+}
+
+fn transfer_args(
+    repo: &git2::Repository,
+    operation: GitTransferOperation,
+    remote: Option<String>,
+    refspec: Option<String>,
+) -> Result<Vec<String>, GitError> {
+    let mut args = vec![operation.subcommand().to_string(), "--progress".to_string()];
+    match operation {
+        GitTransferOperation::Fetch => {}
+        GitTransferOperation::Pull => args.push("--no-edit".to_string()),
+        GitTransferOperation::Push => {
+            // A branch pushed for the first time gets its upstream, like `push -u`.
+            let head = repo.head()?;
+            let has_upstream = head
+                .name()
+                .is_some_and(|name| repo.branch_upstream_name(name).is_ok());
+            if refspec.is_none() && !has_upstream {
+                if !head.is_branch() {
+                    return Err(GitError::new(
+                        GitErrorKind::Git,
+                        "HEAD is detached; pass a refspec to push",
+                    ));
+                }
+                args.push("--set-upstream".to_string());
+                args.push(remote.unwrap_or_else(|| default_remote(repo)));
+                args.push("HEAD".to_string());
+                return Ok(args);
+            }
+        }
+    }
+    match (remote, refspec) {
+        (Some(remote), refspec) => args.extend(std::iter::once(remote).chain(refspec)),
+        (None, Some(refspec)) => args.extend([default_remote(repo), refspec]),
+        (None, None) => {}
+    }
+    Ok(args)
+}
+
+/// Runs `git <args>` non-interactively. Credentials come from the user's credential
+/// helper and SSH agent exactly as in a terminal, but git never waits on a prompt
+/// the user cannot see: a missing credential fails as `authFailed` instead.
+fn run_git(
+    app: &tauri::AppHandle,
+    repo_path: &str,
+    operation: GitTransferOperation,
+    args: &[String],
+) -> Result<(), GitError> {
+    let repo = open_repository(repo_path)?;
+    let mut command = Command::new("git");
+    command
+        .current_dir(git::workdir(&repo)?)
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        // Output is matched against git's English messages.
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let custom_ssh = std::env::var_os("GIT_SSH_COMMAND").is_some()
+        || std::env::var_os("GIT_SSH").is_some()
+        || repo
+            .config()
+            .is_ok_and(|config| config.get_string("core.sshCommand").is_ok());
+    if !custom_ssh {
+        command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|err| GitError::new(GitErrorKind::Io, format!("Failed to run git: {err}")))?;
+    let stdout = child.stdout.take().map(|mut stdout| {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stdout.read_to_string(&mut output);
+            output
+        })
+    });
+
+    let emit = |phase: Option<String>, percent, current, total, done| {
+        events::emit(
+            app,
+            DesktopEvent::GitTransfer(GitTransferEvent {
+                repo: repo_path.to_string(),
+                operation,
+                phase,
+                percent,
+                current,
+                total,
+                done,
+            }),
+        );
+    };
+    // Progress lines end in `\r` while they update and `\n` when a step finishes.
+    let mut messages = Vec::new();
+    let mut last_progress = None;
+    let mut pending = Vec::new();
+    let mut buffer = [0u8; 4096];
+    if let Some(mut stderr) = child.stderr.take() {
+        while let Ok(read) = stderr.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            for &byte in &buffer[..read] {
+                if byte != b'\r' && byte != b'\n' {
+                    pending.push(byte);
+                    continue;
+                }
+                let line = String::from_utf8_lossy(&pending).trim().to_string();
+                pending.clear();
+                if line.is_empty() {
+                    continue;
+                }
+                match parse_progress(&line) {
+                    Some((phase, percent, current, total)) => {
+                        let key = (phase.clone(), percent);
+                        if last_progress.as_ref() != Some(&key) {
+                            emit(Some(phase), Some(percent), current, total, false);
+                            last_progress = Some(key);
+                        }
+                    }
+                    None => messages.push(line),
+                }
+            }
+        }
+    }
+    if !pending.is_empty() {
+        messages.push(String::from_utf8_lossy(&pending).trim().to_string());
+    }
+    let status = child.wait()?;
+    let stdout = stdout
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    emit(None, None, None, None, true);
+
+    if status.success() {
+        log::info!("[git] {} finished in {repo_path}", operation.subcommand());
+        return Ok(());
+    }
+    let details = format!("{}\n{}", messages.join("\n"), stdout.trim())
+        .trim()
+        .to_string();
+    let kind = classify(&details);
+    log::warn!(
+        "[git] {} failed in {repo_path}: {kind:?}",
+        operation.subcommand()
+    );
+    let mut error = GitError::new(kind, details);
+    if kind == GitErrorKind::Conflict {
+        let conflicted = repo
+            .index()
+            .ok()
+            .and_then(|index| {
+                index.conflicts().ok().map(|conflicts| {
+                    conflicts
+                        .flatten()
+                        .filter_map(|conflict| conflict.our.or(conflict.their))
+                        .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+        error = error.with_paths(conflicted);
+    }
+    Err(error)
+}
+
+async fn transfer(
+    app: tauri::AppHandle,
+    repo: String,
+    operation: GitTransferOperation,
+    remote: Option<String>,
+    refspec: Option<String>,
+) -> Result<(), GitError> {
+    // git reads hooks, `core.sshCommand` and credential helpers from the repository's
+    // own config, so only trusted workspaces may talk to their remotes.
+    git::require_trusted(&app, &repo).await?;
+    let remote = validate_argument("remote", remote)?;
+    let refspec = validate_argument("refspec", refspec)?;
+    let args = transfer_args(&open_repository(&repo)?, operation, remote, refspec)?;
+
+    let task_app = app.clone();
+    let task_repo = repo.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        run_git(&task_app, &task_repo, operation, &args)
+    })
+    .await
+    .map_err(|err| GitError::new(GitErrorKind::Io, err.to_string()))?;
+    let action = format!("git {}", operation.subcommand());
+    audit::record(&app, AuditCategory::Process, &action, repo, &result);
+    result
+}
+
+/// `git fetch [remote [refspec]]`; without a remote, the upstream's remote.
+#[tauri::command]
+pub(crate) async fn desktop_git_fetch(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    remote: Option<String>,
+    refspec: Option<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "git fetch")?;
+    transfer(app, repo, GitTransferOperation::Fetch, remote, refspec).await
+}
+
+/// `git pull`, merging or rebasing as the user's `pull.*` config says. Conflicts
+/// fail with `conflict` and list the conflicted files; the merge is left in place.
+#[tauri::command]
+pub(crate) async fn desktop_git_pull(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    remote: Option<String>,
+    refspec: Option<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "git pull")?;
+    transfer(app, repo, GitTransferOperation::Pull, remote, refspec).await
+}
+
+/// `git push`. A branch without an upstream is pushed to `remote` (default
+/// `origin`) and tracks it afterwards.
+#[tauri::command]
+pub(crate) async fn desktop_git_push(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    repo: String,
+    remote: Option<String>,
+    refspec: Option<String>,
+) -> Result<(), GitError> {
+    crate::origin::require_local(&webview, "git push")?;
+    transfer(app, repo, GitTransferOperation::Push, remote, refspec).await
+}
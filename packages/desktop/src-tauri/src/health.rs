@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    i18n, idle, kill_sidecar, local_sidecar, proxy, request_sidecar_restart, resolve_server_target,
+    servers,
+};
+
+const HEALTH_MONITOR_INTERVAL: Duration = Duration::from_secs(10);
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Successful probes slower than this are reported as degraded.
+const DEGRADED_LATENCY: Duration = Duration::from_secs(2);
+/// Consecutive failed probes before the server is considered down.
+const DOWN_AFTER_FAILURES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+enum ServerHealthStatus {
+    Healthy,
+    Degraded,
+    Down,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerHealthEvent {
+    url: String,
+    status: ServerHealthStatus,
+    latency_ms: Option<u64>,
+    consecutive_failures: u32,
+    is_remote: bool,
+}
+
+/// Sent to the main window as `openchamber:reconnect-banner` when a remote server
+/// goes down, and with `visible: false` once it answers again or is left.
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReconnectBannerEvent {
+    url: String,
+    visible: bool,
+    message: String,
+    /// Label of the button that reloads the window.
+    reconnect_label: String,
+}
+
+async fn probe(client: &reqwest::Client, url: &str) -> Option<Duration> {
+    let health_url = format!("{}/health", url.trim_end_matches('/'));
+    let sent_at = Instant::now();
+    let resp = client.get(&health_url).send().await.ok()?;
+    resp.status().is_success().then(|| sent_at.elapsed())
+}
+
+fn classify(latency: Option<Duration>, consecutive_failures: u32) -> ServerHealthStatus {
+    match latency {
+        Some(latency) if latency <= DEGRADED_LATENCY => ServerHealthStatus::Healthy,
+        Some(_) => ServerHealthStatus::Degraded,
+        None if consecutive_failures >= DOWN_AFTER_FAILURES => ServerHealthStatus::Down,
+        None => ServerHealthStatus::Degraded,
+    }
+}
+
+fn set_reconnect_banner(app: &tauri::AppHandle, url: &str, visible: bool) {
+    events::emit_to(
+        app,
+        "main",
+        DesktopEvent::ReconnectBanner(ReconnectBannerEvent {
+            url: url.to_string(),
+            visible,
+            message: i18n::t_args(app, "banner.connectionLost", &[("url", url)]),
+            reconnect_label: i18n::t(app, "banner.reconnect"),
+        }),
+    );
+}
+
+/// Restarts the local sidecar if it is ours and currently supervised. Dev servers
+/// and adopted servers have no child to restart.
+async fn restart_unresponsive_sidecar(app: &tauri::AppHandle) {
+    let Some(state) = local_sidecar(app) else {
+        return;
+    };
+    let supervised = *state
+        .supervised_generation
+        .lock()
+        .expect("sidecar generation mutex");
+    if supervised == 0 || state.child.lock().expect("sidecar mutex").is_none() {
+        return;
+    }
+    log::warn!("[health] local server unresponsive, restarting sidecar");
+    let kill_app = app.clone();
+    if let Err(err) = tauri::async_runtime::spawn_blocking(move || kill_sidecar(kill_app)).await {
+        log::warn!("[health] failed to stop sidecar: {err}");
+        return;
+    }
+    request_sidecar_restart(app, None, None, false);
+}
+
+/// Pings the connected server's `/health` endpoint in the background and reports
+/// healthy/degraded/down transitions.
+pub(crate) fn spawn_health_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut current_url: Option<String> = None;
+        let mut consecutive_failures: u32 = 0;
+        let mut last_status: Option<ServerHealthStatus> = None;
+
+        loop {
+            tokio::time::sleep(idle::poll_interval(&app, HEALTH_MONITOR_INTERVAL)).await;
+
+            let (url, local_url, is_remote) = resolve_server_target(&app);
+            let Some(url) = url else {
+                continue;
+            };
+            if current_url.as_deref() != Some(url.as_str()) {
+                // Switched servers; start counting afresh.
+                current_url = Some(url.clone());
+                consecutive_failures = 0;
+                last_status = None;
+                set_reconnect_banner(&app, &url, false);
+            }
+
+            // Rebuilt each round so proxy and server profile changes take effect.
+            let mut builder = proxy::load(&app)
+                .apply(reqwest::Client::builder())
+                .timeout(HEALTH_PROBE_TIMEOUT);
+            if let Some(tls) = is_remote
+                .then(|| servers::tls_options_for_url(&app, &url))
+                .flatten()
+            {
+                builder = match tls.apply(builder) {
+                    Ok(builder) => builder,
+                    Err(err) => {
+                        log::warn!("[health] {err}");
+                        continue;
+                    }
+                };
+            }
+            let client = match builder.build() {
+                Ok(client) => client,
+                Err(err) => {
+                    log::warn!("[health] failed to build http client: {err}");
+                    continue;
+                }
+            };
+            let latency = probe(&client, &url).await;
+            if latency.is_some() {
+                consecutive_failures = 0;
+            } else {
+                consecutive_failures = consecutive_failures.saturating_add(1);
+            }
+            let status = classify(latency, consecutive_failures);
+
+            if let (false, Some(latency), Some(state)) = (is_remote, latency, local_sidecar(&app)) {
+                *state
+                    .last_health_latency
+                    .lock()
+                    .expect("sidecar latency mutex") = Some(latency);
+            }
+
+            events::emit(
+                &app,
+                DesktopEvent::ServerHealth(ServerHealthEvent {
+                    url: url.clone(),
+                    status,
+                    latency_ms: latency.map(|latency| latency.as_millis() as u64),
+                    consecutive_failures,
+                    is_remote,
+                }),
+            );
+
+            if last_status != Some(status) {
+                log::info!(
+                    "[health] {url} is {status:?} (latency={latency:?}, failures={consecutive_failures})"
+                );
+                if status == ServerHealthStatus::Down {
+                    if is_remote {
+                        set_reconnect_banner(&app, &url, true);
+                    } else if local_url.is_some() {
+                        restart_unresponsive_sidecar(&app).await;
+                        consecutive_failures = 0;
+                    }
+                } else if last_status == Some(ServerHealthStatus::Down) && is_remote {
+                    set_reconnect_banner(&app, &url, false);
+                }
+            }
+            last_status = Some(status);
+        }
+    });
+}
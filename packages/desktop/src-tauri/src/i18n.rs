@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use serde_json::Value;
+
+use crate::{menu, settings, tray};
+
+/// Settings-store key overriding the OS locale; unset means "follow the OS".
+pub(crate) const LOCALE_KEY: &str = "locale";
+
+const FALLBACK_LOCALE: &str = "en";
+
+/// Native menu, tray and dialog strings. Keys missing from a bundle fall back to English.
+const BUNDLES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("de", include_str!("../locales/de.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("ja", include_str!("../locales/ja.json")),
+    ("zh-CN", include_str!("../locales/zh-CN.json")),
+];
+
+type Bundle = HashMap<String, String>;
+
+fn bundles() -> &'static HashMap<&'static str, Bundle> {
+    static BUNDLES_CELL: OnceLock<HashMap<&'static str, Bundle>> = OnceLock::new();
+    BUNDLES_CELL.get_or_init(|| {
+        BUNDLES
+            .iter()
+            .filter_map(|(tag, raw)| match serde_json::from_str::<Bundle>(raw) {
+                Ok(bundle) => Some((*tag, bundle)),
+                Err(err) => {
+                    log::warn!("[i18n] failed to parse {tag} bundle: {err}");
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+/// Maps a BCP 47 tag (`de-AT`, `zh_Hans_CN`, ...) onto a bundled locale.
+fn resolve_locale(tag: &str) -> &'static str {
+    let tag = tag.trim().replace('_', "-");
+    // POSIX locales may carry an encoding or modifier (`de_DE.UTF-8@euro`).
+    let tag = tag.split(['.', '@']).next().unwrap_or_default();
+    if let Some((known, _)) = BUNDLES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(tag))
+    {
+        return known;
+    }
+    let language = tag.split('-').next().unwrap_or_default();
+    BUNDLES
+        .iter()
+        .find(|(known, _)| {
+            known
+                .split('-')
+                .next()
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(language))
+        })
+        .map(|(known, _)| *known)
+        .unwrap_or(FALLBACK_LOCALE)
+}
+
+fn locale_override<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    settings::get_setting(app, LOCALE_KEY)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|tag| !tag.trim().is_empty())
+}
+
+/// The bundled locale in effect: the saved override, else the OS locale.
+pub(crate) fn current_locale<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> &'static str {
+    let tag = locale_override(app)
+        .or_else(sys_locale::get_locale)
+        .unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+    resolve_locale(&tag)
+}
+
+fn lookup(locale: &str, key: &str) -> String {
+    let bundles = bundles();
+    bundles
+        .get(locale)
+        .and_then(|bundle| bundle.get(key))
+        .or_else(|| bundles.get(FALLBACK_LOCALE)?.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Translates `key` for the current locale.
+pub(crate) fn t<R: tauri::Runtime>(app: &tauri::AppHandle<R>, key: &str) -> String {
+    lookup(current_locale(app), key)
+}
+
+/// Translates `key`, substituting `{name}` placeholders from `args`.
+pub(crate) fn t_args<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    key: &str,
+    args: &[(&str, &str)],
+) -> String {
+    args.iter().fold(t(app, key), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
+}
+
+/// Settings side effect for `locale`: rebuilds the native menus in the new language.
+pub(crate) fn apply_locale_setting<R: tauri::Runtime>(app: &tauri::AppHandle<R>, _value: &Value) {
+    log::info!("[i18n] locale is now {}", current_locale(app));
+    if let Err(err) = menu::rebuild_app_menu(app) {
+        log::warn!("[i18n] failed to rebuild app menu: {err}");
+    }
+    if let Err(err) = tray::rebuild_tray_menu(app) {
+        log::warn!("[i18n] failed to rebuild tray menu: {err}");
+    }
+}
+
+/// Overrides the native UI language with `tag` (e.g. `"de"`), or follows the OS
+/// again when `tag` is omitted. Returns the bundled locale now in use.
+#[tauri::command]
+pub(crate) fn desktop_set_locale(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    tag: Option<String>,
+) -> Result<String, String> {
+    let value = match tag.map(|tag| tag.trim().to_string()) {
+        Some(tag) if !tag.is_empty() => {
+            if !tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!("Invalid locale tag: {tag}"));
+            }
+            Value::String(tag)
+        }
+        _ => Value::Null,
+    };
+    settings::set_setting(&app, LOCALE_KEY, value, Some(webview.label()))?;
+    Ok(current_locale(&app).to_string())
+}
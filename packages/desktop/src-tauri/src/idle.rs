@@ -0,0 +1,264 @@
+use std::{sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    local_sidecar, settings,
+};
+
+/// Settings-store key holding `{ "enabled", "idleMinutes", "pauseFileWatching" }`.
+const IDLE_THROTTLING_KEY: &str = "idleThrottling";
+const IDLE_MONITOR_INTERVAL: Duration = Duration::from_secs(15);
+/// How much slower background polling runs while throttled.
+const THROTTLED_POLL_FACTOR: u32 = 6;
+const SIDECAR_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct IdleConfig {
+    enabled: bool,
+    /// Minutes without keyboard or mouse input before the user counts as idle.
+    idle_minutes: u64,
+    /// Ask the local server to stop watching files while throttled.
+    pause_file_watching: bool,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: 5,
+            pause_file_watching: false,
+        }
+    }
+}
+
+fn load_config(app: &tauri::AppHandle) -> IdleConfig {
+    settings::get_setting(app, IDLE_THROTTLING_KEY)
+        .and_then(|value: Value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IdleSnapshot {
+    user_idle: bool,
+    window_hidden: bool,
+    /// Seconds since the last input anywhere on the system; `None` where the
+    /// platform does not tell.
+    idle_secs: Option<u64>,
+    /// Background polling is slowed down.
+    throttled: bool,
+}
+
+impl IdleSnapshot {
+    /// Equal apart from the idle counter, which changes every sample.
+    fn same_state(&self, other: &Self) -> bool {
+        self.user_idle == other.user_idle
+            && self.window_hidden == other.window_hidden
+            && self.throttled == other.throttled
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct IdleState {
+    current: Mutex<IdleSnapshot>,
+}
+
+#[cfg(target_os = "macos")]
+mod core_graphics {
+    pub(super) const COMBINED_SESSION_STATE: i32 = 0;
+    pub(super) const ANY_INPUT_EVENT: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub(super) fn CGEventSourceSecondsSinceLastEventType(
+            source_state: i32,
+            event_type: u32,
+        ) -> f64;
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_idle_time() -> Option<Duration> {
+    // SAFETY: a pure query without pointers.
+    let secs = unsafe {
+        core_graphics::CGEventSourceSecondsSinceLastEventType(
+            core_graphics::COMBINED_SESSION_STATE,
+            core_graphics::ANY_INPUT_EVENT,
+        )
+    };
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+#[cfg(windows)]
+fn system_idle_time() -> Option<Duration> {
+    use windows_sys::Win32::{
+        System::SystemInformation::GetTickCount,
+        UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+    };
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `info` is a valid, sized out pointer for the duration of the call.
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // Both tick counts wrap after 49 days; the difference survives that.
+    let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(Duration::from_millis(u64::from(idle_ms)))
+}
+
+/// Only GNOME exposes idle time to ordinary clients, through Mutter's idle
+/// monitor; it answers `(uint64 12345,)` in milliseconds.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let millis = stdout
+        .trim()
+        .trim_start_matches('(')
+        .trim_start_matches("uint64 ")
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_idle_time() -> Option<Duration> {
+    None
+}
+
+/// No window is on screen: all are hidden (e.g. to the tray) or minimized.
+fn all_windows_hidden(app: &tauri::AppHandle) -> bool {
+    app.webview_windows().values().all(|window| {
+        !window.is_visible().unwrap_or(true) || window.is_minimized().unwrap_or(false)
+    })
+}
+
+/// Current idle state; `throttled` stays false while throttling is disabled.
+#[tauri::command]
+pub(crate) fn desktop_get_idle_state(app: tauri::AppHandle) -> IdleSnapshot {
+    *app.state::<IdleState>()
+        .current
+        .lock()
+        .expect("idle state mutex")
+}
+
+/// `base` while the user is around, several times longer once throttled. For
+/// background loops that poll on a fixed interval.
+pub(crate) fn poll_interval<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    base: Duration,
+) -> Duration {
+    let throttled = app
+        .try_state::<IdleState>()
+        .is_some_and(|state| state.current.lock().expect("idle state mutex").throttled);
+    if throttled {
+        base.saturating_mul(THROTTLED_POLL_FACTOR)
+    } else {
+        base
+    }
+}
+
+/// Tells the local server to pause or resume its file watchers. Servers without
+/// the endpoint keep watching.
+async fn set_sidecar_file_watching(app: &tauri::AppHandle, paused: bool) {
+    let Some(url) =
+        local_sidecar(app).and_then(|state| state.url.lock().expect("sidecar url mutex").clone())
+    else {
+        return;
+    };
+    let Ok(client) = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(SIDECAR_REQUEST_TIMEOUT)
+        .build()
+    else {
+        return;
+    };
+    let endpoint = format!("{}/api/fs/watch", url.trim_end_matches('/'));
+    match client
+        .post(&endpoint)
+        .json(&json!({ "paused": paused }))
+        .send()
+        .await
+    {
+        Ok(resp) if resp.status().is_success() => {
+            log::info!("[idle] file watching paused={paused}");
+        }
+        Ok(resp) => log::debug!(
+            "[idle] server declined to pause watching: {}",
+            resp.status()
+        ),
+        Err(err) => log::debug!("[idle] failed to reach server: {err}"),
+    }
+}
+
+/// Watches for the user going idle or every window being hidden, reports changes
+/// as `openchamber:idle-state` and throttles background polling meanwhile.
+pub(crate) fn spawn_idle_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut watching_paused = false;
+
+        loop {
+            tokio::time::sleep(IDLE_MONITOR_INTERVAL).await;
+
+            let config = load_config(&app);
+            let idle_time = tauri::async_runtime::spawn_blocking(system_idle_time)
+                .await
+                .ok()
+                .flatten();
+            let user_idle = idle_time
+                .is_some_and(|idle| idle.as_secs() >= config.idle_minutes.saturating_mul(60));
+            let window_hidden = all_windows_hidden(&app);
+            let snapshot = IdleSnapshot {
+                user_idle,
+                window_hidden,
+                idle_secs: idle_time.map(|idle| idle.as_secs()),
+                throttled: config.enabled && (user_idle || window_hidden),
+            };
+
+            let changed = {
+                let state = app.state::<IdleState>();
+                let mut current = state.current.lock().expect("idle state mutex");
+                let changed = !current.same_state(&snapshot);
+                *current = snapshot;
+                changed
+            };
+            if changed {
+                log::info!(
+                    "[idle] user_idle={user_idle} window_hidden={window_hidden} throttled={}",
+                    snapshot.throttled
+                );
+                events::emit(&app, DesktopEvent::IdleState(snapshot));
+            }
+
+            let pause = snapshot.throttled && config.pause_file_watching;
+            if pause != watching_paused {
+                set_sidecar_file_watching(&app, pause).await;
+                watching_paused = pause;
+            }
+        }
+    });
+}
@@ -0,0 +1,236 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::events::{self, DesktopEvent};
+
+const DEFAULT_REASON: &str = "OpenChamber agent session is running";
+
+/// Holds an IOKit power assertion that keeps the system from idle-sleeping.
+#[cfg(target_os = "macos")]
+struct Inhibitor {
+    assertion_id: u32,
+}
+
+#[cfg(target_os = "macos")]
+mod iokit {
+    use std::ffi::c_void;
+
+    pub(super) const ASSERTION_LEVEL_ON: u32 = 255;
+    pub(super) const SUCCESS: i32 = 0;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        pub(super) fn IOPMAssertionCreateWithName(
+            assertion_type: *const c_void,
+            level: u32,
+            name: *const c_void,
+            assertion_id: *mut u32,
+        ) -> i32;
+        pub(super) fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Inhibitor {
+    fn acquire(reason: &str) -> Result<Self, String> {
+        use objc2_foundation::NSString;
+
+        // `NSString` is toll-free bridged with the `CFStringRef`s IOKit expects.
+        let assertion_type = NSString::from_str("PreventUserIdleSystemSleep");
+        let name = NSString::from_str(reason);
+        let mut assertion_id = 0;
+        // SAFETY: both strings outlive the call and `assertion_id` is a valid out pointer.
+        let result = unsafe {
+            iokit::IOPMAssertionCreateWithName(
+                (&*assertion_type as *const NSString).cast(),
+                iokit::ASSERTION_LEVEL_ON,
+                (&*name as *const NSString).cast(),
+                &mut assertion_id,
+            )
+        };
+        if result != iokit::SUCCESS {
+            return Err(format!("IOPMAssertionCreateWithName failed ({result})"));
+        }
+        Ok(Self { assertion_id })
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        // SAFETY: the id came from a successful `IOPMAssertionCreateWithName`.
+        unsafe {
+            iokit::IOPMAssertionRelease(self.assertion_id);
+        }
+    }
+}
+
+/// `SetThreadExecutionState` is per-thread, so a parked helper thread holds the
+/// request until it is told to release it.
+#[cfg(windows)]
+struct Inhibitor {
+    release: Option<std::sync::mpsc::Sender<()>>,
+}
+
+#[cfg(windows)]
+impl Inhibitor {
+    fn acquire(_reason: &str) -> Result<Self, String> {
+        use windows_sys::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+        };
+
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<bool>();
+        std::thread::Builder::new()
+            .name("keep-awake".into())
+            .spawn(move || {
+                // SAFETY: plain Win32 call with valid flag constants.
+                let previous =
+                    unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+                let _ = ready_tx.send(previous != 0);
+                if previous == 0 {
+                    return;
+                }
+                let _ = release_rx.recv();
+                // SAFETY: as above; clears the request made by this thread.
+                unsafe {
+                    SetThreadExecutionState(ES_CONTINUOUS);
+                }
+            })
+            .map_err(|err| err.to_string())?;
+
+        match ready_rx.recv() {
+            Ok(true) => Ok(Self {
+                release: Some(release_tx),
+            }),
+            _ => Err("SetThreadExecutionState failed".to_string()),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            let _ = release.send(());
+        }
+    }
+}
+
+/// Keeps a `systemd-inhibit` process alive for as long as sleep is blocked.
+#[cfg(all(unix, not(target_os = "macos")))]
+struct Inhibitor {
+    child: std::process::Child,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl Inhibitor {
+    fn acquire(reason: &str) -> Result<Self, String> {
+        let child = std::process::Command::new("systemd-inhibit")
+            .arg("--what=idle:sleep")
+            .arg("--who=OpenChamber")
+            .arg(format!("--why={reason}"))
+            .arg("--mode=block")
+            .args(["sleep", "infinity"])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Failed to run systemd-inhibit: {err}"))?;
+        Ok(Self { child })
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct ActiveInhibitor {
+    reason: String,
+    _inhibitor: Inhibitor,
+}
+
+#[derive(Default)]
+pub(crate) struct KeepAwakeState {
+    active: Mutex<Option<ActiveInhibitor>>,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SleepPreventionEvent {
+    active: bool,
+    reason: Option<String>,
+}
+
+fn emit_state<R: tauri::Runtime>(app: &tauri::AppHandle<R>, reason: Option<String>) {
+    events::emit(
+        app,
+        DesktopEvent::SleepPrevention(SleepPreventionEvent {
+            active: reason.is_some(),
+            reason,
+        }),
+    );
+}
+
+/// Drops any held power assertion. Called when no sessions remain active and on exit.
+pub(crate) fn release<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<KeepAwakeState>() else {
+        return;
+    };
+    let released = state
+        .active
+        .lock()
+        .expect("keep awake mutex")
+        .take()
+        .is_some();
+    if released {
+        log::info!("[keep-awake] released");
+        emit_state(app, None);
+    }
+}
+
+/// Blocks (or stops blocking) system sleep while agent sessions run. The block is
+/// lifted automatically once the UI reports no busy sessions. Returns whether sleep
+/// is now being prevented.
+#[tauri::command]
+pub(crate) fn desktop_prevent_sleep(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, KeepAwakeState>,
+    enable: bool,
+    reason: Option<String>,
+) -> Result<bool, String> {
+    if !enable {
+        release(&app);
+        return Ok(false);
+    }
+
+    let reason = reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty())
+        .unwrap_or_else(|| DEFAULT_REASON.to_string());
+    let mut active = state.active.lock().expect("keep awake mutex");
+    if active
+        .as_ref()
+        .is_some_and(|current| current.reason == reason)
+    {
+        return Ok(true);
+    }
+
+    // Acquire the new assertion before dropping the old one so there is no gap.
+    let inhibitor = Inhibitor::acquire(&reason)?;
+    *active = Some(ActiveInhibitor {
+        reason: reason.clone(),
+        _inhibitor: inhibitor,
+    });
+    drop(active);
+    log::info!("[keep-awake] preventing sleep: {reason}");
+    emit_state(&app, Some(reason));
+    Ok(true)
+}
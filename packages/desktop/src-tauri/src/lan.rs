@@ -0,0 +1,292 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, UdpSocket},
+    sync::Mutex,
+};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    audit::{self, AuditCategory},
+    events::{self, DesktopEvent},
+    follow_local_server, i18n, kill_sidecar, local_sidecar, secrets, settings, spawn_local_server,
+};
+
+/// Settings-store key holding a [`LanAccessSettings`] object.
+pub(crate) const LAN_ACCESS_KEY: &str = "lanAccess";
+/// Global secret holding the access token other devices log in with.
+const LAN_TOKEN_SECRET: &str = "lanAccessToken";
+const LAN_TOKEN_BYTES: usize = 24;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct LanAccessSettings {
+    enabled: bool,
+    /// Local address to bind; all interfaces when unset.
+    interface: Option<String>,
+}
+
+/// How the sidecar should listen when LAN access is on.
+pub(crate) struct LanBinding {
+    bind_host: IpAddr,
+    token: String,
+}
+
+impl LanBinding {
+    pub(crate) fn bind_host(&self) -> String {
+        self.bind_host.to_string()
+    }
+
+    /// The token doubles as the server's UI password, so every non-desktop
+    /// browser has to present it before it can reach the API.
+    pub(crate) fn env_vars(&self) -> [(&'static str, String); 1] {
+        [("OPENCHAMBER_UI_PASSWORD", self.token.clone())]
+    }
+}
+
+/// Sharing state of the running sidecar, as last announced.
+#[derive(Default)]
+pub(crate) struct LanAccessState {
+    current: Mutex<Option<LanAccessInfo>>,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LanAccessInfo {
+    enabled: bool,
+    /// Address other devices should open, e.g. `http://192.168.1.20:57123`.
+    url: Option<String>,
+    token: Option<String>,
+    bind_host: Option<String>,
+    warning: Option<String>,
+}
+
+fn read_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> LanAccessSettings {
+    settings::get_setting(app, LAN_ACCESS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; LAN_TOKEN_BYTES];
+    getrandom::fill(&mut bytes).map_err(|err| format!("Failed to generate token: {err}"))?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn load_or_create_token<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<String, String> {
+    if let Some(token) = secrets::get_secret(app, None, LAN_TOKEN_SECRET)? {
+        return Ok(token);
+    }
+    let token = generate_token()?;
+    secrets::set_secret(app, None, LAN_TOKEN_SECRET, &token)?;
+    Ok(token)
+}
+
+/// The address of the interface that routes to the internet, used as the share
+/// URL when binding all interfaces. Connecting a UDP socket sends nothing.
+fn primary_lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("1.1.1.1", 53)).ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_loopback())
+}
+
+/// The sidecar binding for the next spawn. Falls back to loopback-only (`None`)
+/// whenever LAN access is off or no token can be stored.
+pub(crate) fn sidecar_binding<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<LanBinding> {
+    let settings = read_settings(app);
+    let binding = settings.enabled.then(|| {
+        let bind_host = match settings.interface.as_deref().map(str::trim) {
+            Some(interface) if !interface.is_empty() => interface.parse().ok()?,
+            _ => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        };
+        match load_or_create_token(app) {
+            Ok(token) => Some(LanBinding { bind_host, token }),
+            Err(err) => {
+                log::warn!("[lan] keeping server on loopback, no access token: {err}");
+                None
+            }
+        }
+    });
+    binding.flatten()
+}
+
+fn access_info<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    binding: Option<&LanBinding>,
+    port: u16,
+) -> LanAccessInfo {
+    let Some(binding) = binding else {
+        return LanAccessInfo {
+            enabled: false,
+            url: None,
+            token: None,
+            bind_host: None,
+            warning: None,
+        };
+    };
+    let share_host = if binding.bind_host.is_unspecified() {
+        primary_lan_ip()
+    } else {
+        Some(binding.bind_host)
+    };
+    let url = share_host.map(|host| match host {
+        IpAddr::V6(v6) => format!("http://[{v6}]:{port}"),
+        IpAddr::V4(v4) => format!("http://{v4}:{port}"),
+    });
+    LanAccessInfo {
+        enabled: true,
+        warning: Some(i18n::t_args(
+            app,
+            "lan.firewallWarning",
+            &[("url", url.as_deref().unwrap_or_default())],
+        )),
+        url,
+        token: Some(binding.token.clone()),
+        bind_host: Some(binding.bind_host()),
+    }
+}
+
+/// Emits `openchamber:lan-access` once a sidecar is listening on `port`. The event
+/// carries the access token, so it only goes to webviews showing the local UI.
+pub(crate) fn announce<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    binding: Option<&LanBinding>,
+    port: u16,
+) {
+    let info = access_info(app, binding, port);
+    if let Some(url) = info.url.as_deref() {
+        log::warn!("[lan] server reachable from the local network at {url}");
+    }
+    if let Some(state) = app.try_state::<LanAccessState>() {
+        *state.current.lock().expect("lan access mutex") = Some(info.clone());
+    }
+    for label in crate::origin::local_webviews(app) {
+        events::emit_to(app, label.as_str(), DesktopEvent::LanAccess(info.clone()));
+    }
+}
+
+/// The token protecting the local server while it is LAN-shared, for native
+/// requests that have to log in like the window does.
+pub(crate) fn access_token<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    app.try_state::<LanAccessState>()?
+        .current
+        .lock()
+        .expect("lan access mutex")
+        .as_ref()
+        .and_then(|info| info.token.clone())
+}
+
+/// Page-load hook for the main window: when the local server is LAN-shared (and
+/// therefore password protected), logs the desktop window in with the token.
+pub(crate) fn inject_session<R: tauri::Runtime>(webview: &tauri::Webview<R>, url: &url::Url) {
+    let Some(token) = webview.try_state::<LanAccessState>().and_then(|state| {
+        state
+            .current
+            .lock()
+            .expect("lan access mutex")
+            .as_ref()
+            .and_then(|info| info.token.clone())
+    }) else {
+        return;
+    };
+    if !crate::origin::is_local(webview.app_handle(), url) {
+        return;
+    }
+    let Ok(token) = serde_json::to_string(&token) else {
+        return;
+    };
+    let script = format!(
+        r#"(function() {{
+  if (window.__OPENCHAMBER_LAN_LOGIN__) return;
+  window.__OPENCHAMBER_LAN_LOGIN__ = true;
+  fetch('/auth/session', {{ credentials: 'same-origin' }}).then(function(status) {{
+    if (status.status !== 401) return;
+    return fetch('/auth/session', {{
+      method: 'POST',
+      credentials: 'same-origin',
+      headers: {{ 'Content-Type': 'application/json' }},
+      body: JSON.stringify({{ password: {token} }})
+    }}).then(function(created) {{ if (created.ok) window.location.reload(); }});
+  }}).catch(function() {{}});
+}})();"#
+    );
+    if let Err(err) = webview.eval(&script) {
+        log::warn!("[lan] failed to inject session login: {err}");
+    }
+}
+
+/// Shares the local server with other devices on the network (or stops sharing).
+///
+/// The sidecar is restarted bound to `interface` (an IP address of this machine,
+/// default all interfaces) and protected by a generated access token, which is
+/// returned and also emitted with the share URL as `openchamber:lan-access`.
+/// Disabling discards the token so the next share gets a fresh one.
+#[tauri::command]
+pub(crate) async fn desktop_enable_lan_access(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    enabled: bool,
+    interface: Option<String>,
+) -> Result<LanAccessInfo, String> {
+    crate::origin::require_local(&webview, "change LAN access")?;
+    let interface = interface
+        .map(|interface| interface.trim().to_string())
+        .filter(|interface| !interface.is_empty());
+    let action = if enabled {
+        "enable lan access"
+    } else {
+        "disable lan access"
+    };
+    let target = interface
+        .clone()
+        .unwrap_or_else(|| "all interfaces".to_string());
+    let result = set_lan_access(&app, &webview, enabled, interface).await;
+    audit::record(
+        &app,
+        AuditCategory::NetworkExposure,
+        action,
+        target,
+        &result,
+    );
+    result
+}
+
+async fn set_lan_access(
+    app: &tauri::AppHandle,
+    webview: &tauri::Webview,
+    enabled: bool,
+    interface: Option<String>,
+) -> Result<LanAccessInfo, String> {
+    if let Some(interface) = interface.as_deref() {
+        interface
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid interface address: {interface}"))?;
+    }
+    settings::set_setting(
+        app,
+        LAN_ACCESS_KEY,
+        json!({ "enabled": enabled, "interface": interface }),
+        Some(webview.label()),
+    )?;
+    if !enabled {
+        secrets::delete_secret(app, None, LAN_TOKEN_SECRET)?;
+    }
+
+    let previous_url =
+        local_sidecar(app).and_then(|state| state.url.lock().expect("sidecar url mutex").clone());
+    let kill_app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || kill_sidecar(kill_app))
+        .await
+        .map_err(|err| err.to_string())?;
+    let url = spawn_local_server(app)
+        .await
+        .map_err(|err| err.to_string())?;
+    follow_local_server(app, previous_url, &url);
+
+    let state = app.state::<LanAccessState>();
+    let current = state.current.lock().expect("lan access mutex").clone();
+    Ok(current.unwrap_or_else(|| access_info(app, None, 0)))
+}
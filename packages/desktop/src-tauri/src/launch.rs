@@ -0,0 +1,238 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    menu, normalize_server_url, open_server_url, tray,
+};
+
+/// VS Code-style workspace files; the first listed folder is opened.
+const WORKSPACE_FILE_EXTENSION: &str = "code-workspace";
+
+#[derive(Deserialize)]
+struct WorkspaceFile {
+    #[serde(default)]
+    folders: Vec<WorkspaceFolder>,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceFolder {
+    path: String,
+}
+
+/// Something the frontend should do on behalf of the command line.
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum LaunchAction {
+    /// `openchamber <path>`
+    OpenWorkspace { path: String },
+    /// `--new-session`, after any workspace on the same command line.
+    NewSession,
+}
+
+/// `openchamber [path] [--new-session] [--server-url <url>] [--minimized]`
+#[derive(Debug, Default)]
+pub(crate) struct LaunchArgs {
+    pub(crate) server_url: Option<String>,
+    pub(crate) minimized: bool,
+    pub(crate) actions: Vec<LaunchAction>,
+}
+
+fn workspace_file_folder(file: &Path) -> Option<PathBuf> {
+    let raw = std::fs::read_to_string(file).ok()?;
+    // Workspace files are JSONC; comments and trailing commas are not supported.
+    let parsed: WorkspaceFile = serde_json::from_str(&raw).ok()?;
+    let folder = parsed.folders.into_iter().next()?;
+    Some(file.parent()?.join(folder.path))
+}
+
+/// Resolves a path argument against the launching process' working directory to a
+/// workspace directory. Accepts directories and `.code-workspace` files.
+fn resolve_workspace(raw: &str, cwd: &Path) -> Option<String> {
+    let mut path = cwd.join(raw);
+    if path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(WORKSPACE_FILE_EXTENSION))
+    {
+        path = workspace_file_folder(&path).unwrap_or(path);
+    }
+    match std::fs::canonicalize(&path) {
+        Ok(path) if path.is_dir() => Some(path.to_string_lossy().to_string()),
+        _ => {
+            log::warn!("[launch] ignoring {raw:?}: not a workspace directory");
+            None
+        }
+    }
+}
+
+fn is_open_workspace(app: &tauri::AppHandle, path: &str) -> bool {
+    menu::open_workspace_path(app)
+        .and_then(|open| std::fs::canonicalize(open).ok())
+        .is_some_and(|open| open == Path::new(path))
+}
+
+/// Parses launch arguments (without the executable). Flags handled elsewhere
+/// (`--profile`, `--portable`, `--safe-mode`) and deep-link URLs are skipped;
+/// unknown flags are logged and ignored, since OSes add their own (e.g. macOS
+/// `-psn_…`).
+pub(crate) fn parse(args: impl IntoIterator<Item = String>, cwd: &Path) -> LaunchArgs {
+    let mut launch = LaunchArgs::default();
+    let mut workspace = None;
+    let mut new_session = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match flag {
+            "--new-session" => new_session = true,
+            "--minimized" => launch.minimized = true,
+            "--server-url" => {
+                let raw = inline_value.or_else(|| args.next()).unwrap_or_default();
+                launch.server_url = normalize_server_url(&raw);
+                if launch.server_url.is_none() {
+                    log::warn!("[launch] ignoring invalid --server-url {raw:?}");
+                }
+            }
+            "--profile" => {
+                if inline_value.is_none() {
+                    args.next();
+                }
+            }
+            "--portable" | "--safe-mode" => {}
+            _ if arg.starts_with('-') => log::warn!("[launch] ignoring unknown argument {arg:?}"),
+            _ if arg.contains("://") => {}
+            _ => workspace = resolve_workspace(&arg, cwd).or(workspace),
+        }
+    }
+    if let Some(path) = workspace {
+        launch.actions.push(LaunchAction::OpenWorkspace { path });
+    }
+    if new_session {
+        launch.actions.push(LaunchAction::NewSession);
+    }
+    launch
+}
+
+/// Launch actions wait here until the frontend calls `desktop_frontend_ready`; a
+/// page load event alone does not mean its listeners are registered.
+pub(crate) struct LaunchState {
+    pending: Mutex<Vec<LaunchAction>>,
+    frontend_ready: AtomicBool,
+    server_url: Option<String>,
+    minimized: AtomicBool,
+}
+
+impl LaunchState {
+    pub(crate) fn new(args: LaunchArgs) -> Self {
+        Self {
+            pending: Mutex::new(args.actions),
+            frontend_ready: AtomicBool::new(false),
+            server_url: args.server_url,
+            minimized: AtomicBool::new(args.minimized),
+        }
+    }
+}
+
+/// Server requested with `--server-url` on the first launch.
+pub(crate) fn initial_server_url<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    app.try_state::<LaunchState>()
+        .and_then(|state| state.server_url.clone())
+}
+
+/// Whether the main window should start minimized. Only true once.
+pub(crate) fn take_start_minimized<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    app.try_state::<LaunchState>()
+        .is_some_and(|state| state.minimized.swap(false, Ordering::SeqCst))
+}
+
+fn dispatch(app: &tauri::AppHandle, actions: Vec<LaunchAction>) {
+    for action in actions {
+        log::info!("[launch] dispatching {action:?}");
+        events::emit(app, DesktopEvent::LaunchAction(action));
+    }
+}
+
+fn enqueue(app: &tauri::AppHandle, mut actions: Vec<LaunchAction>) {
+    let Some(state) = app.try_state::<LaunchState>() else {
+        return;
+    };
+    actions.retain(|action| match action {
+        LaunchAction::OpenWorkspace { path } if is_open_workspace(app, path) => {
+            log::info!("[launch] {path} is already open");
+            false
+        }
+        _ => true,
+    });
+    let mut pending = state.pending.lock().expect("launch actions mutex");
+    if state.frontend_ready.load(Ordering::SeqCst) {
+        drop(pending);
+        dispatch(app, actions);
+    } else {
+        pending.extend(actions);
+    }
+}
+
+/// Handles the arguments of a second launch forwarded by the single-instance plugin.
+pub(crate) fn handle_second_instance(app: &tauri::AppHandle, argv: Vec<String>, cwd: String) {
+    let launch = parse(argv.into_iter().skip(1), Path::new(&cwd));
+    if !launch.minimized {
+        tray::show_main_window(app);
+    }
+    if let Some(url) = launch.server_url {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = open_server_url(&app, &url).await {
+                log::warn!("[launch] failed to open {url}: {err}");
+            }
+        });
+    }
+    enqueue(app, launch.actions);
+}
+
+/// Handles files and folders handed over by the OS file manager ("Open With"). On
+/// macOS these arrive as `RunEvent::Opened`; elsewhere as launch arguments.
+#[cfg(target_os = "macos")]
+pub(crate) fn handle_opened_urls(app: &tauri::AppHandle, urls: &[url::Url]) {
+    let actions: Vec<LaunchAction> = urls
+        .iter()
+        .filter_map(|url| url.to_file_path().ok())
+        .filter_map(|path| resolve_workspace(&path.to_string_lossy(), Path::new("/")))
+        .map(|path| LaunchAction::OpenWorkspace { path })
+        .collect();
+    if actions.is_empty() {
+        return;
+    }
+    tray::show_main_window(app);
+    enqueue(app, actions);
+}
+
+/// Main-window page-load hook: a (re)loading page has lost its listeners.
+pub(crate) fn frontend_unloaded<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(state) = app.try_state::<LaunchState>() {
+        state.frontend_ready.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Called by the frontend once it listens for `openchamber:launch-action`; delivers
+/// everything queued since startup or the last reload.
+#[tauri::command]
+pub(crate) fn desktop_frontend_ready(app: tauri::AppHandle, state: tauri::State<'_, LaunchState>) {
+    let pending = {
+        let mut pending = state.pending.lock().expect("launch actions mutex");
+        state.frontend_ready.store(true, Ordering::SeqCst);
+        std::mem::take(&mut *pending)
+    };
+    dispatch(&app, pending);
+}
@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr, sync::RwLock};
+
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+
+use crate::{portable, profiles, read_desktop_setting_from_disk, write_desktop_setting_to_disk};
+
+const LOG_LEVEL_KEY: &str = "desktopLogLevel";
+const LOG_FILTERS_KEY: &str = "desktopLogFilters";
+const LOG_FILE_MAX_BYTES: u128 = 5 * 1024 * 1024;
+const LOG_FILE_KEEP: usize = 5;
+
+struct LogConfig {
+    level: LevelFilter,
+    /// Per-target overrides, e.g. `sidecar` or `tunnel` -> `debug`.
+    filters: BTreeMap<String, LevelFilter>,
+}
+
+// Consulted by the log plugin's filter on every record, so the level can change at runtime.
+static LOG_CONFIG: RwLock<LogConfig> = RwLock::new(LogConfig {
+    level: LevelFilter::Info,
+    filters: BTreeMap::new(),
+});
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LogConfigPayload {
+    level: String,
+    #[serde(default)]
+    filters: BTreeMap<String, String>,
+}
+
+fn parse_level(raw: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(raw.trim()).map_err(|_| format!("Unknown log level: {raw}"))
+}
+
+fn parse_filters(raw: &BTreeMap<String, String>) -> Result<BTreeMap<String, LevelFilter>, String> {
+    raw.iter()
+        .filter(|(target, _)| !target.trim().is_empty())
+        .map(|(target, level)| Ok((target.trim().to_string(), parse_level(level)?)))
+        .collect()
+}
+
+/// Whether `filter` names `target` or one of its parent modules. The crate name may be
+/// omitted, so `tunnel` matches `openchamber_desktop::tunnel`.
+fn filter_matches(filter: &str, target: &str) -> bool {
+    let matches = |candidate: &str| {
+        candidate == filter
+            || candidate
+                .strip_prefix(filter)
+                .is_some_and(|rest| rest.starts_with("::"))
+    };
+    matches(target)
+        || target
+            .split_once("::")
+            .is_some_and(|(_, without_crate)| matches(without_crate))
+}
+
+fn enabled(metadata: &log::Metadata) -> bool {
+    let Ok(config) = LOG_CONFIG.read() else {
+        return true;
+    };
+    let target = metadata.target();
+    // The most specific (longest) matching filter wins.
+    let level = config
+        .filters
+        .iter()
+        .filter(|(filter, _)| filter_matches(filter, target))
+        .max_by_key(|(filter, _)| filter.len())
+        .map(|(_, level)| *level)
+        .unwrap_or(config.level);
+    metadata.level() <= level
+}
+
+fn load_from_disk() {
+    let level = read_desktop_setting_from_disk(LOG_LEVEL_KEY)
+        .and_then(|value| value.as_str().and_then(|raw| parse_level(raw).ok()))
+        .unwrap_or(LevelFilter::Info);
+    let filters = read_desktop_setting_from_disk(LOG_FILTERS_KEY)
+        .and_then(|value| serde_json::from_value::<BTreeMap<String, String>>(value).ok())
+        .and_then(|raw| parse_filters(&raw).ok())
+        .unwrap_or_default();
+
+    if let Ok(mut config) = LOG_CONFIG.write() {
+        config.level = level;
+        config.filters = filters;
+    }
+}
+
+/// Where the log plugin writes its files.
+pub(crate) fn log_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    portable::log_dir().or_else(|| app.path().app_log_dir().ok())
+}
+
+/// Log plugin with stdout, webview and a size-rotated file in the app log dir (or
+/// `data/logs` when portable). Filtering happens in `enabled`, so the plugin itself
+/// passes everything through.
+pub(crate) fn plugin_builder() -> tauri_plugin_log::Builder {
+    load_from_disk();
+
+    let file_name = profiles::active().map(|profile| format!("openchamber-{profile}"));
+    tauri_plugin_log::Builder::default()
+        .level(LevelFilter::Trace)
+        .filter(enabled)
+        .max_file_size(LOG_FILE_MAX_BYTES)
+        .rotation_strategy(RotationStrategy::KeepSome(LOG_FILE_KEEP))
+        .clear_targets()
+        .targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::Webview),
+            Target::new(match portable::log_dir() {
+                Some(path) => TargetKind::Folder { path, file_name },
+                None => TargetKind::LogDir { file_name },
+            }),
+        ])
+}
+
+#[tauri::command]
+pub(crate) fn desktop_get_log_config() -> Result<LogConfigPayload, String> {
+    let config = LOG_CONFIG.read().map_err(|err| err.to_string())?;
+    Ok(LogConfigPayload {
+        level: config.level.to_string().to_lowercase(),
+        filters: config
+            .filters
+            .iter()
+            .map(|(target, level)| (target.clone(), level.to_string().to_lowercase()))
+            .collect(),
+    })
+}
+
+/// Changes the global level and, if given, replaces the per-target filters.
+#[tauri::command]
+pub(crate) fn desktop_set_log_level(
+    webview: tauri::Webview,
+    level: String,
+    filters: Option<BTreeMap<String, String>>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change logging")?;
+    let level = parse_level(&level)?;
+    let filters = filters.as_ref().map(parse_filters).transpose()?;
+
+    {
+        let mut config = LOG_CONFIG.write().map_err(|err| err.to_string())?;
+        config.level = level;
+        if let Some(filters) = filters {
+            config.filters = filters;
+        }
+    }
+
+    let payload = desktop_get_log_config()?;
+    write_desktop_setting_to_disk(
+        LOG_LEVEL_KEY,
+        serde_json::Value::String(payload.level.clone()),
+    )
+    .map_err(|err| err.to_string())?;
+    write_desktop_setting_to_disk(
+        LOG_FILTERS_KEY,
+        serde_json::to_value(&payload.filters).map_err(|err| err.to_string())?,
+    )
+    .map_err(|err| err.to_string())?;
+
+    log::info!(
+        "[logging] level={} filters={:?}",
+        payload.level,
+        payload.filters
+    );
+    Ok(())
+}
@@ -1,407 +1,210 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod appearance;
+mod archive;
+mod audit;
+mod auth;
+mod backup;
+mod badge;
+mod boot_error;
+mod changelog;
+mod clipboard;
+mod compat;
+mod context_menu;
+mod crash;
+mod deeplink;
+mod dependencies;
+mod devtools;
+mod diagnostics;
+mod editor;
+mod events;
+mod file_drop;
+mod file_index;
+mod file_preview;
+mod find;
+mod fs_ops;
+mod git;
+mod git_diff;
+mod git_history;
+mod git_signing;
+mod git_transfer;
+mod health;
+mod i18n;
+mod idle;
+mod keep_awake;
+mod lan;
+mod launch;
+mod logging;
+mod menu;
+mod metrics;
+mod mini_window;
+mod notifications;
+mod origin;
+mod path_policy;
+mod pdf_export;
+mod portable;
+mod power;
+mod profiles;
+mod progress;
+mod proxy;
+mod pty;
+mod quit_guard;
+mod recents;
+mod reload;
+mod reveal;
+mod rollback;
+mod safe_mode;
+mod screenshot;
+mod search;
+mod secrets;
+mod servers;
+mod session_export;
+mod settings;
+mod settings_sync;
+mod share;
+mod shortcuts;
+mod sidecar_env;
+mod sidecar_log;
+mod spellcheck;
+mod splash;
+mod startup;
+mod telemetry;
+mod titlebar;
+mod tray;
+mod trust;
+mod tunnel;
+mod update_download;
+mod update_patch;
+mod updates;
+mod watchdog;
+mod watcher;
+mod window_registry;
+mod window_state;
+#[cfg(target_os = "macos")]
+mod window_tabs;
+mod workspace_stats;
+mod zoom;
+
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::env;
 use std::{
+    collections::HashMap,
     net::TcpListener,
-    process::Command,
-    sync::Mutex,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 use std::{fs, path::PathBuf};
-use std::env;
-use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
-
-fn eval_in_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>, script: &str) {
-    let Some(window) = app.get_webview_window("main") else {
-        return;
-    };
-    let _ = window.eval(script);
-}
-
-fn dispatch_menu_action<R: tauri::Runtime>(app: &tauri::AppHandle<R>, action: &str) {
-    let _ = app.emit("openchamber:menu-action", action);
-
-    let event = serde_json::to_string("openchamber:menu-action")
-        .unwrap_or_else(|_| "\"openchamber:menu-action\"".into());
-    let detail = serde_json::to_string(action).unwrap_or_else(|_| "\"\"".into());
-    let script = format!("window.dispatchEvent(new CustomEvent({event}, {{ detail: {detail} }}));");
-    eval_in_main_window(app, &script);
-}
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 
-fn dispatch_check_for_updates<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
-    let _ = app.emit("openchamber:check-for-updates", ());
+use events::DesktopEvent;
+use sidecar_log::{record_sidecar_output, SidecarLogState, SidecarStream};
+use ts_rs::TS;
 
-    let event = serde_json::to_string("openchamber:check-for-updates")
-        .unwrap_or_else(|_| "\"openchamber:check-for-updates\"".into());
-    let script = format!("window.dispatchEvent(new Event({event}));");
-    eval_in_main_window(app, &script);
-}
 use tauri_plugin_shell::{process::CommandChild, process::CommandEvent, ShellExt};
-use tauri_plugin_updater::UpdaterExt;
 
-#[cfg(target_os = "macos")]
-const MENU_ITEM_ABOUT_ID: &str = "menu_about";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_CHECK_FOR_UPDATES_ID: &str = "menu_check_for_updates";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_SETTINGS_ID: &str = "menu_settings";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_COMMAND_PALETTE_ID: &str = "menu_command_palette";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_NEW_SESSION_ID: &str = "menu_new_session";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_WORKTREE_CREATOR_ID: &str = "menu_worktree_creator";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_CHANGE_WORKSPACE_ID: &str = "menu_change_workspace";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_OPEN_GIT_TAB_ID: &str = "menu_open_git_tab";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_OPEN_DIFF_TAB_ID: &str = "menu_open_diff_tab";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_OPEN_FILES_TAB_ID: &str = "menu_open_files_tab";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_OPEN_TERMINAL_TAB_ID: &str = "menu_open_terminal_tab";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_THEME_LIGHT_ID: &str = "menu_theme_light";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_THEME_DARK_ID: &str = "menu_theme_dark";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_THEME_SYSTEM_ID: &str = "menu_theme_system";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_TOGGLE_SIDEBAR_ID: &str = "menu_toggle_sidebar";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID: &str = "menu_toggle_memory_debug";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_HELP_DIALOG_ID: &str = "menu_help_dialog";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_DOWNLOAD_LOGS_ID: &str = "menu_download_logs";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_REPORT_BUG_ID: &str = "menu_report_bug";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_REQUEST_FEATURE_ID: &str = "menu_request_feature";
-#[cfg(target_os = "macos")]
-const MENU_ITEM_JOIN_DISCORD_ID: &str = "menu_join_discord";
-
-#[cfg(target_os = "macos")]
-const GITHUB_BUG_REPORT_URL: &str =
-    "https://github.com/btriapitsyn/openchamber/issues/new?template=bug_report.yml";
-#[cfg(target_os = "macos")]
-const GITHUB_FEATURE_REQUEST_URL: &str =
-    "https://github.com/btriapitsyn/openchamber/issues/new?template=feature_request.yml";
-#[cfg(target_os = "macos")]
-const DISCORD_INVITE_URL: &str = "https://discord.gg/ZYRSdnwwKA";
-
-#[cfg(target_os = "macos")]
-fn build_macos_menu<R: tauri::Runtime>(
-    app: &tauri::AppHandle<R>,
-) -> tauri::Result<tauri::menu::Menu<R>> {
-    use tauri::menu::{
-        Menu, MenuItem, PredefinedMenuItem, Submenu, HELP_SUBMENU_ID, WINDOW_SUBMENU_ID,
-    };
-
-    let pkg_info = app.package_info();
-
-    let auto_worktree = app
-        .try_state::<MenuRuntimeState>()
-        .map(|state| *state.auto_worktree.lock().expect("menu state mutex"))
-        .unwrap_or(false);
-
-    let new_session_shortcut = if auto_worktree { "Cmd+Shift+N" } else { "Cmd+N" };
-    let new_worktree_shortcut = if auto_worktree { "Cmd+N" } else { "Cmd+Shift+N" };
-
-    let about = MenuItem::with_id(
-        app,
-        MENU_ITEM_ABOUT_ID,
-        format!("About {}", pkg_info.name),
-        true,
-        None::<&str>,
-    )?;
-
-    let check_for_updates = MenuItem::with_id(
-        app,
-        MENU_ITEM_CHECK_FOR_UPDATES_ID,
-        "Check for Updates",
-        true,
-        None::<&str>,
-    )?;
-
-    let settings = MenuItem::with_id(app, MENU_ITEM_SETTINGS_ID, "Settings", true, Some("Cmd+,"))?;
-
-    let command_palette = MenuItem::with_id(
-        app,
-        MENU_ITEM_COMMAND_PALETTE_ID,
-        "Command Palette",
-        true,
-        Some("Cmd+K"),
-    )?;
-
-    let new_session = MenuItem::with_id(
-        app,
-        MENU_ITEM_NEW_SESSION_ID,
-        "New Session",
-        true,
-        Some(new_session_shortcut),
-    )?;
-
-    let worktree_creator = MenuItem::with_id(
-        app,
-        MENU_ITEM_WORKTREE_CREATOR_ID,
-        "New Worktree",
-        true,
-        Some(new_worktree_shortcut),
-    )?;
-
-    let change_workspace = MenuItem::with_id(
-        app,
-        MENU_ITEM_CHANGE_WORKSPACE_ID,
-        "Add Workspace",
-        true,
-        None::<&str>,
-    )?;
-
-    let open_git_tab =
-        MenuItem::with_id(app, MENU_ITEM_OPEN_GIT_TAB_ID, "Git", true, Some("Cmd+G"))?;
-    let open_diff_tab =
-        MenuItem::with_id(app, MENU_ITEM_OPEN_DIFF_TAB_ID, "Diff", true, Some("Cmd+E"))?;
-    let open_files_tab =
-        MenuItem::with_id(app, MENU_ITEM_OPEN_FILES_TAB_ID, "Files", true, None::<&str>)?;
-    let open_terminal_tab = MenuItem::with_id(
-        app,
-        MENU_ITEM_OPEN_TERMINAL_TAB_ID,
-        "Terminal",
-        true,
-        Some("Cmd+T"),
-    )?;
-
-    let theme_light =
-        MenuItem::with_id(app, MENU_ITEM_THEME_LIGHT_ID, "Light Theme", true, None::<&str>)?;
-    let theme_dark =
-        MenuItem::with_id(app, MENU_ITEM_THEME_DARK_ID, "Dark Theme", true, None::<&str>)?;
-    let theme_system =
-        MenuItem::with_id(app, MENU_ITEM_THEME_SYSTEM_ID, "System Theme", true, None::<&str>)?;
-
-    let toggle_sidebar = MenuItem::with_id(
-        app,
-        MENU_ITEM_TOGGLE_SIDEBAR_ID,
-        "Toggle Session Sidebar",
-        true,
-        Some("Cmd+L"),
-    )?;
-
-    let toggle_memory_debug = MenuItem::with_id(
-        app,
-        MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID,
-        "Toggle Memory Debug",
-        true,
-        Some("Cmd+Shift+D"),
-    )?;
-
-    let help_dialog = MenuItem::with_id(
-        app,
-        MENU_ITEM_HELP_DIALOG_ID,
-        "Keyboard Shortcuts",
-        true,
-        Some("Cmd+."),
-    )?;
-
-    let download_logs = MenuItem::with_id(
-        app,
-        MENU_ITEM_DOWNLOAD_LOGS_ID,
-        "Show Diagnostics",
-        true,
-        Some("Cmd+Shift+L"),
-    )?;
-
-    let report_bug =
-        MenuItem::with_id(app, MENU_ITEM_REPORT_BUG_ID, "Report a Bug", true, None::<&str>)?;
-    let request_feature = MenuItem::with_id(
-        app,
-        MENU_ITEM_REQUEST_FEATURE_ID,
-        "Request a Feature",
-        true,
-        None::<&str>,
-    )?;
-    let join_discord =
-        MenuItem::with_id(app, MENU_ITEM_JOIN_DISCORD_ID, "Join Discord", true, None::<&str>)?;
-
-    let theme_submenu =
-        Submenu::with_items(app, "Theme", true, &[&theme_light, &theme_dark, &theme_system])?;
-
-    let window_menu = Submenu::with_id_and_items(
-        app,
-        WINDOW_SUBMENU_ID,
-        "Window",
-        true,
-        &[
-            &PredefinedMenuItem::minimize(app, None)?,
-            &PredefinedMenuItem::maximize(app, None)?,
-            &PredefinedMenuItem::separator(app)?,
-            &PredefinedMenuItem::close_window(app, None)?,
-        ],
-    )?;
-
-    let help_menu = Submenu::with_id_and_items(
-        app,
-        HELP_SUBMENU_ID,
-        "Help",
-        true,
-        &[
-            &help_dialog,
-            &download_logs,
-            &PredefinedMenuItem::separator(app)?,
-            &report_bug,
-            &request_feature,
-            &PredefinedMenuItem::separator(app)?,
-            &join_discord,
-        ],
-    )?;
-
-    Menu::with_items(
-        app,
-        &[
-            &Submenu::with_items(
-                app,
-                pkg_info.name.clone(),
-                true,
-                &[
-                    &about,
-                    &check_for_updates,
-                    &PredefinedMenuItem::separator(app)?,
-                    &settings,
-                    &command_palette,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::services(app, None)?,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::hide(app, None)?,
-                    &PredefinedMenuItem::hide_others(app, None)?,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::quit(app, None)?,
-                ],
-            )?,
-            &Submenu::with_items(
-                app,
-                "File",
-                true,
-                &[
-                    &new_session,
-                    &worktree_creator,
-                    &PredefinedMenuItem::separator(app)?,
-                    &change_workspace,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::close_window(app, None)?,
-                ],
-            )?,
-            &Submenu::with_items(
-                app,
-                "Edit",
-                true,
-                &[
-                    &PredefinedMenuItem::undo(app, None)?,
-                    &PredefinedMenuItem::redo(app, None)?,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::cut(app, None)?,
-                    &PredefinedMenuItem::copy(app, None)?,
-                    &PredefinedMenuItem::paste(app, None)?,
-                    &PredefinedMenuItem::select_all(app, None)?,
-                ],
-            )?,
-            &Submenu::with_items(
-                app,
-                "View",
-                true,
-                &[
-                    &open_git_tab,
-                    &open_diff_tab,
-                    &open_files_tab,
-                    &open_terminal_tab,
-                    &PredefinedMenuItem::separator(app)?,
-                    &theme_submenu,
-                    &PredefinedMenuItem::separator(app)?,
-                    &toggle_sidebar,
-                    &toggle_memory_debug,
-                    &PredefinedMenuItem::separator(app)?,
-                    &PredefinedMenuItem::fullscreen(app, None)?,
-                ],
-            )?,
-            &window_menu,
-            &help_menu,
-        ],
-    )
-}
+const SIDECAR_NAME: &str = "openchamber-server";
+const SIDECAR_NOTIFY_PREFIX: &str = "[OpenChamberDesktopNotify] ";
+const HEALTH_TIMEOUT_SETTING_KEY: &str = "desktopHealthTimeoutMs";
+const HEALTH_POLL_INTERVAL_SETTING_KEY: &str = "desktopHealthPollIntervalMs";
+const DEFAULT_HEALTH_TIMEOUT: Duration = Duration::from_secs(20);
+const MIN_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_HEALTH_TIMEOUT: Duration = Duration::from_secs(600);
+const DEFAULT_HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const MIN_HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
-#[tauri::command]
-fn desktop_set_auto_worktree_menu(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
-    let Some(state) = app.try_state::<MenuRuntimeState>() else {
-        return Ok(());
-    };
+const DEFAULT_DESKTOP_PORT: u16 = 57123;
 
-    {
-        let mut guard = state.auto_worktree.lock().expect("menu state mutex");
-        *guard = enabled;
-    }
+const SIDECAR_MAX_RESTARTS_SETTING_KEY: &str = "desktopSidecarMaxRestarts";
+const DEFAULT_SIDECAR_MAX_RESTARTS: u32 = 5;
+const SIDECAR_RESTART_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const SIDECAR_RESTART_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const SIDECAR_SHUTDOWN_GRACE_SETTING_KEY: &str = "desktopSidecarShutdownGraceMs";
+const DEFAULT_SIDECAR_SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+const MAX_SIDECAR_SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+// The app is already quitting, so the event loop does not wait the full grace period.
+const EXIT_SIDECAR_SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+#[cfg(unix)]
+const SIDECAR_EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// A sidecar that stayed up this long is considered stable; its crash resets the retry budget.
+const SIDECAR_STABLE_UPTIME: Duration = Duration::from_secs(60);
 
-    #[cfg(target_os = "macos")]
-    {
-        use tauri::menu::MenuItemKind;
+const LOCAL_HOST_ID: &str = "local";
 
-        let new_session_shortcut = if enabled { "Cmd+Shift+N" } else { "Cmd+N" };
-        let new_worktree_shortcut = if enabled { "Cmd+N" } else { "Cmd+Shift+N" };
+/// Settings-store key for the port the local sidecar should try first.
+const SIDECAR_PREFERRED_PORT_KEY: &str = "sidecarPreferredPort";
+const PORT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 
-        if let Some(menu) = app.menu() {
-            if let Some(MenuItemKind::MenuItem(item)) = menu.get(MENU_ITEM_NEW_SESSION_ID) {
-                item.set_accelerator(Some(new_session_shortcut))
-                    .map_err(|err| err.to_string())?;
-            }
-            if let Some(MenuItemKind::MenuItem(item)) = menu.get(MENU_ITEM_WORKTREE_CREATOR_ID) {
-                item.set_accelerator(Some(new_worktree_shortcut))
-                    .map_err(|err| err.to_string())?;
-            }
-        } else {
-            // Should not happen on macOS, but keep as fallback.
-            let menu = build_macos_menu(&app).map_err(|err| err.to_string())?;
-            app.set_menu(menu).map_err(|err| err.to_string())?;
-        }
-    }
+const SIDECAR_CRASH_HISTORY: usize = 10;
 
-    Ok(())
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarCrashRecord {
+    at: String,
+    code: Option<i32>,
+    signal: Option<i32>,
+    uptime_secs: Option<u64>,
 }
 
-const SIDECAR_NAME: &str = "openchamber-server";
-const SIDECAR_NOTIFY_PREFIX: &str = "[OpenChamberDesktopNotify] ";
-const HEALTH_TIMEOUT: Duration = Duration::from_secs(20);
-const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
-
-const DEFAULT_DESKTOP_PORT: u16 = 57123;
-
-const LOCAL_HOST_ID: &str = "local";
-
+/// One profile's local server process.
 #[derive(Default)]
-struct SidecarState {
+struct Sidecar {
     child: Mutex<Option<CommandChild>>,
     url: Mutex<Option<String>>,
+    /// Incremented for every spawned sidecar process.
+    generation: Mutex<u64>,
+    /// Generation of the healthy sidecar currently under supervision (0 = none).
+    supervised_generation: Mutex<u64>,
+    started_at: Mutex<Option<std::time::Instant>>,
+    /// Round-trip time of the most recent successful `/health` request.
+    last_health_latency: Mutex<Option<Duration>>,
+    restart_attempts: Mutex<u32>,
+    /// Most recent crashes, oldest first, for diagnostics.
+    recent_crashes: Mutex<Vec<SidecarCrashRecord>>,
+    /// Most recent resource limit violations, oldest first, for diagnostics.
+    recent_resource_incidents: Mutex<Vec<watchdog::SidecarResourceIncident>>,
 }
 
+/// Local server processes keyed by profile name. A process only ever runs its
+/// own profile (see `profiles::acquire_instance_lock`), so the other entries stay
+/// empty unless something looks them up.
 #[derive(Default)]
-struct DesktopUiInjectionState {
-    script: Mutex<Option<String>>,
+struct SidecarState {
+    by_profile: Mutex<HashMap<String, Arc<Sidecar>>>,
 }
 
-struct WindowFocusState {
-    focused: Mutex<bool>,
+impl SidecarState {
+    fn profile(&self, profile: &str) -> Arc<Sidecar> {
+        self.by_profile
+            .lock()
+            .expect("sidecar profiles mutex")
+            .entry(profile.to_string())
+            .or_default()
+            .clone()
+    }
 }
 
-impl Default for WindowFocusState {
-    fn default() -> Self {
-        Self {
-            focused: Mutex::new(true),
-        }
-    }
+/// The sidecar of the profile this process runs.
+fn local_sidecar<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<Arc<Sidecar>> {
+    let state = app.try_state::<SidecarState>()?;
+    Some(state.profile(profiles::name()))
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct SidecarCrashedEvent {
+    code: Option<i32>,
+    signal: Option<i32>,
+    attempt: u32,
+    max_retries: u32,
+    will_restart: bool,
+    retry_in_ms: Option<u64>,
 }
 
 #[derive(Default)]
-struct MenuRuntimeState {
-    auto_worktree: Mutex<bool>,
+struct DesktopUiInjectionState {
+    script: Mutex<Option<String>>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -438,17 +241,18 @@ fn normalize_host_url(raw: &str) -> Option<String> {
     Some(normalized)
 }
 
-fn settings_file_path() -> PathBuf {
+fn openchamber_data_dir() -> PathBuf {
     if let Ok(dir) = env::var("OPENCHAMBER_DATA_DIR") {
         if !dir.trim().is_empty() {
-            return PathBuf::from(dir.trim()).join("settings.json");
+            return PathBuf::from(dir.trim());
         }
     }
     let home = env::var("HOME").unwrap_or_default();
-    PathBuf::from(home)
-        .join(".config")
-        .join("openchamber")
-        .join("settings.json")
+    PathBuf::from(home).join(".config").join("openchamber")
+}
+
+fn settings_file_path() -> PathBuf {
+    openchamber_data_dir().join("settings.json")
 }
 
 fn read_desktop_local_port_from_disk() -> Option<u16> {
@@ -461,7 +265,75 @@ fn read_desktop_local_port_from_disk() -> Option<u16> {
         .as_ref()
         .and_then(|v| v.get("desktopLocalPort"))
         .and_then(|v| v.as_u64())
-        .and_then(|v| if v > 0 && v <= u16::MAX as u64 { Some(v as u16) } else { None })
+        .and_then(|v| {
+            if v > 0 && v <= u16::MAX as u64 {
+                Some(v as u16)
+            } else {
+                None
+            }
+        })
+}
+
+fn read_desktop_setting_from_disk(key: &str) -> Option<serde_json::Value> {
+    let path = settings_file_path();
+    let raw = fs::read_to_string(path).ok()?;
+    let parsed = serde_json::from_str::<serde_json::Value>(&raw).ok()?;
+    parsed.get(key).cloned()
+}
+
+fn write_desktop_setting_to_disk(key: &str, value: serde_json::Value) -> Result<()> {
+    let path = settings_file_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut root: serde_json::Value = if let Ok(raw) = fs::read_to_string(&path) {
+        serde_json::from_str(&raw).unwrap_or(serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+
+    if !root.is_object() {
+        root = serde_json::json!({});
+    }
+
+    root[key] = value;
+    fs::write(&path, serde_json::to_string_pretty(&root)?)?;
+    Ok(())
+}
+
+fn read_sidecar_max_restarts(app: &tauri::AppHandle) -> u32 {
+    settings::get_setting(app, SIDECAR_MAX_RESTARTS_SETTING_KEY)
+        .and_then(|v| v.as_u64())
+        .map(|v| v.min(u32::MAX as u64) as u32)
+        .unwrap_or(DEFAULT_SIDECAR_MAX_RESTARTS)
+}
+
+/// How long to wait for a server's `/health` to answer before giving up. Cold
+/// first launches (dependency installs, large repo indexing) may need more than the default.
+fn read_health_timeout<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Duration {
+    settings::get_setting(app, HEALTH_TIMEOUT_SETTING_KEY)
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_HEALTH_TIMEOUT)
+        .clamp(MIN_HEALTH_TIMEOUT, MAX_HEALTH_TIMEOUT)
+}
+
+fn read_health_poll_interval<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Duration {
+    settings::get_setting(app, HEALTH_POLL_INTERVAL_SETTING_KEY)
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_HEALTH_POLL_INTERVAL)
+        .clamp(MIN_HEALTH_POLL_INTERVAL, MAX_HEALTH_POLL_INTERVAL)
+}
+
+/// How long the sidecar gets to exit after SIGTERM before it is killed.
+fn read_sidecar_shutdown_grace(app: &tauri::AppHandle) -> Duration {
+    settings::get_setting(app, SIDECAR_SHUTDOWN_GRACE_SETTING_KEY)
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SIDECAR_SHUTDOWN_GRACE)
+        .min(MAX_SIDECAR_SHUTDOWN_GRACE)
 }
 
 fn write_desktop_local_port_to_disk(port: u16) -> Result<()> {
@@ -485,7 +357,6 @@ fn write_desktop_local_port_to_disk(port: u16) -> Result<()> {
     Ok(())
 }
 
-
 fn read_desktop_hosts_config_from_disk() -> DesktopHostsConfig {
     let path = settings_file_path();
     let raw = fs::read_to_string(path).ok();
@@ -580,16 +451,17 @@ fn write_desktop_hosts_config_to_disk(config: &DesktopHostsConfig) -> Result<()>
 }
 
 #[tauri::command]
-fn desktop_hosts_get() -> Result<DesktopHostsConfig, String> {
+fn desktop_hosts_get(webview: tauri::Webview) -> Result<DesktopHostsConfig, String> {
+    crate::origin::require_local(&webview, "read hosts")?;
     Ok(read_desktop_hosts_config_from_disk())
 }
 
 #[tauri::command]
-fn desktop_hosts_set(config: DesktopHostsConfig) -> Result<(), String> {
+fn desktop_hosts_set(webview: tauri::Webview, config: DesktopHostsConfig) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change hosts")?;
     write_desktop_hosts_config_to_disk(&config).map_err(|err| err.to_string())
 }
 
-
 #[derive(Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct HostProbeResult {
@@ -598,11 +470,16 @@ struct HostProbeResult {
 }
 
 #[tauri::command]
-async fn desktop_host_probe(url: String) -> Result<HostProbeResult, String> {
+async fn desktop_host_probe(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    url: String,
+) -> Result<HostProbeResult, String> {
+    crate::origin::require_local(&webview, "probe hosts")?;
     let normalized = normalize_host_url(&url).ok_or_else(|| "Invalid URL".to_string())?;
     let health = format!("{}/health", normalized.trim_end_matches('/'));
-    let client = reqwest::Client::builder()
-        .no_proxy()
+    let client = proxy::load(&app)
+        .apply(reqwest::Client::builder())
         .timeout(Duration::from_secs(2))
         .build()
         .map_err(|err| err.to_string())?;
@@ -635,7 +512,7 @@ async fn desktop_host_probe(url: String) -> Result<HostProbeResult, String> {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, TS)]
 #[serde(tag = "event", content = "data")]
 enum UpdateProgressEvent {
     #[serde(rename_all = "camelCase")]
@@ -649,16 +526,20 @@ enum UpdateProgressEvent {
         total: Option<u64>,
     },
     Finished,
+    Cancelled,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
 struct DesktopUpdateInfo {
     available: bool,
+    channel: updates::UpdateChannel,
     current_version: String,
     version: Option<String>,
     body: Option<String>,
     date: Option<String>,
+    /// Set when the update can be downloaded as a patch against this version.
+    patch: Option<update_patch::UpdatePatchInfo>,
 }
 
 struct PendingUpdate(Mutex<Option<tauri_plugin_updater::Update>>);
@@ -673,17 +554,6 @@ fn is_nonempty_string(value: &str) -> bool {
     !value.trim().is_empty()
 }
 
-const CHANGELOG_URL: &str = "https://raw.githubusercontent.com/btriapitsyn/openchamber/main/CHANGELOG.md";
-
-fn parse_semver_num(value: &str) -> Option<u32> {
-    let trimmed = value.trim().trim_start_matches('v');
-    let mut parts = trimmed.split('.');
-    let major: u32 = parts.next()?.parse().ok()?;
-    let minor: u32 = parts.next()?.parse().ok()?;
-    let patch: u32 = parts.next()?.parse().ok()?;
-    Some(major.saturating_mul(10_000) + minor.saturating_mul(100) + patch)
-}
-
 fn is_placeholder_release_notes(body: &Option<String>) -> bool {
     let Some(body) = body.as_ref() else {
         return true;
@@ -697,162 +567,565 @@ fn is_placeholder_release_notes(body: &Option<String>) -> bool {
         .starts_with("see release notes at")
 }
 
-async fn fetch_changelog_notes(from_version: &str, to_version: &str) -> Option<String> {
-    let from_num = parse_semver_num(from_version)?;
-    let to_num = parse_semver_num(to_version)?;
-    if to_num <= from_num {
-        return None;
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarNotifyPayload {
+    title: Option<String>,
+    body: Option<String>,
+    tag: Option<String>,
+    require_hidden: Option<bool>,
+    category: Option<String>,
+}
+
+fn notification_allowed(app: &tauri::AppHandle, category: Option<&str>, focused: bool) -> bool {
+    let category = notifications::NotificationCategory::parse(category);
+    app.try_state::<notifications::NotificationPrefsState>()
+        .map(|prefs| prefs.allows(category, focused))
+        .unwrap_or(true)
+}
+
+fn maybe_show_sidecar_notification(app: &tauri::AppHandle, payload: SidecarNotifyPayload) {
+    let require_hidden = payload.require_hidden.unwrap_or(false);
+    let focused = window_registry::is_app_focused(app);
+    if require_hidden && focused {
+        return;
+    }
+    if !notification_allowed(app, payload.category.as_deref(), focused) {
+        return;
     }
 
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .ok()?;
+    let title = payload
+        .title
+        .filter(|t| is_nonempty_string(t))
+        .unwrap_or_else(|| "OpenChamber".to_string());
+    let body = payload.body.filter(|b| is_nonempty_string(b));
+    let _tag = payload.tag;
 
-    let response = client.get(CHANGELOG_URL).send().await.ok()?;
-    if !response.status().is_success() {
-        return None;
+    use tauri_plugin_notification::NotificationExt;
+
+    let mut builder = app.notification().builder().title(title);
+    if let Some(body) = body {
+        builder = builder.body(body);
     }
-    let changelog = response.text().await.ok()?;
-    if changelog.trim().is_empty() {
-        return None;
+
+    #[cfg(target_os = "macos")]
+    {
+        builder = builder.sound("Glass");
+    }
+    let _ = builder.show();
+    if !focused {
+        badge::increment_badge(app);
     }
+}
+
+/// How far a startup `/health` probe has got.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "kebab-case")]
+enum HealthStage {
+    /// Nothing is answering yet.
+    Waiting,
+    /// The server answers but not successfully (still booting).
+    Starting,
+    Ready,
+    TimedOut,
+}
+
+/// Fields of the `/health` body worth reporting; all optional across server versions.
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthBody {
+    version: Option<String>,
+    is_open_code_ready: Option<bool>,
+    last_open_code_error: Option<String>,
+}
+
+/// Emitted as `openchamber:health-progress` whenever a startup probe changes stage.
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct HealthProgressEvent {
+    url: String,
+    stage: HealthStage,
+    elapsed_ms: u64,
+    timeout_ms: u64,
+    server_version: Option<String>,
+    open_code_ready: Option<bool>,
+    open_code_error: Option<String>,
+}
+
+/// Polls `/health` until it succeeds, returning the latency of the successful request.
+async fn wait_for_health<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    url: &str,
+) -> Option<Duration> {
+    let client = match proxy::load(app).apply(reqwest::Client::builder()).build() {
+        Ok(c) => c,
+        Err(_) => return None,
+    };
+
+    let timeout = read_health_timeout(app);
+    let poll_interval = read_health_poll_interval(app);
+    let started = std::time::Instant::now();
+    let health_url = format!("{}/health", url.trim_end_matches('/'));
+    let mut last_stage: Option<HealthStage> = None;
+    let mut report = |stage: HealthStage, body: &HealthBody| {
+        if last_stage == Some(stage) {
+            return;
+        }
+        last_stage = Some(stage);
+        events::emit(
+            app,
+            DesktopEvent::HealthProgress(HealthProgressEvent {
+                url: url.to_string(),
+                stage,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                timeout_ms: timeout.as_millis() as u64,
+                server_version: body.version.clone(),
+                open_code_ready: body.is_open_code_ready,
+                open_code_error: body.last_open_code_error.clone(),
+            }),
+        );
+    };
 
-    let mut markers: Vec<(usize, Option<u32>)> = Vec::new();
-    let mut offset: usize = 0;
-    for line in changelog.lines() {
-        let line_trimmed = line.trim_end_matches('\r');
-        if line_trimmed.starts_with("## [") {
-            let ver = line_trimmed
-                .strip_prefix("## [")
-                .and_then(|rest| rest.split(']').next())
-                .unwrap_or("");
-            markers.push((offset, parse_semver_num(ver)));
+    while started.elapsed() < timeout {
+        let sent_at = std::time::Instant::now();
+        match client.get(&health_url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                let latency = sent_at.elapsed();
+                let body = resp.json::<HealthBody>().await.unwrap_or_default();
+                report(HealthStage::Ready, &body);
+                return Some(latency);
+            }
+            Ok(_) => report(HealthStage::Starting, &HealthBody::default()),
+            Err(_) => report(HealthStage::Waiting, &HealthBody::default()),
         }
-        offset = offset.saturating_add(line.len().saturating_add(1));
+        tokio::time::sleep(poll_interval).await;
     }
 
-    if markers.is_empty() {
-        return None;
+    log::warn!("[health] {url} not healthy after {timeout:?}");
+    report(HealthStage::TimedOut, &HealthBody::default());
+    None
+}
+
+/// Stops the local sidecar, waiting up to the configured shutdown grace period.
+/// Blocks, so async callers run it on `spawn_blocking`.
+fn kill_sidecar(app: tauri::AppHandle) {
+    kill_sidecar_within(app, MAX_SIDECAR_SHUTDOWN_GRACE);
+}
+
+/// Like [`kill_sidecar`], but never waits longer than `max_grace`.
+fn kill_sidecar_within(app: tauri::AppHandle, max_grace: Duration) {
+    let Some(state) = local_sidecar(&app) else {
+        return;
+    };
+
+    // Intentional kills must not be mistaken for crashes by the supervisor.
+    *state
+        .supervised_generation
+        .lock()
+        .expect("sidecar generation mutex") = 0;
+
+    let child = state.child.lock().expect("sidecar mutex").take();
+    if let Some(child) = child {
+        shutdown_sidecar_child(child, read_sidecar_shutdown_grace(&app).min(max_grace));
     }
+}
 
-    let mut relevant: Vec<String> = Vec::new();
-    for idx in 0..markers.len() {
-        let (start, ver_num) = markers[idx];
-        let end = markers.get(idx + 1).map(|m| m.0).unwrap_or_else(|| changelog.len());
-        let Some(ver_num) = ver_num else {
-            continue;
-        };
-        if ver_num <= from_num || ver_num > to_num {
-            continue;
+/// Asks the sidecar to exit with SIGTERM so in-flight work (e.g. git operations)
+/// can finish, and only kills it if it is still running after `grace`.
+#[cfg(unix)]
+fn shutdown_sidecar_child(child: CommandChild, grace: Duration) {
+    let pid = child.pid();
+    let Ok(raw_pid) = libc::pid_t::try_from(pid) else {
+        log::warn!("[sidecar] shutdown pid={pid} path=kill reason=invalid-pid");
+        let _ = child.kill();
+        return;
+    };
+
+    // SAFETY: plain signal delivery to a child process we spawned.
+    if grace.is_zero() || unsafe { libc::kill(raw_pid, libc::SIGTERM) } != 0 {
+        log::info!(
+            "[sidecar] shutdown pid={pid} path=kill grace_ms={}",
+            grace.as_millis()
+        );
+        let _ = child.kill();
+        return;
+    }
+
+    let started = std::time::Instant::now();
+    while started.elapsed() < grace {
+        // SAFETY: signal 0 only checks that the process still exists.
+        if unsafe { libc::kill(raw_pid, 0) } != 0 {
+            log::info!(
+                "[sidecar] shutdown pid={pid} path=sigterm elapsed_ms={}",
+                started.elapsed().as_millis()
+            );
+            return;
         }
-        if start >= changelog.len() || end <= start {
-            continue;
+        std::thread::sleep(SIDECAR_EXIT_POLL_INTERVAL);
+    }
+
+    log::warn!(
+        "[sidecar] shutdown pid={pid} path=sigterm-timeout-kill grace_ms={}",
+        grace.as_millis()
+    );
+    let _ = child.kill();
+}
+
+/// Windows has no SIGTERM equivalent for console children, so the sidecar is killed directly.
+#[cfg(not(unix))]
+fn shutdown_sidecar_child(child: CommandChild, _grace: Duration) {
+    log::info!(
+        "[sidecar] shutdown pid={} path=kill reason=unsupported-platform",
+        child.pid()
+    );
+    let _ = child.kill();
+}
+
+fn sidecar_restart_backoff(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    SIDECAR_RESTART_INITIAL_BACKOFF
+        .saturating_mul(factor)
+        .min(SIDECAR_RESTART_MAX_BACKOFF)
+}
+
+/// Called once the event stream of a sidecar process ends. Restarts the sidecar
+/// with exponential backoff if it was the supervised (healthy) process.
+fn handle_sidecar_exit(
+    app: &tauri::AppHandle,
+    generation: u64,
+    code: Option<i32>,
+    signal: Option<i32>,
+) {
+    let Some(state) = local_sidecar(app) else {
+        return;
+    };
+
+    {
+        let mut supervised = state
+            .supervised_generation
+            .lock()
+            .expect("sidecar generation mutex");
+        if *supervised != generation {
+            return;
         }
-        let end_clamped = end.min(changelog.len());
-        let section = changelog[start..end_clamped].trim();
-        if !section.is_empty() {
-            relevant.push(section.to_string());
+        *supervised = 0;
+    }
+
+    {
+        let mut child = state.child.lock().expect("sidecar mutex");
+        *child = None;
+    }
+
+    let uptime = state
+        .started_at
+        .lock()
+        .expect("sidecar started mutex")
+        .take()
+        .map(|started| started.elapsed());
+    let was_stable = uptime
+        .map(|uptime| uptime >= SIDECAR_STABLE_UPTIME)
+        .unwrap_or(false);
+
+    {
+        let mut crashes = state.recent_crashes.lock().expect("sidecar crashes mutex");
+        if crashes.len() >= SIDECAR_CRASH_HISTORY {
+            crashes.remove(0);
         }
+        crashes.push(SidecarCrashRecord {
+            at: chrono::Utc::now().to_rfc3339(),
+            code,
+            signal,
+            uptime_secs: uptime.map(|uptime| uptime.as_secs()),
+        });
     }
 
-    if relevant.is_empty() {
-        None
+    log::error!("[sidecar] crashed code={code:?} signal={signal:?}");
+    crash::record_sidecar_crash(app, code, signal, uptime);
+    request_sidecar_restart(app, code, signal, was_stable);
+}
+
+/// Consumes one restart attempt, notifies the webview and schedules the restart
+/// unless the retry budget is exhausted.
+fn request_sidecar_restart(
+    app: &tauri::AppHandle,
+    code: Option<i32>,
+    signal: Option<i32>,
+    reset_attempts: bool,
+) {
+    let Some(state) = local_sidecar(app) else {
+        return;
+    };
+
+    let max_retries = read_sidecar_max_restarts(app);
+    let attempt = {
+        let mut attempts = state
+            .restart_attempts
+            .lock()
+            .expect("sidecar restart mutex");
+        if reset_attempts {
+            *attempts = 0;
+        }
+        *attempts = attempts.saturating_add(1);
+        *attempts
+    };
+
+    let will_restart = attempt <= max_retries;
+    let delay = sidecar_restart_backoff(attempt);
+
+    events::emit(
+        app,
+        DesktopEvent::SidecarCrashed(SidecarCrashedEvent {
+            code,
+            signal,
+            attempt,
+            max_retries,
+            will_restart,
+            retry_in_ms: will_restart.then_some(delay.as_millis() as u64),
+        }),
+    );
+
+    if will_restart {
+        log::warn!("[sidecar] restarting in {delay:?} (attempt {attempt}/{max_retries})");
+        telemetry::record(app, telemetry::TelemetryEventKind::SidecarRestarted);
+        schedule_sidecar_restart(app.clone(), delay);
     } else {
-        Some(relevant.join("\n\n"))
+        log::error!("[sidecar] giving up after {max_retries} restart attempts");
+    }
+}
+
+fn schedule_sidecar_restart(app: tauri::AppHandle, delay: Duration) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+
+        let previous_url = local_sidecar(&app)
+            .and_then(|state| state.url.lock().expect("sidecar url mutex").clone());
+
+        match spawn_local_server(&app).await {
+            Ok(url) => {
+                log::info!("[sidecar] restarted at {url}");
+                events::emit(&app, DesktopEvent::SidecarRestarted(url.clone()));
+                follow_local_server(&app, previous_url, &url);
+            }
+            Err(err) => {
+                log::error!("[sidecar] restart failed: {err}");
+                request_sidecar_restart(&app, None, None, false);
+            }
+        }
+    });
+}
+
+/// Points the main window at a respawned sidecar if it came back on a different port.
+fn follow_local_server(app: &tauri::AppHandle, previous_url: Option<String>, url: &str) {
+    if previous_url.as_deref() == Some(url) {
+        return;
+    }
+    let (Some(previous), Some(window)) = (previous_url, app.get_webview_window("main")) else {
+        return;
+    };
+    let on_local = window
+        .url()
+        .map(|current| current.as_str().starts_with(&previous))
+        .unwrap_or(false);
+    if on_local {
+        if let Ok(parsed) = url::Url::parse(url) {
+            let _ = window.navigate(parsed);
+        }
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DesktopServerInfo {
+    /// Origin the main window is currently connected to.
+    url: Option<String>,
+    /// URL of the local OpenChamber server.
+    local_url: Option<String>,
+    is_remote: bool,
+    sidecar_pid: Option<u32>,
+    uptime_secs: Option<u64>,
+    last_health_latency_ms: Option<u64>,
+}
+
+fn url_origin(raw: &str) -> Option<String> {
+    url::Url::parse(raw)
+        .ok()
+        .map(|u| u.origin().ascii_serialization())
+}
+
+/// Origin the main window is connected to, the local server URL, and whether the two differ.
+fn resolve_server_target(app: &tauri::AppHandle) -> (Option<String>, Option<String>, bool) {
+    let local_url =
+        local_sidecar(app).and_then(|state| state.url.lock().expect("sidecar url mutex").clone());
+    let url = app
+        .get_webview_window("main")
+        .and_then(|window| window.url().ok())
+        .filter(|current| matches!(current.scheme(), "http" | "https"))
+        .map(|current| current.origin().ascii_serialization())
+        .or_else(|| local_url.as_deref().and_then(url_origin));
+    let is_remote = match (&url, local_url.as_deref().and_then(url_origin)) {
+        (Some(current), Some(local)) => *current != local,
+        _ => false,
+    };
+    (url, local_url, is_remote)
+}
+
+#[tauri::command]
+fn desktop_server_info(app: tauri::AppHandle) -> Result<DesktopServerInfo, String> {
+    let state = local_sidecar(&app).ok_or_else(|| "Sidecar state unavailable".to_string())?;
+
+    let (url, local_url, is_remote) = resolve_server_target(&app);
+    let sidecar_pid = state
+        .child
+        .lock()
+        .expect("sidecar mutex")
+        .as_ref()
+        .map(|child| child.pid());
+    let uptime_secs = state
+        .started_at
+        .lock()
+        .expect("sidecar started mutex")
+        .map(|started| started.elapsed().as_secs());
+    let last_health_latency_ms = state
+        .last_health_latency
+        .lock()
+        .expect("sidecar latency mutex")
+        .map(|latency| latency.as_millis() as u64);
+
+    Ok(DesktopServerInfo {
+        url,
+        local_url,
+        is_remote,
+        sidecar_pid,
+        uptime_secs,
+        last_health_latency_ms,
+    })
+}
+
+fn build_local_url(port: u16) -> String {
+    format!("http://127.0.0.1:{port}")
+}
+
+#[derive(Clone, Copy, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+enum SidecarPortSource {
+    Preferred,
+    LastUsed,
+    Default,
+    Random,
+}
+
+#[derive(Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
-struct SidecarNotifyPayload {
-    title: Option<String>,
-    body: Option<String>,
-    tag: Option<String>,
-    require_hidden: Option<bool>,
+struct SkippedPort {
+    port: u16,
+    reason: String,
 }
 
-fn maybe_show_sidecar_notification(app: &tauri::AppHandle, payload: SidecarNotifyPayload) {
-    let require_hidden = payload.require_hidden.unwrap_or(false);
-    if require_hidden {
-        let focused = app
-            .try_state::<WindowFocusState>()
-            .map(|state| *state.focused.lock().expect("focus mutex"))
-            .unwrap_or(false);
-        if focused {
-            return;
-        }
-    }
-
-    let title = payload
-        .title
-        .filter(|t| is_nonempty_string(t))
-        .unwrap_or_else(|| "OpenChamber".to_string());
-    let body = payload.body.filter(|b| is_nonempty_string(b));
-    let _tag = payload.tag;
+/// How the local server port was chosen, emitted as `openchamber:server-info`.
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct SidecarPortDecision {
+    url: String,
+    port: u16,
+    source: SidecarPortSource,
+    preferred_port: Option<u16>,
+    /// An already running OpenChamber server was reused instead of spawning one.
+    adopted: bool,
+    skipped: Vec<SkippedPort>,
+}
 
-    use tauri_plugin_notification::NotificationExt;
+enum PortOccupant {
+    OpenChamber { version: Option<String> },
+    Other,
+}
 
-    let mut builder = app.notification().builder().title(title);
-    if let Some(body) = body {
-        builder = builder.body(body);
-    }
+fn read_preferred_sidecar_port(app: &tauri::AppHandle) -> Option<u16> {
+    settings::get_setting(app, SIDECAR_PREFERRED_PORT_KEY)
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .filter(|port| *port > 0)
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        builder = builder.sound("Glass");
-    }
-    let _ = builder.show();
+fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
-async fn wait_for_health(url: &str) -> bool {
-    let client = match reqwest::Client::builder().no_proxy().build() {
-        Ok(c) => c,
-        Err(_) => return false,
+/// Identifies whatever is listening on `port` by its `/health` response.
+async fn probe_port_occupant(port: u16) -> PortOccupant {
+    let Ok(client) = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(PORT_PROBE_TIMEOUT)
+        .build()
+    else {
+        return PortOccupant::Other;
     };
-
-    let deadline = std::time::Instant::now() + HEALTH_TIMEOUT;
-    let health_url = format!("{}/health", url.trim_end_matches('/'));
-
-    while std::time::Instant::now() < deadline {
-        if let Ok(resp) = client.get(&health_url).send().await {
-            if resp.status().is_success() {
-                return true;
-            }
-        }
-        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+    let health_url = format!("{}/health", build_local_url(port));
+    let Ok(resp) = client.get(&health_url).send().await else {
+        return PortOccupant::Other;
+    };
+    if !resp.status().is_success() {
+        return PortOccupant::Other;
     }
-
-    false
-}
-
-fn kill_sidecar(app: tauri::AppHandle) {
-    let Some(state) = app.try_state::<SidecarState>() else {
-        return;
+    let Ok(body) = resp.json::<serde_json::Value>().await else {
+        return PortOccupant::Other;
     };
-
-    let mut guard = state.child.lock().expect("sidecar mutex");
-    if let Some(child) = guard.take() {
-        let _ = child.kill();
+    let is_openchamber = body.get("status").and_then(|v| v.as_str()) == Some("ok")
+        && body.get("isOpenCodeReady").is_some();
+    if !is_openchamber {
+        return PortOccupant::Other;
+    }
+    PortOccupant::OpenChamber {
+        version: body
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
     }
 }
 
-fn build_local_url(port: u16) -> String {
-    format!("http://127.0.0.1:{port}")
+fn report_port_decision(app: &tauri::AppHandle, decision: SidecarPortDecision) {
+    log::info!(
+        "[sidecar] using {} (source={:?}, adopted={}, skipped={})",
+        decision.url,
+        serde_json::to_value(decision.source).unwrap_or_default(),
+        decision.adopted,
+        decision.skipped.len()
+    );
+    events::emit(app, DesktopEvent::ServerInfo(decision));
 }
 
 async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
-    let stored_port = read_desktop_local_port_from_disk();
-    let mut candidates: Vec<Option<u16>> = Vec::new();
-    if let Some(port) = stored_port {
-        candidates.push(Some(port));
+    let preferred_port = read_preferred_sidecar_port(app);
+    let mut candidates: Vec<(Option<u16>, SidecarPortSource)> = Vec::new();
+    if let Some(port) = preferred_port {
+        candidates.push((Some(port), SidecarPortSource::Preferred));
+    }
+    if let Some(port) = read_desktop_local_port_from_disk() {
+        candidates.push((Some(port), SidecarPortSource::LastUsed));
+    }
+    // The default port belongs to the default profile.
+    if profiles::active().is_none() {
+        candidates.push((Some(DEFAULT_DESKTOP_PORT), SidecarPortSource::Default));
     }
-    candidates.push(Some(DEFAULT_DESKTOP_PORT));
-    candidates.push(None);
+    candidates.push((None, SidecarPortSource::Random));
 
+    let mut seen_ports = std::collections::HashSet::new();
+    candidates.retain(|(port, _)| port.is_none_or(|port| seen_ports.insert(port)));
+    let mut skipped: Vec<SkippedPort> = Vec::new();
+    let app_version = app.package_info().version.to_string();
+
+    splash::set_stage(app, splash::BootStage::ResolvingResources);
     let dist_dir = resolve_web_dist_dir(app)?;
-    let no_proxy = "localhost,127.0.0.1";
+    startup::mark(app, startup::StartupPhase::ResourcesResolved);
+    let proxy_env = proxy::load(app).env_vars();
+    let lan_binding = lan::sidecar_binding(app);
+    let bind_host = lan_binding
+        .as_ref()
+        .map_or_else(|| "127.0.0.1".to_string(), lan::LanBinding::bind_host);
+    let lan_env = lan_binding
+        .as_ref()
+        .map(|binding| binding.env_vars().to_vec())
+        .unwrap_or_default();
+    let custom_env = sidecar_env::load(app);
 
     // macOS app launch env often lacks user PATH entries.
     let mut path_segments: Vec<String> = Vec::new();
@@ -904,6 +1177,9 @@ async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
             push_unique(format!("{home}/.bun/bin"));
             push_unique(format!("{home}/.cargo/bin"));
             push_unique(format!("{home}/bin"));
+            for dir in sidecar_env::toolchain_dirs(home) {
+                push_unique(dir);
+            }
         }
     }
 
@@ -913,26 +1189,80 @@ async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
         }
     }
 
+    // User-configured entries come last, so they add tools without shadowing system ones.
+    for entry in &custom_env.path_entries {
+        push_unique(entry.clone());
+    }
+
+    dependencies::check(app, &path_segments);
     let augmented_path = path_segments.join(":");
 
-    for candidate in candidates {
+    for (candidate, source) in candidates {
         let port = match candidate {
             Some(p) => p,
             None => pick_unused_port()?,
         };
         let url = build_local_url(port);
 
-        let cmd = app
+        if candidate.is_some() && !is_port_free(port) {
+            match probe_port_occupant(port).await {
+                // A leftover server is loopback-only and unprotected, so never adopt it for LAN sharing.
+                // Named profiles cannot tell their own leftover from another profile's.
+                PortOccupant::OpenChamber { version }
+                    if lan_binding.is_none()
+                        && profiles::active().is_none()
+                        && version.as_deref().is_none_or(|v| v == app_version) =>
+                {
+                    // Likely left over from a previous run; reuse it rather than fight for the port.
+                    if let Some(state) = local_sidecar(app) {
+                        *state.url.lock().expect("sidecar url mutex") = Some(url.clone());
+                    }
+                    report_port_decision(
+                        app,
+                        SidecarPortDecision {
+                            url: url.clone(),
+                            port,
+                            source,
+                            preferred_port,
+                            adopted: true,
+                            skipped,
+                        },
+                    );
+                    lan::announce(app, None, port);
+                    return Ok(url);
+                }
+                PortOccupant::OpenChamber { version } => skipped.push(SkippedPort {
+                    port,
+                    reason: format!(
+                        "stale OpenChamber server (version {})",
+                        version.unwrap_or_default()
+                    ),
+                }),
+                PortOccupant::Other => skipped.push(SkippedPort {
+                    port,
+                    reason: "in use by another process".to_string(),
+                }),
+            }
+            log::warn!("[sidecar] port {port} unavailable, trying next candidate");
+            continue;
+        }
+
+        splash::set_stage(app, splash::BootStage::StartingServer);
+        let mut cmd = app
             .shell()
             .sidecar(SIDECAR_NAME)
             .map_err(|err| anyhow!("Failed to resolve sidecar '{SIDECAR_NAME}': {err}"))?
             .args(["--port", &port.to_string()])
-            .env("OPENCHAMBER_HOST", "127.0.0.1")
+            .env("OPENCHAMBER_HOST", bind_host.clone())
             .env("OPENCHAMBER_DIST_DIR", dist_dir.clone())
             .env("OPENCHAMBER_DESKTOP_NOTIFY", "true")
             .env("PATH", augmented_path.clone())
-            .env("NO_PROXY", no_proxy)
-            .env("no_proxy", no_proxy);
+            .envs(proxy_env.clone())
+            .envs(lan_env.clone())
+            .envs(custom_env.variables.clone());
+        if let Some(cwd) = custom_env.cwd.as_ref() {
+            cmd = cmd.current_dir(cwd);
+        }
 
         let (rx, child) = match cmd.spawn() {
             Ok(v) => v,
@@ -942,9 +1272,19 @@ async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
             }
         };
 
+        let generation = local_sidecar(app)
+            .map(|state| {
+                let mut guard = state.generation.lock().expect("sidecar generation mutex");
+                *guard = guard.wrapping_add(1).max(1);
+                *guard
+            })
+            .unwrap_or(0);
+
         let app_handle = app.clone();
         tauri::async_runtime::spawn(async move {
             let mut rx = rx;
+            let mut exit_code: Option<i32> = None;
+            let mut exit_signal: Option<i32> = None;
             while let Some(event) = rx.recv().await {
                 match event {
                     CommandEvent::Stdout(bytes) => {
@@ -955,8 +1295,14 @@ async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
                             {
                                 maybe_show_sidecar_notification(&app_handle, parsed);
                             }
+                        } else {
+                            record_sidecar_output(&app_handle, SidecarStream::Stdout, &line);
                         }
                     }
+                    CommandEvent::Stderr(bytes) => {
+                        let line = String::from_utf8_lossy(&bytes);
+                        record_sidecar_output(&app_handle, SidecarStream::Stderr, &line);
+                    }
                     CommandEvent::Error(error) => {
                         log::warn!("[sidecar] error: {error}");
                     }
@@ -966,24 +1312,60 @@ async fn spawn_local_server(app: &tauri::AppHandle) -> Result<String> {
                             payload.code,
                             payload.signal
                         );
+                        exit_code = payload.code;
+                        exit_signal = payload.signal;
                         break;
                     }
                     _ => {}
                 }
             }
+            handle_sidecar_exit(&app_handle, generation, exit_code, exit_signal);
         });
 
-        if let Some(state) = app.try_state::<SidecarState>() {
+        if let Some(state) = local_sidecar(app) {
             *state.child.lock().expect("sidecar mutex") = Some(child);
             *state.url.lock().expect("sidecar url mutex") = Some(url.clone());
         }
-
-        if !wait_for_health(&url).await {
-            kill_sidecar(app.clone());
+        startup::mark(app, startup::StartupPhase::SidecarSpawned);
+
+        splash::set_stage(app, splash::BootStage::WaitingForHealth);
+        let Some(latency) = wait_for_health(app, &url).await else {
+            let kill_app = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || kill_sidecar(kill_app)).await;
+            skipped.push(SkippedPort {
+                port,
+                reason: "sidecar failed health check".to_string(),
+            });
             continue;
+        };
+        startup::mark(app, startup::StartupPhase::SidecarHealthy);
+
+        if let Some(state) = local_sidecar(app) {
+            *state.started_at.lock().expect("sidecar started mutex") =
+                Some(std::time::Instant::now());
+            *state
+                .last_health_latency
+                .lock()
+                .expect("sidecar latency mutex") = Some(latency);
+            *state
+                .supervised_generation
+                .lock()
+                .expect("sidecar generation mutex") = generation;
         }
 
         let _ = write_desktop_local_port_to_disk(port);
+        report_port_decision(
+            app,
+            SidecarPortDecision {
+                url: url.clone(),
+                port,
+                source,
+                preferred_port,
+                adopted: false,
+                skipped,
+            },
+        );
+        lan::announce(app, lan_binding.as_ref(), port);
         return Ok(url);
     }
 
@@ -1031,6 +1413,7 @@ struct DesktopNotifyPayload {
     title: Option<String>,
     body: Option<String>,
     tag: Option<String>,
+    category: Option<String>,
 }
 
 #[tauri::command]
@@ -1042,8 +1425,14 @@ fn desktop_notify(
         title: None,
         body: None,
         tag: None,
+        category: None,
     });
 
+    let focused = window_registry::is_app_focused(&app);
+    if !notification_allowed(&app, payload.category.as_deref(), focused) {
+        return Ok(false);
+    }
+
     use tauri_plugin_notification::NotificationExt;
 
     let mut builder = app
@@ -1068,15 +1457,36 @@ fn desktop_notify(
         builder = builder.sound("Glass");
     }
 
-    builder.show().map(|_| true).map_err(|err| err.to_string())
+    builder.show().map_err(|err| err.to_string())?;
+    if !focused {
+        badge::increment_badge(&app);
+    }
+    Ok(true)
 }
 
 #[tauri::command]
 async fn desktop_check_for_updates(
     app: tauri::AppHandle,
     pending: tauri::State<'_, PendingUpdate>,
+    channel: Option<String>,
+) -> Result<DesktopUpdateInfo, String> {
+    if let Some(raw) = channel {
+        let requested = updates::UpdateChannel::parse(&raw)
+            .ok_or_else(|| format!("Unknown update channel: {raw}"))?;
+        updates::set_update_channel(&app, requested);
+    }
+    let channel = updates::update_channel(&app);
+
+    check_for_updates(&app, &pending, channel).await
+}
+
+/// Queries the channel endpoint and caches the result in `PendingUpdate`.
+async fn check_for_updates(
+    app: &tauri::AppHandle,
+    pending: &PendingUpdate,
+    channel: updates::UpdateChannel,
 ) -> Result<DesktopUpdateInfo, String> {
-    let updater = app.updater().map_err(|err| err.to_string())?;
+    let updater = updates::channel_updater(app, channel)?;
     let update = updater.check().await.map_err(|err| err.to_string())?;
 
     let current_version = app.package_info().version.to_string();
@@ -1085,25 +1495,32 @@ async fn desktop_check_for_updates(
         *pending.0.lock().expect("pending update mutex") = Some(update.clone());
         let mut body = update.body.clone();
         if is_placeholder_release_notes(&body) {
-            if let Some(notes) = fetch_changelog_notes(&current_version, &update.version).await {
+            if let Some(notes) =
+                changelog::notes_between(app, &current_version, &update.version).await
+            {
                 body = Some(notes);
             }
         }
         DesktopUpdateInfo {
             available: true,
+            channel,
             current_version,
             version: Some(update.version.clone()),
             body,
             date: update.date.map(|date| date.to_string()),
+            patch: update_patch::find_patch(app, &update)
+                .map(|patch| update_patch::patch_info(&patch)),
         }
     } else {
         *pending.0.lock().expect("pending update mutex") = None;
         DesktopUpdateInfo {
             available: false,
+            channel,
             current_version,
             version: None,
             body: None,
             date: None,
+            patch: None,
         }
     };
 
@@ -1113,45 +1530,23 @@ async fn desktop_check_for_updates(
 #[tauri::command]
 async fn desktop_download_and_install_update(
     app: tauri::AppHandle,
+    webview: tauri::Webview,
     pending: tauri::State<'_, PendingUpdate>,
 ) -> Result<(), String> {
+    crate::origin::require_local(&webview, "install updates")?;
     let Some(update) = pending.0.lock().expect("pending update mutex").take() else {
         return Err("No pending update".to_string());
     };
 
-    let mut downloaded: u64 = 0;
-    let mut total: Option<u64> = None;
-    let mut started = false;
-
-    update
-        .download_and_install(
-            |chunk_length, content_length| {
-                if !started {
-                    total = content_length;
-                    let _ = app.emit(
-                        "openchamber:update-progress",
-                        UpdateProgressEvent::Started { content_length },
-                    );
-                    started = true;
-                }
-
-                downloaded = downloaded.saturating_add(chunk_length as u64);
-                let _ = app.emit(
-                    "openchamber:update-progress",
-                    UpdateProgressEvent::Progress {
-                        chunk_length,
-                        downloaded,
-                        total,
-                    },
-                );
-            },
-            || {
-                let _ = app.emit("openchamber:update-progress", UpdateProgressEvent::Finished);
-            },
-        )
-        .await
-        .map_err(|err| err.to_string())?;
+    let snapshot_app = app.clone();
+    let _ = tauri::async_runtime::spawn_blocking(move || rollback::preserve_current(&snapshot_app))
+        .await;
 
+    let path = update_download::download_verified(&app, &update).await?;
+    let bytes = std::fs::read(&path).map_err(|err| err.to_string())?;
+    update_patch::store_base(&app, &update, &bytes);
+    update.install(bytes).map_err(|err| err.to_string())?;
+    let _ = std::fs::remove_file(&path);
     Ok(())
 }
 
@@ -1163,9 +1558,12 @@ fn desktop_restart(app: tauri::AppHandle) {
 fn create_main_window(app: &tauri::AppHandle, url: &str, local_origin: &str) -> Result<()> {
     let parsed = url::Url::parse(url).map_err(|err| anyhow!("Invalid URL: {err}"))?;
 
-    let home = std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).unwrap_or_default();
+    let home =
+        std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).unwrap_or_default();
     #[cfg(target_os = "macos")]
     fn macos_major_version() -> Option<u32> {
+        use std::process::Command;
+
         fn cmd_stdout(cmd: &str, args: &[&str]) -> Option<String> {
             let output = Command::new(cmd).args(args).output().ok()?;
             if !output.status.success() {
@@ -1176,11 +1574,16 @@ fn create_main_window(app: &tauri::AppHandle, url: &str, local_origin: &str) ->
 
         // Use marketing version (sw_vers), but map legacy 10.x to minor (10.15 -> 15).
         // This matches WebKit UA fallback logic in the UI.
-        if let Some(raw) = cmd_stdout("/usr/bin/sw_vers", &["-productVersion"]).or_else(|| cmd_stdout("sw_vers", &["-productVersion"])) {
+        if let Some(raw) = cmd_stdout("/usr/bin/sw_vers", &["-productVersion"])
+            .or_else(|| cmd_stdout("sw_vers", &["-productVersion"]))
+        {
             let raw = raw.trim();
             let mut parts = raw.split('.');
             let major = parts.next().and_then(|v| v.parse::<u32>().ok())?;
-            let minor = parts.next().and_then(|v| v.parse::<u32>().ok()).unwrap_or(0);
+            let minor = parts
+                .next()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(0);
             return Some(if major == 10 { minor } else { major });
         }
 
@@ -1226,51 +1629,257 @@ fn create_main_window(app: &tauri::AppHandle, url: &str, local_origin: &str) ->
         *state.script.lock().expect("desktop ui injection mutex") = Some(init_script.clone());
     }
 
-    let mut builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(parsed))
-        .title("OpenChamber")
+    // After a successful retry the window already exists, showing the offline page;
+    // the injection script is applied by `on_page_load` for navigations.
+    if let Some(window) = app.get_webview_window("main") {
+        window.navigate(parsed)?;
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    let builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(parsed))
+        .title(profiles::window_title())
         .inner_size(1280.0, 800.0)
         .decorations(true)
         .visible(false)
-        .initialization_script(&init_script)
-        ;
+        .incognito(safe_mode::is_enabled())
+        .initialization_script(&init_script);
+    let builder = match portable::webview_data_dir() {
+        Some(dir) => builder.data_directory(dir),
+        None => builder,
+    };
 
     #[cfg(target_os = "macos")]
-    {
-        builder = builder
+    let builder = {
+        window_tabs::apply(app, builder)
             .hidden_title(true)
             .title_bar_style(tauri::TitleBarStyle::Overlay)
-            .traffic_light_position(tauri::Position::Logical(tauri::LogicalPosition { x: 17.0, y: 26.0 }));
-    }
+            .traffic_light_position(tauri::Position::Logical(tauri::LogicalPosition {
+                x: 17.0,
+                y: 26.0,
+            }))
+    };
 
     let window = builder.build()?;
+    window_state::restore(&window);
+    zoom::restore_zoom(&window);
+    appearance::restore_theme(&window);
+    spellcheck::restore_spellcheck(&window);
 
     let _ = window.show();
-    let _ = window.set_focus();
+    if launch::take_start_minimized(app) {
+        let _ = window.minimize();
+    } else {
+        let _ = window.set_focus();
+    }
+    splash::close(app);
 
     Ok(())
 }
 
+/// Starts the local server and opens the main window on the selected host. On
+/// failure the main window shows the offline error page instead, whose Retry
+/// button runs this again.
+async fn boot(handle: tauri::AppHandle) {
+    let _ = proxy::refresh(&handle).await;
+    // Always ensure local server is running for escape hatch.
+    let local_url = if cfg!(debug_assertions) {
+        let dev_url = "http://127.0.0.1:3001";
+        if wait_for_health(&handle, dev_url).await.is_some() {
+            dev_url.to_string()
+        } else {
+            match spawn_local_server(&handle).await {
+                Ok(local) => local,
+                Err(err) => {
+                    log::error!("[desktop] failed to start local server: {err}");
+                    boot_error::show(&handle, &err.to_string());
+                    return;
+                }
+            }
+        }
+    } else {
+        match spawn_local_server(&handle).await {
+            Ok(local) => local,
+            Err(err) => {
+                log::error!("[desktop] failed to start local server: {err}");
+                boot_error::show(&handle, &err.to_string());
+                return;
+            }
+        }
+    };
+
+    // Ensure local URL is always available to desktop commands,
+    // even when we are using the Vite dev server (no sidecar child).
+    if let Some(state) = local_sidecar(&handle) {
+        *state.url.lock().expect("sidecar url mutex") = Some(local_url.clone());
+    }
+
+    // Selected host: --server-url, then env override, then desktop default host, else local.
+    let env_target = launch::initial_server_url(&handle).or_else(|| {
+        std::env::var("OPENCHAMBER_SERVER_URL")
+            .ok()
+            .and_then(|raw| normalize_server_url(&raw))
+    });
+
+    let mut initial_url = env_target.unwrap_or_else(|| local_url.clone());
+
+    if initial_url == local_url {
+        let cfg = read_desktop_hosts_config_from_disk();
+        if let Some(default_id) = cfg.default_host_id {
+            if default_id == LOCAL_HOST_ID {
+                initial_url = local_url.clone();
+            } else if let Some(host) = cfg.hosts.into_iter().find(|h| h.id == default_id) {
+                initial_url = host.url;
+            }
+        }
+    }
+
+    splash::set_stage(&handle, splash::BootStage::LoadingUi);
+    match open_server_url(&handle, &initial_url).await {
+        Ok(()) => startup::mark(&handle, startup::StartupPhase::WindowCreated),
+        Err(err) => {
+            log::error!("[desktop] failed to create window: {err}");
+            boot_error::show(&handle, &err.to_string());
+        }
+    }
+}
+
+/// Recreates the main window on the local server once every window was closed,
+/// so a menu or tray action has somewhere to go.
+fn reopen_main_window(app: &tauri::AppHandle) {
+    // Until the first window exists, boot is still creating it.
+    if !startup::reached(app, startup::StartupPhase::WindowCreated) {
+        return;
+    }
+    let Some(url) =
+        local_sidecar(app).and_then(|state| state.url.lock().expect("sidecar url mutex").clone())
+    else {
+        window_registry::discard_pending_actions(app);
+        return;
+    };
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = open_server_url(&handle, &url).await {
+            log::warn!("[window-registry] failed to reopen the main window: {err}");
+            window_registry::discard_pending_actions(&handle);
+        }
+    });
+}
+
+/// Checks the server at `url` for API compatibility and opens it in the main
+/// window, creating the window if needed.
+async fn open_server_url(handle: &tauri::AppHandle, initial_url: &str) -> Result<()> {
+    let local_url = local_sidecar(handle)
+        .and_then(|state| state.url.lock().expect("sidecar url mutex").clone())
+        .unwrap_or_default();
+    let local_origin = url::Url::parse(&local_url)
+        .ok()
+        .map(|u| u.origin().ascii_serialization())
+        .unwrap_or_else(|| local_url.clone());
+
+    let mut client_builder = proxy::load(handle).apply(reqwest::Client::builder());
+    if let Some(tls) = servers::tls_options_for_url(handle, initial_url) {
+        client_builder = match tls.apply(client_builder) {
+            Ok(builder) => builder,
+            Err(err) => {
+                log::warn!("[compat] ignoring TLS options for version check: {err}");
+                proxy::load(handle).apply(reqwest::Client::builder())
+            }
+        };
+    }
+    if let Ok(client) = client_builder.build() {
+        if let compat::Compatibility::UpdateRequested =
+            compat::check(handle, &client, initial_url, None).await
+        {
+            compat::request_update_check(handle);
+        }
+    }
+
+    create_main_window(handle, initial_url, &local_origin)
+}
+
 fn main() {
-    let log_builder = tauri_plugin_log::Builder::default()
-        .level(log::LevelFilter::Info)
-        .clear_targets()
-        .targets([
-            tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout),
-            tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Webview),
-        ]);
-
-    let builder = tauri::Builder::default()
+    let started = std::time::Instant::now();
+    portable::init();
+    safe_mode::init();
+    if let Err(err) = profiles::init() {
+        eprintln!("openchamber: {err}");
+        std::process::exit(2);
+    }
+    match profiles::acquire_instance_lock() {
+        Ok(true) => {}
+        Ok(false) => {
+            eprintln!(
+                "openchamber: profile {:?} is already running",
+                profiles::active().unwrap_or_default()
+            );
+            return;
+        }
+        Err(err) => eprintln!("openchamber: failed to lock profile: {err}"),
+    }
+
+    let mut builder = tauri::Builder::default();
+    if profiles::active().is_none() {
+        // Must be registered first so a second launch (e.g. from a deep link) is
+        // forwarded to the running instance before any other plugin starts.
+        builder = builder.plugin(tauri_plugin_single_instance::init(
+            launch::handle_second_instance,
+        ));
+    }
+    let builder = builder
         .manage(SidecarState::default())
+        .manage(SidecarLogState::default())
         .manage(DesktopUiInjectionState::default())
-        .manage(WindowFocusState::default())
-        .manage(MenuRuntimeState::default())
+        .manage(window_registry::WindowRegistry::default())
+        .manage(settings::SettingsStore::default())
+        .manage(menu::MenuContextState::default())
+        .manage(quit_guard::QuitGuardState::default())
+        .manage(keep_awake::KeepAwakeState::default())
+        .manage(lan::LanAccessState::default())
+        .manage(splash::SplashState::default())
+        .manage(titlebar::TitlebarState::default())
+        .manage(compat::CompatState::default())
+        .manage(context_menu::ContextMenuState::default())
+        .manage(find::FindState::default())
+        .manage(metrics::MetricsState::default())
+        .manage(idle::IdleState::default())
+        .manage(startup::StartupState::new(started))
+        .manage(tray::BackgroundModeState::from_disk())
         .manage(PendingUpdate(Mutex::new(None)))
+        .manage(update_download::UpdateDownloadState::default())
+        .manage(secrets::SecretsState::default())
+        .manage(recents::RecentsState::default())
+        .manage(trust::TrustState::default())
+        .manage(path_policy::PathPolicyState::default())
+        .manage(audit::AuditState::default())
+        .manage(auth::AuthState::default())
+        .manage(tunnel::TunnelState::default())
+        .manage(git::GitStatusWatchState::default())
+        .manage(watcher::WatcherState::default())
+        .manage(search::SearchState::default())
+        .manage(file_index::FileIndexState::default())
+        .manage(pty::PtyState::default())
+        .manage(shortcuts::GlobalShortcutsState::default())
+        .manage(badge::BadgeState::default())
+        .manage(notifications::NotificationPrefsState::from_disk())
+        .manage(deeplink::DeepLinkState::default())
+        .manage(backup::BackupState::default())
+        .manage(telemetry::TelemetryState::default())
+        .manage(proxy::ProxyState::default())
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcuts::handle_shortcut)
+                .build(),
+        )
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .plugin(log_builder.build())
-        .on_page_load(|window, _payload| {
+        .plugin(logging::plugin_builder().build())
+        .on_page_load(|window, payload| {
             if let Some(state) = window.app_handle().try_state::<DesktopUiInjectionState>() {
                 if let Ok(guard) = state.script.lock() {
                     if let Some(script) = guard.as_ref() {
@@ -1278,220 +1887,260 @@ fn main() {
                     }
                 }
             }
-        })
-        .menu(|app| {
-            #[cfg(target_os = "macos")]
-            {
-                build_macos_menu(app)
+            if window.label() == "main" {
+                servers::inject_auth(window, payload.url());
             }
-
-            #[cfg(not(target_os = "macos"))]
+            if window.label() == "main" && payload.event() == tauri::webview::PageLoadEvent::Started
             {
-                tauri::menu::Menu::default(app)
+                launch::frontend_unloaded(window.app_handle());
             }
-        })
-        .on_menu_event(|app, event| {
-            #[cfg(target_os = "macos")]
+            if window.label() == "main"
+                && payload.event() == tauri::webview::PageLoadEvent::Finished
             {
-                let id = event.id().as_ref();
-
-                log::info!("[menu] click id={}", id);
-
-                #[cfg(debug_assertions)]
-                {
-                    let msg = serde_json::to_string(id).unwrap_or_else(|_| "\"(unserializable)\"".into());
-                    eval_in_main_window(app, &format!("console.log('[menu] id=', {});", msg));
-                }
-
-                if id == MENU_ITEM_CHECK_FOR_UPDATES_ID {
-                    dispatch_check_for_updates(app);
-                    return;
-                }
-
-                if id == MENU_ITEM_REPORT_BUG_ID {
-                    use tauri_plugin_shell::ShellExt;
-                    #[allow(deprecated)]
-                    {
-                        let _ = app.shell().open(GITHUB_BUG_REPORT_URL, None);
-                    }
-                    return;
-                }
-
-                if id == MENU_ITEM_REQUEST_FEATURE_ID {
-                    use tauri_plugin_shell::ShellExt;
-                    #[allow(deprecated)]
-                    {
-                        let _ = app.shell().open(GITHUB_FEATURE_REQUEST_URL, None);
-                    }
-                    return;
-                }
-
-                if id == MENU_ITEM_JOIN_DISCORD_ID {
-                    use tauri_plugin_shell::ShellExt;
-                    #[allow(deprecated)]
-                    {
-                        let _ = app.shell().open(DISCORD_INVITE_URL, None);
-                    }
-                    return;
-                }
-
-                if id == MENU_ITEM_ABOUT_ID {
-                    dispatch_menu_action(app, "about");
-                    return;
-                }
-                if id == MENU_ITEM_SETTINGS_ID {
-                    dispatch_menu_action(app, "settings");
-                    return;
-                }
-                if id == MENU_ITEM_COMMAND_PALETTE_ID {
-                    dispatch_menu_action(app, "command-palette");
-                    return;
-                }
-
-                if id == MENU_ITEM_NEW_SESSION_ID {
-                    dispatch_menu_action(app, "new-session");
-                    return;
-                }
-                if id == MENU_ITEM_WORKTREE_CREATOR_ID {
-                    dispatch_menu_action(app, "new-worktree-session");
-                    return;
-                }
-                if id == MENU_ITEM_CHANGE_WORKSPACE_ID {
-                    dispatch_menu_action(app, "change-workspace");
-                    return;
-                }
-
-                if id == MENU_ITEM_OPEN_GIT_TAB_ID {
-                    dispatch_menu_action(app, "open-git-tab");
-                    return;
-                }
-                if id == MENU_ITEM_OPEN_DIFF_TAB_ID {
-                    dispatch_menu_action(app, "open-diff-tab");
-                    return;
-                }
-
-                if id == MENU_ITEM_OPEN_FILES_TAB_ID {
-                    dispatch_menu_action(app, "open-files-tab");
-                    return;
-                }
-                if id == MENU_ITEM_OPEN_TERMINAL_TAB_ID {
-                    dispatch_menu_action(app, "open-terminal-tab");
-                    return;
-                }
-
-                if id == MENU_ITEM_THEME_LIGHT_ID {
-                    dispatch_menu_action(app, "theme-light");
-                    return;
-                }
-                if id == MENU_ITEM_THEME_DARK_ID {
-                    dispatch_menu_action(app, "theme-dark");
-                    return;
-                }
-                if id == MENU_ITEM_THEME_SYSTEM_ID {
-                    dispatch_menu_action(app, "theme-system");
-                    return;
-                }
-
-                if id == MENU_ITEM_TOGGLE_SIDEBAR_ID {
-                    dispatch_menu_action(app, "toggle-sidebar");
-                    return;
-                }
-                if id == MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID {
-                    dispatch_menu_action(app, "toggle-memory-debug");
-                    return;
-                }
-
-                if id == MENU_ITEM_HELP_DIALOG_ID {
-                    dispatch_menu_action(app, "help-dialog");
-                    return;
-                }
-                if id == MENU_ITEM_DOWNLOAD_LOGS_ID {
-                    dispatch_menu_action(app, "download-logs");
+                lan::inject_session(window, payload.url());
+                compat::flush_pending_update_check(window.app_handle());
+                deeplink::flush_pending_deep_links(window.app_handle());
+                window_registry::flush_pending_actions(window.app_handle());
+            }
+            if payload.event() == tauri::webview::PageLoadEvent::Finished {
+                spellcheck::inject(window);
+                if window.label() == "main" {
+                    startup::mark(window.app_handle(), startup::StartupPhase::FirstPageLoaded);
                 }
             }
         })
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::Focused(focused) = event {
+        .menu(menu::build_app_menu)
+        .on_menu_event(|app, event| menu::handle_menu_event(app, event.id().as_ref()))
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::Focused(focused) => {
                 let app = window.app_handle();
-                if let Some(state) = app.try_state::<WindowFocusState>() {
-                    *state.focused.lock().expect("focus mutex") = *focused;
+                window_registry::handle_focus_changed(app, window.label(), *focused);
+                if *focused {
+                    badge::set_badge(app, 0);
+                }
+            }
+            tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                window_state::schedule_save(window);
+            }
+            tauri::WindowEvent::DragDrop(drag_drop) => {
+                file_drop::handle_drag_drop(window, drag_drop);
+            }
+            tauri::WindowEvent::ThemeChanged(theme) => {
+                appearance::handle_theme_changed(window, *theme);
+            }
+            tauri::WindowEvent::Destroyed => {
+                settings::unsubscribe(window.app_handle(), window.label());
+                metrics::unsubscribe(window.app_handle(), window.label());
+                titlebar::forget(window.app_handle(), window.label());
+                window_registry::forget(window.app_handle(), window.label());
+            }
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                window_state::save_now(window);
+                if !tray::hide_to_tray_on_close(window, api) {
+                    quit_guard::intercept_close(window, api);
                 }
             }
+            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             desktop_notify,
             desktop_check_for_updates,
             desktop_download_and_install_update,
+            update_download::desktop_download_update,
+            update_download::desktop_install_update,
+            update_download::desktop_cancel_update_download,
+            updates::desktop_set_updater_settings,
             desktop_restart,
-            desktop_set_auto_worktree_menu,
+            desktop_server_info,
+            menu::desktop_set_auto_worktree_menu,
+            menu::desktop_set_theme_menu_state,
+            menu::desktop_set_menu_context,
+            i18n::desktop_set_locale,
+            quit_guard::desktop_set_busy_state,
+            quit_guard::desktop_quit_check_reply,
+            keep_awake::desktop_prevent_sleep,
+            lan::desktop_enable_lan_access,
+            proxy::desktop_set_proxy,
+            sidecar_env::desktop_set_sidecar_env,
+            power::desktop_get_power_state,
+            settings::desktop_settings_get,
+            settings::desktop_settings_set,
+            settings::desktop_settings_subscribe,
+            logging::desktop_get_log_config,
+            logging::desktop_set_log_level,
             desktop_hosts_get,
             desktop_hosts_set,
             desktop_host_probe,
+            tray::desktop_get_background_mode,
+            tray::desktop_set_background_mode,
+            progress::desktop_set_progress,
+            badge::desktop_set_badge,
+            badge::desktop_clear_badge,
+            notifications::desktop_get_notification_prefs,
+            notifications::desktop_set_notification_prefs,
+            crash::desktop_list_crash_reports,
+            crash::desktop_delete_crash_report,
+            deeplink::desktop_take_pending_deep_links,
+            launch::desktop_frontend_ready,
+            diagnostics::desktop_export_diagnostics,
+            audit::desktop_get_audit_log,
+            auth::desktop_authenticate,
+            auth::desktop_set_os_auth,
+            boot_error::desktop_retry_local_server,
+            boot_error::desktop_open_diagnostics,
+            screenshot::desktop_capture_window,
+            zoom::desktop_set_zoom,
+            mini_window::desktop_open_mini_window,
+            mini_window::desktop_set_always_on_top,
+            titlebar::desktop_set_window_title,
+            titlebar::desktop_set_represented_file,
+            titlebar::desktop_set_document_edited,
+            context_menu::desktop_show_context_menu,
+            find::desktop_find_in_page,
+            find::desktop_find_next,
+            find::desktop_find_previous,
+            find::desktop_find_stop,
+            spellcheck::desktop_set_spellcheck,
+            pdf_export::desktop_print_to_pdf,
+            share::desktop_share,
+            metrics::desktop_get_process_metrics,
+            metrics::desktop_subscribe_process_metrics,
+            metrics::desktop_unsubscribe_process_metrics,
+            idle::desktop_get_idle_state,
+            startup::desktop_get_startup_report,
+            reload::desktop_reload,
+            devtools::desktop_toggle_devtools,
+            devtools::desktop_set_devtools_enabled,
+            appearance::desktop_get_os_theme,
+            git::desktop_git_status,
+            git::desktop_git_watch_status,
+            git::desktop_git_stage,
+            git::desktop_git_unstage,
+            git::desktop_git_commit,
+            git_diff::desktop_git_diff,
+            git_history::desktop_git_log,
+            git_history::desktop_git_blame,
+            git_signing::desktop_git_signing_status,
+            git_transfer::desktop_git_fetch,
+            git_transfer::desktop_git_pull,
+            git_transfer::desktop_git_push,
+            git::desktop_git_branches,
+            git::desktop_git_create_branch,
+            git::desktop_git_checkout,
+            git::desktop_git_delete_branch,
+            git::desktop_git_stash_list,
+            git::desktop_git_stash_save,
+            git::desktop_git_stash_apply,
+            git::desktop_git_stash_pop,
+            git::desktop_git_stash_drop,
+            git::desktop_worktree_create,
+            git::desktop_worktree_list,
+            git::desktop_worktree_remove,
+            git::desktop_worktree_prune,
+            git::desktop_pick_workspace,
+            pty::desktop_pty_spawn,
+            pty::desktop_pty_write,
+            pty::desktop_pty_resize,
+            pty::desktop_pty_kill,
+            pty::desktop_pty_scrollback,
+            shortcuts::desktop_shortcut_list,
+            shortcuts::desktop_shortcut_register,
+            shortcuts::desktop_shortcut_unregister,
+            secrets::desktop_secret_set,
+            secrets::desktop_secret_get,
+            secrets::desktop_secret_delete,
+            secrets::desktop_secret_list,
+            editor::desktop_detect_editors,
+            editor::desktop_open_in_editor,
+            editor::desktop_set_editor_command,
+            recents::desktop_recent_workspaces,
+            recents::desktop_add_recent_workspace,
+            recents::desktop_pin_recent_workspace,
+            recents::desktop_clear_recents,
+            trust::desktop_get_trust,
+            trust::desktop_set_trust,
+            clipboard::desktop_clipboard_write_text,
+            clipboard::desktop_clipboard_read_text,
+            clipboard::desktop_clipboard_write_image,
+            clipboard::desktop_clipboard_read_image,
+            reveal::desktop_reveal_path,
+            reveal::desktop_open_path,
+            servers::desktop_servers_list,
+            servers::desktop_server_add,
+            servers::desktop_server_set_credentials,
+            servers::desktop_server_remove,
+            servers::desktop_connect_server,
+            tunnel::desktop_tunnel_open,
+            tunnel::desktop_tunnel_close,
+            watcher::desktop_watch_path,
+            watcher::desktop_unwatch_path,
+            workspace_stats::desktop_workspace_stats,
+            search::desktop_search,
+            search::desktop_search_cancel,
+            file_index::desktop_quick_open,
+            fs_ops::desktop_fs_trash,
+            fs_ops::desktop_fs_rename,
+            fs_ops::desktop_fs_create,
+            file_preview::desktop_read_file_preview,
+            archive::desktop_archive_extract,
+            archive::desktop_archive_create,
+            session_export::desktop_export_session,
+            session_export::desktop_import_session,
+            backup::desktop_backup_now,
+            backup::desktop_backup_list,
+            backup::desktop_restore_backup,
+            settings_sync::desktop_export_settings,
+            settings_sync::desktop_import_settings,
+            telemetry::desktop_telemetry_preview,
+            telemetry::desktop_set_telemetry,
+            changelog::desktop_get_changelog,
+            changelog::desktop_open_whats_new,
+            rollback::desktop_rollback_info,
+            rollback::desktop_rollback_update,
         ])
         .setup(|app| {
+            startup::mark(app.handle(), startup::StartupPhase::Setup);
+            if safe_mode::is_enabled() {
+                log::warn!(
+                    "[safe-mode] starting with a temporary webview profile and default settings"
+                );
+            }
+            crash::install_panic_hook(app.handle());
+            #[cfg(target_os = "macos")]
+            window_tabs::configure(app.handle());
+            crash::prompt_for_unseen_crash(app.handle());
+            if let Err(err) = tray::setup_tray(app.handle()) {
+                log::warn!("[tray] failed to create tray icon: {err}");
+            }
+            deeplink::setup_deep_links(app.handle());
+            shortcuts::register_saved_shortcuts(app.handle());
             let handle = app.handle().clone();
-            tauri::async_runtime::spawn(async move {
-                // Always ensure local server is running for escape hatch.
-                let local_url = if cfg!(debug_assertions) {
-                    let dev_url = "http://127.0.0.1:3001";
-                    if wait_for_health(dev_url).await {
-                        dev_url.to_string()
-                    } else {
-                        match spawn_local_server(&handle).await {
-                            Ok(local) => local,
-                            Err(err) => {
-                                log::error!("[desktop] failed to start local server: {err}");
-                                return;
-                            }
-                        }
-                    }
-                } else {
-                    match spawn_local_server(&handle).await {
-                        Ok(local) => local,
-                        Err(err) => {
-                            log::error!("[desktop] failed to start local server: {err}");
-                            return;
-                        }
-                    }
-                };
-
-                // Ensure local URL is always available to desktop commands,
-                // even when we are using the Vite dev server (no sidecar child).
-                if let Some(state) = handle.try_state::<SidecarState>() {
-                    *state.url.lock().expect("sidecar url mutex") = Some(local_url.clone());
-                }
-
-                let local_origin = url::Url::parse(&local_url)
-                    .ok()
-                    .map(|u| u.origin().ascii_serialization())
-                    .unwrap_or_else(|| local_url.clone());
-
-                // Selected host: env override first, then desktop default host, else local.
-                let env_target = std::env::var("OPENCHAMBER_SERVER_URL")
-                    .ok()
-                    .and_then(|raw| normalize_server_url(&raw));
-
-                let mut initial_url = env_target.unwrap_or_else(|| local_url.clone());
-
-                if initial_url == local_url {
-                    let cfg = read_desktop_hosts_config_from_disk();
-                    if let Some(default_id) = cfg.default_host_id {
-                        if default_id == LOCAL_HOST_ID {
-                            initial_url = local_url.clone();
-                        } else if let Some(host) = cfg.hosts.into_iter().find(|h| h.id == default_id) {
-                            initial_url = host.url;
-                        }
-                    }
-                }
-
-                if let Err(err) = create_main_window(&handle, &initial_url, &local_origin) {
-                    log::error!("[desktop] failed to create window: {err}");
-                }
-            });
+            window_registry::set_reopen_hook(app.handle(), move || reopen_main_window(&handle));
+            updates::spawn_background_update_checks(app.handle().clone());
+            git::spawn_git_status_watcher(app.handle().clone());
+            git::spawn_worktree_maintenance(app.handle().clone());
+            health::spawn_health_monitor(app.handle().clone());
+            watchdog::spawn_resource_watchdog(app.handle().clone());
+            idle::spawn_idle_monitor(app.handle().clone());
+            backup::spawn_backup_scheduler(app.handle().clone());
+            telemetry::record_startup(app.handle());
+            changelog::check_after_update(app.handle());
+            telemetry::spawn_uploader(app.handle().clone());
+            power::spawn_power_monitor(app.handle().clone());
+            app.manage(window_state::WindowStateStore::load(app.handle()));
+            app.manage(servers::ServerProfilesState::load(app.handle()));
+            // Parsed here rather than in `main` so argument warnings reach the log.
+            let cwd = env::current_dir().unwrap_or_default();
+            app.manage(launch::LaunchState::new(launch::parse(
+                env::args().skip(1),
+                &cwd,
+            )));
+            splash::show(app.handle());
+
+            tauri::async_runtime::spawn(boot(app.handle().clone()));
 
             Ok(())
-        })
-        ;
+        });
 
     let app = builder
         .build(tauri::generate_context!())
@@ -1499,12 +2148,27 @@ fn main() {
 
     app.run(|app_handle, event| {
         match event {
-            tauri::RunEvent::ExitRequested { .. } => {
-                // Best-effort cleanup; never block shutdown.
-                kill_sidecar(app_handle.clone());
+            // Held back while the quit guard asks for confirmation.
+            tauri::RunEvent::ExitRequested { code, api, .. }
+                if !quit_guard::intercept_exit(app_handle, code, &api) =>
+            {
+                // Best-effort cleanup on the event loop, so the wait is kept short.
+                kill_sidecar_within(app_handle.clone(), EXIT_SIDECAR_SHUTDOWN_GRACE);
             }
             tauri::RunEvent::Exit => {
-                kill_sidecar(app_handle.clone());
+                kill_sidecar_within(app_handle.clone(), EXIT_SIDECAR_SHUTDOWN_GRACE);
+                tunnel::close_all_tunnels(app_handle);
+                pty::kill_all_ptys(app_handle);
+                keep_awake::release(app_handle);
+                update_download::install_deferred_update(app_handle);
+            }
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Reopen { .. } => {
+                tray::show_main_window(app_handle);
+            }
+            #[cfg(target_os = "macos")]
+            tauri::RunEvent::Opened { urls } => {
+                launch::handle_opened_urls(app_handle, &urls);
             }
             _ => {}
         }
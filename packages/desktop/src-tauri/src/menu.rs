@@ -0,0 +1,1055 @@
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::menu::{
+    CheckMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu, HELP_SUBMENU_ID,
+    WINDOW_SUBMENU_ID,
+};
+use tauri::Manager;
+use tauri_plugin_shell::ShellExt;
+
+use crate::appearance::{self, ThemePreference};
+use crate::updates::{set_update_channel, update_channel, UpdateChannel};
+#[cfg(target_os = "macos")]
+use crate::window_tabs;
+use crate::{
+    changelog, context_menu,
+    events::{self, DesktopEvent, MenuAction},
+    find, i18n, pdf_export, recents, reload, rollback, settings, tray, window_registry, zoom,
+};
+
+pub(crate) const MENU_ITEM_ABOUT_ID: &str = "menu_about";
+pub(crate) const MENU_ITEM_CHECK_FOR_UPDATES_ID: &str = "menu_check_for_updates";
+const MENU_ITEM_ROLLBACK_UPDATE_ID: &str = "menu_rollback_update";
+pub(crate) const MENU_ITEM_SETTINGS_ID: &str = "menu_settings";
+pub(crate) const MENU_ITEM_COMMAND_PALETTE_ID: &str = "menu_command_palette";
+pub(crate) const MENU_ITEM_NEW_SESSION_ID: &str = "menu_new_session";
+pub(crate) const MENU_ITEM_WORKTREE_CREATOR_ID: &str = "menu_worktree_creator";
+pub(crate) const MENU_ITEM_CHANGE_WORKSPACE_ID: &str = "menu_change_workspace";
+pub(crate) const MENU_OPEN_RECENT_SUBMENU_ID: &str = "menu_open_recent";
+pub(crate) const MENU_ITEM_CLEAR_RECENTS_ID: &str = "menu_clear_recents";
+pub(crate) const MENU_ITEM_EXPORT_PDF_ID: &str = "menu_export_pdf";
+/// Open Recent entries are `menu_recent_workspace:<index>`.
+pub(crate) const RECENT_WORKSPACE_MENU_PREFIX: &str = "menu_recent_workspace:";
+pub(crate) const MENU_ITEM_OPEN_GIT_TAB_ID: &str = "menu_open_git_tab";
+pub(crate) const MENU_ITEM_OPEN_DIFF_TAB_ID: &str = "menu_open_diff_tab";
+pub(crate) const MENU_ITEM_OPEN_FILES_TAB_ID: &str = "menu_open_files_tab";
+pub(crate) const MENU_ITEM_OPEN_TERMINAL_TAB_ID: &str = "menu_open_terminal_tab";
+pub(crate) const MENU_ITEM_THEME_LIGHT_ID: &str = "menu_theme_light";
+pub(crate) const MENU_ITEM_THEME_DARK_ID: &str = "menu_theme_dark";
+pub(crate) const MENU_ITEM_THEME_SYSTEM_ID: &str = "menu_theme_system";
+pub(crate) const MENU_ITEM_ZOOM_IN_ID: &str = "menu_zoom_in";
+pub(crate) const MENU_ITEM_ZOOM_OUT_ID: &str = "menu_zoom_out";
+pub(crate) const MENU_ITEM_ZOOM_RESET_ID: &str = "menu_zoom_reset";
+pub(crate) const MENU_ITEM_TOGGLE_SIDEBAR_ID: &str = "menu_toggle_sidebar";
+pub(crate) const MENU_ITEM_FIND_ID: &str = "menu_find";
+pub(crate) const MENU_ITEM_FIND_NEXT_ID: &str = "menu_find_next";
+pub(crate) const MENU_ITEM_FIND_PREVIOUS_ID: &str = "menu_find_previous";
+pub(crate) const MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID: &str = "menu_toggle_memory_debug";
+pub(crate) const MENU_ITEM_RELOAD_ID: &str = "menu_reload";
+pub(crate) const MENU_ITEM_FORCE_RELOAD_ID: &str = "menu_force_reload";
+pub(crate) const MENU_ITEM_HELP_DIALOG_ID: &str = "menu_help_dialog";
+pub(crate) const MENU_ITEM_WHATS_NEW_ID: &str = "menu_whats_new";
+pub(crate) const MENU_ITEM_DOWNLOAD_LOGS_ID: &str = "menu_download_logs";
+pub(crate) const MENU_ITEM_REPORT_BUG_ID: &str = "menu_report_bug";
+pub(crate) const MENU_ITEM_REQUEST_FEATURE_ID: &str = "menu_request_feature";
+pub(crate) const MENU_ITEM_JOIN_DISCORD_ID: &str = "menu_join_discord";
+pub(crate) const MENU_ITEM_UPDATE_CHANNEL_STABLE_ID: &str = "menu_update_channel_stable";
+pub(crate) const MENU_ITEM_UPDATE_CHANNEL_BETA_ID: &str = "menu_update_channel_beta";
+pub(crate) const MENU_ITEM_UPDATE_CHANNEL_NIGHTLY_ID: &str = "menu_update_channel_nightly";
+
+// Window management items. macOS uses the predefined items instead, which
+// are unsupported on Linux (and partially on Windows).
+#[cfg(not(target_os = "macos"))]
+pub(crate) const MENU_ITEM_MINIMIZE_ID: &str = "menu_minimize";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const MENU_ITEM_MAXIMIZE_ID: &str = "menu_maximize";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const MENU_ITEM_CLOSE_WINDOW_ID: &str = "menu_close_window";
+#[cfg(not(target_os = "macos"))]
+pub(crate) const MENU_ITEM_QUIT_ID: &str = "menu_quit";
+// Native window tabs only exist on macOS.
+#[cfg(target_os = "macos")]
+pub(crate) const MENU_ITEM_SHOW_NEXT_TAB_ID: &str = "menu_show_next_tab";
+#[cfg(target_os = "macos")]
+pub(crate) const MENU_ITEM_SHOW_PREVIOUS_TAB_ID: &str = "menu_show_previous_tab";
+
+const GITHUB_BUG_REPORT_URL: &str =
+    "https://github.com/btriapitsyn/openchamber/issues/new?template=bug_report.yml";
+const GITHUB_FEATURE_REQUEST_URL: &str =
+    "https://github.com/btriapitsyn/openchamber/issues/new?template=feature_request.yml";
+const DISCORD_INVITE_URL: &str = "https://discord.gg/ZYRSdnwwKA";
+
+fn update_channel_menu_id(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => MENU_ITEM_UPDATE_CHANNEL_STABLE_ID,
+        UpdateChannel::Beta => MENU_ITEM_UPDATE_CHANNEL_BETA_ID,
+        UpdateChannel::Nightly => MENU_ITEM_UPDATE_CHANNEL_NIGHTLY_ID,
+    }
+}
+
+fn theme_menu_id(preference: ThemePreference) -> &'static str {
+    match preference {
+        ThemePreference::Light => MENU_ITEM_THEME_LIGHT_ID,
+        ThemePreference::Dark => MENU_ITEM_THEME_DARK_ID,
+        ThemePreference::System => MENU_ITEM_THEME_SYSTEM_ID,
+    }
+}
+
+/// Recursive lookup; `Menu::get` only searches top-level items.
+pub(crate) fn find_menu_item<R: tauri::Runtime>(
+    items: Vec<MenuItemKind<R>>,
+    id: &str,
+) -> Option<MenuItemKind<R>> {
+    for item in items {
+        if item.id().as_ref() == id {
+            return Some(item);
+        }
+        if let MenuItemKind::Submenu(submenu) = &item {
+            if let Some(found) = find_menu_item(submenu.items().unwrap_or_default(), id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn find_app_menu_item<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    id: &str,
+) -> Option<MenuItemKind<R>> {
+    let menu = app.menu()?;
+    find_menu_item(menu.items().unwrap_or_default(), id)
+}
+
+pub(crate) fn sync_update_channel_menu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    channel: UpdateChannel,
+) {
+    for candidate in UpdateChannel::ALL {
+        if let Some(MenuItemKind::Check(item)) =
+            find_app_menu_item(app, update_channel_menu_id(candidate))
+        {
+            let _ = item.set_checked(candidate == channel);
+        }
+    }
+}
+
+pub(crate) fn sync_theme_menu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    preference: ThemePreference,
+) {
+    for candidate in ThemePreference::ALL {
+        if let Some(MenuItemKind::Check(item)) = find_app_menu_item(app, theme_menu_id(candidate)) {
+            let _ = item.set_checked(candidate == preference);
+        }
+    }
+}
+
+/// What the web UI currently has open, used to grey out items that cannot act.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MenuContext {
+    has_workspace: bool,
+    is_git_repo: bool,
+    /// Directory of the open workspace, so requests to open it again only focus the window.
+    workspace_path: Option<String>,
+}
+
+/// `None` until the web UI reports a context; older builds never do, so every
+/// item stays enabled for them.
+#[derive(Default)]
+pub(crate) struct MenuContextState {
+    context: Mutex<Option<MenuContext>>,
+}
+
+/// Items that need an open workspace.
+const WORKSPACE_MENU_ITEM_IDS: &[&str] = &[
+    MENU_ITEM_NEW_SESSION_ID,
+    MENU_ITEM_OPEN_GIT_TAB_ID,
+    MENU_ITEM_OPEN_DIFF_TAB_ID,
+    MENU_ITEM_OPEN_FILES_TAB_ID,
+    MENU_ITEM_OPEN_TERMINAL_TAB_ID,
+];
+/// Items that need the workspace to be a git repository.
+const GIT_MENU_ITEM_IDS: &[&str] = &[MENU_ITEM_WORKTREE_CREATOR_ID];
+
+fn set_menu_item_enabled<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str, enabled: bool) {
+    if let Some(MenuItemKind::MenuItem(item)) = find_app_menu_item(app, id) {
+        let _ = item.set_enabled(enabled);
+    }
+}
+
+fn apply_menu_context<R: tauri::Runtime>(app: &tauri::AppHandle<R>, context: &MenuContext) {
+    for id in WORKSPACE_MENU_ITEM_IDS {
+        set_menu_item_enabled(app, id, context.has_workspace);
+    }
+    for id in GIT_MENU_ITEM_IDS {
+        set_menu_item_enabled(app, id, context.has_workspace && context.is_git_repo);
+    }
+    tray::apply_menu_context(app, context.has_workspace);
+}
+
+/// Accelerators bound in the app menu, checked for conflicts by global shortcuts.
+/// Keep in sync with `AppMenuItems::new`.
+pub(crate) const MENU_ACCELERATORS: &[&str] = &[
+    "CmdOrCtrl+,",
+    "CmdOrCtrl+K",
+    "CmdOrCtrl+N",
+    "CmdOrCtrl+Shift+N",
+    "CmdOrCtrl+G",
+    "CmdOrCtrl+E",
+    "CmdOrCtrl+T",
+    "CmdOrCtrl+L",
+    "CmdOrCtrl+Shift+D",
+    "CmdOrCtrl+.",
+    "CmdOrCtrl+Shift+L",
+    "CmdOrCtrl+=",
+    "CmdOrCtrl+-",
+    "CmdOrCtrl+0",
+    #[cfg(not(target_os = "macos"))]
+    "Ctrl+Q",
+];
+
+fn new_session_shortcuts(auto_worktree: bool) -> (&'static str, &'static str) {
+    if auto_worktree {
+        ("CmdOrCtrl+Shift+N", "CmdOrCtrl+N")
+    } else {
+        ("CmdOrCtrl+N", "CmdOrCtrl+Shift+N")
+    }
+}
+
+/// Items shared by every platform layout.
+struct AppMenuItems<R: tauri::Runtime> {
+    about: MenuItem<R>,
+    check_for_updates: MenuItem<R>,
+    rollback_update: MenuItem<R>,
+    settings: MenuItem<R>,
+    command_palette: MenuItem<R>,
+    new_session: MenuItem<R>,
+    worktree_creator: MenuItem<R>,
+    change_workspace: MenuItem<R>,
+    open_recent_submenu: Submenu<R>,
+    export_pdf: MenuItem<R>,
+    open_git_tab: MenuItem<R>,
+    open_diff_tab: MenuItem<R>,
+    open_files_tab: MenuItem<R>,
+    open_terminal_tab: MenuItem<R>,
+    theme_submenu: Submenu<R>,
+    zoom_in: MenuItem<R>,
+    zoom_out: MenuItem<R>,
+    zoom_reset: MenuItem<R>,
+    toggle_sidebar: MenuItem<R>,
+    find: MenuItem<R>,
+    find_next: MenuItem<R>,
+    find_previous: MenuItem<R>,
+    toggle_memory_debug: MenuItem<R>,
+    reload: MenuItem<R>,
+    force_reload: MenuItem<R>,
+    help_dialog: MenuItem<R>,
+    whats_new: MenuItem<R>,
+    download_logs: MenuItem<R>,
+    report_bug: MenuItem<R>,
+    request_feature: MenuItem<R>,
+    join_discord: MenuItem<R>,
+    update_channel_submenu: Submenu<R>,
+}
+
+impl<R: tauri::Runtime> AppMenuItems<R> {
+    fn new(app: &tauri::AppHandle<R>) -> tauri::Result<Self> {
+        let pkg_info = app.package_info();
+
+        let auto_worktree = settings::get_bool_setting(app, settings::AUTO_WORKTREE_KEY);
+
+        let (new_session_shortcut, new_worktree_shortcut) = new_session_shortcuts(auto_worktree);
+
+        let current_theme = appearance::theme_preference(app);
+        let theme_light = CheckMenuItem::with_id(
+            app,
+            MENU_ITEM_THEME_LIGHT_ID,
+            i18n::t(app, "menu.themeLight"),
+            true,
+            current_theme == ThemePreference::Light,
+            None::<&str>,
+        )?;
+        let theme_dark = CheckMenuItem::with_id(
+            app,
+            MENU_ITEM_THEME_DARK_ID,
+            i18n::t(app, "menu.themeDark"),
+            true,
+            current_theme == ThemePreference::Dark,
+            None::<&str>,
+        )?;
+        let theme_system = CheckMenuItem::with_id(
+            app,
+            MENU_ITEM_THEME_SYSTEM_ID,
+            i18n::t(app, "menu.themeSystem"),
+            true,
+            current_theme == ThemePreference::System,
+            None::<&str>,
+        )?;
+
+        let current_channel = update_channel(app);
+        let mut channel_items = Vec::with_capacity(UpdateChannel::ALL.len());
+        for channel in UpdateChannel::ALL {
+            channel_items.push(CheckMenuItem::with_id(
+                app,
+                update_channel_menu_id(channel),
+                i18n::t(app, channel.label_key()),
+                true,
+                channel == current_channel,
+                None::<&str>,
+            )?);
+        }
+        let channel_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = channel_items
+            .iter()
+            .map(|item| item as &dyn tauri::menu::IsMenuItem<R>)
+            .collect();
+
+        let update_channel_submenu =
+            Submenu::with_items(app, i18n::t(app, "menu.updateChannel"), true, &channel_refs)?;
+
+        let open_recent_submenu = Submenu::with_id(
+            app,
+            MENU_OPEN_RECENT_SUBMENU_ID,
+            i18n::t(app, "menu.openRecent"),
+            true,
+        )?;
+        recents::populate_recents_menu(
+            app,
+            &open_recent_submenu,
+            &recents::recent_workspaces(app),
+        )?;
+
+        Ok(Self {
+            about: MenuItem::with_id(
+                app,
+                MENU_ITEM_ABOUT_ID,
+                i18n::t_args(app, "menu.about", &[("name", &pkg_info.name)]),
+                true,
+                None::<&str>,
+            )?,
+            check_for_updates: MenuItem::with_id(
+                app,
+                MENU_ITEM_CHECK_FOR_UPDATES_ID,
+                i18n::t(app, "menu.checkForUpdates"),
+                true,
+                None::<&str>,
+            )?,
+            rollback_update: MenuItem::with_id(
+                app,
+                MENU_ITEM_ROLLBACK_UPDATE_ID,
+                i18n::t(app, "menu.rollbackUpdate"),
+                rollback::previous_snapshot(app).is_some(),
+                None::<&str>,
+            )?,
+            settings: MenuItem::with_id(
+                app,
+                MENU_ITEM_SETTINGS_ID,
+                i18n::t(app, "menu.settings"),
+                true,
+                Some("CmdOrCtrl+,"),
+            )?,
+            command_palette: MenuItem::with_id(
+                app,
+                MENU_ITEM_COMMAND_PALETTE_ID,
+                i18n::t(app, "menu.commandPalette"),
+                true,
+                Some("CmdOrCtrl+K"),
+            )?,
+            new_session: MenuItem::with_id(
+                app,
+                MENU_ITEM_NEW_SESSION_ID,
+                i18n::t(app, "menu.newSession"),
+                true,
+                Some(new_session_shortcut),
+            )?,
+            worktree_creator: MenuItem::with_id(
+                app,
+                MENU_ITEM_WORKTREE_CREATOR_ID,
+                i18n::t(app, "menu.newWorktree"),
+                true,
+                Some(new_worktree_shortcut),
+            )?,
+            change_workspace: MenuItem::with_id(
+                app,
+                MENU_ITEM_CHANGE_WORKSPACE_ID,
+                i18n::t(app, "menu.addWorkspace"),
+                true,
+                None::<&str>,
+            )?,
+            open_recent_submenu,
+            export_pdf: MenuItem::with_id(
+                app,
+                MENU_ITEM_EXPORT_PDF_ID,
+                i18n::t(app, "menu.exportPdf"),
+                true,
+                None::<&str>,
+            )?,
+            open_git_tab: MenuItem::with_id(
+                app,
+                MENU_ITEM_OPEN_GIT_TAB_ID,
+                i18n::t(app, "menu.git"),
+                true,
+                Some("CmdOrCtrl+G"),
+            )?,
+            open_diff_tab: MenuItem::with_id(
+                app,
+                MENU_ITEM_OPEN_DIFF_TAB_ID,
+                i18n::t(app, "menu.diff"),
+                true,
+                Some("CmdOrCtrl+E"),
+            )?,
+            open_files_tab: MenuItem::with_id(
+                app,
+                MENU_ITEM_OPEN_FILES_TAB_ID,
+                i18n::t(app, "menu.files"),
+                true,
+                None::<&str>,
+            )?,
+            open_terminal_tab: MenuItem::with_id(
+                app,
+                MENU_ITEM_OPEN_TERMINAL_TAB_ID,
+                i18n::t(app, "menu.terminal"),
+                true,
+                Some("CmdOrCtrl+T"),
+            )?,
+            theme_submenu: Submenu::with_items(
+                app,
+                i18n::t(app, "menu.theme"),
+                true,
+                &[&theme_light, &theme_dark, &theme_system],
+            )?,
+            zoom_in: MenuItem::with_id(
+                app,
+                MENU_ITEM_ZOOM_IN_ID,
+                i18n::t(app, "menu.zoomIn"),
+                true,
+                Some("CmdOrCtrl+="),
+            )?,
+            zoom_out: MenuItem::with_id(
+                app,
+                MENU_ITEM_ZOOM_OUT_ID,
+                i18n::t(app, "menu.zoomOut"),
+                true,
+                Some("CmdOrCtrl+-"),
+            )?,
+            zoom_reset: MenuItem::with_id(
+                app,
+                MENU_ITEM_ZOOM_RESET_ID,
+                i18n::t(app, "menu.actualSize"),
+                true,
+                Some("CmdOrCtrl+0"),
+            )?,
+            toggle_sidebar: MenuItem::with_id(
+                app,
+                MENU_ITEM_TOGGLE_SIDEBAR_ID,
+                i18n::t(app, "menu.toggleSidebar"),
+                true,
+                Some("CmdOrCtrl+L"),
+            )?,
+            find: MenuItem::with_id(
+                app,
+                MENU_ITEM_FIND_ID,
+                i18n::t(app, "menu.find"),
+                true,
+                Some("CmdOrCtrl+F"),
+            )?,
+            find_next: MenuItem::with_id(
+                app,
+                MENU_ITEM_FIND_NEXT_ID,
+                i18n::t(app, "menu.findNext"),
+                true,
+                Some("CmdOrCtrl+G"),
+            )?,
+            find_previous: MenuItem::with_id(
+                app,
+                MENU_ITEM_FIND_PREVIOUS_ID,
+                i18n::t(app, "menu.findPrevious"),
+                true,
+                Some("CmdOrCtrl+Shift+G"),
+            )?,
+            toggle_memory_debug: MenuItem::with_id(
+                app,
+                MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID,
+                i18n::t(app, "menu.toggleMemoryDebug"),
+                true,
+                Some("CmdOrCtrl+Shift+D"),
+            )?,
+            reload: MenuItem::with_id(
+                app,
+                MENU_ITEM_RELOAD_ID,
+                i18n::t(app, "menu.reload"),
+                true,
+                Some("CmdOrCtrl+R"),
+            )?,
+            force_reload: MenuItem::with_id(
+                app,
+                MENU_ITEM_FORCE_RELOAD_ID,
+                i18n::t(app, "menu.forceReload"),
+                true,
+                Some("CmdOrCtrl+Shift+R"),
+            )?,
+            help_dialog: MenuItem::with_id(
+                app,
+                MENU_ITEM_HELP_DIALOG_ID,
+                i18n::t(app, "menu.keyboardShortcuts"),
+                true,
+                Some("CmdOrCtrl+."),
+            )?,
+            whats_new: MenuItem::with_id(
+                app,
+                MENU_ITEM_WHATS_NEW_ID,
+                i18n::t(app, "menu.whatsNew"),
+                true,
+                None::<&str>,
+            )?,
+            download_logs: MenuItem::with_id(
+                app,
+                MENU_ITEM_DOWNLOAD_LOGS_ID,
+                i18n::t(app, "menu.showDiagnostics"),
+                true,
+                Some("CmdOrCtrl+Shift+L"),
+            )?,
+            report_bug: MenuItem::with_id(
+                app,
+                MENU_ITEM_REPORT_BUG_ID,
+                i18n::t(app, "menu.reportBug"),
+                true,
+                None::<&str>,
+            )?,
+            request_feature: MenuItem::with_id(
+                app,
+                MENU_ITEM_REQUEST_FEATURE_ID,
+                i18n::t(app, "menu.requestFeature"),
+                true,
+                None::<&str>,
+            )?,
+            join_discord: MenuItem::with_id(
+                app,
+                MENU_ITEM_JOIN_DISCORD_ID,
+                i18n::t(app, "menu.joinDiscord"),
+                true,
+                None::<&str>,
+            )?,
+            update_channel_submenu,
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn build_app_menu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> tauri::Result<Menu<R>> {
+    let pkg_info = app.package_info();
+    let items = AppMenuItems::new(app)?;
+
+    let window_menu = Submenu::with_id_and_items(
+        app,
+        WINDOW_SUBMENU_ID,
+        i18n::t(app, "menu.window"),
+        true,
+        &[
+            &PredefinedMenuItem::minimize(app, None)?,
+            &PredefinedMenuItem::maximize(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::close_window(app, None)?,
+        ],
+    )?;
+    if window_tabs::enabled(app) {
+        window_menu.append_items(&[
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                MENU_ITEM_SHOW_NEXT_TAB_ID,
+                i18n::t(app, "menu.showNextTab"),
+                true,
+                Some("Ctrl+Tab"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                MENU_ITEM_SHOW_PREVIOUS_TAB_ID,
+                i18n::t(app, "menu.showPreviousTab"),
+                true,
+                Some("Ctrl+Shift+Tab"),
+            )?,
+        ])?;
+    }
+
+    let help_menu = Submenu::with_id_and_items(
+        app,
+        HELP_SUBMENU_ID,
+        i18n::t(app, "menu.help"),
+        true,
+        &[
+            &items.help_dialog,
+            &items.whats_new,
+            &items.download_logs,
+            &PredefinedMenuItem::separator(app)?,
+            &items.report_bug,
+            &items.request_feature,
+            &PredefinedMenuItem::separator(app)?,
+            &items.join_discord,
+        ],
+    )?;
+
+    Menu::with_items(
+        app,
+        &[
+            &Submenu::with_items(
+                app,
+                pkg_info.name.clone(),
+                true,
+                &[
+                    &items.about,
+                    &items.check_for_updates,
+                    &items.rollback_update,
+                    &items.update_channel_submenu,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.settings,
+                    &items.command_palette,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::services(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::hide(app, None)?,
+                    &PredefinedMenuItem::hide_others(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::quit(app, None)?,
+                ],
+            )?,
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.file"),
+                true,
+                &[
+                    &items.new_session,
+                    &items.worktree_creator,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.change_workspace,
+                    &items.open_recent_submenu,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.export_pdf,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::close_window(app, None)?,
+                ],
+            )?,
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.edit"),
+                true,
+                &[
+                    &PredefinedMenuItem::undo(app, None)?,
+                    &PredefinedMenuItem::redo(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::cut(app, None)?,
+                    &PredefinedMenuItem::copy(app, None)?,
+                    &PredefinedMenuItem::paste(app, None)?,
+                    &PredefinedMenuItem::select_all(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.find,
+                    &items.find_next,
+                    &items.find_previous,
+                ],
+            )?,
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.view"),
+                true,
+                &[
+                    &items.open_git_tab,
+                    &items.open_diff_tab,
+                    &items.open_files_tab,
+                    &items.open_terminal_tab,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.theme_submenu,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.zoom_in,
+                    &items.zoom_out,
+                    &items.zoom_reset,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.toggle_sidebar,
+                    &items.toggle_memory_debug,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.reload,
+                    &items.force_reload,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::fullscreen(app, None)?,
+                ],
+            )?,
+            &window_menu,
+            &help_menu,
+        ],
+    )
+}
+
+/// Windows/Linux layout: app-level entries move into File and Help, and window
+/// management uses regular items since the predefined ones are unsupported there.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn build_app_menu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> tauri::Result<Menu<R>> {
+    let items = AppMenuItems::new(app)?;
+
+    let minimize = MenuItem::with_id(
+        app,
+        MENU_ITEM_MINIMIZE_ID,
+        i18n::t(app, "menu.minimize"),
+        true,
+        None::<&str>,
+    )?;
+    let maximize = MenuItem::with_id(
+        app,
+        MENU_ITEM_MAXIMIZE_ID,
+        i18n::t(app, "menu.maximize"),
+        true,
+        None::<&str>,
+    )?;
+    let close_window = MenuItem::with_id(
+        app,
+        MENU_ITEM_CLOSE_WINDOW_ID,
+        i18n::t(app, "menu.closeWindow"),
+        true,
+        Some("Ctrl+W"),
+    )?;
+    let quit = MenuItem::with_id(
+        app,
+        MENU_ITEM_QUIT_ID,
+        i18n::t(app, "menu.quit"),
+        true,
+        Some("Ctrl+Q"),
+    )?;
+
+    let window_menu = Submenu::with_id_and_items(
+        app,
+        WINDOW_SUBMENU_ID,
+        i18n::t(app, "menu.window"),
+        true,
+        &[
+            &minimize,
+            &maximize,
+            &PredefinedMenuItem::separator(app)?,
+            &close_window,
+        ],
+    )?;
+
+    let help_menu = Submenu::with_id_and_items(
+        app,
+        HELP_SUBMENU_ID,
+        i18n::t(app, "menu.help"),
+        true,
+        &[
+            &items.help_dialog,
+            &items.whats_new,
+            &items.download_logs,
+            &PredefinedMenuItem::separator(app)?,
+            &items.report_bug,
+            &items.request_feature,
+            &PredefinedMenuItem::separator(app)?,
+            &items.join_discord,
+            &PredefinedMenuItem::separator(app)?,
+            &items.check_for_updates,
+            &items.rollback_update,
+            &items.update_channel_submenu,
+            &items.about,
+        ],
+    )?;
+
+    Menu::with_items(
+        app,
+        &[
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.file"),
+                true,
+                &[
+                    &items.new_session,
+                    &items.worktree_creator,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.change_workspace,
+                    &items.open_recent_submenu,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.export_pdf,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.settings,
+                    &items.command_palette,
+                    &PredefinedMenuItem::separator(app)?,
+                    &quit,
+                ],
+            )?,
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.edit"),
+                true,
+                &[
+                    &PredefinedMenuItem::cut(app, None)?,
+                    &PredefinedMenuItem::copy(app, None)?,
+                    &PredefinedMenuItem::paste(app, None)?,
+                    &PredefinedMenuItem::select_all(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.find,
+                    &items.find_next,
+                    &items.find_previous,
+                ],
+            )?,
+            &Submenu::with_items(
+                app,
+                i18n::t(app, "menu.view"),
+                true,
+                &[
+                    &items.open_git_tab,
+                    &items.open_diff_tab,
+                    &items.open_files_tab,
+                    &items.open_terminal_tab,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.theme_submenu,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.zoom_in,
+                    &items.zoom_out,
+                    &items.zoom_reset,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.toggle_sidebar,
+                    &items.toggle_memory_debug,
+                    &PredefinedMenuItem::separator(app)?,
+                    &items.reload,
+                    &items.force_reload,
+                ],
+            )?,
+            &window_menu,
+            &help_menu,
+        ],
+    )
+}
+
+pub(crate) fn open_external_url<R: tauri::Runtime>(app: &tauri::AppHandle<R>, url: &str) {
+    #[allow(deprecated)]
+    {
+        let _ = app.shell().open(url, None);
+    }
+}
+
+fn select_update_channel<R: tauri::Runtime>(app: &tauri::AppHandle<R>, channel: UpdateChannel) {
+    set_update_channel(app, channel);
+    events::emit(app, DesktopEvent::UpdateChannelChanged(channel));
+}
+
+/// Maps a menu item id to its action. Shared by every native menu.
+pub(crate) fn handle_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str) {
+    log::info!("[menu] click id={}", id);
+
+    match id {
+        MENU_ITEM_CHECK_FOR_UPDATES_ID => events::emit(app, DesktopEvent::CheckForUpdates),
+        MENU_ITEM_ROLLBACK_UPDATE_ID => rollback::rollback_from_menu(app),
+        MENU_ITEM_REPORT_BUG_ID => open_external_url(app, GITHUB_BUG_REPORT_URL),
+        MENU_ITEM_REQUEST_FEATURE_ID => open_external_url(app, GITHUB_FEATURE_REQUEST_URL),
+        MENU_ITEM_JOIN_DISCORD_ID => open_external_url(app, DISCORD_INVITE_URL),
+        MENU_ITEM_ABOUT_ID => window_registry::dispatch_menu_action(app, MenuAction::About),
+        MENU_ITEM_SETTINGS_ID => window_registry::dispatch_menu_action(app, MenuAction::Settings),
+        MENU_ITEM_COMMAND_PALETTE_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::CommandPalette)
+        }
+        MENU_ITEM_NEW_SESSION_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::NewSession)
+        }
+        MENU_ITEM_WORKTREE_CREATOR_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::NewWorktreeSession)
+        }
+        MENU_ITEM_CHANGE_WORKSPACE_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::ChangeWorkspace)
+        }
+        MENU_ITEM_CLEAR_RECENTS_ID => recents::clear_recents(app),
+        MENU_ITEM_EXPORT_PDF_ID => pdf_export::export_focused_window(app),
+        id if id.starts_with(RECENT_WORKSPACE_MENU_PREFIX) => {
+            recents::open_recent_from_menu(app, id)
+        }
+        id if id.starts_with(context_menu::CONTEXT_MENU_ID_PREFIX) => {
+            context_menu::handle_menu_event(app, id)
+        }
+        MENU_ITEM_OPEN_GIT_TAB_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::OpenGitTab)
+        }
+        MENU_ITEM_OPEN_DIFF_TAB_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::OpenDiffTab)
+        }
+        MENU_ITEM_OPEN_FILES_TAB_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::OpenFilesTab)
+        }
+        MENU_ITEM_OPEN_TERMINAL_TAB_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::OpenTerminalTab)
+        }
+        MENU_ITEM_THEME_LIGHT_ID => {
+            appearance::set_theme_preference(app, ThemePreference::Light);
+            // A check item toggles itself on click, so re-sync even when unchanged.
+            sync_theme_menu(app, ThemePreference::Light);
+            window_registry::dispatch_menu_action(app, MenuAction::ThemeLight);
+        }
+        MENU_ITEM_THEME_DARK_ID => {
+            appearance::set_theme_preference(app, ThemePreference::Dark);
+            // A check item toggles itself on click, so re-sync even when unchanged.
+            sync_theme_menu(app, ThemePreference::Dark);
+            window_registry::dispatch_menu_action(app, MenuAction::ThemeDark);
+        }
+        MENU_ITEM_THEME_SYSTEM_ID => {
+            appearance::set_theme_preference(app, ThemePreference::System);
+            // A check item toggles itself on click, so re-sync even when unchanged.
+            sync_theme_menu(app, ThemePreference::System);
+            window_registry::dispatch_menu_action(app, MenuAction::ThemeSystem);
+        }
+        MENU_ITEM_ZOOM_IN_ID => zoom::zoom_in(app),
+        MENU_ITEM_ZOOM_OUT_ID => zoom::zoom_out(app),
+        MENU_ITEM_ZOOM_RESET_ID => zoom::reset_zoom(app),
+        MENU_ITEM_TOGGLE_SIDEBAR_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::ToggleSidebar)
+        }
+        MENU_ITEM_FIND_ID => window_registry::dispatch_menu_action(app, MenuAction::Find),
+        MENU_ITEM_FIND_NEXT_ID => find::find_next(app),
+        MENU_ITEM_FIND_PREVIOUS_ID => find::find_previous(app),
+        MENU_ITEM_TOGGLE_MEMORY_DEBUG_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::ToggleMemoryDebug)
+        }
+        MENU_ITEM_RELOAD_ID => reload::reload_focused_window(app, false),
+        MENU_ITEM_FORCE_RELOAD_ID => reload::reload_focused_window(app, true),
+        MENU_ITEM_HELP_DIALOG_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::HelpDialog)
+        }
+        MENU_ITEM_WHATS_NEW_ID => changelog::show_whats_new(app),
+        MENU_ITEM_DOWNLOAD_LOGS_ID => {
+            window_registry::dispatch_menu_action(app, MenuAction::DownloadLogs)
+        }
+        MENU_ITEM_UPDATE_CHANNEL_STABLE_ID => select_update_channel(app, UpdateChannel::Stable),
+        MENU_ITEM_UPDATE_CHANNEL_BETA_ID => select_update_channel(app, UpdateChannel::Beta),
+        MENU_ITEM_UPDATE_CHANNEL_NIGHTLY_ID => select_update_channel(app, UpdateChannel::Nightly),
+        #[cfg(not(target_os = "macos"))]
+        MENU_ITEM_MINIMIZE_ID => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.minimize();
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        MENU_ITEM_MAXIMIZE_ID => {
+            if let Some(window) = app.get_webview_window("main") {
+                if window.is_maximized().unwrap_or(false) {
+                    let _ = window.unmaximize();
+                } else {
+                    let _ = window.maximize();
+                }
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        MENU_ITEM_CLOSE_WINDOW_ID => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.close();
+            }
+        }
+        #[cfg(not(target_os = "macos"))]
+        MENU_ITEM_QUIT_ID => app.exit(0),
+        #[cfg(target_os = "macos")]
+        MENU_ITEM_SHOW_NEXT_TAB_ID => window_tabs::select_adjacent_tab(app, true),
+        #[cfg(target_os = "macos")]
+        MENU_ITEM_SHOW_PREVIOUS_TAB_ID => window_tabs::select_adjacent_tab(app, false),
+        _ => {}
+    }
+}
+
+/// Swaps the New Session / New Worktree accelerators to match the auto-worktree setting.
+pub(crate) fn apply_auto_worktree<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    enabled: bool,
+) -> tauri::Result<()> {
+    let (new_session_shortcut, new_worktree_shortcut) = new_session_shortcuts(enabled);
+
+    if app.menu().is_some() {
+        if let Some(MenuItemKind::MenuItem(item)) =
+            find_app_menu_item(app, MENU_ITEM_NEW_SESSION_ID)
+        {
+            item.set_accelerator(Some(new_session_shortcut))?;
+        }
+        if let Some(MenuItemKind::MenuItem(item)) =
+            find_app_menu_item(app, MENU_ITEM_WORKTREE_CREATOR_ID)
+        {
+            item.set_accelerator(Some(new_worktree_shortcut))?;
+        }
+    } else {
+        // Should not happen once the app menu is installed, but keep as fallback.
+        let menu = build_app_menu(app)?;
+        app.set_menu(menu)?;
+    }
+
+    Ok(())
+}
+
+/// Kept for older web builds; equivalent to setting `autoWorktree` in the settings store.
+#[tauri::command]
+pub(crate) fn desktop_set_auto_worktree_menu(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    enabled: bool,
+) -> Result<(), String> {
+    settings::set_setting(
+        &app,
+        settings::AUTO_WORKTREE_KEY,
+        serde_json::Value::Bool(enabled),
+        Some(webview.label()),
+    )
+}
+
+/// Marks the active theme in the Theme menu; equivalent to setting `themePreference`
+/// in the settings store.
+#[tauri::command]
+pub(crate) fn desktop_set_theme_menu_state(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    theme: String,
+) -> Result<(), String> {
+    let preference = ThemePreference::parse(&theme);
+    if preference.as_str() != theme.trim().to_ascii_lowercase() {
+        return Err(format!("Unknown theme: {theme}"));
+    }
+    settings::set_setting(
+        &app,
+        appearance::THEME_PREFERENCE_KEY,
+        serde_json::Value::String(preference.as_str().to_string()),
+        Some(webview.label()),
+    )?;
+    sync_theme_menu(&app, preference);
+    Ok(())
+}
+
+/// Replaces the app menu with a freshly built one, e.g. after a locale change.
+pub(crate) fn rebuild_app_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+    let menu = build_app_menu(app)?;
+    app.set_menu(menu)?;
+    let context = app
+        .try_state::<MenuContextState>()
+        .and_then(|state| state.context.lock().expect("menu context mutex").clone());
+    if let Some(context) = context {
+        apply_menu_context(app, &context);
+    }
+    Ok(())
+}
+
+/// Enables or disables menu and tray items to match what the web UI has open.
+#[tauri::command]
+pub(crate) fn desktop_set_menu_context(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, MenuContextState>,
+    context: MenuContext,
+) -> Result<(), String> {
+    let mut current = state.context.lock().expect("menu context mutex");
+    if current.as_ref() == Some(&context) {
+        return Ok(());
+    }
+    *current = Some(context.clone());
+    drop(current);
+    apply_menu_context(&app, &context);
+    Ok(())
+}
+
+/// Workspace directory the web UI last reported as open.
+pub(crate) fn open_workspace_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    let state = app.try_state::<MenuContextState>()?;
+    let context = state.context.lock().expect("menu context mutex");
+    context
+        .as_ref()
+        .filter(|context| context.has_workspace)
+        .and_then(|context| context.workspace_path.clone())
+}
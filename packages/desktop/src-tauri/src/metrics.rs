@@ -0,0 +1,254 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Serialize;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    idle, local_sidecar,
+};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Name prefixes of webview helper processes started inside our process tree:
+/// WebKitGTK's (truncated to 15 characters on Linux) and WebView2's.
+const WEBVIEW_PROCESS_PREFIXES: &[&str] = &["WebKit", "msedgewebview2"];
+
+#[derive(Clone, Copy, Debug, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessUsage {
+    pub(crate) process_count: usize,
+    pub(crate) rss_bytes: u64,
+    /// Summed over processes; 100 is one fully busy core.
+    pub(crate) cpu_percent: f32,
+}
+
+impl ProcessUsage {
+    fn add(&mut self, process: &Process) {
+        self.process_count += 1;
+        self.rss_bytes += process.memory();
+        self.cpu_percent += process.cpu_usage();
+    }
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessMetrics {
+    app: ProcessUsage,
+    /// `None` on macOS, where WebKit's helpers are XPC services outside our
+    /// process tree.
+    webview: Option<ProcessUsage>,
+    /// The local server and everything it started. `None` without a sidecar.
+    sidecar: Option<ProcessUsage>,
+    total_memory_bytes: u64,
+    cpu_count: usize,
+}
+
+/// Sampler shared by the command and the event loop, plus the webviews
+/// subscribed to `openchamber:process-metrics` with their intervals.
+pub(crate) struct MetricsState {
+    system: Mutex<System>,
+    subscribers: Mutex<Subscribers>,
+}
+
+#[derive(Default)]
+struct Subscribers {
+    intervals: HashMap<String, Duration>,
+    /// Whether the sampling loop is alive; it clears this when it exits.
+    running: bool,
+}
+
+impl Default for MetricsState {
+    fn default() -> Self {
+        Self {
+            system: Mutex::new(System::new()),
+            subscribers: Mutex::new(Subscribers::default()),
+        }
+    }
+}
+
+/// `root` and every process below it.
+fn process_tree(system: &System, root: Pid) -> HashSet<Pid> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+    let mut tree = HashSet::from([root]);
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        for child in children.get(&pid).into_iter().flatten() {
+            if tree.insert(*child) {
+                pending.push(*child);
+            }
+        }
+    }
+    tree
+}
+
+fn is_webview_process(process: &Process) -> bool {
+    let name = process.name().to_string_lossy();
+    WEBVIEW_PROCESS_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Refreshes every process and attributes them. CPU usage is measured since the
+/// previous call, so the first sample reads zero.
+fn sample(system: &mut System, sidecar: Option<u32>) -> ProcessMetrics {
+    system.refresh_memory();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_memory().with_cpu(),
+    );
+
+    let own = Pid::from_u32(std::process::id());
+    let sidecar = sidecar.map(Pid::from_u32);
+    let sidecar_tree = sidecar
+        .map(|pid| process_tree(system, pid))
+        .unwrap_or_default();
+
+    let mut app = ProcessUsage::default();
+    let mut webview = ProcessUsage::default();
+    let mut sidecar_usage = ProcessUsage::default();
+    // An adopted sidecar is not below us, so its tree is walked separately.
+    for pid in &sidecar_tree {
+        if let Some(process) = system.process(*pid) {
+            sidecar_usage.add(process);
+        }
+    }
+    for pid in process_tree(system, own) {
+        if sidecar_tree.contains(&pid) {
+            continue;
+        }
+        let Some(process) = system.process(pid) else {
+            continue;
+        };
+        if pid == own {
+            app.add(process);
+        } else if is_webview_process(process) {
+            webview.add(process);
+        }
+    }
+
+    ProcessMetrics {
+        app,
+        webview: (!cfg!(target_os = "macos")).then_some(webview),
+        sidecar: sidecar.map(|_| sidecar_usage),
+        total_memory_bytes: system.total_memory(),
+        cpu_count: std::thread::available_parallelism().map_or(1, |count| count.get()),
+    }
+}
+
+fn sample_now(app: &tauri::AppHandle) -> ProcessMetrics {
+    let sidecar = local_sidecar(app).and_then(|state| {
+        state
+            .child
+            .lock()
+            .expect("sidecar mutex")
+            .as_ref()
+            .map(|child| child.pid())
+    });
+    let state = app.state::<MetricsState>();
+    let mut system = state.system.lock().expect("metrics mutex");
+    sample(&mut system, sidecar)
+}
+
+/// The sidecar's share of a fresh sample; `None` without a local sidecar.
+pub(crate) fn sample_sidecar(app: &tauri::AppHandle) -> Option<ProcessUsage> {
+    sample_now(app).sidecar
+}
+
+/// Shortest interval any subscriber asked for, with their labels; `None` once
+/// nobody listens, which also marks the loop as stopped.
+fn next_round(app: &tauri::AppHandle) -> Option<(Duration, Vec<String>)> {
+    let state = app.state::<MetricsState>();
+    let mut subscribers = state.subscribers.lock().expect("metrics subscribers mutex");
+    let Some(interval) = subscribers.intervals.values().min().copied() else {
+        subscribers.running = false;
+        return None;
+    };
+    Some((interval, subscribers.intervals.keys().cloned().collect()))
+}
+
+fn spawn_sampler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        while let Some((interval, labels)) = next_round(&app) {
+            let sample_app = app.clone();
+            match tauri::async_runtime::spawn_blocking(move || sample_now(&sample_app)).await {
+                Ok(metrics) => {
+                    for label in labels {
+                        events::emit_to(
+                            &app,
+                            label.as_str(),
+                            DesktopEvent::ProcessMetrics(metrics.clone()),
+                        );
+                    }
+                }
+                Err(err) => log::warn!("[metrics] sampling failed: {err}"),
+            }
+            tokio::time::sleep(idle::poll_interval(&app, interval)).await;
+        }
+    });
+}
+
+pub(crate) fn unsubscribe<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: &str) {
+    if let Some(state) = app.try_state::<MetricsState>() {
+        state
+            .subscribers
+            .lock()
+            .expect("metrics subscribers mutex")
+            .intervals
+            .remove(label);
+    }
+}
+
+/// Memory and CPU use of the app, its webview processes and the local server.
+#[tauri::command]
+pub(crate) async fn desktop_get_process_metrics(
+    app: tauri::AppHandle,
+) -> Result<ProcessMetrics, String> {
+    tauri::async_runtime::spawn_blocking(move || sample_now(&app))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Sends `openchamber:process-metrics` to the calling webview every
+/// `interval_ms` (default 5 s, clamped to 1–60 s) until it unsubscribes or closes.
+#[tauri::command]
+pub(crate) fn desktop_subscribe_process_metrics(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    interval_ms: Option<u64>,
+) {
+    let interval = interval_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_INTERVAL)
+        .clamp(MIN_INTERVAL, MAX_INTERVAL);
+    let start = {
+        let state = app.state::<MetricsState>();
+        let mut subscribers = state.subscribers.lock().expect("metrics subscribers mutex");
+        subscribers
+            .intervals
+            .insert(webview.label().to_string(), interval);
+        !std::mem::replace(&mut subscribers.running, true)
+    };
+    if start {
+        spawn_sampler(app);
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_unsubscribe_process_metrics(app: tauri::AppHandle, webview: tauri::Webview) {
+    unsubscribe(&app, webview.label());
+}
@@ -0,0 +1,92 @@
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{
+    appearance, deeplink::is_valid_session_id, resolve_server_target, spellcheck, window_state,
+    zoom,
+};
+
+const MINI_WINDOW_PREFIX: &str = "mini-";
+const MINI_WINDOW_WIDTH: f64 = 420.0;
+const MINI_WINDOW_HEIGHT: f64 = 560.0;
+
+/// Frameless windows have no title bar to grab, so give the page a thin drag strip.
+const DRAG_STRIP_SCRIPT: &str = "(function(){try{var add=function(){if(document.getElementById('__oc-mini-drag'))return;var d=document.createElement('div');d.id='__oc-mini-drag';d.setAttribute('data-tauri-drag-region','');d.style.cssText='position:fixed;top:0;left:0;right:0;height:14px;z-index:2147483647;cursor:grab;';document.body.appendChild(d);};if(document.body){add();}else{document.addEventListener('DOMContentLoaded',add);}}catch(_e){}})();";
+
+fn mini_window_label(session_id: &str) -> String {
+    format!("{MINI_WINDOW_PREFIX}{session_id}")
+}
+
+/// Opens (or focuses) a small always-on-top window showing a single session.
+/// Returns the window label.
+#[tauri::command]
+pub(crate) fn desktop_open_mini_window(
+    app: tauri::AppHandle,
+    session_id: String,
+) -> Result<String, String> {
+    let session_id = session_id.trim();
+    if !is_valid_session_id(session_id) {
+        return Err(format!("Invalid session id: {session_id}"));
+    }
+
+    let label = mini_window_label(session_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(label);
+    }
+
+    let (origin, _, _) = resolve_server_target(&app);
+    let origin = origin.ok_or_else(|| "No server is connected".to_string())?;
+    let mut url = url::Url::parse(&origin).map_err(|err| err.to_string())?;
+    url.query_pairs_mut()
+        .append_pair("session", session_id)
+        .append_pair("tab", "chat")
+        .append_pair("mini", "1");
+
+    let mut builder = WebviewWindowBuilder::new(&app, &label, WebviewUrl::External(url))
+        .title("OpenChamber")
+        .inner_size(MINI_WINDOW_WIDTH, MINI_WINDOW_HEIGHT)
+        .min_inner_size(280.0, 200.0)
+        .decorations(false)
+        .always_on_top(true)
+        .visible(false)
+        .incognito(crate::safe_mode::is_enabled())
+        .initialization_script(DRAG_STRIP_SCRIPT);
+    if let Some(script) = app
+        .try_state::<crate::DesktopUiInjectionState>()
+        .and_then(|state| {
+            state
+                .script
+                .lock()
+                .expect("desktop ui injection mutex")
+                .clone()
+        })
+    {
+        builder = builder.initialization_script(&script);
+    }
+
+    let window = builder.build().map_err(|err| err.to_string())?;
+    window_state::restore(&window);
+    zoom::restore_zoom(&window);
+    appearance::restore_theme(&window);
+    spellcheck::restore_spellcheck(&window);
+    let _ = window.show();
+    let _ = window.set_focus();
+    log::info!("[mini-window] opened {label}");
+    Ok(label)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_always_on_top(
+    app: tauri::AppHandle,
+    label: String,
+    always_on_top: bool,
+) -> Result<(), String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("No window labelled {label}"))?;
+    window
+        .set_always_on_top(always_on_top)
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{read_desktop_setting_from_disk, write_desktop_setting_to_disk};
+
+const NOTIFICATION_PREFS_KEY: &str = "desktopNotificationPrefs";
+
+/// What a notification is about. Sent by the web UI / sidecar as `category`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NotificationCategory {
+    SessionFinished,
+    Error,
+    Update,
+    Other,
+}
+
+impl NotificationCategory {
+    pub(crate) fn parse(raw: Option<&str>) -> Self {
+        match raw.map(str::trim) {
+            Some("sessionFinished") | Some("session-finished") => Self::SessionFinished,
+            Some("error") | Some("errors") => Self::Error,
+            Some("update") | Some("updates") => Self::Update,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct NotificationCategories {
+    session_finished: bool,
+    errors: bool,
+    updates: bool,
+}
+
+impl Default for NotificationCategories {
+    fn default() -> Self {
+        Self {
+            session_finished: true,
+            errors: true,
+            updates: true,
+        }
+    }
+}
+
+/// Local-time window (`"HH:MM"`) during which notifications are suppressed.
+/// `start` after `end` spans midnight, e.g. 22:00-07:00.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuietHours {
+    start: String,
+    end: String,
+}
+
+impl QuietHours {
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (
+            NaiveTime::parse_from_str(&self.start, "%H:%M"),
+            NaiveTime::parse_from_str(&self.end, "%H:%M"),
+        ) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct NotificationPrefs {
+    enabled: bool,
+    categories: NotificationCategories,
+    quiet_hours: Option<QuietHours>,
+    only_when_unfocused: bool,
+}
+
+impl Default for NotificationPrefs {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            categories: NotificationCategories::default(),
+            quiet_hours: None,
+            only_when_unfocused: false,
+        }
+    }
+}
+
+impl NotificationPrefs {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(quiet) = &self.quiet_hours {
+            for value in [&quiet.start, &quiet.end] {
+                NaiveTime::parse_from_str(value, "%H:%M")
+                    .map_err(|_| format!("Invalid quiet hours time: {value}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct NotificationPrefsState(Mutex<NotificationPrefs>);
+
+impl NotificationPrefsState {
+    pub(crate) fn from_disk() -> Self {
+        let prefs = read_desktop_setting_from_disk(NOTIFICATION_PREFS_KEY)
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+        Self(Mutex::new(prefs))
+    }
+
+    /// Applies the user's policy before a native notification is shown.
+    pub(crate) fn allows(&self, category: NotificationCategory, focused: bool) -> bool {
+        let prefs = self.0.lock().expect("notification prefs mutex");
+        if !prefs.enabled || (prefs.only_when_unfocused && focused) {
+            return false;
+        }
+        let category_enabled = match category {
+            NotificationCategory::SessionFinished => prefs.categories.session_finished,
+            NotificationCategory::Error => prefs.categories.errors,
+            NotificationCategory::Update => prefs.categories.updates,
+            NotificationCategory::Other => true,
+        };
+        if !category_enabled {
+            return false;
+        }
+        !prefs
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|quiet| quiet.contains(chrono::Local::now().time()))
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_get_notification_prefs(
+    state: tauri::State<'_, NotificationPrefsState>,
+) -> Result<NotificationPrefs, String> {
+    Ok(state.0.lock().expect("notification prefs mutex").clone())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_notification_prefs(
+    state: tauri::State<'_, NotificationPrefsState>,
+    prefs: NotificationPrefs,
+) -> Result<(), String> {
+    prefs.validate()?;
+    let value = serde_json::to_value(&prefs).map_err(|err| err.to_string())?;
+    write_desktop_setting_to_disk(NOTIFICATION_PREFS_KEY, value).map_err(|err| err.to_string())?;
+    *state.0.lock().expect("notification prefs mutex") = prefs;
+    Ok(())
+}
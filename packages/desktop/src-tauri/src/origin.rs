@@ -0,0 +1,69 @@
+use tauri::Manager;
+
+use crate::{
+    git::{GitError, GitErrorKind},
+    local_sidecar,
+    path_policy::{PathError, PathErrorKind},
+};
+
+/// A command was called from a page other than the app's own UI.
+pub(crate) struct ForbiddenOrigin(String);
+
+impl From<ForbiddenOrigin> for String {
+    fn from(err: ForbiddenOrigin) -> Self {
+        err.0
+    }
+}
+
+impl From<ForbiddenOrigin> for PathError {
+    fn from(err: ForbiddenOrigin) -> Self {
+        PathError::new(PathErrorKind::Forbidden, err.0)
+    }
+}
+
+impl From<ForbiddenOrigin> for GitError {
+    fn from(err: ForbiddenOrigin) -> Self {
+        GitError::new(GitErrorKind::Forbidden, err.0)
+    }
+}
+
+/// Whether `url` is served by the local server, i.e. is the app's own UI rather
+/// than a remote server opened in the window.
+pub(crate) fn is_local<R: tauri::Runtime>(app: &tauri::AppHandle<R>, url: &url::Url) -> bool {
+    local_sidecar(app)
+        .and_then(|state| state.url.lock().expect("sidecar url mutex").clone())
+        .and_then(|local| url::Url::parse(&local).ok())
+        .is_some_and(|local| local.origin() == url.origin())
+}
+
+/// Labels of the webviews currently showing the app's own UI, for events that must
+/// not reach remote servers.
+pub(crate) fn local_webviews<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Vec<String> {
+    app.webview_windows()
+        .into_iter()
+        .filter(|(_, window)| window.url().is_ok_and(|url| is_local(app, &url)))
+        .map(|(label, _)| label)
+        .collect()
+}
+
+/// Refuses `operation` unless the calling webview shows the app's own UI. Remote
+/// servers open in the same windows with the same IPC bridge, so commands that
+/// reach the shell, secrets or local files check their caller first.
+pub(crate) fn require_local<R: tauri::Runtime>(
+    webview: &tauri::Webview<R>,
+    operation: &str,
+) -> Result<(), ForbiddenOrigin> {
+    let url = webview
+        .url()
+        .map_err(|err| ForbiddenOrigin(err.to_string()))?;
+    if is_local(webview.app_handle(), &url) {
+        return Ok(());
+    }
+    log::warn!(
+        "[origin] refused {operation} from {}",
+        url.origin().ascii_serialization()
+    );
+    Err(ForbiddenOrigin(format!(
+        "{operation} is not available to remote servers"
+    )))
+}
@@ -0,0 +1,316 @@
+use std::{
+    collections::BTreeSet,
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::{git, logging, recents};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PathErrorKind {
+    InvalidPath,
+    NotFound,
+    /// Outside every workspace root and the app's own directories.
+    OutsideScope,
+    /// Inside a workspace root, but a symlink resolves to somewhere outside it.
+    SymlinkEscape,
+    /// The target of a create or rename is already taken.
+    AlreadyExists,
+    /// Called from a page other than the app's own UI, such as a remote server.
+    Forbidden,
+    Io,
+}
+
+/// Error returned by the native file commands. Serialized as `{ kind, message }`
+/// like [`git::GitError`], so the UI can tell a denial from a missing file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathError {
+    kind: PathErrorKind,
+    message: String,
+}
+
+impl PathError {
+    pub(crate) fn new(kind: PathErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for PathError {
+    fn from(message: String) -> Self {
+        Self::new(PathErrorKind::Io, message)
+    }
+}
+
+/// Paths the user handed to the app outside of a workspace, e.g. by dropping
+/// them onto a window. Kept for the session only.
+#[derive(Default)]
+pub(crate) struct PathPolicyState {
+    granted: Mutex<BTreeSet<PathBuf>>,
+}
+
+/// `canonicalize` returns `\\?\C:\...` paths on Windows, which Explorer and most
+/// editors do not understand.
+pub(crate) fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(stripped) = path.to_str().and_then(|raw| raw.strip_prefix(r"\\?\")) {
+            if !stripped.starts_with("UNC\\") {
+                return PathBuf::from(stripped);
+            }
+        }
+    }
+    path
+}
+
+fn canonical(path: &Path) -> Option<PathBuf> {
+    fs::canonicalize(path).ok().map(strip_verbatim_prefix)
+}
+
+/// Resolves `.` and `..` without touching the filesystem, so symlinks stay as written.
+fn lexical(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Where native file commands may operate: registered workspace roots, paths the
+/// user granted this session and the app's own data, config and log directories.
+pub(crate) struct PathPolicy {
+    /// Roots as registered, for spotting symlink escapes.
+    declared: Vec<PathBuf>,
+    /// The same roots with symlinks resolved.
+    resolved: Vec<PathBuf>,
+}
+
+impl PathPolicy {
+    pub(crate) fn for_app<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Self {
+        let mut roots: Vec<PathBuf> = recents::recent_workspaces(app)
+            .iter()
+            .map(|entry| PathBuf::from(entry.path()))
+            .collect();
+        roots.extend(git::watched_workspace(app).map(PathBuf::from));
+        if let Some(state) = app.try_state::<PathPolicyState>() {
+            roots.extend(
+                state
+                    .granted
+                    .lock()
+                    .expect("path policy mutex")
+                    .iter()
+                    .cloned(),
+            );
+        }
+        let paths = app.path();
+        roots.extend(paths.app_data_dir().ok());
+        roots.extend(paths.app_config_dir().ok());
+        roots.extend(logging::log_dir(app));
+        Self::from_roots(&roots)
+    }
+
+    fn from_roots(roots: &[PathBuf]) -> Self {
+        let declared: Vec<PathBuf> = roots
+            .iter()
+            .filter(|root| root.is_absolute())
+            .map(|root| lexical(root))
+            .collect();
+        let resolved = declared.iter().filter_map(|root| canonical(root)).collect();
+        Self { declared, resolved }
+    }
+
+    /// The canonical form of `path` if it may be used for `operation`. Denials are
+    /// logged, so an unexpected `outsideScope` can be traced from the diagnostics.
+    pub(crate) fn check(&self, operation: &str, path: &str) -> Result<PathBuf, PathError> {
+        let trimmed = path.trim();
+        if trimmed.is_empty() {
+            return Err(PathError::new(PathErrorKind::InvalidPath, "Path is empty"));
+        }
+        let requested = Path::new(trimmed);
+        if !requested.is_absolute() {
+            return Err(PathError::new(
+                PathErrorKind::InvalidPath,
+                format!("Path must be absolute: {trimmed}"),
+            ));
+        }
+        let resolved = canonical(requested).ok_or_else(|| {
+            PathError::new(
+                PathErrorKind::NotFound,
+                format!("Path does not exist: {trimmed}"),
+            )
+        })?;
+        if self.resolved.iter().any(|root| resolved.starts_with(root)) {
+            return Ok(resolved);
+        }
+
+        let written = lexical(requested);
+        let escaped = self
+            .declared
+            .iter()
+            .chain(&self.resolved)
+            .any(|root| written.starts_with(root));
+        let error = if escaped {
+            PathError::new(
+                PathErrorKind::SymlinkEscape,
+                format!(
+                    "{trimmed} resolves to {} outside the workspace",
+                    resolved.display()
+                ),
+            )
+        } else {
+            PathError::new(
+                PathErrorKind::OutsideScope,
+                format!("{trimmed} is outside the open workspaces"),
+            )
+        };
+        log::warn!(
+            "[path-policy] denied {operation} of {}: {:?}",
+            resolved.display(),
+            error.kind
+        );
+        Err(error)
+    }
+
+    /// Whether `resolved` is one of the roots itself rather than something below it.
+    pub(crate) fn is_root(&self, resolved: &Path) -> bool {
+        self.resolved.iter().any(|root| root == resolved)
+    }
+}
+
+/// [`PathPolicy::check`] against the roots as they are right now.
+pub(crate) fn check<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    operation: &str,
+    path: &str,
+) -> Result<PathBuf, PathError> {
+    PathPolicy::for_app(app).check(operation, path)
+}
+
+/// Lets later commands use `path` and everything below it, for paths the user
+/// picked themselves.
+pub(crate) fn grant<R: tauri::Runtime>(app: &tauri::AppHandle<R>, path: &Path) {
+    let Some(path) = canonical(path) else {
+        return;
+    };
+    if let Some(state) = app.try_state::<PathPolicyState>() {
+        state
+            .granted
+            .lock()
+            .expect("path policy mutex")
+            .insert(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, canonical scratch directory for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "openchamber-path-policy-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        canonical(&dir).unwrap()
+    }
+
+    fn kind(result: Result<PathBuf, PathError>) -> PathErrorKind {
+        result.unwrap_err().kind
+    }
+
+    #[test]
+    fn lexical_resolves_dots_without_the_filesystem() {
+        assert_eq!(lexical(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(lexical(Path::new("/a/b/../../..")), PathBuf::from("/"));
+        assert_eq!(
+            lexical(Path::new("/missing/dir")),
+            PathBuf::from("/missing/dir")
+        );
+    }
+
+    #[test]
+    fn check_rejects_empty_relative_and_missing_paths() {
+        let root = scratch("invalid");
+        let policy = PathPolicy::from_roots(std::slice::from_ref(&root));
+
+        assert_eq!(kind(policy.check("read", "  ")), PathErrorKind::InvalidPath);
+        assert_eq!(
+            kind(policy.check("read", "relative/file")),
+            PathErrorKind::InvalidPath
+        );
+        let missing = root.join("missing");
+        assert_eq!(
+            kind(policy.check("read", &missing.to_string_lossy())),
+            PathErrorKind::NotFound
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn check_allows_paths_inside_a_root_only() {
+        let base = scratch("scope");
+        let root = base.join("workspace");
+        let outside = base.join("elsewhere");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        let policy = PathPolicy::from_roots(std::slice::from_ref(&root));
+
+        let inside = root.join("src");
+        assert_eq!(
+            policy.check("read", &inside.to_string_lossy()).unwrap(),
+            inside
+        );
+        assert!(policy.is_root(&root));
+        assert_eq!(
+            kind(policy.check("read", &outside.to_string_lossy())),
+            PathErrorKind::OutsideScope
+        );
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_classifies_symlinks_out_of_a_root_as_escapes() {
+        let base = scratch("symlink");
+        let root = base.join("workspace");
+        let outside = base.join("secrets");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        let policy = PathPolicy::from_roots(std::slice::from_ref(&root));
+
+        let through_link = root.join("link");
+        assert_eq!(
+            kind(policy.check("read", &through_link.to_string_lossy())),
+            PathErrorKind::SymlinkEscape
+        );
+        // Climbing out with `..` is not a symlink escape, just out of scope.
+        let climbed = root.join("..").join("secrets");
+        assert_eq!(
+            kind(policy.check("read", &climbed.to_string_lossy())),
+            PathErrorKind::OutsideScope
+        );
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,381 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+
+use crate::audit::{self, AuditCategory};
+
+type Done = oneshot::Sender<Result<(), String>>;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PageSize {
+    #[default]
+    A4,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    /// Portrait width and height in inches.
+    #[cfg(any(windows, target_os = "macos"))]
+    fn inches(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (8.27, 11.69),
+            PageSize::Letter => (8.5, 11.0),
+            PageSize::Legal => (8.5, 14.0),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn gtk_name(self) -> &'static str {
+        match self {
+            PageSize::A4 => gtk::PAPER_NAME_A4.as_str(),
+            PageSize::Letter => gtk::PAPER_NAME_LETTER.as_str(),
+            PageSize::Legal => gtk::PAPER_NAME_LEGAL.as_str(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct PdfOptions {
+    page_size: PageSize,
+    landscape: bool,
+    /// Keep background colors and images, e.g. diff highlighting.
+    print_background: bool,
+    /// Suggested name for the save dialog.
+    file_name: Option<String>,
+}
+
+/// Prints with WebView2's `PrintToPdf`, which writes the file without any UI.
+#[cfg(windows)]
+fn start_export<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    target: PathBuf,
+    options: PdfOptions,
+    done: Done,
+) -> Result<(), String> {
+    use webview2_com::{Microsoft::Web::WebView2::Win32::*, PrintToPdfCompletedHandler};
+    use windows::core::{Interface, HSTRING};
+
+    window
+        .with_webview(move |platform| {
+            // SAFETY: `with_webview` runs on the UI thread that owns the controller.
+            let result = unsafe {
+                (|| -> windows::core::Result<()> {
+                    let webview: ICoreWebView2_7 = platform.controller().CoreWebView2()?.cast()?;
+                    let environment: ICoreWebView2Environment6 = platform.environment().cast()?;
+                    let settings = environment.CreatePrintSettings()?;
+                    let (width, height) = options.page_size.inches();
+                    settings.SetPageWidth(width)?;
+                    settings.SetPageHeight(height)?;
+                    settings.SetOrientation(if options.landscape {
+                        COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE
+                    } else {
+                        COREWEBVIEW2_PRINT_ORIENTATION_PORTRAIT
+                    })?;
+                    settings.SetShouldPrintBackgrounds(options.print_background)?;
+                    settings.SetShouldPrintHeaderAndFooter(false)?;
+                    let handler =
+                        PrintToPdfCompletedHandler::create(Box::new(move |result, written| {
+                            let _ = done.send(match result {
+                                Err(err) => Err(err.to_string()),
+                                Ok(()) if written => Ok(()),
+                                Ok(()) => Err("WebView2 could not write the PDF".to_string()),
+                            });
+                            Ok(())
+                        }));
+                    webview.PrintToPdf(&HSTRING::from(target.as_os_str()), &settings, &handler)
+                })()
+            };
+            // On failure `done` is dropped, which the caller reports as interrupted.
+            if let Err(err) = result {
+                log::warn!("[pdf-export] PrintToPdf failed: {err}");
+            }
+        })
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod mac {
+    use std::{cell::RefCell, ffi::c_void};
+
+    use objc2::{
+        define_class, msg_send,
+        rc::Retained,
+        runtime::{Bool, NSObject},
+        DefinedClass, MainThreadMarker, MainThreadOnly,
+    };
+    use objc2_app_kit::NSPrintOperation;
+
+    use super::Done;
+
+    define_class!(
+        /// Receives the end of a modal print operation.
+        #[unsafe(super(NSObject))]
+        #[thread_kind = MainThreadOnly]
+        #[name = "OpenChamberPdfExportDelegate"]
+        #[ivars = RefCell<Option<Done>>]
+        pub(super) struct PrintDelegate;
+
+        impl PrintDelegate {
+            #[unsafe(method(printOperationDidRun:success:contextInfo:))]
+            fn did_run(&self, _operation: &NSPrintOperation, success: Bool, _context: *mut c_void) {
+                if let Some(done) = self.ivars().borrow_mut().take() {
+                    let _ = done.send(if success.as_bool() {
+                        Ok(())
+                    } else {
+                        Err("The PDF could not be written".to_string())
+                    });
+                }
+            }
+        }
+    );
+
+    impl PrintDelegate {
+        pub(super) fn new(mtm: MainThreadMarker, done: Done) -> Retained<Self> {
+            let this = Self::alloc(mtm).set_ivars(RefCell::new(Some(done)));
+            // SAFETY: `NSObject`'s designated initializer.
+            unsafe { msg_send![super(this), init] }
+        }
+    }
+
+    thread_local! {
+        /// The print operation does not retain its delegate; keep the latest alive.
+        pub(super) static ACTIVE_DELEGATE: RefCell<Option<Retained<PrintDelegate>>> =
+            const { RefCell::new(None) };
+    }
+}
+
+/// Runs a WebKit print operation whose job is saved to `target` instead of sent
+/// to a printer. WebKit only prints through a window-modal operation.
+#[cfg(target_os = "macos")]
+fn start_export<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    target: PathBuf,
+    options: PdfOptions,
+    done: Done,
+) -> Result<(), String> {
+    window
+        .with_webview(move |platform| {
+            use objc2::{runtime::AnyObject, sel};
+            use objc2_app_kit::{
+                NSPaperOrientation, NSPrintInfo, NSPrintJobSavingURL, NSPrintSaveJob, NSWindow,
+            };
+            use objc2_foundation::{MainThreadMarker, NSObjectProtocol, NSSize, NSString, NSURL};
+            use objc2_web_kit::WKWebView;
+
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            // SAFETY: Tauri hands out the live WKWebView and its NSWindow on the main thread.
+            let (webview, ns_window) = unsafe {
+                (
+                    &*(platform.inner() as *const WKWebView),
+                    &*(platform.ns_window() as *const NSWindow),
+                )
+            };
+
+            let info = NSPrintInfo::new();
+            let (width, height) = options.page_size.inches();
+            info.setPaperSize(NSSize::new(width * 72.0, height * 72.0));
+            info.setOrientation(if options.landscape {
+                NSPaperOrientation::Landscape
+            } else {
+                NSPaperOrientation::Portrait
+            });
+            let url = NSURL::fileURLWithPath(&NSString::from_str(&target.to_string_lossy()));
+            // SAFETY: the job disposition and saving URL keys take an NSString and an NSURL.
+            unsafe {
+                info.setJobDisposition(NSPrintSaveJob);
+                let url: &AnyObject = &url;
+                info.dictionary().insert(NSPrintJobSavingURL, url);
+            }
+
+            // SAFETY: the preference is only set where WebKit implements it (macOS 13.3+).
+            unsafe {
+                let preferences = webview.configuration().preferences();
+                if preferences.respondsToSelector(sel!(setShouldPrintBackgrounds:)) {
+                    preferences.setShouldPrintBackgrounds(options.print_background);
+                }
+            }
+
+            // SAFETY: `info` is a fully configured print info for this web view.
+            let operation = unsafe { webview.printOperationWithPrintInfo(&info) };
+            operation.setShowsPrintPanel(false);
+            operation.setShowsProgressPanel(false);
+            // WKWebView renders blank pages unless the printed view has a frame.
+            if let Some(view) = operation.view() {
+                view.setFrame(webview.bounds());
+            }
+
+            let delegate = mac::PrintDelegate::new(mtm, done);
+            let delegate_object: &AnyObject = &delegate;
+            // SAFETY: the delegate implements the selector with the documented
+            // signature and is kept alive in `ACTIVE_DELEGATE`.
+            unsafe {
+                operation.runOperationModalForWindow_delegate_didRunSelector_contextInfo(
+                    ns_window,
+                    Some(delegate_object),
+                    Some(sel!(printOperationDidRun:success:contextInfo:)),
+                    std::ptr::null_mut(),
+                );
+            }
+            mac::ACTIVE_DELEGATE.with(|active| active.replace(Some(delegate)));
+        })
+        .map_err(|err| err.to_string())
+}
+
+/// Prints through GTK's "Print to File" backend without showing the dialog.
+#[cfg(target_os = "linux")]
+fn start_export<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    target: PathBuf,
+    options: PdfOptions,
+    done: Done,
+) -> Result<(), String> {
+    use std::{cell::RefCell, rc::Rc};
+
+    use gtk::glib;
+    use webkit2gtk::{PrintOperation, PrintOperationExt, SettingsExt, WebViewExt};
+
+    let uri = url::Url::from_file_path(&target)
+        .map_err(|()| format!("Invalid export path: {}", target.display()))?
+        .to_string();
+    window
+        .with_webview(move |platform| {
+            let webview = platform.inner();
+            if let Some(settings) = WebViewExt::settings(&webview) {
+                settings.set_print_backgrounds(options.print_background);
+            }
+
+            let print_settings = gtk::PrintSettings::new();
+            // The file backend's printer is known by its translated name.
+            print_settings.set_printer(&glib::dgettext(Some("gtk30"), "Print to File"));
+            print_settings.set(gtk::PRINT_SETTINGS_OUTPUT_FILE_FORMAT, Some("pdf"));
+            print_settings.set(gtk::PRINT_SETTINGS_OUTPUT_URI, Some(&uri));
+
+            let page_setup = gtk::PageSetup::new();
+            page_setup.set_paper_size(&gtk::PaperSize::new(Some(options.page_size.gtk_name())));
+            page_setup.set_orientation(if options.landscape {
+                gtk::PageOrientation::Landscape
+            } else {
+                gtk::PageOrientation::Portrait
+            });
+
+            let operation = PrintOperation::new(&webview);
+            operation.set_print_settings(&print_settings);
+            operation.set_page_setup(&page_setup);
+            // `failed` is emitted before `finished`, so whichever comes first wins.
+            let done = Rc::new(RefCell::new(Some(done)));
+            let failed = Rc::clone(&done);
+            operation.connect_failed(move |_, err| {
+                if let Some(done) = failed.borrow_mut().take() {
+                    let _ = done.send(Err(err.to_string()));
+                }
+            });
+            operation.connect_finished(move |_| {
+                if let Some(done) = done.borrow_mut().take() {
+                    let _ = done.send(Ok(()));
+                }
+            });
+            operation.print();
+        })
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn start_export<R: tauri::Runtime>(
+    _window: &tauri::WebviewWindow<R>,
+    _target: PathBuf,
+    _options: PdfOptions,
+    _done: Done,
+) -> Result<(), String> {
+    Err("PDF export is not supported on this platform".to_string())
+}
+
+/// Asks where to save, then prints `label` to that PDF. Returns the saved path,
+/// or `None` if the save dialog was cancelled.
+async fn export<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    label: &str,
+    options: PdfOptions,
+) -> Result<Option<String>, String> {
+    let window = app
+        .get_webview_window(label)
+        .ok_or_else(|| format!("No window labelled {label}"))?;
+
+    let default_name = options.file_name.clone().unwrap_or_else(|| {
+        format!(
+            "openchamber-{}.pdf",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )
+    });
+    let dialog_app = app.clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .set_file_name(default_name)
+            .add_filter("PDF document", &["pdf"])
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let target = picked.into_path().map_err(|err| err.to_string())?;
+
+    let (done, finished) = oneshot::channel();
+    let result = match start_export(&window, target.clone(), options, done) {
+        Ok(()) => finished
+            .await
+            .unwrap_or_else(|_| Err("PDF export was interrupted".to_string())),
+        Err(err) => Err(err),
+    };
+    audit::record(
+        app,
+        AuditCategory::FileWrite,
+        "export pdf",
+        target.to_string_lossy(),
+        &result,
+    );
+    result?;
+
+    log::info!("[pdf-export] saved {}", target.display());
+    Ok(Some(target.to_string_lossy().to_string()))
+}
+
+/// File → Export as PDF… on the focused window, with default options.
+pub(crate) fn export_focused_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let label = app
+        .webview_windows()
+        .into_iter()
+        .find(|(_, window)| window.is_focused().unwrap_or(false))
+        .map(|(label, _)| label)
+        .unwrap_or_else(|| "main".to_string());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = export(&app, &label, PdfOptions::default()).await {
+            log::warn!("[pdf-export] export of {label} failed: {err}");
+        }
+    });
+}
+
+/// Saves a window's content (the calling one when `label` is omitted) as a PDF,
+/// e.g. a session transcript or a diff, after asking where to put it.
+#[tauri::command]
+pub(crate) async fn desktop_print_to_pdf(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    label: Option<String>,
+    options: Option<PdfOptions>,
+) -> Result<Option<String>, String> {
+    crate::origin::require_local(&webview, "export PDFs")?;
+    let label = label.unwrap_or_else(|| webview.label().to_string());
+    export(&app, &label, options.unwrap_or_default()).await
+}
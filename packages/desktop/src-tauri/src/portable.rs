@@ -0,0 +1,57 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const PORTABLE_FLAG: &str = "--portable";
+/// An empty file with this name next to the executable turns portable mode on.
+const MARKER_FILE_NAME: &str = "portable";
+const DATA_DIR_NAME: &str = "data";
+
+/// `data/` beside the executable when running portable.
+static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn executable_dir() -> Option<PathBuf> {
+    // An AppImage runs from a temporary mount; the image file itself is what moves around.
+    let exe = env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .or_else(|| env::current_exe().ok())?;
+    exe.parent().map(Path::to_path_buf)
+}
+
+/// Detects portable mode from `--portable` or the marker file. Runs at the start of
+/// `main`, before [`crate::profiles::init`], so profiles nest inside the portable
+/// data dir. The sidecar follows `OPENCHAMBER_DATA_DIR` into `data/openchamber`.
+pub(crate) fn init() {
+    let requested = env::args().skip(1).any(|arg| arg == PORTABLE_FLAG);
+    let root = executable_dir()
+        .filter(|dir| requested || dir.join(MARKER_FILE_NAME).is_file())
+        .map(|dir| dir.join(DATA_DIR_NAME));
+    if let Some(root) = &root {
+        env::set_var("OPENCHAMBER_DATA_DIR", root.join("openchamber"));
+    }
+    let _ = ROOT.set(root);
+}
+
+pub(crate) fn root() -> Option<&'static Path> {
+    ROOT.get().and_then(Option::as_deref)
+}
+
+pub(crate) fn is_enabled() -> bool {
+    root().is_some()
+}
+
+/// Replaces the app config and data dirs; their file names do not overlap.
+pub(crate) fn app_dir() -> Option<PathBuf> {
+    root().map(|root| root.join("app"))
+}
+
+pub(crate) fn log_dir() -> Option<PathBuf> {
+    root().map(|root| root.join("logs"))
+}
+
+/// Cookies and local storage of the main window.
+pub(crate) fn webview_data_dir() -> Option<PathBuf> {
+    root().map(|root| root.join("webview"))
+}
@@ -0,0 +1,204 @@
+use std::{net::UdpSocket, time::Duration};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::events::{self, DesktopEvent};
+
+const POWER_MONITOR_INTERVAL: Duration = Duration::from_secs(30);
+/// Battery level (percent) at or below which running on battery counts as low.
+const LOW_BATTERY_PERCENT: u8 = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "kebab-case")]
+enum PowerStatus {
+    PluggedIn,
+    OnBattery,
+    LowBattery,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PowerInfo {
+    status: PowerStatus,
+    /// `None` on machines without a battery.
+    battery_percent: Option<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NetworkInfo {
+    online: bool,
+    /// `None` when the platform does not report whether the connection is metered.
+    metered: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DesktopPowerState {
+    power: PowerInfo,
+    network: NetworkInfo,
+}
+
+impl PowerInfo {
+    fn new(on_battery: bool, battery_percent: Option<u8>) -> Self {
+        let status = match (on_battery, battery_percent) {
+            (false, _) => PowerStatus::PluggedIn,
+            (true, Some(percent)) if percent <= LOW_BATTERY_PERCENT => PowerStatus::LowBattery,
+            (true, _) => PowerStatus::OnBattery,
+        };
+        Self {
+            status,
+            battery_percent,
+        }
+    }
+}
+
+/// Parses `pmset -g batt`, e.g. "Now drawing from 'Battery Power'" / "\t-InternalBattery-0 (id=...)\t84%; discharging".
+#[cfg(target_os = "macos")]
+fn read_power() -> PowerInfo {
+    let output = std::process::Command::new("/usr/bin/pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    let on_battery = output.contains("'Battery Power'");
+    let battery_percent = output
+        .lines()
+        .filter(|line| line.contains("InternalBattery"))
+        .find_map(|line| {
+            let end = line.find('%')?;
+            let start = line[..end]
+                .rfind(|c: char| !c.is_ascii_digit())
+                .map_or(0, |index| index + 1);
+            line[start..end].parse().ok()
+        });
+    PowerInfo::new(on_battery, battery_percent)
+}
+
+#[cfg(windows)]
+fn read_power() -> PowerInfo {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // BatteryFlag 128 = no system battery; BatteryLifePercent 255 = unknown.
+    const NO_BATTERY: u8 = 128;
+    const UNKNOWN_PERCENT: u8 = 255;
+
+    // SAFETY: `status` is a valid out pointer for the duration of the call.
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerInfo::new(false, None);
+    }
+    let has_battery = status.BatteryFlag & NO_BATTERY == 0;
+    let battery_percent = (has_battery && status.BatteryLifePercent != UNKNOWN_PERCENT)
+        .then_some(status.BatteryLifePercent);
+    PowerInfo::new(has_battery && status.ACLineStatus == 0, battery_percent)
+}
+
+/// Reads `/sys/class/power_supply`; a machine is on battery when no mains
+/// supply is online but a battery is present.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_power() -> PowerInfo {
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut mains_online = false;
+    let mut battery_percent = None;
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            match read(dir.join("type")).as_str() {
+                "Mains" | "USB" => mains_online |= read(dir.join("online")) == "1",
+                "Battery" if read(dir.join("scope")) != "Device" => {
+                    battery_percent = battery_percent.or(read(dir.join("capacity")).parse().ok());
+                }
+                _ => {}
+            }
+        }
+    }
+    PowerInfo::new(battery_percent.is_some() && !mains_online, battery_percent)
+}
+
+/// True when the OS has a route to the internet. Connecting a UDP socket sends
+/// nothing; it only resolves a route.
+fn has_route() -> bool {
+    UdpSocket::bind(("0.0.0.0", 0))
+        .and_then(|socket| socket.connect(("1.1.1.1", 53)))
+        .is_ok()
+}
+
+/// Asks NetworkManager for its global metered state.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_metered() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    // Prints e.g. "u 4"; NMMetered: 1 yes, 2 no, 3 guess-yes, 4 guess-no.
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "u 1" | "u 3" => Some(true),
+        "u 2" | "u 4" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn read_metered() -> Option<bool> {
+    None
+}
+
+fn read_network() -> NetworkInfo {
+    let online = has_route();
+    NetworkInfo {
+        online,
+        metered: online.then(read_metered).flatten(),
+    }
+}
+
+/// Samples power and network state, emitting `openchamber:power` and
+/// `openchamber:network` whenever either changes.
+pub(crate) fn spawn_power_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_power: Option<PowerInfo> = None;
+        let mut last_network: Option<NetworkInfo> = None;
+
+        loop {
+            let sampled = tauri::async_runtime::spawn_blocking(|| (read_power(), read_network()));
+            if let Ok((power, network)) = sampled.await {
+                if last_power.as_ref() != Some(&power) {
+                    log::info!("[power] {:?}", power.status);
+                    events::emit(&app, DesktopEvent::Power(power.clone()));
+                    last_power = Some(power);
+                }
+                if last_network.as_ref() != Some(&network) {
+                    log::info!(
+                        "[power] network online={} metered={:?}",
+                        network.online,
+                        network.metered
+                    );
+                    events::emit(&app, DesktopEvent::Network(network.clone()));
+                    last_network = Some(network);
+                }
+            }
+            tokio::time::sleep(POWER_MONITOR_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_get_power_state() -> Result<DesktopPowerState, String> {
+    tauri::async_runtime::spawn_blocking(|| DesktopPowerState {
+        power: read_power(),
+        network: read_network(),
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
@@ -0,0 +1,117 @@
+use std::{
+    env,
+    fs::{self, File},
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+use crate::{openchamber_data_dir, portable};
+
+const PROFILE_FLAG: &str = "--profile";
+const DEFAULT_PROFILE: &str = "default";
+pub(crate) const PROFILES_DIR_NAME: &str = "profiles";
+const INSTANCE_LOCK_FILE_NAME: &str = "instance.lock";
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+/// Profile selected with `--profile <name>`; `None` for the default profile.
+static ACTIVE: OnceLock<Option<String>> = OnceLock::new();
+/// Held for the lifetime of the process so a profile runs at most once.
+static INSTANCE_LOCK: OnceLock<File> = OnceLock::new();
+
+fn validate_name(raw: &str) -> Result<Option<String>, String> {
+    let name = raw.trim().to_ascii_lowercase();
+    if name.is_empty() {
+        return Err(format!("{PROFILE_FLAG} needs a name"));
+    }
+    if name.len() > MAX_PROFILE_NAME_LEN
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid profile name {raw:?}: use up to {MAX_PROFILE_NAME_LEN} letters, digits, '-' or '_'"
+        ));
+    }
+    Ok((name != DEFAULT_PROFILE).then_some(name))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<String>, String> {
+    let mut profile = None;
+    while let Some(arg) = args.next() {
+        if arg == PROFILE_FLAG {
+            profile = validate_name(&args.next().unwrap_or_default())?;
+        } else if let Some(value) = arg
+            .strip_prefix(PROFILE_FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            profile = validate_name(value)?;
+        }
+    }
+    Ok(profile)
+}
+
+/// Reads `--profile <name>` from the command line. Must run at the very start of
+/// `main`, before any thread exists: a named profile points `OPENCHAMBER_DATA_DIR`
+/// at its own directory, which both the desktop settings file and the sidecar
+/// (inheriting our environment) follow.
+pub(crate) fn init() -> Result<(), String> {
+    let profile = parse_args(env::args().skip(1))?;
+    if let Some(name) = &profile {
+        let dir = openchamber_data_dir().join(PROFILES_DIR_NAME).join(name);
+        env::set_var("OPENCHAMBER_DATA_DIR", &dir);
+    }
+    let _ = ACTIVE.set(profile);
+    Ok(())
+}
+
+pub(crate) fn active() -> Option<&'static str> {
+    ACTIVE.get().and_then(Option::as_deref)
+}
+
+/// The active profile's name, `default` included.
+pub(crate) fn name() -> &'static str {
+    active().unwrap_or(DEFAULT_PROFILE)
+}
+
+/// Maps one of the app's config/data dirs to the active profile's copy of it,
+/// inside the portable data dir when running portable.
+pub(crate) fn scoped(dir: PathBuf) -> PathBuf {
+    let dir = portable::app_dir().unwrap_or(dir);
+    match active() {
+        Some(name) => dir.join(PROFILES_DIR_NAME).join(name),
+        None => dir,
+    }
+}
+
+pub(crate) fn window_title() -> String {
+    let title = match active() {
+        Some(name) => format!("OpenChamber ({name})"),
+        None => "OpenChamber".to_string(),
+    };
+    if crate::safe_mode::is_enabled() {
+        format!("{title} [Safe Mode]")
+    } else {
+        title
+    }
+}
+
+/// Named profiles skip the single-instance plugin, which is keyed by the bundle
+/// identifier and would forward them to whichever profile is already running.
+/// Instead an exclusive lock in the profile's data dir keeps each profile to one
+/// process. Returns `false` if the profile is already running.
+pub(crate) fn acquire_instance_lock() -> Result<bool, String> {
+    if active().is_none() {
+        return Ok(true);
+    }
+    let dir = openchamber_data_dir();
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let file = File::create(dir.join(INSTANCE_LOCK_FILE_NAME)).map_err(|err| err.to_string())?;
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = INSTANCE_LOCK.set(file);
+            Ok(true)
+        }
+        Err(fs::TryLockError::WouldBlock) => Ok(false),
+        Err(fs::TryLockError::Error(err)) => Err(err.to_string()),
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use tauri::{
+    window::{ProgressBarState, ProgressBarStatus},
+    Manager,
+};
+
+/// State of the taskbar (Windows, Linux/Unity) or dock (macOS) progress indicator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TaskProgress {
+    None,
+    Indeterminate,
+    Percent(u64),
+    Error,
+}
+
+impl TaskProgress {
+    /// Percent for a byte transfer, or indeterminate when the total is unknown.
+    pub(crate) fn from_bytes(done: u64, total: Option<u64>) -> Self {
+        match total {
+            Some(total) if total > 0 => Self::Percent((done.saturating_mul(100) / total).min(100)),
+            _ => Self::Indeterminate,
+        }
+    }
+}
+
+pub(crate) fn set_progress<R: tauri::Runtime>(app: &tauri::AppHandle<R>, progress: TaskProgress) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let (status, value) = match progress {
+        TaskProgress::None => (ProgressBarStatus::None, None),
+        TaskProgress::Indeterminate => (ProgressBarStatus::Indeterminate, None),
+        TaskProgress::Percent(percent) => (ProgressBarStatus::Normal, Some(percent.min(100))),
+        TaskProgress::Error => (ProgressBarStatus::Error, Some(100)),
+    };
+    if let Err(err) = window.set_progress_bar(ProgressBarState {
+        status: Some(status),
+        progress: value,
+    }) {
+        log::debug!("[progress] failed to set progress bar: {err}");
+    }
+}
+
+/// `desktop_set_progress` accepts a number (0-100), a mode string, or `null`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ProgressInput {
+    Percent(f64),
+    Mode(String),
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_progress(
+    app: tauri::AppHandle,
+    progress: Option<ProgressInput>,
+) -> Result<(), String> {
+    let progress = match progress {
+        None => TaskProgress::None,
+        Some(ProgressInput::Percent(percent)) if percent.is_finite() => {
+            TaskProgress::Percent(percent.clamp(0.0, 100.0).round() as u64)
+        }
+        Some(ProgressInput::Percent(_)) => return Err("Progress must be a finite number".into()),
+        Some(ProgressInput::Mode(mode)) => match mode.trim() {
+            "none" => TaskProgress::None,
+            "indeterminate" => TaskProgress::Indeterminate,
+            "error" => TaskProgress::Error,
+            other => return Err(format!("Unknown progress mode: {other}")),
+        },
+    };
+    set_progress(&app, progress);
+    Ok(())
+}
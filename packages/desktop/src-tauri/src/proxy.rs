@@ -0,0 +1,316 @@
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::{async_runtime::JoinHandle, Manager};
+use url::Url;
+
+use crate::{secrets, settings};
+
+/// Settings-store key holding a [`ProxySettings`] object.
+pub(crate) const PROXY_SETTINGS_KEY: &str = "proxy";
+/// Global secret holding the proxy password, kept out of the settings file.
+pub(crate) const PROXY_PASSWORD_SECRET: &str = "proxyPassword";
+
+/// Loopback traffic (sidecar, tunnels, dev server) never goes through a proxy.
+const LOOPBACK_NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ProxyMode {
+    /// Proxy from `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY`, else the OS network settings.
+    #[default]
+    System,
+    /// Explicit `http://`, `https://` or `socks5://` URL.
+    Manual,
+    None,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProxySettings {
+    mode: ProxyMode,
+    url: Option<String>,
+    username: Option<String>,
+    /// Extra comma-separated hosts to reach directly.
+    no_proxy: Option<String>,
+}
+
+/// The proxy to use for outgoing requests, resolved from settings and the OS.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProxyConfig {
+    /// Proxy URL including credentials; `None` means connect directly.
+    url: Option<Url>,
+    no_proxy: String,
+    /// Whether the user explicitly asked for direct connections.
+    disabled: bool,
+}
+
+/// The last resolved [`ProxyConfig`], so requests don't query the OS every time.
+#[derive(Default)]
+pub(crate) struct ProxyState {
+    resolved: Mutex<Option<ProxyConfig>>,
+    /// Held while resolving, so the last refresh to finish read the newest settings.
+    refreshing: Mutex<()>,
+}
+
+fn env_proxy() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+    ]
+    .iter()
+    .find_map(|name| std::env::var(name).ok())
+    .filter(|value| !value.trim().is_empty())
+}
+
+fn env_no_proxy() -> Option<String> {
+    ["NO_PROXY", "no_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Reads the HTTPS (else HTTP, else SOCKS) proxy from `scutil --proxy`.
+#[cfg(target_os = "macos")]
+fn os_proxy() -> Option<String> {
+    let output = std::process::Command::new("/usr/sbin/scutil")
+        .arg("--proxy")
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| {
+        text.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        })
+    };
+    [("HTTPS", "http"), ("HTTP", "http"), ("SOCKS", "socks5")]
+        .iter()
+        .find_map(|(prefix, scheme)| {
+            if value(&format!("{prefix}Enable")).as_deref() != Some("1") {
+                return None;
+            }
+            let host = value(&format!("{prefix}Proxy"))?;
+            let port = value(&format!("{prefix}Port"))?;
+            Some(format!("{scheme}://{host}:{port}"))
+        })
+}
+
+/// Reads the WinINET proxy (`ProxyEnable`/`ProxyServer`) from the registry.
+#[cfg(windows)]
+fn os_proxy() -> Option<String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+
+    let query = |name: &str| {
+        let output = std::process::Command::new("reg")
+            .args(["query", KEY, "/v", name])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout).to_string();
+        text.lines()
+            .find(|line| line.trim_start().starts_with(name))
+            .and_then(|line| line.split_whitespace().last().map(str::to_string))
+    };
+    if query("ProxyEnable").as_deref() != Some("0x1") {
+        return None;
+    }
+    // Either "host:port" or per-protocol "http=host:port;https=host:port".
+    let server = query("ProxyServer")?;
+    let chosen = if server.contains('=') {
+        server
+            .split(';')
+            .filter_map(|entry| entry.split_once('='))
+            .find(|(scheme, _)| *scheme == "https" || *scheme == "http")
+            .map(|(_, address)| address.to_string())?
+    } else {
+        server
+    };
+    Some(format!("http://{chosen}"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn os_proxy() -> Option<String> {
+    None
+}
+
+pub(crate) fn parse_proxy_url(raw: &str) -> Result<Url, String> {
+    let raw = raw.trim();
+    let with_scheme = if raw.contains("://") {
+        raw.to_string()
+    } else {
+        format!("http://{raw}")
+    };
+    let url = Url::parse(&with_scheme).map_err(|err| format!("Invalid proxy URL: {err}"))?;
+    match url.scheme() {
+        "http" | "https" | "socks5" | "socks5h" => Ok(url),
+        scheme => Err(format!("Unsupported proxy scheme: {scheme}")),
+    }
+}
+
+fn read_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> ProxySettings {
+    settings::get_setting(app, PROXY_SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// The effective proxy, as of the last [`refresh`]. Only resolves on the spot when
+/// called before the first refresh has finished.
+pub(crate) fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> ProxyConfig {
+    let Some(state) = app.try_state::<ProxyState>() else {
+        return resolve(app);
+    };
+    let cached = state.resolved.lock().expect("proxy mutex").clone();
+    cached.unwrap_or_else(|| {
+        let config = resolve(app);
+        *state.resolved.lock().expect("proxy mutex") = Some(config.clone());
+        config
+    })
+}
+
+/// Resolves the proxy again on a blocking thread, since that may run `scutil` or
+/// `reg`. Runs at startup and whenever the `proxy` setting or the proxy password
+/// changes; requests keep using the previous config until it finishes.
+pub(crate) fn refresh<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> JoinHandle<()> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(state) = app.try_state::<ProxyState>() else {
+            return;
+        };
+        let _refreshing = state.refreshing.lock().expect("proxy refresh mutex");
+        let config = resolve(&app);
+        *state.resolved.lock().expect("proxy mutex") = Some(config);
+    })
+}
+
+/// Called when the `proxy` setting or the proxy password changed.
+pub(crate) fn invalidate<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    drop(refresh(app));
+}
+
+/// Reads the settings, the environment and the OS configuration; blocking.
+fn resolve<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> ProxyConfig {
+    let settings = read_settings(app);
+    let mut no_proxy = LOOPBACK_NO_PROXY.to_string();
+
+    let raw_url = match settings.mode {
+        ProxyMode::None => {
+            return ProxyConfig {
+                url: None,
+                no_proxy,
+                disabled: true,
+            }
+        }
+        ProxyMode::Manual => settings.url.clone().filter(|url| !url.trim().is_empty()),
+        ProxyMode::System => {
+            if let Some(extra) = env_no_proxy() {
+                no_proxy.push(',');
+                no_proxy.push_str(&extra);
+            }
+            env_proxy().or_else(os_proxy)
+        }
+    };
+    if let Some(extra) = settings
+        .no_proxy
+        .as_deref()
+        .filter(|v| !v.trim().is_empty())
+    {
+        no_proxy.push(',');
+        no_proxy.push_str(extra.trim());
+    }
+
+    let url = raw_url.and_then(|raw| match parse_proxy_url(&raw) {
+        Ok(url) => Some(url),
+        Err(err) => {
+            log::warn!("[proxy] ignoring proxy: {err}");
+            None
+        }
+    });
+    let url = url.map(|mut url| {
+        if let Some(username) = settings.username.as_deref().filter(|u| !u.is_empty()) {
+            let _ = url.set_username(username);
+            if let Ok(Some(password)) = secrets::get_secret(app, None, PROXY_PASSWORD_SECRET) {
+                let _ = url.set_password(Some(&password));
+            }
+        }
+        url
+    });
+
+    ProxyConfig {
+        url,
+        no_proxy,
+        disabled: false,
+    }
+}
+
+impl ProxyConfig {
+    pub(crate) fn url(&self) -> Option<&Url> {
+        self.url.as_ref()
+    }
+
+    /// Configures a reqwest client to use this proxy (or none at all).
+    pub(crate) fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        let Some(url) = self.url.as_ref() else {
+            return builder.no_proxy();
+        };
+        match reqwest::Proxy::all(url.as_str()) {
+            Ok(proxy) => builder
+                .no_proxy()
+                .proxy(proxy.no_proxy(reqwest::NoProxy::from_string(&self.no_proxy))),
+            Err(err) => {
+                log::warn!("[proxy] failed to configure proxy: {err}");
+                builder.no_proxy()
+            }
+        }
+    }
+
+    /// Proxy environment for child processes such as the sidecar.
+    pub(crate) fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("NO_PROXY", self.no_proxy.clone()),
+            ("no_proxy", self.no_proxy.clone()),
+        ];
+        let proxy = match (&self.url, self.disabled) {
+            (Some(url), _) => url.to_string(),
+            // Blank out proxies inherited from the launch environment.
+            (None, true) => String::new(),
+            (None, false) => return vars,
+        };
+        for name in [
+            "HTTP_PROXY",
+            "http_proxy",
+            "HTTPS_PROXY",
+            "https_proxy",
+            "ALL_PROXY",
+            "all_proxy",
+        ] {
+            vars.push((name, proxy.clone()));
+        }
+        vars
+    }
+}
+
+/// Replaces the `proxy` setting (`null` clears it) after the user confirms.
+#[tauri::command]
+pub(crate) async fn desktop_set_proxy(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    proxy: serde_json::Value,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change the proxy")?;
+    if !proxy.is_null() {
+        let parsed = serde_json::from_value::<ProxySettings>(proxy.clone())
+            .map_err(|err| format!("Invalid proxy setting: {err}"))?;
+        if let (ProxyMode::Manual, Some(url)) = (parsed.mode, parsed.url.as_deref()) {
+            parse_proxy_url(url)?;
+        }
+    }
+    settings::set_protected_setting(&app, &webview, PROXY_SETTINGS_KEY, proxy).await
+}
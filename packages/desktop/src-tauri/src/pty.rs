@@ -0,0 +1,331 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::{
+    audit::{self, AuditCategory},
+    trust,
+};
+
+// Per-session output kept in Rust so a reloaded Terminal tab can repaint its history.
+const SCROLLBACK_LIMIT_BYTES: usize = 512 * 1024;
+const READ_BUFFER_BYTES: usize = 16 * 1024;
+
+struct PtySession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    scrollback: Arc<Mutex<String>>,
+}
+
+/// Local shells running in pseudo-terminals owned by the desktop process.
+#[derive(Default)]
+pub(crate) struct PtyState {
+    sessions: Mutex<HashMap<String, PtySession>>,
+    next_id: AtomicU64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PtySessionInfo {
+    id: String,
+    pid: Option<u32>,
+    data_event: String,
+    exit_event: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PtyExitEvent {
+    id: String,
+    exit_code: Option<u32>,
+    signal: Option<String>,
+}
+
+fn data_event_name(id: &str) -> String {
+    format!("openchamber:pty-data:{id}")
+}
+
+fn exit_event_name(id: &str) -> String {
+    format!("openchamber:pty-exit:{id}")
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+fn append_scrollback(scrollback: &Mutex<String>, chunk: &str) {
+    let mut buffer = scrollback.lock().expect("pty scrollback mutex");
+    buffer.push_str(chunk);
+    if buffer.len() > SCROLLBACK_LIMIT_BYTES {
+        let mut cut = buffer.len() - SCROLLBACK_LIMIT_BYTES;
+        while !buffer.is_char_boundary(cut) {
+            cut += 1;
+        }
+        buffer.drain(..cut);
+    }
+}
+
+/// Length of the prefix of `buf` that can be decoded without splitting a UTF-8 sequence.
+fn complete_utf8_len(buf: &[u8]) -> usize {
+    match std::str::from_utf8(buf) {
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        _ => buf.len(),
+    }
+}
+
+/// Output goes only to `label`, the webview that spawned the shell.
+fn spawn_reader(
+    app: tauri::AppHandle,
+    label: String,
+    id: String,
+    mut reader: Box<dyn Read + Send>,
+    scrollback: Arc<Mutex<String>>,
+) -> std::io::Result<()> {
+    thread::Builder::new()
+        .name(format!("{id}-reader"))
+        .spawn(move || {
+            let event = data_event_name(&id);
+            let mut buf = vec![0u8; READ_BUFFER_BYTES];
+            let mut pending: Vec<u8> = Vec::new();
+            loop {
+                let read = match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => read,
+                };
+                pending.extend_from_slice(&buf[..read]);
+                let complete = complete_utf8_len(&pending);
+                if complete == 0 {
+                    continue;
+                }
+                let chunk = String::from_utf8_lossy(&pending[..complete]).to_string();
+                pending.drain(..complete);
+
+                append_scrollback(&scrollback, &chunk);
+                let _ = app.emit_to(label.as_str(), &event, chunk);
+            }
+        })
+        .map(|_| ())
+}
+
+fn spawn_waiter(
+    app: tauri::AppHandle,
+    label: String,
+    id: String,
+    mut child: Box<dyn portable_pty::Child + Send + Sync>,
+) -> std::io::Result<()> {
+    thread::Builder::new()
+        .name(format!("{id}-waiter"))
+        .spawn(move || {
+            let status = child.wait().ok();
+            if let Some(state) = app.try_state::<PtyState>() {
+                state
+                    .sessions
+                    .lock()
+                    .expect("pty sessions mutex")
+                    .remove(&id);
+            }
+            log::info!("[pty] {id} exited");
+            let _ = app.emit_to(
+                label.as_str(),
+                &exit_event_name(&id),
+                PtyExitEvent {
+                    id: id.clone(),
+                    exit_code: status.as_ref().map(|s| s.exit_code()),
+                    signal: status.as_ref().and_then(|s| s.signal().map(str::to_string)),
+                },
+            );
+        })
+        .map(|_| ())
+}
+
+/// The directory the shell starts in: `cwd`, which must exist, else the home dir.
+fn resolve_cwd(cwd: Option<String>) -> Result<String, String> {
+    let dir = match cwd.filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => dir,
+        None => std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+            .map_err(|_| "Could not find the home directory".to_string())?,
+    };
+    if !std::path::Path::new(&dir).is_dir() {
+        return Err(format!("Not a directory: {dir}"));
+    }
+    Ok(dir)
+}
+
+fn build_command(shell: Option<String>, cwd: &str) -> CommandBuilder {
+    let mut cmd = match shell.filter(|s| !s.trim().is_empty()) {
+        Some(shell) => CommandBuilder::new(shell.trim()),
+        None => CommandBuilder::new_default_prog(),
+    };
+    cmd.cwd(cwd);
+
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+    cmd.env("TERM_PROGRAM", "OpenChamber");
+    cmd
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_pty_spawn(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, PtyState>,
+    cols: u16,
+    rows: u16,
+    cwd: Option<String>,
+    shell: Option<String>,
+) -> Result<PtySessionInfo, String> {
+    crate::origin::require_local(&webview, "spawn a terminal")?;
+    let cwd = resolve_cwd(cwd)?;
+    trust::require_trusted(&app, &cwd).await?;
+    let pair = native_pty_system()
+        .openpty(pty_size(cols, rows))
+        .map_err(|err| err.to_string())?;
+    let audit_target = format!("{} in {cwd}", shell.as_deref().unwrap_or("default shell"));
+    let child = pair
+        .slave
+        .spawn_command(build_command(shell, &cwd))
+        .map_err(|err| err.to_string());
+    audit::record(
+        &app,
+        AuditCategory::Process,
+        "spawn terminal",
+        audit_target,
+        &child,
+    );
+    let child = child?;
+    // The child holds its own handle to the slave side; closing ours lets reads hit EOF on exit.
+    drop(pair.slave);
+
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|err| err.to_string())?;
+    let writer = pair.master.take_writer().map_err(|err| err.to_string())?;
+
+    let id = format!("pty-{}", state.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    let pid = child.process_id();
+    let scrollback = Arc::new(Mutex::new(String::new()));
+
+    state.sessions.lock().expect("pty sessions mutex").insert(
+        id.clone(),
+        PtySession {
+            master: pair.master,
+            writer,
+            killer: child.clone_killer(),
+            scrollback: scrollback.clone(),
+        },
+    );
+
+    let label = webview.label().to_string();
+    spawn_reader(app.clone(), label.clone(), id.clone(), reader, scrollback)
+        .map_err(|err| err.to_string())?;
+    spawn_waiter(app, label, id.clone(), child).map_err(|err| err.to_string())?;
+
+    log::info!("[pty] spawned {id} pid={pid:?}");
+    Ok(PtySessionInfo {
+        data_event: data_event_name(&id),
+        exit_event: exit_event_name(&id),
+        id,
+        pid,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn desktop_pty_write(
+    webview: tauri::Webview,
+    state: tauri::State<'_, PtyState>,
+    id: String,
+    data: String,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "write to a terminal")?;
+    let mut sessions = state.sessions.lock().expect("pty sessions mutex");
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| format!("Unknown terminal session: {id}"))?;
+    session
+        .writer
+        .write_all(data.as_bytes())
+        .and_then(|_| session.writer.flush())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_pty_resize(
+    webview: tauri::Webview,
+    state: tauri::State<'_, PtyState>,
+    id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "resize a terminal")?;
+    let sessions = state.sessions.lock().expect("pty sessions mutex");
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| format!("Unknown terminal session: {id}"))?;
+    session
+        .master
+        .resize(pty_size(cols, rows))
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_pty_kill(
+    webview: tauri::Webview,
+    state: tauri::State<'_, PtyState>,
+    id: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "close a terminal")?;
+    let mut sessions = state.sessions.lock().expect("pty sessions mutex");
+    let Some(session) = sessions.get_mut(&id) else {
+        return Ok(false);
+    };
+    // The waiter thread removes the session and emits the exit event.
+    session.killer.kill().map_err(|err| err.to_string())?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_pty_scrollback(
+    webview: tauri::Webview,
+    state: tauri::State<'_, PtyState>,
+    id: String,
+) -> Result<String, String> {
+    crate::origin::require_local(&webview, "read terminal output")?;
+    let sessions = state.sessions.lock().expect("pty sessions mutex");
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| format!("Unknown terminal session: {id}"))?;
+    let scrollback = session
+        .scrollback
+        .lock()
+        .expect("pty scrollback mutex")
+        .clone();
+    Ok(scrollback)
+}
+
+/// Kills every shell on app exit so none outlive the window.
+pub(crate) fn kill_all_ptys<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<PtyState>() else {
+        return;
+    };
+    let mut sessions = state.sessions.lock().expect("pty sessions mutex");
+    for (_, mut session) in sessions.drain() {
+        let _ = session.killer.kill();
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    i18n, keep_awake,
+};
+
+/// How long the webview gets to report its live session count before the last
+/// count pushed through `desktop_set_busy_state` is used instead.
+const BUSY_CHECK_TIMEOUT: Duration = Duration::from_millis(1500);
+/// A confirmed quit lets the close/exit events it triggers through for this long.
+const CONFIRMATION_VALIDITY: Duration = Duration::from_secs(5);
+
+/// Running agent sessions as reported by the web UI, plus the state of an open prompt.
+#[derive(Default)]
+pub(crate) struct QuitGuardState {
+    busy_count: Mutex<u32>,
+    confirmed_at: Mutex<Option<Instant>>,
+    prompting: AtomicBool,
+    next_check_id: AtomicU64,
+    pending_checks: Mutex<HashMap<u64, oneshot::Sender<u32>>>,
+}
+
+impl QuitGuardState {
+    fn busy_count(&self) -> u32 {
+        *self.busy_count.lock().expect("quit guard busy mutex")
+    }
+
+    fn is_confirmed(&self) -> bool {
+        self.confirmed_at
+            .lock()
+            .expect("quit guard confirmation mutex")
+            .is_some_and(|at| at.elapsed() < CONFIRMATION_VALIDITY)
+    }
+
+    fn confirm(&self) {
+        *self
+            .confirmed_at
+            .lock()
+            .expect("quit guard confirmation mutex") = Some(Instant::now());
+    }
+
+    /// True when quitting should be held back for a confirmation.
+    fn should_intercept(&self) -> bool {
+        !self.is_confirmed() && self.busy_count() > 0
+    }
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuitCheckEvent {
+    request_id: u64,
+}
+
+/// What to do once the user confirms.
+enum PendingQuit {
+    Exit(i32),
+    CloseWindow(String),
+}
+
+/// Asks the webview for its live session count, falling back to the last pushed count.
+async fn current_busy_count(app: &tauri::AppHandle) -> u32 {
+    let state = app.state::<QuitGuardState>();
+    let request_id = state.next_check_id.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = oneshot::channel();
+    state
+        .pending_checks
+        .lock()
+        .expect("quit guard pending mutex")
+        .insert(request_id, tx);
+
+    events::emit(app, DesktopEvent::QuitCheck(QuitCheckEvent { request_id }));
+    let reply = tokio::time::timeout(BUSY_CHECK_TIMEOUT, rx).await;
+    state
+        .pending_checks
+        .lock()
+        .expect("quit guard pending mutex")
+        .remove(&request_id);
+
+    match reply {
+        Ok(Ok(count)) => count,
+        _ => {
+            log::info!("[quit-guard] no quit-check reply; using last reported busy count");
+            state.busy_count()
+        }
+    }
+}
+
+fn confirm_with_dialog(app: &tauri::AppHandle, count: u32) -> bool {
+    let message = if count == 1 {
+        i18n::t(app, "dialog.quit.messageOne")
+    } else {
+        i18n::t_args(
+            app,
+            "dialog.quit.messageMany",
+            &[("count", &count.to_string())],
+        )
+    };
+    app.dialog()
+        .message(message)
+        .title(i18n::t(app, "dialog.quit.title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.quit.confirm"),
+            i18n::t(app, "dialog.cancel"),
+        ))
+        .blocking_show()
+}
+
+fn spawn_confirmation(app: tauri::AppHandle, pending: PendingQuit) {
+    let state = app.state::<QuitGuardState>();
+    if state.prompting.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let count = current_busy_count(&app).await;
+        let proceed = if count == 0 {
+            true
+        } else {
+            let dialog_app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || confirm_with_dialog(&dialog_app, count))
+                .await
+                .unwrap_or(false)
+        };
+
+        let state = app.state::<QuitGuardState>();
+        state.prompting.store(false, Ordering::SeqCst);
+        if !proceed {
+            log::info!("[quit-guard] quit cancelled with {count} running session(s)");
+            return;
+        }
+
+        log::info!("[quit-guard] quitting with {count} running session(s)");
+        state.confirm();
+        match pending {
+            PendingQuit::Exit(code) => app.exit(code),
+            PendingQuit::CloseWindow(label) => {
+                if let Some(window) = app.get_webview_window(&label) {
+                    let _ = window.close();
+                }
+            }
+        }
+    });
+}
+
+/// `RunEvent::ExitRequested` hook. Returns `true` if the exit was held back for a
+/// confirmation; restarts (e.g. after an update) are never intercepted.
+pub(crate) fn intercept_exit(
+    app: &tauri::AppHandle,
+    code: Option<i32>,
+    api: &tauri::ExitRequestApi,
+) -> bool {
+    if code == Some(tauri::RESTART_EXIT_CODE) {
+        return false;
+    }
+    let Some(state) = app.try_state::<QuitGuardState>() else {
+        return false;
+    };
+    if !state.should_intercept() {
+        return false;
+    }
+    api.prevent_exit();
+    spawn_confirmation(app.clone(), PendingQuit::Exit(code.unwrap_or(0)));
+    true
+}
+
+/// Main-window `CloseRequested` hook, used when closing is not turned into a hide.
+/// Returns `true` if the close was held back for a confirmation.
+pub(crate) fn intercept_close(window: &tauri::Window, api: &tauri::CloseRequestApi) -> bool {
+    if window.label() != "main" {
+        return false;
+    }
+    let app = window.app_handle();
+    let Some(state) = app.try_state::<QuitGuardState>() else {
+        return false;
+    };
+    if !state.should_intercept() {
+        return false;
+    }
+    api.prevent_close();
+    spawn_confirmation(
+        app.clone(),
+        PendingQuit::CloseWindow(window.label().to_string()),
+    );
+    true
+}
+
+/// Reports how many agent sessions are running so quitting can ask for confirmation.
+/// Dropping to zero also lifts any sleep prevention.
+#[tauri::command]
+pub(crate) fn desktop_set_busy_state(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, QuitGuardState>,
+    count: u32,
+) -> Result<(), String> {
+    *state.busy_count.lock().expect("quit guard busy mutex") = count;
+    if count == 0 {
+        keep_awake::release(&app);
+    }
+    Ok(())
+}
+
+/// Answers an `openchamber:quit-check` event with the live session count.
+#[tauri::command]
+pub(crate) fn desktop_quit_check_reply(
+    state: tauri::State<'_, QuitGuardState>,
+    request_id: u64,
+    count: u32,
+) -> Result<(), String> {
+    *state.busy_count.lock().expect("quit guard busy mutex") = count;
+    if let Some(tx) = state
+        .pending_checks
+        .lock()
+        .expect("quit guard pending mutex")
+        .remove(&request_id)
+    {
+        let _ = tx.send(count);
+    }
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::menu::{MenuItem, MenuItemKind, PredefinedMenuItem, Submenu};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::events::{self, DesktopEvent};
+use crate::menu::{
+    find_app_menu_item, MENU_ITEM_CLEAR_RECENTS_ID, MENU_OPEN_RECENT_SUBMENU_ID,
+    RECENT_WORKSPACE_MENU_PREFIX,
+};
+use crate::{profiles, trust};
+
+const RECENTS_FILE_NAME: &str = "recent-workspaces.json";
+/// Unpinned entries kept; pinned entries never age out.
+const MAX_RECENT_WORKSPACES: usize = 15;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecentWorkspace {
+    path: String,
+    /// Milliseconds since the Unix epoch.
+    last_opened_at: u64,
+    #[serde(default)]
+    pinned: bool,
+}
+
+impl RecentWorkspace {
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Recently opened workspaces, loaded lazily from the app config dir.
+#[derive(Default)]
+pub(crate) struct RecentsState {
+    entries: Mutex<Option<Vec<RecentWorkspace>>>,
+}
+
+fn recents_file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(RECENTS_FILE_NAME))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub(crate) fn normalize_path(path: &str) -> Option<String> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let resolved = fs::canonicalize(trimmed).unwrap_or_else(|_| PathBuf::from(trimmed));
+    let resolved = resolved.to_string_lossy();
+    Some(
+        resolved
+            .strip_prefix(r"\\?\")
+            .unwrap_or(&resolved)
+            .trim_end_matches(['/', '\\'])
+            .to_string(),
+    )
+}
+
+/// Pinned first, then most recently opened.
+fn sort_and_trim(entries: &mut Vec<RecentWorkspace>) {
+    entries.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(b.last_opened_at.cmp(&a.last_opened_at))
+    });
+    let mut unpinned = 0;
+    entries.retain(|entry| {
+        if entry.pinned {
+            return true;
+        }
+        unpinned += 1;
+        unpinned <= MAX_RECENT_WORKSPACES
+    });
+}
+
+fn with_entries<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut Vec<RecentWorkspace>) -> (T, bool),
+) -> T {
+    let state = app.state::<RecentsState>();
+    let (result, snapshot) = {
+        let mut guard = state.entries.lock().expect("recents mutex");
+        let entries = guard.get_or_insert_with(|| {
+            recents_file_path(app)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|raw| serde_json::from_str(&raw).ok())
+                .unwrap_or_default()
+        });
+        let (result, changed) = f(entries);
+        if changed {
+            sort_and_trim(entries);
+        }
+        (result, changed.then(|| entries.clone()))
+    };
+
+    if let Some(entries) = snapshot {
+        if let Some(path) = recents_file_path(app) {
+            let write = || -> anyhow::Result<()> {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, serde_json::to_string_pretty(&entries)?)?;
+                Ok(())
+            };
+            if let Err(err) = write() {
+                log::warn!("[recents] failed to persist recents: {err}");
+            }
+        }
+        refresh_recents_menu(app, &entries);
+        publish_native_recents(app, &entries);
+    }
+    result
+}
+
+pub(crate) fn recent_workspaces<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Vec<RecentWorkspace> {
+    with_entries(app, |entries| (entries.clone(), false))
+}
+
+/// Moves `path` to the top of the recents list, adding it if needed.
+pub(crate) fn record_workspace<R: tauri::Runtime>(app: &tauri::AppHandle<R>, path: &str) {
+    let Some(path) = normalize_path(path) else {
+        return;
+    };
+    #[cfg(windows)]
+    add_to_windows_recent_docs(&path);
+    with_entries(app, |entries| {
+        match entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => entry.last_opened_at = now_millis(),
+            None => entries.push(RecentWorkspace {
+                path,
+                last_opened_at: now_millis(),
+                pinned: false,
+            }),
+        }
+        ((), true)
+    });
+}
+
+fn menu_label(path: &str) -> String {
+    let home =
+        std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+    match home.and_then(|home| {
+        Path::new(path)
+            .strip_prefix(&home)
+            .ok()
+            .map(Path::to_path_buf)
+    }) {
+        Some(relative) if !relative.as_os_str().is_empty() => {
+            format!("~{}{}", std::path::MAIN_SEPARATOR, relative.display())
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Rebuilds the items of the File > Open Recent submenu.
+pub(crate) fn populate_recents_menu<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    submenu: &Submenu<R>,
+    entries: &[RecentWorkspace],
+) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        submenu.append(&MenuItem::with_id(
+            app,
+            format!("{RECENT_WORKSPACE_MENU_PREFIX}{index}"),
+            menu_label(&entry.path),
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    if !entries.is_empty() {
+        submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    submenu.append(&MenuItem::with_id(
+        app,
+        MENU_ITEM_CLEAR_RECENTS_ID,
+        crate::i18n::t(app, "menu.clearRecents"),
+        !entries.is_empty(),
+        None::<&str>,
+    )?)?;
+    Ok(())
+}
+
+fn refresh_recents_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>, entries: &[RecentWorkspace]) {
+    if let Some(MenuItemKind::Submenu(submenu)) =
+        find_app_menu_item(app, MENU_OPEN_RECENT_SUBMENU_ID)
+    {
+        if let Err(err) = populate_recents_menu(app, &submenu, entries) {
+            log::warn!("[recents] failed to update Open Recent menu: {err}");
+        }
+    }
+}
+
+/// Mirrors the list into the dock menu's recent items via `NSDocumentController`.
+#[cfg(target_os = "macos")]
+fn publish_native_recents<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    entries: &[RecentWorkspace],
+) {
+    let paths: Vec<String> = entries.iter().map(|entry| entry.path.clone()).collect();
+    let _ = app.run_on_main_thread(move || {
+        use objc2_app_kit::NSDocumentController;
+        use objc2_foundation::{MainThreadMarker, NSString, NSURL};
+
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        let controller = NSDocumentController::sharedDocumentController(mtm);
+        // SAFETY: `clearRecentDocuments:` accepts a nil sender.
+        unsafe { controller.clearRecentDocuments(None) };
+        // Oldest first so the most recent ends up on top.
+        for path in paths.iter().rev() {
+            let url = NSURL::fileURLWithPath(&NSString::from_str(path));
+            controller.noteNewRecentDocumentURL(&url);
+        }
+    });
+}
+
+/// Windows jump lists only grow through `SHAddToRecentDocs`, done in `record_workspace`.
+#[cfg(not(target_os = "macos"))]
+fn publish_native_recents<R: tauri::Runtime>(
+    _app: &tauri::AppHandle<R>,
+    _entries: &[RecentWorkspace],
+) {
+}
+
+#[cfg(windows)]
+fn add_to_windows_recent_docs(path: &str) {
+    use windows_sys::Win32::UI::Shell::{SHAddToRecentDocs, SHARD_PATHW};
+
+    let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `wide` is a NUL-terminated UTF-16 path that outlives the call.
+    unsafe { SHAddToRecentDocs(SHARD_PATHW as u32, wide.as_ptr().cast()) };
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OpenWorkspaceEvent {
+    path: String,
+}
+
+/// Handles a click on an Open Recent entry; `id` carries the entry's index.
+pub(crate) fn open_recent_from_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str) {
+    let Some(index) = id
+        .strip_prefix(RECENT_WORKSPACE_MENU_PREFIX)
+        .and_then(|raw| raw.parse::<usize>().ok())
+    else {
+        return;
+    };
+    let Some(entry) = recent_workspaces(app).into_iter().nth(index) else {
+        return;
+    };
+    if !Path::new(&entry.path).is_dir() {
+        log::warn!("[recents] workspace no longer exists: {}", entry.path);
+    }
+    record_workspace(app, &entry.path);
+    events::emit(
+        app,
+        DesktopEvent::OpenWorkspace(OpenWorkspaceEvent { path: entry.path }),
+    );
+}
+
+/// Drops every unpinned entry.
+pub(crate) fn clear_recents<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    with_entries(app, |entries| {
+        let before = entries.len();
+        entries.retain(|entry| entry.pinned);
+        ((), entries.len() != before)
+    });
+}
+
+#[tauri::command]
+pub(crate) fn desktop_recent_workspaces(app: tauri::AppHandle) -> Vec<RecentWorkspace> {
+    recent_workspaces(&app)
+}
+
+/// Records a workspace the frontend opened, asking whether to trust it first if
+/// it is new.
+#[tauri::command]
+pub(crate) async fn desktop_add_recent_workspace(app: tauri::AppHandle, path: String) {
+    trust::ensure_decided(&app, &path).await;
+    record_workspace(&app, &path);
+}
+
+#[tauri::command]
+pub(crate) fn desktop_pin_recent_workspace(
+    app: tauri::AppHandle,
+    path: String,
+    pinned: bool,
+) -> Result<(), String> {
+    let path = normalize_path(&path).ok_or_else(|| "Path is empty".to_string())?;
+    with_entries(&app, |entries| {
+        match entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) if entry.pinned != pinned => {
+                entry.pinned = pinned;
+                (Ok(()), true)
+            }
+            Some(_) => (Ok(()), false),
+            None => (Err(format!("Not a recent workspace: {path}")), false),
+        }
+    })
+}
+
+/// Clears unpinned recents; pinned workspaces are kept.
+#[tauri::command]
+pub(crate) fn desktop_clear_recents(app: tauri::AppHandle) {
+    clear_recents(&app);
+}
@@ -0,0 +1,106 @@
+use tauri::Manager;
+
+/// WebKitGTK can skip the cache itself.
+#[cfg(target_os = "linux")]
+fn reload_bypassing_cache<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+) -> Result<(), String> {
+    use webkit2gtk::WebViewExt;
+
+    window
+        .with_webview(|platform| platform.inner().reload_bypass_cache())
+        .map_err(|err| err.to_string())
+}
+
+/// `reloadFromOrigin` revalidates every resource with the server.
+#[cfg(target_os = "macos")]
+fn reload_bypassing_cache<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+) -> Result<(), String> {
+    window
+        .with_webview(|platform| {
+            use objc2_web_kit::WKWebView;
+
+            // SAFETY: Tauri hands out the live WKWebView on the main thread.
+            let webview = unsafe { &*(platform.inner() as *const WKWebView) };
+            // SAFETY: as above; the returned navigation is not needed.
+            unsafe { webview.reloadFromOrigin() };
+        })
+        .map_err(|err| err.to_string())
+}
+
+/// WebView2 has no API for it, but the DevTools protocol does.
+#[cfg(windows)]
+fn reload_bypassing_cache<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+) -> Result<(), String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::w;
+
+    window
+        .with_webview(|platform| {
+            // SAFETY: `with_webview` runs on the UI thread that owns the controller.
+            let result = unsafe {
+                platform.controller().CoreWebView2().and_then(|webview| {
+                    webview.CallDevToolsProtocolMethod(
+                        w!("Page.reload"),
+                        w!(r#"{"ignoreCache":true}"#),
+                        None::<&ICoreWebView2CallDevToolsProtocolMethodCompletedHandler>,
+                    )
+                })
+            };
+            if let Err(err) = result {
+                log::warn!("[reload] hard reload failed: {err}");
+            }
+        })
+        .map_err(|err| err.to_string())
+}
+
+/// Reloads the window's page, re-running its injected scripts. `ignore_cache`
+/// refetches every resource instead of revalidating cached ones.
+pub(crate) fn reload_window<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    ignore_cache: bool,
+) -> Result<(), String> {
+    log::info!(
+        "[reload] reloading {} (ignore_cache={ignore_cache})",
+        window.label()
+    );
+    if ignore_cache {
+        reload_bypassing_cache(window)
+    } else {
+        window.reload().map_err(|err| err.to_string())
+    }
+}
+
+/// View → Reload UI / Force Reload UI on the focused window, else the main one.
+pub(crate) fn reload_focused_window<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    ignore_cache: bool,
+) {
+    let windows = app.webview_windows();
+    let window = windows
+        .values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| windows.get("main"));
+    if let Some(window) = window {
+        if let Err(err) = reload_window(window, ignore_cache) {
+            log::warn!("[reload] failed to reload {}: {err}", window.label());
+        }
+    }
+}
+
+/// Reloads the window with `label`, or the calling one.
+#[tauri::command]
+pub(crate) fn desktop_reload(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    label: Option<String>,
+    ignore_cache: Option<bool>,
+) -> Result<(), String> {
+    let label = label.unwrap_or_else(|| webview.label().to_string());
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("No window labelled {label}"))?;
+    reload_window(&window, ignore_cache.unwrap_or(false))
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::{
+    audit::{self, AuditCategory},
+    path_policy::{self, PathError},
+};
+
+fn spawn_detached(mut command: Command) -> Result<(), String> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to launch file manager: {err}"))
+}
+
+fn reveal_command(path: &Path) -> Command {
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // Explorer parses `/select,` itself, so the path must be quoted by hand.
+        let mut command = Command::new("explorer");
+        command.raw_arg(format!("/select,\"{}\"", path.display()));
+        command
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        // There is no portable "select this file" on Linux; open the containing folder.
+        let folder = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let mut command = Command::new("xdg-open");
+        command.arg(folder);
+        command
+    }
+}
+
+fn open_command(path: &Path) -> Command {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    let mut command = Command::new(program);
+    command.arg(path);
+    command
+}
+
+/// Shows `path` selected in Finder/Explorer, or its folder in the Linux file manager.
+pub(crate) fn reveal_path(app: &tauri::AppHandle, path: &str) -> Result<(), PathError> {
+    let path = path_policy::check(app, "reveal", path)?;
+    log::info!("[reveal] revealing {}", path.display());
+    let result = spawn_detached(reveal_command(&path));
+    audit::record(
+        app,
+        AuditCategory::Process,
+        "reveal",
+        path.to_string_lossy(),
+        &result,
+    );
+    Ok(result?)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_reveal_path(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+) -> Result<(), PathError> {
+    crate::origin::require_local(&webview, "reveal files")?;
+    reveal_path(&app, &path)
+}
+
+/// Opens `path` with the default application for its type.
+#[tauri::command]
+pub(crate) fn desktop_open_path(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+) -> Result<(), PathError> {
+    crate::origin::require_local(&webview, "open files")?;
+    let path = path_policy::check(&app, "open", &path)?;
+    log::info!("[reveal] opening {}", path.display());
+    let result = spawn_detached(open_command(&path));
+    audit::record(
+        &app,
+        AuditCategory::Process,
+        "open",
+        path.to_string_lossy(),
+        &result,
+    );
+    Ok(result?)
+}
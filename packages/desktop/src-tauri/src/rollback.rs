@@ -0,0 +1,394 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{
+    audit::{self, AuditCategory},
+    i18n, settings,
+};
+
+/// Settings-store key holding `{ "keep": n }`: how many previous versions to keep
+/// for rollback. `0` turns snapshots off.
+const ROLLBACK_SETTINGS_KEY: &str = "updateRollback";
+const ROLLBACK_DIR_NAME: &str = "rollback";
+const SNAPSHOT_PREFIX: &str = "openchamber-";
+const SNAPSHOT_SUFFIX: &str = ".tar.gz";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RollbackConfig {
+    keep: usize,
+}
+
+impl Default for RollbackConfig {
+    fn default() -> Self {
+        Self { keep: 1 }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RollbackSnapshot {
+    version: String,
+    path: String,
+    /// Milliseconds since the Unix epoch.
+    saved_at: u64,
+    size: u64,
+}
+
+fn load_config<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> RollbackConfig {
+    settings::get_setting(app, ROLLBACK_SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Shared by every profile, like the installed app itself.
+fn rollback_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(ROLLBACK_DIR_NAME))
+        .map_err(|err| err.to_string())
+}
+
+/// What the updater replaces: the `.app` bundle.
+#[cfg(target_os = "macos")]
+fn install_root() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    exe.ancestors()
+        .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "The app is not running from an application bundle".to_string())
+}
+
+/// What the updater replaces: the install directory the NSIS installer wrote.
+#[cfg(windows)]
+fn install_root() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    exe.parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Could not find the install directory".to_string())
+}
+
+/// What the updater replaces: the AppImage. Package-manager installs update, and
+/// so roll back, through the package manager.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn install_root() -> Result<PathBuf, String> {
+    std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .ok_or_else(|| "Rollback is only available for the AppImage build".to_string())
+}
+
+fn snapshot_version(path: &Path) -> Option<String> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix(SNAPSHOT_PREFIX)?
+        .strip_suffix(SNAPSHOT_SUFFIX)
+        .map(str::to_string)
+}
+
+/// Saved versions, newest first.
+fn list_snapshots(dir: &Path) -> Vec<RollbackSnapshot> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<RollbackSnapshot> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let version = snapshot_version(&path)?;
+            let metadata = entry.metadata().ok()?;
+            Some(RollbackSnapshot {
+                version,
+                path: path.to_string_lossy().to_string(),
+                saved_at: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |elapsed| elapsed.as_millis() as u64),
+                size: metadata.len(),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.saved_at));
+    snapshots
+}
+
+/// The version a rollback would go back to: the newest snapshot of another version.
+pub(crate) fn previous_snapshot<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Option<RollbackSnapshot> {
+    let current = app.package_info().version.to_string();
+    list_snapshots(&rollback_dir(app).ok()?)
+        .into_iter()
+        .find(|snapshot| snapshot.version != current)
+}
+
+/// Adds `path` under `name`, recursing into directories. Symlinks are stored as
+/// links; bundles rely on them.
+fn append_tree(
+    builder: &mut tar::Builder<GzEncoder<fs::File>>,
+    path: &Path,
+    name: &Path,
+    skip: Option<&Path>,
+) -> std::io::Result<()> {
+    if skip.is_some_and(|skip| path == skip) {
+        return Ok(());
+    }
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        builder.append_dir(name, path)?;
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            append_tree(builder, &entry.path(), &name.join(entry.file_name()), skip)?;
+        }
+        Ok(())
+    } else {
+        builder.append_path_with_name(path, name)
+    }
+}
+
+fn write_snapshot(root: &Path, target: &Path) -> Result<(), String> {
+    let name = root
+        .file_name()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("Unexpected install path: {}", root.display()))?;
+    let partial = target.with_extension("partial");
+    let write = || -> std::io::Result<()> {
+        let file = fs::File::create(&partial)?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::fast()));
+        builder.follow_symlinks(false);
+        // A portable Windows install keeps its data next to the executable.
+        let skip = crate::portable::root();
+        append_tree(&mut builder, root, &name, skip)?;
+        builder.into_inner()?.finish()?;
+        fs::rename(&partial, target)
+    };
+    write().map_err(|err| {
+        let _ = fs::remove_file(&partial);
+        format!("Failed to save {}: {err}", root.display())
+    })
+}
+
+/// Saves the running version before an update replaces it, and deletes snapshots
+/// beyond the configured number. Failures are logged; they never block an update.
+pub(crate) fn preserve_current<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let config = load_config(app);
+    let result = rollback_dir(app).and_then(|dir| {
+        if config.keep == 0 {
+            return Ok(dir);
+        }
+        fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let version = app.package_info().version.to_string();
+        let target = dir.join(format!("{SNAPSHOT_PREFIX}{version}{SNAPSHOT_SUFFIX}"));
+        write_snapshot(&install_root()?, &target)?;
+        log::info!("[rollback] saved {version} for rollback");
+        Ok(dir)
+    });
+    match result {
+        Ok(dir) => {
+            for old in list_snapshots(&dir).into_iter().skip(config.keep) {
+                if let Err(err) = fs::remove_file(&old.path) {
+                    log::warn!("[rollback] failed to remove {}: {err}", old.path);
+                }
+            }
+        }
+        Err(err) => log::warn!("[rollback] could not save the current version: {err}"),
+    }
+}
+
+/// Unpacks `snapshot` into a fresh directory next to the install, so the swap
+/// that follows is a rename on the same volume. Returns the staging dir and the
+/// unpacked copy of the install root.
+fn stage(snapshot: &Path, root: &Path) -> Result<(PathBuf, PathBuf), String> {
+    let parent = root
+        .parent()
+        .ok_or_else(|| format!("Unexpected install path: {}", root.display()))?;
+    let name = root
+        .file_name()
+        .ok_or_else(|| format!("Unexpected install path: {}", root.display()))?;
+    let staging = parent.join(format!(".{}.rollback", name.to_string_lossy()));
+    let _ = fs::remove_dir_all(&staging);
+    let unpack = || -> std::io::Result<()> {
+        fs::create_dir_all(&staging)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(snapshot)?));
+        archive.set_preserve_permissions(true);
+        archive.unpack(&staging)
+    };
+    if let Err(err) = unpack() {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!(
+            "Could not unpack the previous version next to {}: {err}",
+            root.display()
+        ));
+    }
+    let staged = staging.join(name);
+    if fs::symlink_metadata(&staged).is_err() {
+        let _ = fs::remove_dir_all(&staging);
+        return Err("The saved version does not match this installation".to_string());
+    }
+    Ok((staging, staged))
+}
+
+/// Swaps the staged copy in place of the running install. Both platforms allow
+/// replacing a running app; the old copy only goes away once the swap succeeded.
+#[cfg(not(windows))]
+fn install_snapshot<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    snapshot: &Path,
+) -> Result<(), String> {
+    let root = install_root()?;
+    let (staging, staged) = stage(snapshot, &root)?;
+    let previous = staging.join(".replaced");
+    let swap = fs::rename(&root, &previous).and_then(|()| {
+        fs::rename(&staged, &root).inspect_err(|_| {
+            let _ = fs::rename(&previous, &root);
+        })
+    });
+    let _ = fs::remove_dir_all(&staging);
+    swap.map_err(|err| format!("Could not replace {}: {err}", root.display()))?;
+    app.restart();
+}
+
+/// Windows won't replace a running executable, so the files are copied once the
+/// app has quit, and the restored version is started after that.
+#[cfg(windows)]
+fn install_snapshot<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    snapshot: &Path,
+) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+
+    let root = install_root()?;
+    // Per-machine installs under Program Files need the installer's elevation.
+    let probe = root.join(".rollback-write-test");
+    fs::write(&probe, b"")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|_| {
+            format!(
+                "{} is not writable; reinstall the previous version instead",
+                root.display()
+            )
+        })?;
+    let (staging, staged) = stage(snapshot, &root)?;
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    let quote = |path: &Path| format!("'{}'", path.to_string_lossy().replace('\'', "''"));
+    let keep = crate::portable::root()
+        .map(|data| format!(" /XD {}", quote(data)))
+        .unwrap_or_default();
+    let script = format!(
+        "Wait-Process -Id {pid} -ErrorAction SilentlyContinue; \
+         robocopy {staged} {root} /MIR{keep} /NFL /NDL /NJH /NJS /NP | Out-Null; \
+         Remove-Item -LiteralPath {staging} -Recurse -Force; \
+         Start-Process -FilePath {exe}",
+        pid = std::process::id(),
+        staged = quote(&staged),
+        root = quote(&root),
+        staging = quote(&staging),
+        exe = quote(&exe),
+    );
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW | DETACHED_PROCESS)
+        .spawn()
+        .map_err(|err| {
+            let _ = fs::remove_dir_all(&staging);
+            format!("Failed to start the rollback: {err}")
+        })?;
+    app.exit(0);
+    Ok(())
+}
+
+async fn rollback<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    let snapshot = previous_snapshot(app)
+        .ok_or_else(|| "No previous version is available to roll back to".to_string())?;
+    log::info!(
+        "[rollback] rolling back from {} to {}",
+        app.package_info().version,
+        snapshot.version
+    );
+    let task_app = app.clone();
+    let path = PathBuf::from(&snapshot.path);
+    let result = tauri::async_runtime::spawn_blocking(move || install_snapshot(&task_app, &path))
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|result| result);
+    audit::record(
+        app,
+        AuditCategory::FileWrite,
+        "roll back update",
+        &snapshot.version,
+        &result,
+    );
+    result
+}
+
+/// Asks before rolling back from the menu, and reports a failure in a dialog since
+/// there is no caller to return it to.
+pub(crate) fn rollback_from_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(snapshot) = previous_snapshot(app) else {
+        return;
+    };
+    let app = app.clone();
+    let name = app.package_info().name.clone();
+    app.dialog()
+        .message(i18n::t_args(
+            &app,
+            "dialog.rollback.message",
+            &[("name", &name), ("version", &snapshot.version)],
+        ))
+        .title(i18n::t_args(
+            &app,
+            "dialog.rollback.title",
+            &[("version", &snapshot.version)],
+        ))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(&app, "dialog.rollback.confirm"),
+            i18n::t(&app, "dialog.cancel"),
+        ))
+        .show(move |confirmed| {
+            if !confirmed {
+                return;
+            }
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = rollback(&app).await {
+                    log::error!("[rollback] {err}");
+                    app.dialog()
+                        .message(i18n::t_args(
+                            &app,
+                            "dialog.rollback.failed",
+                            &[("error", &err)],
+                        ))
+                        .kind(MessageDialogKind::Error)
+                        .show(|_| {});
+                }
+            });
+        });
+}
+
+/// The version a rollback would restore, or `None` if none is saved.
+#[tauri::command]
+pub(crate) fn desktop_rollback_info(app: tauri::AppHandle) -> Option<RollbackSnapshot> {
+    previous_snapshot(&app)
+}
+
+/// Reinstalls the version that ran before the last update and restarts into it.
+/// Settings and sessions are left as they are.
+#[tauri::command]
+pub(crate) async fn desktop_rollback_update(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "roll back the app")?;
+    rollback(&app).await
+}
@@ -0,0 +1,17 @@
+use std::{env, sync::OnceLock};
+
+const SAFE_MODE_FLAG: &str = "--safe-mode";
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Reads `--safe-mode` from the command line at the start of `main`. In safe mode
+/// windows get a throwaway webview profile (no cookies, local storage or cache)
+/// and the desktop settings store starts from defaults and is never written, so a
+/// problem that persists lies in the native layer rather than frontend state.
+pub(crate) fn init() {
+    let _ = ENABLED.set(env::args().skip(1).any(|arg| arg == SAFE_MODE_FLAG));
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
@@ -0,0 +1,296 @@
+use std::io::Cursor;
+
+use base64::Engine;
+use image::{imageops::FilterType, DynamicImage, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::audit::{self, AuditCategory};
+
+const MIN_SCALE: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CaptureFormat {
+    #[default]
+    Png,
+    #[serde(alias = "jpg")]
+    Jpeg,
+}
+
+impl CaptureFormat {
+    fn image_format(self) -> ImageFormat {
+        match self {
+            CaptureFormat::Png => ImageFormat::Png,
+            CaptureFormat::Jpeg => ImageFormat::Jpeg,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct CaptureOptions {
+    /// Ask where to save the image instead of returning its bytes.
+    save: bool,
+    copy_to_clipboard: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureResult {
+    width: u32,
+    height: u32,
+    /// Encoded image, omitted when it was saved to disk.
+    data_base64: Option<String>,
+    saved_path: Option<String>,
+}
+
+/// Reads the main window's pixels with `screencapture`, which includes the
+/// native title bar and web content exactly as composited.
+#[cfg(target_os = "macos")]
+fn capture_rgba(window: &tauri::WebviewWindow) -> Result<RgbaImage, String> {
+    use objc2_app_kit::NSWindow;
+
+    let ns_window = window.ns_window().map_err(|err| err.to_string())?;
+    // SAFETY: Tauri returns a valid `NSWindow` pointer for the lifetime of the window.
+    let window_number = unsafe { &*(ns_window as *const NSWindow) }.windowNumber();
+
+    let target = std::env::temp_dir().join(format!(
+        "openchamber-capture-{}-{window_number}.png",
+        std::process::id()
+    ));
+    let status = std::process::Command::new("/usr/sbin/screencapture")
+        .args(["-x", "-o", "-t", "png"])
+        .arg(format!("-l{window_number}"))
+        .arg(&target)
+        .status()
+        .map_err(|err| format!("Failed to run screencapture: {err}"))?;
+    let bytes = std::fs::read(&target);
+    let _ = std::fs::remove_file(&target);
+    if !status.success() {
+        return Err(format!("screencapture exited with {status}"));
+    }
+    let bytes = bytes.map_err(|err| err.to_string())?;
+    image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+        .map(|image| image.to_rgba8())
+        .map_err(|err| err.to_string())
+}
+
+/// Renders the client area with `PrintWindow`, which also works for occluded windows.
+#[cfg(windows)]
+fn capture_rgba(window: &tauri::WebviewWindow) -> Result<RgbaImage, String> {
+    use windows_sys::Win32::Foundation::{HWND, RECT};
+    use windows_sys::Win32::Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+        ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows_sys::Win32::Storage::Xps::{PrintWindow, PW_CLIENTONLY};
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    // Not exported by windows-sys; required to capture DirectComposition (WebView2) content.
+    const PW_RENDERFULLCONTENT: u32 = 0x2;
+
+    let hwnd = window.hwnd().map_err(|err| err.to_string())?.0 as HWND;
+    // SAFETY: every GDI handle created here is released before returning, and the
+    // pixel buffer is sized for the requested 32-bit top-down DIB.
+    unsafe {
+        let mut rect: RECT = std::mem::zeroed();
+        if GetClientRect(hwnd, &mut rect) == 0 {
+            return Err("Failed to read window size".to_string());
+        }
+        let width = rect.right - rect.left;
+        let height = rect.bottom - rect.top;
+        if width <= 0 || height <= 0 {
+            return Err("Window has no visible area".to_string());
+        }
+
+        let window_dc = GetDC(hwnd);
+        let memory_dc = CreateCompatibleDC(window_dc);
+        let bitmap = CreateCompatibleBitmap(window_dc, width, height);
+        let previous = SelectObject(memory_dc, bitmap);
+        let printed = PrintWindow(hwnd, memory_dc, PW_CLIENTONLY | PW_RENDERFULLCONTENT);
+
+        let mut info: BITMAPINFO = std::mem::zeroed();
+        info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = width;
+        info.bmiHeader.biHeight = -height;
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = BI_RGB;
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        let lines = GetDIBits(
+            memory_dc,
+            bitmap,
+            0,
+            height as u32,
+            pixels.as_mut_ptr().cast(),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        SelectObject(memory_dc, previous);
+        DeleteObject(bitmap);
+        DeleteDC(memory_dc);
+        ReleaseDC(hwnd, window_dc);
+
+        if printed == 0 || lines == 0 {
+            return Err("Failed to capture window contents".to_string());
+        }
+
+        // GDI hands back BGRA with an undefined alpha channel.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or_else(|| "Unexpected capture buffer size".to_string())
+    }
+}
+
+/// Copies the GTK window's pixels. Must run on the main thread; under Wayland GDK
+/// cannot read back on-screen windows and this returns an error.
+#[cfg(target_os = "linux")]
+fn capture_rgba(window: &tauri::WebviewWindow) -> Result<RgbaImage, String> {
+    use gtk::prelude::*;
+
+    let gtk_window = window.gtk_window().map_err(|err| err.to_string())?;
+    let gdk_window = gtk_window
+        .window()
+        .ok_or_else(|| "Window is not realized".to_string())?;
+    let pixbuf = gdk_window
+        .pixbuf(0, 0, gdk_window.width(), gdk_window.height())
+        .ok_or_else(|| "Window capture is not supported on this display server".to_string())?;
+    let bytes = pixbuf
+        .save_to_bufferv("png", &[])
+        .map_err(|err| err.to_string())?;
+    image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+        .map(|image| image.to_rgba8())
+        .map_err(|err| err.to_string())
+}
+
+/// Captures the main window on the main thread, where the windowing APIs expect to be called.
+async fn capture_main_window(app: &tauri::AppHandle) -> Result<RgbaImage, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(capture_rgba(&window));
+    })
+    .map_err(|err| err.to_string())?;
+    rx.await
+        .map_err(|_| "Window capture was interrupted".to_string())?
+}
+
+fn encode(image: &RgbaImage, format: CaptureFormat) -> Result<Vec<u8>, String> {
+    let mut out = Cursor::new(Vec::new());
+    let result = match format {
+        // JPEG has no alpha channel.
+        CaptureFormat::Jpeg => DynamicImage::ImageRgba8(image.clone())
+            .to_rgb8()
+            .write_to(&mut out, format.image_format()),
+        CaptureFormat::Png => image.write_to(&mut out, format.image_format()),
+    };
+    result.map_err(|err| err.to_string())?;
+    Ok(out.into_inner())
+}
+
+/// PNG of the main window for the diagnostics bundle, if it can be captured.
+pub(crate) async fn capture_main_window_png(app: &tauri::AppHandle) -> Option<Vec<u8>> {
+    match capture_main_window(app).await {
+        Ok(image) => encode(&image, CaptureFormat::Png).ok(),
+        Err(err) => {
+            log::warn!("[screenshot] capture failed: {err}");
+            None
+        }
+    }
+}
+
+/// Captures the main window, downscaled by `scale` (0.1–1.0), and returns the encoded
+/// image or saves it through a save dialog. Returns `None` if that dialog is cancelled.
+#[tauri::command]
+pub(crate) async fn desktop_capture_window(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    format: Option<CaptureFormat>,
+    scale: Option<f64>,
+    options: Option<CaptureOptions>,
+) -> Result<Option<CaptureResult>, String> {
+    crate::origin::require_local(&webview, "capture the window")?;
+    let format = format.unwrap_or_default();
+    let options = options.unwrap_or_default();
+    let scale = scale.unwrap_or(1.0).clamp(MIN_SCALE, 1.0);
+
+    let mut image = capture_main_window(&app).await?;
+    if scale < 1.0 {
+        let width = ((image.width() as f64 * scale).round() as u32).max(1);
+        let height = ((image.height() as f64 * scale).round() as u32).max(1);
+        image = image::imageops::resize(&image, width, height, FilterType::Triangle);
+    }
+    let (width, height) = image.dimensions();
+
+    if options.copy_to_clipboard {
+        let clip = tauri::image::Image::new_owned(image.as_raw().clone(), width, height);
+        if let Err(err) = app.clipboard().write_image(&clip) {
+            log::warn!("[screenshot] failed to copy to clipboard: {err}");
+        }
+    }
+
+    let bytes = encode(&image, format)?;
+
+    if !options.save {
+        return Ok(Some(CaptureResult {
+            width,
+            height,
+            data_base64: Some(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+            saved_path: None,
+        }));
+    }
+
+    let default_name = format!(
+        "openchamber-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    let dialog_app = app.clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .set_file_name(default_name)
+            .add_filter("Image", &[format.extension()])
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let target = picked.into_path().map_err(|err| err.to_string())?;
+    let written = std::fs::write(&target, &bytes);
+    audit::record(
+        &app,
+        AuditCategory::FileWrite,
+        "save screenshot",
+        target.to_string_lossy(),
+        &written,
+    );
+    written.map_err(|err| err.to_string())?;
+    log::info!("[screenshot] saved to {}", target.display());
+
+    Ok(Some(CaptureResult {
+        width,
+        height,
+        data_base64: None,
+        saved_path: Some(target.to_string_lossy().to_string()),
+    }))
+}
@@ -0,0 +1,457 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    path_policy::{self, PathError, PathErrorKind},
+    watcher::IgnoreFilter,
+};
+
+const DEFAULT_MAX_RESULTS: usize = 2000;
+/// Larger files are skipped; they are nearly always generated or data.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// A NUL byte in this prefix marks a file as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+/// Previews are cut to this many characters around the first match.
+const MAX_PREVIEW_CHARS: usize = 400;
+const BATCH_SIZE: usize = 200;
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct SearchOptions {
+    /// Treat `query` as a regular expression instead of literal text.
+    regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+    /// Only search paths matching one of these globs (`*`, `**`, `?`), relative to
+    /// the workspace.
+    include: Vec<String>,
+    /// Skip paths matching any of these globs.
+    exclude: Vec<String>,
+    /// Also search files the gitignore excludes.
+    include_ignored: bool,
+    /// Stop after this many matches; defaults to 2000.
+    max_results: Option<usize>,
+}
+
+/// A match range within `SearchMatch::preview`, in UTF-16 code units so it
+/// indexes JS strings.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct SearchRange {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct SearchMatch {
+    /// Relative to the workspace, with `/` separators.
+    path: String,
+    /// 1-based.
+    line: usize,
+    /// 1-based, in UTF-16 code units.
+    column: usize,
+    preview: String,
+    ranges: Vec<SearchRange>,
+}
+
+/// A batch of results for `search_id`. The last event of a search has `done` set,
+/// also when it was cancelled.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchResultsEvent {
+    search_id: String,
+    matches: Vec<SearchMatch>,
+    done: bool,
+    cancelled: bool,
+    /// The match limit was reached before every file was searched.
+    truncated: bool,
+    files_searched: usize,
+}
+
+/// Searches in progress, keyed by id, with the flag that cancels them.
+#[derive(Default)]
+pub(crate) struct SearchState {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    next_id: AtomicU64,
+}
+
+/// `*` and `?` stay within a path segment, `**/` spans any number of them.
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let glob = glob.trim().trim_start_matches("./").trim_end_matches('/');
+    if glob.is_empty() {
+        return None;
+    }
+    let mut pattern = String::from("^");
+    // A glob without a slash matches the file name at any depth, as in gitignore.
+    if !glob.contains('/') {
+        pattern.push_str("(?:.*/)?");
+    }
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            other => pattern.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    // A directory glob covers everything inside it.
+    pattern.push_str("(?:/.*)?$");
+    Regex::new(&pattern).ok()
+}
+
+struct Matcher {
+    pattern: Regex,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl Matcher {
+    fn new(query: &str, options: &SearchOptions) -> Result<Self, String> {
+        let mut pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        if options.whole_word {
+            pattern = format!(r"\b(?:{pattern})\b");
+        }
+        let pattern = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|err| format!("Invalid search pattern: {err}"))?;
+        let globs = |globs: &[String]| globs.iter().filter_map(|glob| glob_regex(glob)).collect();
+        Ok(Self {
+            pattern,
+            include: globs(&options.include),
+            exclude: globs(&options.exclude),
+        })
+    }
+
+    fn wants(&self, relative: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.is_match(relative)))
+            && !self.exclude.iter().any(|glob| glob.is_match(relative))
+    }
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// The line cut down to at most `MAX_PREVIEW_CHARS` around its first match, with
+/// the match ranges rebased onto the cut.
+fn preview(line: &str, spans: &[(usize, usize)]) -> (String, Vec<SearchRange>) {
+    let first = spans.first().map_or(0, |span| span.0);
+    let mut start = 0;
+    if line[..first].chars().count() > MAX_PREVIEW_CHARS / 4 {
+        start = line[..first]
+            .char_indices()
+            .rev()
+            .nth(MAX_PREVIEW_CHARS / 4 - 1)
+            .map_or(0, |(index, _)| index);
+    }
+    let end = line[start..]
+        .char_indices()
+        .nth(MAX_PREVIEW_CHARS)
+        .map_or(line.len(), |(index, _)| start + index);
+    let text = &line[start..end];
+    let ranges = spans
+        .iter()
+        .filter(|(span_start, span_end)| *span_start >= start && *span_end <= end)
+        .map(|(span_start, span_end)| SearchRange {
+            start: utf16_len(&line[start..*span_start]),
+            end: utf16_len(&line[start..*span_end]),
+        })
+        .collect();
+    (text.to_string(), ranges)
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+fn search_file(path: &Path, relative: &str, matcher: &Matcher) -> Vec<SearchMatch> {
+    let Ok(mut file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    let mut bytes = Vec::new();
+    if file.read_to_end(&mut bytes).is_err() || is_binary(&bytes) {
+        return Vec::new();
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let mut matches = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let spans: Vec<(usize, usize)> = matcher
+            .pattern
+            .find_iter(line)
+            .filter(|found| !found.is_empty())
+            .map(|found| (found.start(), found.end()))
+            .collect();
+        let Some(&(first, _)) = spans.first() else {
+            continue;
+        };
+        let (preview, ranges) = preview(line, &spans);
+        matches.push(SearchMatch {
+            path: relative.to_string(),
+            line: index + 1,
+            column: utf16_len(&line[..first]) + 1,
+            preview,
+            ranges,
+        });
+    }
+    matches
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Files below `root` worth searching, skipping `.git`, symlinks, oversized files
+/// and (unless asked) whatever the gitignore excludes.
+fn collect_files(
+    root: &Path,
+    matcher: &Matcher,
+    include_ignored: bool,
+    cancelled: &AtomicBool,
+) -> Vec<PathBuf> {
+    let filter = IgnoreFilter::new(root);
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_symlink() || entry.file_name() == ".git" {
+                continue;
+            }
+            if !include_ignored && filter.is_ignored(&path) {
+                continue;
+            }
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if matcher.wants(&relative_path(root, &path))
+                && entry
+                    .metadata()
+                    .is_ok_and(|metadata| metadata.len() <= MAX_FILE_BYTES)
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Collects matches from the workers and sends them on in batches, so a search
+/// with thousands of hits doesn't flood the webview with events.
+struct Batcher<'a> {
+    app: &'a tauri::AppHandle,
+    search_id: &'a str,
+    pending: Mutex<(Vec<SearchMatch>, Instant)>,
+}
+
+impl Batcher<'_> {
+    fn push(&self, found: Vec<SearchMatch>) {
+        let mut pending = self.pending.lock().expect("search batch mutex");
+        pending.0.extend(found);
+        if pending.0.len() >= BATCH_SIZE || pending.1.elapsed() >= BATCH_INTERVAL {
+            let matches = std::mem::take(&mut pending.0);
+            pending.1 = Instant::now();
+            drop(pending);
+            self.emit(matches, false, false, false, 0);
+        }
+    }
+
+    fn emit(
+        &self,
+        matches: Vec<SearchMatch>,
+        done: bool,
+        cancelled: bool,
+        truncated: bool,
+        files_searched: usize,
+    ) {
+        events::emit(
+            self.app,
+            DesktopEvent::SearchResults(SearchResultsEvent {
+                search_id: self.search_id.to_string(),
+                matches,
+                done,
+                cancelled,
+                truncated,
+                files_searched,
+            }),
+        );
+    }
+}
+
+fn run_search(
+    app: &tauri::AppHandle,
+    search_id: &str,
+    root: &Path,
+    matcher: &Matcher,
+    options: &SearchOptions,
+    cancelled: &AtomicBool,
+) {
+    let started = Instant::now();
+    let files = collect_files(root, matcher, options.include_ignored, cancelled);
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1);
+    let next_file = AtomicUsize::new(0);
+    let found = AtomicUsize::new(0);
+    let searched = AtomicUsize::new(0);
+    let batcher = Batcher {
+        app,
+        search_id,
+        pending: Mutex::new((Vec::new(), Instant::now())),
+    };
+
+    let workers = thread::available_parallelism().map_or(4, |count| count.get());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if cancelled.load(Ordering::Relaxed) || found.load(Ordering::Relaxed) >= max_results
+                {
+                    break;
+                }
+                let Some(path) = files.get(next_file.fetch_add(1, Ordering::Relaxed)) else {
+                    break;
+                };
+                let mut matches = search_file(path, &relative_path(root, path), matcher);
+                searched.fetch_add(1, Ordering::Relaxed);
+                if matches.is_empty() {
+                    continue;
+                }
+                let before = found.fetch_add(matches.len(), Ordering::Relaxed);
+                if before >= max_results {
+                    break;
+                }
+                matches.truncate(max_results - before);
+                batcher.push(matches);
+            });
+        }
+    });
+
+    let was_cancelled = cancelled.load(Ordering::Relaxed);
+    let truncated = found.load(Ordering::Relaxed) >= max_results;
+    let files_searched = searched.load(Ordering::Relaxed);
+    let remaining = std::mem::take(&mut batcher.pending.lock().expect("search batch mutex").0);
+    batcher.emit(remaining, true, was_cancelled, truncated, files_searched);
+    log::info!(
+        "[search] {search_id}: {} matches in {files_searched} files, {:?}{}",
+        found.load(Ordering::Relaxed).min(max_results),
+        started.elapsed(),
+        if was_cancelled { " (cancelled)" } else { "" }
+    );
+}
+
+/// Starts a project-wide search and returns its id right away. Matches arrive as
+/// `openchamber:search-results` events carrying that id; `desktop_search_cancel`
+/// stops it early.
+#[tauri::command]
+pub(crate) fn desktop_search(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, SearchState>,
+    workspace: String,
+    query: String,
+    opts: Option<SearchOptions>,
+) -> Result<String, PathError> {
+    crate::origin::require_local(&webview, "search files")?;
+    let root = path_policy::check(&app, "search", &workspace)?;
+    if !root.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a directory: {}", workspace.trim()),
+        ));
+    }
+    if query.is_empty() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            "Search query must not be empty",
+        ));
+    }
+    let options = opts.unwrap_or_default();
+    let matcher = Matcher::new(&query, &options)
+        .map_err(|message| PathError::new(PathErrorKind::InvalidPath, message))?;
+
+    let search_id = format!(
+        "search-{}",
+        state.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state
+        .running
+        .lock()
+        .expect("search state mutex")
+        .insert(search_id.clone(), cancelled.clone());
+
+    let id = search_id.clone();
+    thread::spawn(move || {
+        run_search(&app, &id, &root, &matcher, &options, &cancelled);
+        if let Some(state) = app.try_state::<SearchState>() {
+            state
+                .running
+                .lock()
+                .expect("search state mutex")
+                .remove(&id);
+        }
+    });
+    Ok(search_id)
+}
+
+/// Stops a running search; it still sends a final event with `cancelled` set.
+/// Returns `false` if the search already finished.
+#[tauri::command]
+pub(crate) fn desktop_search_cancel(
+    state: tauri::State<'_, SearchState>,
+    search_id: String,
+) -> bool {
+    match state
+        .running
+        .lock()
+        .expect("search state mutex")
+        .get(&search_id)
+    {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
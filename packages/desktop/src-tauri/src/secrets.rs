@@ -0,0 +1,352 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::Manager;
+
+use crate::{
+    audit::{self, AuditCategory},
+    auth::{self, AuthPurpose},
+    portable, profiles,
+};
+
+pub(crate) const SECRETS_INDEX_FILE_NAME: &str = "secrets-index.json";
+const PORTABLE_SECRETS_FILE_NAME: &str = "secrets.json";
+const GLOBAL_NAMESPACE: &str = "global";
+const MAX_SECRET_KEY_LEN: usize = 256;
+
+/// Secret values live in the OS keychain (Keychain, Credential Manager, Secret Service).
+/// Keychains cannot be enumerated portably, so the names (never the values) are
+/// tracked in an index file in the app config dir.
+///
+/// In portable mode the keychain would stay behind on the host machine, so values
+/// are kept in a file next to the index instead. That file is not encrypted; it is
+/// only as safe as the drive or folder the app lives in.
+#[derive(Default)]
+pub(crate) struct SecretsState {
+    index: Mutex<Option<BTreeMap<String, BTreeSet<String>>>>,
+    portable_values: Mutex<Option<BTreeMap<String, String>>>,
+}
+
+fn keychain_service<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> String {
+    let identifier = &app.config().identifier;
+    match profiles::active() {
+        Some(profile) => format!("{identifier}.profile.{profile}"),
+        None => identifier.clone(),
+    }
+}
+
+fn namespace(workspace: Option<&str>) -> String {
+    workspace
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .unwrap_or(GLOBAL_NAMESPACE)
+        .to_string()
+}
+
+fn validate_key(key: &str) -> Result<&str, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Secret key must not be empty".to_string());
+    }
+    if key.len() > MAX_SECRET_KEY_LEN {
+        return Err(format!(
+            "Secret key exceeds {MAX_SECRET_KEY_LEN} characters"
+        ));
+    }
+    Ok(key)
+}
+
+fn account(namespace: &str, key: &str) -> String {
+    format!("{namespace}:{key}")
+}
+
+fn entry<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    namespace: &str,
+    key: &str,
+) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(&keychain_service(app), &account(namespace, key))
+        .map_err(|err| err.to_string())
+}
+
+fn config_file_path<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    file_name: &str,
+) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(file_name))
+}
+
+fn index_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    config_file_path(app, SECRETS_INDEX_FILE_NAME)
+}
+
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+/// File-backed stand-in for the keychain in portable mode, keyed like keychain accounts.
+fn with_portable_values<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut BTreeMap<String, String>) -> (T, bool),
+) -> Result<T, String> {
+    let path = config_file_path(app, PORTABLE_SECRETS_FILE_NAME)
+        .ok_or_else(|| "Config directory unavailable".to_string())?;
+    let state = app.state::<SecretsState>();
+    let mut guard = state
+        .portable_values
+        .lock()
+        .expect("portable secrets mutex");
+    let values = guard.get_or_insert_with(|| {
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    });
+
+    let (result, changed) = f(values);
+    if changed {
+        let raw = serde_json::to_string_pretty(values).map_err(|err| err.to_string())?;
+        write_private(&path, &raw).map_err(|err| err.to_string())?;
+    }
+    Ok(result)
+}
+
+fn with_index<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut BTreeMap<String, BTreeSet<String>>) -> (T, bool),
+) -> T {
+    let state = app.state::<SecretsState>();
+    let mut guard = state.index.lock().expect("secrets index mutex");
+    let index = guard.get_or_insert_with(|| {
+        index_path(app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    });
+
+    let (result, changed) = f(index);
+    if changed {
+        if let Some(path) = index_path(app) {
+            let write = || -> anyhow::Result<()> {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, serde_json::to_string_pretty(index)?)?;
+                Ok(())
+            };
+            if let Err(err) = write() {
+                log::warn!("[secrets] failed to write index: {err}");
+            }
+        }
+    }
+    result
+}
+
+/// Lets caches built from a secret pick up its new value.
+fn secret_changed<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    workspace: Option<&str>,
+    key: &str,
+) {
+    if workspace.is_none() && key == crate::proxy::PROXY_PASSWORD_SECRET {
+        crate::proxy::invalidate(app);
+    }
+}
+
+pub(crate) fn set_secret<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    workspace: Option<&str>,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let key = validate_key(key)?;
+    let namespace = namespace(workspace);
+    if portable::is_enabled() {
+        with_portable_values(app, |values| {
+            let previous = values.insert(account(&namespace, key), value.to_string());
+            ((), previous.as_deref() != Some(value))
+        })?;
+    } else {
+        entry(app, &namespace, key)?
+            .set_password(value)
+            .map_err(|err| err.to_string())?;
+    }
+    with_index(app, |index| {
+        let inserted = index
+            .entry(namespace.clone())
+            .or_default()
+            .insert(key.to_string());
+        ((), inserted)
+    });
+    secret_changed(app, workspace, key);
+    Ok(())
+}
+
+pub(crate) fn get_secret<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    workspace: Option<&str>,
+    key: &str,
+) -> Result<Option<String>, String> {
+    let key = validate_key(key)?;
+    if portable::is_enabled() {
+        let account = account(&namespace(workspace), key);
+        return with_portable_values(app, |values| (values.get(&account).cloned(), false));
+    }
+    match entry(app, &namespace(workspace), key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+pub(crate) fn delete_secret<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    workspace: Option<&str>,
+    key: &str,
+) -> Result<bool, String> {
+    let key = validate_key(key)?;
+    let namespace = namespace(workspace);
+    let existed = if portable::is_enabled() {
+        with_portable_values(app, |values| {
+            let existed = values.remove(&account(&namespace, key)).is_some();
+            (existed, existed)
+        })?
+    } else {
+        match entry(app, &namespace, key)?.delete_credential() {
+            Ok(()) => true,
+            Err(keyring::Error::NoEntry) => false,
+            Err(err) => return Err(err.to_string()),
+        }
+    };
+    with_index(app, |index| {
+        let removed = index
+            .get_mut(&namespace)
+            .map(|keys| keys.remove(key))
+            .unwrap_or(false);
+        if index.get(&namespace).is_some_and(|keys| keys.is_empty()) {
+            index.remove(&namespace);
+        }
+        ((), removed)
+    });
+    secret_changed(app, workspace, key);
+    Ok(existed)
+}
+
+pub(crate) fn list_secrets<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    workspace: Option<&str>,
+) -> Vec<String> {
+    let namespace = namespace(workspace);
+    with_index(app, |index| {
+        let keys = index
+            .get(&namespace)
+            .map(|keys| keys.iter().cloned().collect())
+            .unwrap_or_default();
+        (keys, false)
+    })
+}
+
+/// Every stored secret as namespace -> key -> value, for settings export. Keys whose
+/// value has gone missing from the keychain are left out.
+pub(crate) fn export_all<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, String> {
+    let index = with_index(app, |index| (index.clone(), false));
+    let mut exported = BTreeMap::new();
+    for (namespace, keys) in index {
+        let mut values = BTreeMap::new();
+        for key in keys {
+            if let Some(value) = get_secret(app, Some(&namespace), &key)? {
+                values.insert(key, value);
+            }
+        }
+        if !values.is_empty() {
+            exported.insert(namespace, values);
+        }
+    }
+    Ok(exported)
+}
+
+/// Records frontend access to a secret by namespace and key; never the value.
+fn audit_secret<T>(
+    app: &tauri::AppHandle,
+    action: &str,
+    workspace: Option<&str>,
+    key: &str,
+    result: &Result<T, String>,
+) {
+    let target = format!("{}/{key}", namespace(workspace));
+    audit::record(app, AuditCategory::Secret, action, target, result);
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_secret_set(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    workspace: Option<String>,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "store a secret")?;
+    let result = set_secret(&app, workspace.as_deref(), &key, &value);
+    audit_secret(&app, "store secret", workspace.as_deref(), &key, &result);
+    result
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_secret_get(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    workspace: Option<String>,
+    key: String,
+) -> Result<Option<String>, String> {
+    crate::origin::require_local(&webview, "read a secret")?;
+    let result = match auth::require_presence(&app, AuthPurpose::RevealSecret, &key).await {
+        Ok(()) => get_secret(&app, workspace.as_deref(), &key),
+        Err(err) => Err(err),
+    };
+    audit_secret(&app, "read secret", workspace.as_deref(), &key, &result);
+    result
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_secret_delete(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    workspace: Option<String>,
+    key: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "delete a secret")?;
+    let result = delete_secret(&app, workspace.as_deref(), &key);
+    audit_secret(&app, "delete secret", workspace.as_deref(), &key, &result);
+    result
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_secret_list(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    workspace: Option<String>,
+) -> Result<Vec<String>, String> {
+    crate::origin::require_local(&webview, "list secrets")?;
+    Ok(list_secrets(&app, workspace.as_deref()))
+}
@@ -0,0 +1,723 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::{
+    auth::{self, AuthPurpose},
+    compat,
+    events::{self, DesktopEvent},
+    i18n, normalize_server_url, profiles, proxy, secrets,
+};
+
+pub(crate) const SERVERS_FILE_NAME: &str = "servers.json";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ServerTlsOptions {
+    /// Skip certificate validation entirely. Only meant for self-signed test servers;
+    /// adding a profile with this set asks for confirmation first.
+    pub(crate) accept_invalid_certs: bool,
+    /// PEM file with extra root certificates, e.g. an internal company CA.
+    pub(crate) ca_bundle_path: Option<String>,
+    /// SHA-256 of the server's leaf certificate (hex, colons optional). When set,
+    /// a matching certificate is trusted even if it does not chain to a known root.
+    pub(crate) pinned_sha256: Option<String>,
+}
+
+impl ServerTlsOptions {
+    fn pinned_fingerprint(&self) -> Option<String> {
+        self.pinned_sha256
+            .as_deref()
+            .map(|pin| {
+                pin.chars()
+                    .filter(|c| c.is_ascii_hexdigit())
+                    .collect::<String>()
+                    .to_ascii_lowercase()
+            })
+            .filter(|pin| !pin.is_empty())
+    }
+
+    /// Applies the CA bundle, pinning and insecure options to a client builder.
+    pub(crate) fn apply(
+        &self,
+        mut builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, String> {
+        if let Some(path) = self
+            .ca_bundle_path
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+        {
+            let pem = fs::read(path.trim())
+                .map_err(|err| format!("Failed to read CA bundle {path}: {err}"))?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|err| format!("Invalid CA bundle {path}: {err}"))?;
+            if certs.is_empty() {
+                return Err(format!("CA bundle {path} contains no certificates"));
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if self.pinned_fingerprint().is_some() {
+            // The pin replaces chain validation; every response is checked against it.
+            builder = builder.danger_accept_invalid_certs(true).tls_info(true);
+        } else if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder)
+    }
+
+    /// Rejects a response whose leaf certificate does not match the pin.
+    pub(crate) fn verify_response(&self, response: &reqwest::Response) -> Result<(), String> {
+        let Some(pin) = self.pinned_fingerprint() else {
+            return Ok(());
+        };
+        let certificate = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .ok_or_else(|| {
+                "Server did not present a TLS certificate to check the pin against".to_string()
+            })?;
+        let actual: String = Sha256::digest(certificate)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if actual == pin {
+            Ok(())
+        } else {
+            Err(format!(
+                "Server certificate does not match the pinned fingerprint (got {actual})"
+            ))
+        }
+    }
+}
+
+/// Credentials for a server behind an authenticating reverse proxy. Stored in the
+/// OS keychain, never in `servers.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ServerCredentials {
+    Bearer {
+        token: String,
+    },
+    Basic {
+        username: String,
+        password: String,
+    },
+    /// Raw `Authorization` header value.
+    Header {
+        value: String,
+    },
+}
+
+impl ServerCredentials {
+    fn authorization(&self) -> String {
+        match self {
+            ServerCredentials::Bearer { token } => format!("Bearer {}", token.trim()),
+            ServerCredentials::Basic { username, password } => format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"))
+            ),
+            ServerCredentials::Header { value } => value.trim().to_string(),
+        }
+    }
+}
+
+/// A named remote OpenChamber server the main window can be pointed at.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerProfile {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) url: String,
+    /// Plaintext header from older builds; moved to the keychain on load.
+    #[serde(default, skip_serializing)]
+    auth_header: Option<String>,
+    /// Whether keychain credentials exist for this profile.
+    #[serde(default)]
+    pub(crate) has_credentials: bool,
+    #[serde(default)]
+    pub(crate) tls: ServerTlsOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerProfileInput {
+    name: String,
+    url: String,
+    /// Shorthand for `credentials: { type: "header", value }`.
+    auth_header: Option<String>,
+    credentials: Option<ServerCredentials>,
+    #[serde(default)]
+    tls: ServerTlsOptions,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServersFile {
+    #[serde(default)]
+    profiles: Vec<ServerProfile>,
+    #[serde(default)]
+    active_profile_id: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct ServerProfilesState {
+    profiles: Mutex<Vec<ServerProfile>>,
+    active_profile_id: Mutex<Option<String>>,
+    /// Origin and `Authorization` value of the connected server, injected into its pages.
+    active_auth: Mutex<Option<(String, String)>>,
+}
+
+impl ServerProfilesState {
+    pub(crate) fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Self {
+        let mut file = servers_file_path(app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str::<ServersFile>(&raw).ok())
+            .unwrap_or_default();
+
+        let mut migrated = false;
+        for profile in &mut file.profiles {
+            let Some(header) = profile.auth_header.take() else {
+                continue;
+            };
+            let credentials = ServerCredentials::Header {
+                value: header.clone(),
+            };
+            match store_credentials(app, &profile.id, Some(&credentials)) {
+                Ok(()) => {
+                    profile.has_credentials = true;
+                    migrated = true;
+                }
+                Err(err) => {
+                    log::warn!("[servers] failed to move credentials to keychain: {err}");
+                    profile.auth_header = Some(header);
+                }
+            }
+        }
+
+        let state = Self {
+            profiles: Mutex::new(file.profiles),
+            active_profile_id: Mutex::new(file.active_profile_id),
+            active_auth: Mutex::new(None),
+        };
+        if migrated {
+            if let Err(err) = write_to_disk(app, &state) {
+                log::warn!("[servers] failed to persist migrated profiles: {err}");
+            }
+        }
+        state
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<ServerProfile> {
+        self.profiles
+            .lock()
+            .expect("server profiles mutex")
+            .iter()
+            .find(|profile| profile.id == id)
+            .cloned()
+    }
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerConnectedEvent {
+    profile_id: String,
+    url: String,
+}
+
+fn servers_file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(SERVERS_FILE_NAME))
+}
+
+fn write_to_disk<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &ServerProfilesState,
+) -> anyhow::Result<()> {
+    let Some(path) = servers_file_path(app) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = ServersFile {
+        profiles: state
+            .profiles
+            .lock()
+            .expect("server profiles mutex")
+            .clone(),
+        active_profile_id: state
+            .active_profile_id
+            .lock()
+            .expect("active server mutex")
+            .clone(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&file)?)?;
+    Ok(())
+}
+
+fn credentials_secret_key(profile_id: &str) -> String {
+    format!("server-auth:{profile_id}")
+}
+
+/// Saves (or with `None`, deletes) a profile's credentials in the keychain.
+fn store_credentials<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    profile_id: &str,
+    credentials: Option<&ServerCredentials>,
+) -> Result<(), String> {
+    let key = credentials_secret_key(profile_id);
+    match credentials {
+        Some(credentials) => {
+            let raw = serde_json::to_string(credentials).map_err(|err| err.to_string())?;
+            secrets::set_secret(app, None, &key, &raw)
+        }
+        None => secrets::delete_secret(app, None, &key).map(|_| ()),
+    }
+}
+
+fn load_credentials<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    profile: &ServerProfile,
+) -> Result<Option<ServerCredentials>, String> {
+    if let Some(header) = profile.auth_header.clone() {
+        return Ok(Some(ServerCredentials::Header { value: header }));
+    }
+    if !profile.has_credentials {
+        return Ok(None);
+    }
+    let Some(raw) = secrets::get_secret(app, None, &credentials_secret_key(&profile.id))? else {
+        return Ok(None);
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|err| format!("Stored server credentials are unreadable: {err}"))
+}
+
+fn next_profile_id(existing: &[ServerProfile]) -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let mut candidate = format!("srv-{millis:x}");
+    let mut suffix = 1;
+    while existing.iter().any(|profile| profile.id == candidate) {
+        candidate = format!("srv-{millis:x}-{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+fn health_client(
+    app: &tauri::AppHandle,
+    profile: &ServerProfile,
+) -> Result<reqwest::Client, String> {
+    let builder = proxy::load(app)
+        .apply(reqwest::Client::builder())
+        .timeout(CONNECT_TIMEOUT);
+    profile
+        .tls
+        .apply(builder)?
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+async fn check_server_health(
+    app: &tauri::AppHandle,
+    profile: &ServerProfile,
+    authorization: Option<&str>,
+) -> Result<(), String> {
+    let client = health_client(app, profile)?;
+    let health_url = format!("{}/health", profile.url.trim_end_matches('/'));
+
+    if profile.tls.pinned_fingerprint().is_some() {
+        // Check the pin before any credentials go over the connection.
+        let response = client
+            .get(&health_url)
+            .send()
+            .await
+            .map_err(|err| format!("Server unreachable: {err}"))?;
+        profile.tls.verify_response(&response)?;
+    }
+
+    let mut request = client.get(&health_url);
+    if let Some(header) = authorization {
+        request = request.header(reqwest::header::AUTHORIZATION, header);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|err| format!("Server unreachable: {err}"))?;
+    profile.tls.verify_response(&response)?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else if status.as_u16() == 401 || status.as_u16() == 403 {
+        Err("Server rejected the credentials".to_string())
+    } else {
+        Err(format!("Server health check failed with status {status}"))
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_servers_list(
+    webview: tauri::Webview,
+    state: tauri::State<'_, ServerProfilesState>,
+) -> Result<Vec<ServerProfile>, String> {
+    crate::origin::require_local(&webview, "list servers")?;
+    Ok(state
+        .profiles
+        .lock()
+        .expect("server profiles mutex")
+        .clone())
+}
+
+/// Asks before saving a profile that turns off certificate validation.
+async fn confirm_insecure_tls(app: &tauri::AppHandle, url: &str) -> Result<bool, String> {
+    let app = app.clone();
+    let url = url.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        app.dialog()
+            .message(i18n::t_args(
+                &app,
+                "dialog.insecureTls.message",
+                &[("url", &url)],
+            ))
+            .title(i18n::t(&app, "dialog.insecureTls.title"))
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                i18n::t(&app, "dialog.insecureTls.confirm"),
+                i18n::t(&app, "dialog.cancel"),
+            ))
+            .blocking_show()
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// TLS options of the saved profile serving `url`, if any.
+pub(crate) fn tls_options_for_url<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    url: &str,
+) -> Option<ServerTlsOptions> {
+    let origin = url::Url::parse(url).ok()?.origin();
+    let state = app.try_state::<ServerProfilesState>()?;
+    let profiles = state.profiles.lock().expect("server profiles mutex");
+    profiles
+        .iter()
+        .find(|profile| url::Url::parse(&profile.url).is_ok_and(|parsed| parsed.origin() == origin))
+        .map(|profile| profile.tls.clone())
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_server_add(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, ServerProfilesState>,
+    profile: ServerProfileInput,
+) -> Result<ServerProfile, String> {
+    crate::origin::require_local(&webview, "add a server")?;
+    let url = normalize_server_url(&profile.url).ok_or_else(|| "Invalid URL".to_string())?;
+    // Surface unreadable CA bundles now rather than on the first connection.
+    let _ = profile.tls.apply(reqwest::Client::builder())?;
+    if profile.tls.accept_invalid_certs
+        && profile.tls.pinned_fingerprint().is_none()
+        && !confirm_insecure_tls(&app, &url).await?
+    {
+        return Err("Adding the server was cancelled".to_string());
+    }
+    let name = match profile.name.trim() {
+        "" => url.clone(),
+        name => name.to_string(),
+    };
+    let credentials = profile.credentials.or_else(|| {
+        profile
+            .auth_header
+            .map(|header| header.trim().to_string())
+            .filter(|header| !header.is_empty())
+            .map(|value| ServerCredentials::Header { value })
+    });
+
+    let created = {
+        let mut profiles = state.profiles.lock().expect("server profiles mutex");
+        let created = ServerProfile {
+            id: next_profile_id(&profiles),
+            name,
+            url,
+            auth_header: None,
+            has_credentials: false,
+            tls: profile.tls,
+        };
+        profiles.push(created.clone());
+        created
+    };
+    let created = match credentials {
+        Some(credentials) => set_profile_credentials(&app, &state, &created.id, Some(credentials))?,
+        None => {
+            write_to_disk(&app, &state).map_err(|err| err.to_string())?;
+            created
+        }
+    };
+    Ok(created)
+}
+
+fn set_profile_credentials(
+    app: &tauri::AppHandle,
+    state: &ServerProfilesState,
+    profile_id: &str,
+    credentials: Option<ServerCredentials>,
+) -> Result<ServerProfile, String> {
+    if state.get(profile_id).is_none() {
+        return Err(format!("Unknown server profile: {profile_id}"));
+    }
+    store_credentials(app, profile_id, credentials.as_ref())?;
+    let updated = {
+        let mut profiles = state.profiles.lock().expect("server profiles mutex");
+        let profile = profiles
+            .iter_mut()
+            .find(|profile| profile.id == profile_id)
+            .ok_or_else(|| format!("Unknown server profile: {profile_id}"))?;
+        profile.auth_header = None;
+        profile.has_credentials = credentials.is_some();
+        profile.clone()
+    };
+    write_to_disk(app, state).map_err(|err| err.to_string())?;
+    Ok(updated)
+}
+
+/// Saved profiles, for settings export.
+pub(crate) fn saved_profiles(state: &ServerProfilesState) -> Vec<ServerProfile> {
+    state
+        .profiles
+        .lock()
+        .expect("server profiles mutex")
+        .clone()
+}
+
+/// Adds imported profiles, replacing saved ones with the same id. Credentials are
+/// not part of a profile, so `has_credentials` is recomputed from the keychain;
+/// import the secrets first. Returns how many profiles were imported.
+pub(crate) fn import_profiles<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &ServerProfilesState,
+    imported: Vec<ServerProfile>,
+) -> Result<usize, String> {
+    let stored: Vec<String> = secrets::list_secrets(app, None);
+    let mut count = 0;
+    {
+        let mut profiles = state.profiles.lock().expect("server profiles mutex");
+        for mut profile in imported {
+            let Some(url) = normalize_server_url(&profile.url) else {
+                log::warn!(
+                    "[servers] skipped imported profile with invalid URL {}",
+                    profile.url
+                );
+                continue;
+            };
+            profile.url = url;
+            profile.auth_header = None;
+            profile.has_credentials = stored.contains(&credentials_secret_key(&profile.id));
+            match profiles
+                .iter_mut()
+                .find(|existing| existing.id == profile.id)
+            {
+                Some(existing) => *existing = profile,
+                None => profiles.push(profile),
+            }
+            count += 1;
+        }
+    }
+    write_to_disk(app, state).map_err(|err| err.to_string())?;
+    Ok(count)
+}
+
+/// Replaces (or with `null`, clears) the keychain credentials of a saved profile.
+/// Takes effect on the next health check or connection.
+#[tauri::command]
+pub(crate) fn desktop_server_set_credentials(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, ServerProfilesState>,
+    profile_id: String,
+    credentials: Option<ServerCredentials>,
+) -> Result<ServerProfile, String> {
+    crate::origin::require_local(&webview, "store server credentials")?;
+    set_profile_credentials(&app, &state, &profile_id, credentials)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_server_remove(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, ServerProfilesState>,
+    profile_id: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "remove a server")?;
+    let removed = {
+        let mut profiles = state.profiles.lock().expect("server profiles mutex");
+        let before = profiles.len();
+        profiles.retain(|profile| profile.id != profile_id);
+        profiles.len() != before
+    };
+    if !removed {
+        return Ok(false);
+    }
+    if let Err(err) = store_credentials(&app, &profile_id, None) {
+        log::warn!("[servers] failed to delete credentials for {profile_id}: {err}");
+    }
+
+    {
+        let mut active = state.active_profile_id.lock().expect("active server mutex");
+        if active.as_deref() == Some(profile_id.as_str()) {
+            *active = None;
+        }
+    }
+
+    write_to_disk(&app, &state).map_err(|err| err.to_string())?;
+    Ok(true)
+}
+
+/// Health-checks the profile and, if it responds, points the main window at it.
+///
+/// The profile's TLS options (CA bundle, pinned fingerprint, insecure flag) apply to
+/// the health check only. The webview validates certificates against the OS trust
+/// store, so servers signed by an internal CA also need that CA installed system-wide.
+///
+/// Credentials reach the webview two ways: basic auth is embedded in the navigation
+/// URL so the proxy accepts the initial document request, and [`inject_auth`] adds the
+/// `Authorization` header to the SPA's own fetch/XHR calls. Bearer-only proxies must
+/// therefore let the document itself through (or set a session cookie).
+#[tauri::command]
+pub(crate) async fn desktop_connect_server(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, ServerProfilesState>,
+    profile_id: String,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "connect to a server")?;
+    let profile = state
+        .get(&profile_id)
+        .ok_or_else(|| format!("Unknown server profile: {profile_id}"))?;
+
+    let credentials = load_credentials(&app, &profile)?;
+    if credentials.is_some() {
+        auth::require_presence(&app, AuthPurpose::ConnectServer, &profile.name).await?;
+    }
+    let authorization = credentials.as_ref().map(ServerCredentials::authorization);
+    check_server_health(&app, &profile, authorization.as_deref()).await?;
+    let client = health_client(&app, &profile)?;
+    if let compat::Compatibility::UpdateRequested =
+        compat::check(&app, &client, &profile.url, authorization.as_deref()).await
+    {
+        compat::request_update_check(&app);
+        return Err("Server version is not compatible with this app".to_string());
+    }
+
+    let mut target = url::Url::parse(&profile.url).map_err(|err| err.to_string())?;
+    *state.active_auth.lock().expect("active server auth mutex") =
+        authorization.map(|header| (target.origin().ascii_serialization(), header));
+    if let Some(ServerCredentials::Basic { username, password }) = &credentials {
+        let _ = target.set_username(username);
+        let _ = target.set_password(Some(password));
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    window.navigate(target).map_err(|err| err.to_string())?;
+
+    *state.active_profile_id.lock().expect("active server mutex") = Some(profile.id.clone());
+    if let Err(err) = write_to_disk(&app, &state) {
+        log::warn!("[servers] failed to persist active server: {err}");
+    }
+
+    log::info!("[servers] connected to {} ({})", profile.name, profile.url);
+    events::emit(
+        &app,
+        DesktopEvent::ServerConnected(ServerConnectedEvent {
+            profile_id: profile.id,
+            url: profile.url,
+        }),
+    );
+    Ok(())
+}
+
+/// Page-load hook for the main window: when it shows the connected server, patches
+/// `fetch` and `XMLHttpRequest` so same-origin requests carry its `Authorization`
+/// header. Requests that already set the header are left alone.
+pub(crate) fn inject_auth<R: tauri::Runtime>(webview: &tauri::Webview<R>, url: &url::Url) {
+    let Some(state) = webview.try_state::<ServerProfilesState>() else {
+        return;
+    };
+    let Some((origin, header)) = state
+        .active_auth
+        .lock()
+        .expect("active server auth mutex")
+        .clone()
+    else {
+        return;
+    };
+    if url.origin().ascii_serialization() != origin {
+        return;
+    }
+    let Ok(header) = serde_json::to_string(&header) else {
+        return;
+    };
+    let script = format!(
+        r#"(function() {{
+  window.__OPENCHAMBER_SERVER_AUTH__ = {header};
+  if (window.__OPENCHAMBER_SERVER_AUTH_PATCHED__) return;
+  window.__OPENCHAMBER_SERVER_AUTH_PATCHED__ = true;
+  var sameOrigin = function(target) {{
+    try {{ return new URL(target, window.location.href).origin === window.location.origin; }}
+    catch (_) {{ return false; }}
+  }};
+  var originalFetch = window.fetch;
+  window.fetch = function(input, init) {{
+    var target = input instanceof Request ? input.url : String(input);
+    if (!sameOrigin(target)) return originalFetch.call(this, input, init);
+    var request = new Request(input, init);
+    if (!request.headers.has('Authorization')) {{
+      request.headers.set('Authorization', window.__OPENCHAMBER_SERVER_AUTH__);
+    }}
+    return originalFetch.call(this, request);
+  }};
+  var originalOpen = XMLHttpRequest.prototype.open;
+  var originalSetHeader = XMLHttpRequest.prototype.setRequestHeader;
+  var originalSend = XMLHttpRequest.prototype.send;
+  XMLHttpRequest.prototype.open = function(method, target) {{
+    this.__openchamberAuth = sameOrigin(target);
+    return originalOpen.apply(this, arguments);
+  }};
+  XMLHttpRequest.prototype.setRequestHeader = function(name) {{
+    if (String(name).toLowerCase() === 'authorization') this.__openchamberAuth = false;
+    return originalSetHeader.apply(this, arguments);
+  }};
+  XMLHttpRequest.prototype.send = function() {{
+    if (this.__openchamberAuth) {{
+      originalSetHeader.call(this, 'Authorization', window.__OPENCHAMBER_SERVER_AUTH__);
+    }}
+    return originalSend.apply(this, arguments);
+  }};
+}})();"#
+    );
+    if let Err(err) = webview.eval(&script) {
+        log::warn!("[servers] failed to inject server auth: {err}");
+    }
+}
@@ -0,0 +1,662 @@
+use std::{fmt::Write as _, fs, time::Duration};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::{
+    audit::{self, AuditCategory},
+    lan, local_sidecar, path_policy,
+};
+
+/// Long sessions take a while to serialize on the server.
+const SERVER_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Identifies an exported session document, in every format.
+const DOCUMENT_FORMAT: &str = "openchamber-session";
+const DOCUMENT_VERSION: u64 = 1;
+/// Markdown exports carry the full document in a comment with this prefix, so
+/// they can be imported again.
+const MARKDOWN_EMBED_PREFIX: &str = "<!-- openchamber-session:";
+/// HTML exports carry it in a `<script>` element with this id.
+const HTML_EMBED_ID: &str = "openchamber-session";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SessionExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl SessionExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    fn filter_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Json => "JSON",
+            Self::Html => "HTML page",
+        }
+    }
+}
+
+/// An exported session: the server's session record and messages as they were
+/// fetched, plus where and when they came from.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionDocument {
+    format: String,
+    version: u64,
+    /// Milliseconds since the Unix epoch.
+    exported_at: i64,
+    app_version: String,
+    session: Value,
+    messages: Vec<Value>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportedSession {
+    id: String,
+    title: String,
+    /// Messages in the imported transcript.
+    messages: usize,
+}
+
+/// The local server, logged in with the LAN access token when it is shared, which
+/// makes it require a session cookie.
+struct ServerClient {
+    client: reqwest::Client,
+    base: String,
+    cookie: Option<String>,
+}
+
+impl ServerClient {
+    async fn connect(app: &tauri::AppHandle) -> Result<Self, String> {
+        let base = local_sidecar(app)
+            .and_then(|state| state.url.lock().expect("sidecar url mutex").clone())
+            .ok_or_else(|| "The local server is not running".to_string())?
+            .trim_end_matches('/')
+            .to_string();
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .timeout(SERVER_REQUEST_TIMEOUT)
+            .build()
+            .map_err(|err| err.to_string())?;
+        let cookie = match lan::access_token(app) {
+            Some(token) => {
+                let response = client
+                    .post(format!("{base}/auth/session"))
+                    .json(&json!({ "password": token }))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|err| format!("Failed to sign in to the local server: {err}"))?;
+                let cookies: Vec<&str> = response
+                    .headers()
+                    .get_all(reqwest::header::SET_COOKIE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .filter_map(|value| value.split(';').next())
+                    .collect();
+                (!cookies.is_empty()).then(|| cookies.join("; "))
+            }
+            None => None,
+        };
+        Ok(Self {
+            client,
+            base,
+            cookie,
+        })
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        directory: Option<&str>,
+    ) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, format!("{}/api{path}", self.base));
+        if let Some(directory) = directory {
+            request = request.query(&[("directory", directory)]);
+        }
+        if let Some(cookie) = &self.cookie {
+            request = request.header(reqwest::header::COOKIE, cookie);
+        }
+        request
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> Result<Value, String> {
+        let response = request.send().await.map_err(|err| err.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Server responded with {status}: {}", body.trim()));
+        }
+        response.json().await.map_err(|err| err.to_string())
+    }
+}
+
+fn text<'a>(value: &'a Value, pointer: &str) -> Option<&'a str> {
+    value.pointer(pointer).and_then(Value::as_str)
+}
+
+fn format_time(millis: Option<i64>) -> String {
+    millis
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn session_title(document: &SessionDocument) -> &str {
+    text(&document.session, "/title")
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or("Untitled session")
+}
+
+/// `User`, or `Assistant (provider/model)` when the message says which model.
+fn speaker(info: &Value) -> String {
+    match text(info, "/role") {
+        Some("user") => "User".to_string(),
+        _ => match (text(info, "/providerID"), text(info, "/modelID")) {
+            (Some(provider), Some(model)) => format!("Assistant ({provider}/{model})"),
+            (None, Some(model)) => format!("Assistant ({model})"),
+            _ => "Assistant".to_string(),
+        },
+    }
+}
+
+/// What a transcript shows of one part of a message.
+enum Block {
+    Text(String),
+    Reasoning(String),
+    Tool {
+        name: String,
+        status: String,
+        title: Option<String>,
+        output: Option<String>,
+    },
+    File(String),
+}
+
+/// `None` for bookkeeping parts (step markers, snapshots) that mean nothing
+/// outside the app.
+fn block(part: &Value) -> Option<Block> {
+    match text(part, "/type")? {
+        "text" if part.get("synthetic").and_then(Value::as_bool) != Some(true) => {
+            Some(Block::Text(text(part, "/text")?.to_string()))
+        }
+        "reasoning" => Some(Block::Reasoning(text(part, "/text")?.to_string())),
+        "tool" => Some(Block::Tool {
+            name: text(part, "/tool").unwrap_or("tool").to_string(),
+            status: text(part, "/state/status").unwrap_or_default().to_string(),
+            title: text(part, "/state/title").map(str::to_string),
+            output: text(part, "/state/output")
+                .or_else(|| text(part, "/state/error"))
+                .map(str::to_string),
+        }),
+        // The URL is often the whole file as a data URL, so only the name is kept.
+        "file" => Some(Block::File(
+            text(part, "/filename").unwrap_or("file").to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// A fence longer than any backtick run in `content`.
+fn fence(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// The conversation as Markdown, without metadata; also what an import hands to
+/// the new session.
+fn markdown_transcript(document: &SessionDocument) -> String {
+    let mut out = String::new();
+    for message in &document.messages {
+        let info = message.get("info").unwrap_or(&Value::Null);
+        let _ = writeln!(
+            out,
+            "## {} · {}\n",
+            speaker(info),
+            format_time(info.pointer("/time/created").and_then(Value::as_i64))
+        );
+        let parts = message.get("parts").and_then(Value::as_array);
+        for part in parts.into_iter().flatten().filter_map(block) {
+            match part {
+                Block::Text(text) => {
+                    let _ = writeln!(out, "{}\n", text.trim_end());
+                }
+                Block::Reasoning(text) => {
+                    let quoted: Vec<String> = text
+                        .trim_end()
+                        .lines()
+                        .map(|line| format!("> {line}"))
+                        .collect();
+                    let _ = writeln!(out, "> **Thinking**\n>\n{}\n", quoted.join("\n"));
+                }
+                Block::Tool {
+                    name,
+                    status,
+                    title,
+                    output,
+                } => {
+                    let summary = match title {
+                        Some(title) => format!("{name}: {title}"),
+                        None => name,
+                    };
+                    let _ = writeln!(
+                        out,
+                        "<details>\n<summary>{} ({})</summary>\n",
+                        escape_html(&summary),
+                        escape_html(&status)
+                    );
+                    if let Some(output) = output {
+                        let fence = fence(&output);
+                        let _ = writeln!(out, "{fence}\n{}\n{fence}\n", output.trim_end());
+                    }
+                    let _ = writeln!(out, "</details>\n");
+                }
+                Block::File(name) => {
+                    let _ = writeln!(out, "*Attached: {name}*\n");
+                }
+            }
+        }
+    }
+    out
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn render_markdown(document: &SessionDocument) -> Result<String, String> {
+    let session = &document.session;
+    let mut out = String::from("---\n");
+    let _ = writeln!(out, "title: {}", yaml_string(session_title(document)));
+    let _ = writeln!(
+        out,
+        "session: {}",
+        yaml_string(text(session, "/id").unwrap_or_default())
+    );
+    if let Some(directory) = text(session, "/directory") {
+        let _ = writeln!(out, "directory: {}", yaml_string(directory));
+    }
+    let _ = writeln!(
+        out,
+        "created: {}",
+        yaml_string(&format_time(
+            session.pointer("/time/created").and_then(Value::as_i64)
+        ))
+    );
+    let _ = writeln!(
+        out,
+        "exported: {}",
+        yaml_string(&format_time(Some(document.exported_at)))
+    );
+    let _ = writeln!(out, "messages: {}", document.messages.len());
+    let _ = writeln!(out, "openchamber: {}", yaml_string(&document.app_version));
+    let _ = writeln!(out, "---\n\n# {}\n", session_title(document));
+    out.push_str(&markdown_transcript(document));
+    let embedded = serde_json::to_vec(document).map_err(|err| err.to_string())?;
+    let _ = writeln!(
+        out,
+        "{MARKDOWN_EMBED_PREFIX}{} -->",
+        base64::engine::general_purpose::STANDARD.encode(embedded)
+    );
+    Ok(out)
+}
+
+fn escape_html(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_html(document: &SessionDocument) -> Result<String, String> {
+    let title = escape_html(session_title(document));
+    let mut body = String::new();
+    for message in &document.messages {
+        let info = message.get("info").unwrap_or(&Value::Null);
+        let role = if text(info, "/role") == Some("user") {
+            "user"
+        } else {
+            "assistant"
+        };
+        let _ = writeln!(
+            body,
+            "<section class=\"{role}\"><h2>{} <time>{}</time></h2>",
+            escape_html(&speaker(info)),
+            format_time(info.pointer("/time/created").and_then(Value::as_i64))
+        );
+        let parts = message.get("parts").and_then(Value::as_array);
+        for part in parts.into_iter().flatten().filter_map(block) {
+            match part {
+                Block::Text(text) => {
+                    let _ = writeln!(body, "<div class=\"text\">{}</div>", escape_html(&text));
+                }
+                Block::Reasoning(text) => {
+                    let _ = writeln!(
+                        body,
+                        "<blockquote class=\"text\">{}</blockquote>",
+                        escape_html(&text)
+                    );
+                }
+                Block::Tool {
+                    name,
+                    status,
+                    title,
+                    output,
+                } => {
+                    let summary = match title {
+                        Some(title) => format!("{name}: {title}"),
+                        None => name,
+                    };
+                    let _ = writeln!(
+                        body,
+                        "<details><summary>{} ({})</summary><pre>{}</pre></details>",
+                        escape_html(&summary),
+                        escape_html(&status),
+                        escape_html(output.as_deref().unwrap_or_default())
+                    );
+                }
+                Block::File(name) => {
+                    let _ = writeln!(body, "<p><em>Attached: {}</em></p>", escape_html(&name));
+                }
+            }
+        }
+        body.push_str("</section>\n");
+    }
+    // `</` can't appear inside a script element; JSON allows escaping the slash.
+    let embedded = serde_json::to_string(document)
+        .map_err(|err| err.to_string())?
+        .replace("</", "<\\/");
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="generator" content="OpenChamber {version}">
+<title>{title}</title>
+<style>
+body {{ font: 15px/1.5 system-ui, sans-serif; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; color: #222; }}
+h2 {{ font-size: 0.95rem; margin: 0 0 0.5rem; }}
+time {{ color: #888; font-weight: normal; }}
+section {{ border-radius: 8px; padding: 0.75rem 1rem; margin: 1rem 0; }}
+section.user {{ background: #eef3fb; }}
+section.assistant {{ background: #f6f6f6; }}
+.text {{ white-space: pre-wrap; }}
+blockquote {{ color: #666; border-left: 3px solid #ccc; margin: 0.5rem 0; padding-left: 0.75rem; }}
+pre {{ white-space: pre-wrap; background: #fff; padding: 0.5rem; overflow-x: auto; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p><time>{created}</time> · exported {exported}</p>
+{body}<script type="application/json" id="{HTML_EMBED_ID}">{embedded}</script>
+</body>
+</html>
+"#,
+        version = escape_html(&document.app_version),
+        created = format_time(
+            document
+                .session
+                .pointer("/time/created")
+                .and_then(Value::as_i64)
+        ),
+        exported = format_time(Some(document.exported_at)),
+    ))
+}
+
+fn render(document: &SessionDocument, format: SessionExportFormat) -> Result<String, String> {
+    match format {
+        SessionExportFormat::Markdown => render_markdown(document),
+        SessionExportFormat::Json => {
+            serde_json::to_string_pretty(document).map_err(|err| err.to_string())
+        }
+        SessionExportFormat::Html => render_html(document),
+    }
+}
+
+/// Reads a document back from any of the export formats.
+fn parse_document(contents: &str) -> Result<SessionDocument, String> {
+    let json = if contents.trim_start().starts_with('{') {
+        contents.to_string()
+    } else if let Some(start) = contents.rfind(MARKDOWN_EMBED_PREFIX) {
+        let encoded = contents[start + MARKDOWN_EMBED_PREFIX.len()..]
+            .split("-->")
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|err| format!("The embedded session is damaged: {err}"))?;
+        String::from_utf8(bytes).map_err(|err| err.to_string())?
+    } else if let Some(start) = contents.find(&format!("id=\"{HTML_EMBED_ID}\">")) {
+        let start = start + HTML_EMBED_ID.len() + 6;
+        let end = contents[start..]
+            .find("</script>")
+            .ok_or_else(|| "The embedded session is damaged".to_string())?;
+        contents[start..start + end].to_string()
+    } else {
+        return Err("Not an exported OpenChamber session".to_string());
+    };
+    let document: SessionDocument = serde_json::from_str(&json)
+        .map_err(|err| format!("Not an exported OpenChamber session: {err}"))?;
+    if document.format != DOCUMENT_FORMAT {
+        return Err("Not an exported OpenChamber session".to_string());
+    }
+    if document.version > DOCUMENT_VERSION {
+        return Err(format!(
+            "The session was exported by a newer OpenChamber (format version {})",
+            document.version
+        ));
+    }
+    Ok(document)
+}
+
+/// Percent-encodes a path segment.
+fn urlencode(segment: &str) -> String {
+    url::form_urlencoded::byte_serialize(segment.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// A file name from the session title, without characters file systems reject.
+fn default_file_name(document: &SessionDocument, format: SessionExportFormat) -> String {
+    let title: String = session_title(document)
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(80)
+        .collect();
+    let title = title.trim().trim_matches('.');
+    let title = if title.is_empty() { "session" } else { title };
+    format!("{title}.{}", format.extension())
+}
+
+/// Fetches a session's transcript from the local server, asks where to save it,
+/// and writes it as Markdown, JSON or a standalone HTML page. Every format embeds
+/// the full session, so any of them can be imported again. Returns the saved path,
+/// or `None` if the save dialog was cancelled.
+#[tauri::command]
+pub(crate) async fn desktop_export_session(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    session_id: String,
+    format: SessionExportFormat,
+    directory: Option<String>,
+) -> Result<Option<String>, String> {
+    crate::origin::require_local(&webview, "export sessions")?;
+    let server = ServerClient::connect(&app).await?;
+    let directory = directory.as_deref().filter(|dir| !dir.trim().is_empty());
+    let path = format!("/session/{}", urlencode(&session_id));
+    let session = ServerClient::send(server.request(reqwest::Method::GET, &path, directory))
+        .await
+        .map_err(|err| format!("Failed to load the session: {err}"))?;
+    let messages = ServerClient::send(server.request(
+        reqwest::Method::GET,
+        &format!("{path}/message"),
+        directory,
+    ))
+    .await
+    .map_err(|err| format!("Failed to load the session's messages: {err}"))?;
+    let document = SessionDocument {
+        format: DOCUMENT_FORMAT.to_string(),
+        version: DOCUMENT_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        app_version: app.package_info().version.to_string(),
+        session,
+        messages: match messages {
+            Value::Array(messages) => messages,
+            _ => return Err("The server returned no messages".to_string()),
+        },
+    };
+    let contents = render(&document, format)?;
+
+    let default_name = default_file_name(&document, format);
+    let dialog_app = app.clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .set_file_name(default_name)
+            .add_filter(format.filter_name(), &[format.extension()])
+            .blocking_save_file()
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let target = picked.into_path().map_err(|err| err.to_string())?;
+
+    let result = fs::write(&target, contents).map_err(|err| err.to_string());
+    audit::record(
+        &app,
+        AuditCategory::FileWrite,
+        "export session",
+        target.to_string_lossy(),
+        &result,
+    );
+    result?;
+    log::info!(
+        "[session-export] exported {session_id} ({} messages) to {}",
+        document.messages.len(),
+        target.display()
+    );
+    Ok(Some(target.to_string_lossy().to_string()))
+}
+
+/// Restores an exported session as a new session on the local server. The server
+/// can't take messages written elsewhere, so the transcript becomes the first
+/// message of the new session without asking the model to reply; the agent then
+/// has the whole conversation as context. Without `path`, asks for the file.
+/// Returns `None` if the open dialog was cancelled.
+#[tauri::command]
+pub(crate) async fn desktop_import_session(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: Option<String>,
+    directory: Option<String>,
+) -> Result<Option<ImportedSession>, String> {
+    crate::origin::require_local(&webview, "import sessions")?;
+    let source = match path {
+        Some(path) => {
+            path_policy::check(&app, "import session", &path).map_err(|err| err.to_string())?
+        }
+        None => {
+            let dialog_app = app.clone();
+            let picked = tauri::async_runtime::spawn_blocking(move || {
+                dialog_app
+                    .dialog()
+                    .file()
+                    .add_filter("Exported session", &["md", "json", "html"])
+                    .blocking_pick_file()
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+            let Some(picked) = picked else {
+                return Ok(None);
+            };
+            picked.into_path().map_err(|err| err.to_string())?
+        }
+    };
+    let contents =
+        fs::read_to_string(&source).map_err(|err| format!("{}: {err}", source.display()))?;
+    let document = parse_document(&contents)?;
+
+    let server = ServerClient::connect(&app).await?;
+    let directory = directory
+        .as_deref()
+        .filter(|dir| !dir.trim().is_empty())
+        .or_else(|| text(&document.session, "/directory"));
+    let title = format!("{} (imported)", session_title(&document));
+    let created = ServerClient::send(
+        server
+            .request(reqwest::Method::POST, "/session", directory)
+            .json(&json!({ "title": title })),
+    )
+    .await
+    .map_err(|err| format!("Failed to create the session: {err}"))?;
+    let id = text(&created, "/id")
+        .ok_or_else(|| "The server did not return a session id".to_string())?
+        .to_string();
+
+    let transcript = format!(
+        "This session was imported from an export of \"{}\" ({}). \
+         The conversation so far:\n\n{}",
+        session_title(&document),
+        format_time(Some(document.exported_at)),
+        markdown_transcript(&document)
+    );
+    let path = format!("/session/{}/message", urlencode(&id));
+    ServerClient::send(
+        server
+            .request(reqwest::Method::POST, &path, directory)
+            .json(&json!({
+                "noReply": true,
+                "parts": [{ "type": "text", "text": transcript }],
+            })),
+    )
+    .await
+    .map_err(|err| format!("Failed to restore the transcript: {err}"))?;
+
+    log::info!(
+        "[session-export] imported {} as {id} ({} messages)",
+        source.display(),
+        document.messages.len()
+    );
+    Ok(Some(ImportedSession {
+        id,
+        title,
+        messages: document.messages.len(),
+    }))
+}
@@ -0,0 +1,457 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::Manager;
+use ts_rs::TS;
+
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{
+    audit::{self, AuditCategory},
+    events::{self, DesktopEvent},
+    i18n, profiles,
+};
+
+pub(crate) const SETTINGS_FILE_NAME: &str = "desktop-settings.json";
+const SETTINGS_SCHEMA_VERSION: u64 = 3;
+
+/// Desktop preferences that used to live in the server's `settings.json`; schema
+/// versions 2 and 3 copy them into this store.
+const LEGACY_KEYS: &[&str] = &[
+    crate::updates::UPDATE_CHANNEL_SETTING_KEY,
+    crate::updates::UPDATE_CHECK_INTERVAL_SETTING_KEY,
+    crate::SIDECAR_MAX_RESTARTS_SETTING_KEY,
+    crate::HEALTH_TIMEOUT_SETTING_KEY,
+    crate::HEALTH_POLL_INTERVAL_SETTING_KEY,
+    crate::SIDECAR_SHUTDOWN_GRACE_SETTING_KEY,
+];
+
+/// Whether "New Session" creates a worktree by default; swaps the menu accelerators.
+pub(crate) const AUTO_WORKTREE_KEY: &str = "autoWorktree";
+
+/// Keys the generic setter and settings import refuse, because they can run
+/// programs or send data elsewhere: each has its own command that checks with the
+/// user before changing it.
+const PROTECTED_KEYS: &[&str] = &[
+    crate::auth::OS_AUTH_KEY,
+    crate::devtools::DEVTOOLS_ENABLED_KEY,
+    crate::editor::EDITOR_COMMAND_KEY,
+    crate::lan::LAN_ACCESS_KEY,
+    crate::proxy::PROXY_SETTINGS_KEY,
+    crate::sidecar_env::SIDECAR_ENV_KEY,
+    crate::telemetry::TELEMETRY_KEY,
+    crate::updates::UPDATER_SETTINGS_KEY,
+];
+
+pub(crate) fn is_protected(key: &str) -> bool {
+    PROTECTED_KEYS.contains(&key.trim())
+}
+
+/// Desktop-owned settings shared by every window, stored as
+/// `{ "schemaVersion": n, "values": { ... } }` in the app config dir.
+/// Loaded lazily because the app menu reads it before `setup` runs.
+#[derive(Default)]
+pub(crate) struct SettingsStore {
+    values: Mutex<Option<Map<String, Value>>>,
+    /// Webview label -> keys it wants change events for (`None` = all keys).
+    subscribers: Mutex<HashMap<String, Option<HashSet<String>>>>,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsChangedEvent {
+    key: String,
+    value: Value,
+    /// Label of the webview that made the change, so it can skip its own echo.
+    source: Option<String>,
+}
+
+/// `None` in safe mode, which keeps settings in memory only.
+fn settings_file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    if crate::safe_mode::is_enabled() {
+        return None;
+    }
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(SETTINGS_FILE_NAME))
+}
+
+fn schema_version(doc: &Value) -> u64 {
+    doc.get("schemaVersion")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// Fills `LEGACY_KEYS` the document does not have yet from the server's `settings.json`.
+fn copy_legacy_keys(doc: &mut Value) {
+    let Some(values) = doc.get_mut("values").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for key in LEGACY_KEYS {
+        if values.contains_key(*key) {
+            continue;
+        }
+        if let Some(value) = crate::read_desktop_setting_from_disk(key) {
+            values.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Brings a stored document up to `SETTINGS_SCHEMA_VERSION`, one version at a time.
+fn migrate(mut doc: Value) -> Map<String, Value> {
+    let mut version = schema_version(&doc);
+
+    if version == 0 {
+        // Unversioned files were a flat key/value object.
+        let values = match doc {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        doc = serde_json::json!({ "schemaVersion": 1, "values": values });
+        version = 1;
+    }
+
+    if version == 1 {
+        copy_legacy_keys(&mut doc);
+        version = 2;
+    }
+
+    if version == 2 {
+        // Version 3 added the health and shutdown timings to `LEGACY_KEYS`; keys
+        // copied by version 2 are already present and left alone.
+        copy_legacy_keys(&mut doc);
+        version = 3;
+    }
+
+    if version > SETTINGS_SCHEMA_VERSION {
+        log::warn!("[settings] file has newer schema version {version}; reading as-is");
+    }
+
+    match doc.get_mut("values").map(Value::take) {
+        Some(Value::Object(values)) => values,
+        _ => Map::new(),
+    }
+}
+
+/// Loads the stored values, saving them back right away if they needed migrating
+/// so each migration runs once.
+fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Map<String, Value> {
+    let doc = settings_file_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .unwrap_or(Value::Null);
+    let outdated = schema_version(&doc) < SETTINGS_SCHEMA_VERSION;
+    let values = migrate(doc);
+    if outdated {
+        if let Err(err) = persist(app, &values) {
+            log::warn!("[settings] failed to save migrated settings: {err}");
+        }
+    }
+    values
+}
+
+fn persist<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    values: &Map<String, Value>,
+) -> anyhow::Result<()> {
+    let Some(path) = settings_file_path(app) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let doc = serde_json::json!({
+        "schemaVersion": SETTINGS_SCHEMA_VERSION,
+        "values": values,
+    });
+    fs::write(&path, serde_json::to_string_pretty(&doc)?)?;
+    Ok(())
+}
+
+fn with_values<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut Map<String, Value>) -> T,
+) -> Option<T> {
+    let store = app.try_state::<SettingsStore>()?;
+    let mut guard = store.values.lock().expect("settings mutex");
+    let values = guard.get_or_insert_with(|| load(app));
+    Some(f(values))
+}
+
+pub(crate) fn get_setting<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    key: &str,
+) -> Option<Value> {
+    with_values(app, |values| values.get(key).cloned()).flatten()
+}
+
+/// Every stored setting as one object.
+pub(crate) fn all_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Value {
+    with_values(app, |values| Value::Object(values.clone())).unwrap_or(Value::Null)
+}
+
+pub(crate) fn get_bool_setting<R: tauri::Runtime>(app: &tauri::AppHandle<R>, key: &str) -> bool {
+    get_setting(app, key)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Native side effects of a setting changing, regardless of which window changed it.
+fn apply_side_effects<R: tauri::Runtime>(app: &tauri::AppHandle<R>, key: &str, value: &Value) {
+    if key == AUTO_WORKTREE_KEY {
+        if let Err(err) = crate::menu::apply_auto_worktree(app, value.as_bool().unwrap_or(false)) {
+            log::warn!("[settings] failed to update menu accelerators: {err}");
+        }
+    }
+    if key == crate::zoom::ZOOM_FACTORS_KEY {
+        crate::zoom::apply_zoom_factors(app, value);
+    }
+    if key == crate::appearance::THEME_PREFERENCE_KEY {
+        crate::appearance::apply_theme_setting(app, value);
+    }
+    if key == crate::i18n::LOCALE_KEY {
+        crate::i18n::apply_locale_setting(app, value);
+    }
+    if key == crate::spellcheck::SPELLCHECK_KEY {
+        crate::spellcheck::apply_spellcheck_setting(app, value);
+    }
+    if key == crate::proxy::PROXY_SETTINGS_KEY {
+        crate::proxy::invalidate(app);
+    }
+    if key == crate::telemetry::TELEMETRY_KEY {
+        crate::telemetry::apply_telemetry_setting(app, value);
+    }
+    if key == crate::updates::UPDATE_CHANNEL_SETTING_KEY {
+        crate::updates::apply_update_channel_setting(app, value);
+    }
+    if key == crate::updates::UPDATER_SETTINGS_KEY {
+        crate::updates::apply_updater_setting(app, value);
+    }
+}
+
+fn notify_subscribers<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    key: &str,
+    value: &Value,
+    source: Option<&str>,
+) {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return;
+    };
+    let labels: Vec<String> = store
+        .subscribers
+        .lock()
+        .expect("settings subscribers mutex")
+        .iter()
+        .filter(|(_, keys)| keys.as_ref().is_none_or(|keys| keys.contains(key)))
+        .map(|(label, _)| label.clone())
+        .collect();
+
+    let event = SettingsChangedEvent {
+        key: key.to_string(),
+        value: value.clone(),
+        source: source.map(str::to_string),
+    };
+    for label in labels {
+        events::emit_to(
+            app,
+            label.as_str(),
+            DesktopEvent::SettingsChanged(event.clone()),
+        );
+    }
+}
+
+/// Stores `value` under `key` (`null` removes it), persists, and notifies subscribers.
+pub(crate) fn set_setting<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    key: &str,
+    value: Value,
+    source: Option<&str>,
+) -> Result<(), String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Setting key must not be empty".to_string());
+    }
+
+    let changed = with_values(app, |values| {
+        let previous = if value.is_null() {
+            values.remove(key)
+        } else {
+            values.insert(key.to_string(), value.clone())
+        };
+        if previous.as_ref().unwrap_or(&Value::Null) == &value {
+            return Ok(false);
+        }
+        persist(app, values).map(|()| true)
+    })
+    .ok_or_else(|| "Settings store unavailable".to_string())?
+    .map_err(|err| err.to_string())?;
+
+    if changed {
+        apply_side_effects(app, key, &value);
+        notify_subscribers(app, key, &value, source);
+    }
+    Ok(())
+}
+
+fn confirm_protected_change(app: &tauri::AppHandle, key: &str) -> bool {
+    app.dialog()
+        .message(i18n::t_args(
+            app,
+            "dialog.protectedSetting.message",
+            &[("setting", key)],
+        ))
+        .title(i18n::t(app, "dialog.protectedSetting.title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.protectedSetting.confirm"),
+            i18n::t(app, "dialog.cancel"),
+        ))
+        .blocking_show()
+}
+
+/// Stores a [protected](is_protected) setting for its dedicated command, once the
+/// user confirms in a native dialog. The outcome is audit-logged without the value.
+pub(crate) async fn set_protected_setting(
+    app: &tauri::AppHandle,
+    webview: &tauri::Webview,
+    key: &str,
+    value: Value,
+) -> Result<(), String> {
+    if get_setting(app, key).unwrap_or(Value::Null) == value {
+        return Ok(());
+    }
+    let dialog_app = app.clone();
+    let dialog_key = key.to_string();
+    let confirmed = tauri::async_runtime::spawn_blocking(move || {
+        confirm_protected_change(&dialog_app, &dialog_key)
+    })
+    .await
+    .unwrap_or(false);
+    let result = if confirmed {
+        set_setting(app, key, value, Some(webview.label()))
+    } else {
+        Err(format!("Changing {key} was cancelled"))
+    };
+    audit::record(app, AuditCategory::Settings, "change setting", key, &result);
+    result
+}
+
+pub(crate) fn unsubscribe<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: &str) {
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        store
+            .subscribers
+            .lock()
+            .expect("settings subscribers mutex")
+            .remove(label);
+    }
+}
+
+/// Returns one setting, or the whole settings object when `key` is omitted.
+#[tauri::command]
+pub(crate) fn desktop_settings_get(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    key: Option<String>,
+) -> Result<Value, String> {
+    crate::origin::require_local(&webview, "read settings")?;
+    match key {
+        Some(key) => Ok(get_setting(&app, &key).unwrap_or(Value::Null)),
+        None => Ok(all_settings(&app)),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_settings_set(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    key: String,
+    value: Value,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change settings")?;
+    if is_protected(&key) {
+        return Err(format!(
+            "{key} cannot be changed through the generic setter"
+        ));
+    }
+    set_setting(&app, &key, value, Some(webview.label()))
+}
+
+/// Subscribes the calling webview to `openchamber:settings-changed` events for `keys`
+/// (all keys when omitted) and returns the current settings object.
+#[tauri::command]
+pub(crate) fn desktop_settings_subscribe(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    store: tauri::State<'_, SettingsStore>,
+    keys: Option<Vec<String>>,
+) -> Result<Value, String> {
+    crate::origin::require_local(&webview, "read settings")?;
+    store
+        .subscribers
+        .lock()
+        .expect("settings subscribers mutex")
+        .insert(
+            webview.label().to_string(),
+            keys.map(|keys| keys.into_iter().collect()),
+        );
+    Ok(all_settings(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Every legacy key, so the migration has nothing to read from disk.
+    fn legacy_values() -> Map<String, Value> {
+        LEGACY_KEYS
+            .iter()
+            .map(|key| (key.to_string(), json!(format!("stored {key}"))))
+            .collect()
+    }
+
+    #[test]
+    fn migrate_wraps_unversioned_files() {
+        let mut flat = legacy_values();
+        flat.insert("autoWorktree".to_string(), json!(true));
+        let values = migrate(Value::Object(flat.clone()));
+        assert_eq!(values, flat);
+    }
+
+    #[test]
+    fn migrate_keeps_values_of_versioned_files() {
+        let mut stored = legacy_values();
+        stored.insert("locale".to_string(), json!("de"));
+        for version in 1..=SETTINGS_SCHEMA_VERSION {
+            let doc = json!({ "schemaVersion": version, "values": stored.clone() });
+            assert_eq!(migrate(doc), stored, "schema version {version}");
+        }
+    }
+
+    #[test]
+    fn migrate_reads_newer_files_as_is() {
+        let doc = json!({
+            "schemaVersion": SETTINGS_SCHEMA_VERSION + 1,
+            "values": { "locale": "fr" },
+        });
+        assert_eq!(
+            migrate(doc),
+            Map::from_iter([("locale".to_string(), json!("fr"))])
+        );
+    }
+
+    #[test]
+    fn migrate_drops_malformed_values() {
+        let doc = json!({ "schemaVersion": SETTINGS_SCHEMA_VERSION, "values": 5 });
+        assert!(migrate(doc).is_empty());
+    }
+}
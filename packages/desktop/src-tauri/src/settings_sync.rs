@@ -0,0 +1,367 @@
+use std::{collections::BTreeMap, fs, num::NonZeroU32, path::Path};
+
+use base64::Engine;
+use ring::{aead, pbkdf2};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+use crate::{
+    audit::{self, AuditCategory},
+    fs_ops,
+    path_policy::{self, PathPolicy},
+    secrets,
+    servers::{self, ServerProfile, ServerProfilesState},
+    settings,
+    shortcuts::{self, ShortcutAction},
+};
+
+const BUNDLE_FORMAT: &str = "openchamber-settings";
+const BUNDLE_VERSION: u64 = 1;
+const KDF_NAME: &str = "pbkdf2-sha256";
+const CIPHER_NAME: &str = "aes-256-gcm";
+const KDF_ITERATIONS: u32 = 600_000;
+/// Refuses files that would make the import spin on key derivation.
+const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+const MIN_PASSPHRASE_LEN: usize = 8;
+const SALT_LEN: usize = 16;
+/// Binds the ciphertext to this file format.
+const AAD: &[u8] = b"openchamber-settings/1";
+
+/// Protected preferences (LAN access, proxy, telemetry, editor command and the
+/// like) are left out of exports and skipped on import, so a settings file cannot
+/// turn on LAN access or repoint the proxy. In the app they change only through
+/// their dedicated commands, which ask the user first.
+fn is_machine_only(key: &str) -> bool {
+    settings::is_protected(key)
+}
+
+/// Secrets sealed with a key derived from the user's passphrase. The plaintext is
+/// the JSON of namespace -> key -> value.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedSecrets {
+    kdf: String,
+    iterations: u32,
+    /// Base64.
+    salt: String,
+    cipher: String,
+    /// Base64.
+    nonce: String,
+    /// Base64 ciphertext with the authentication tag appended.
+    data: String,
+}
+
+/// Everything needed to set the app up the same way on another machine.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsBundle {
+    format: String,
+    version: u64,
+    /// Milliseconds since the Unix epoch.
+    exported_at: i64,
+    app_version: String,
+    #[serde(default)]
+    preferences: Map<String, Value>,
+    #[serde(default)]
+    shortcuts: BTreeMap<ShortcutAction, String>,
+    /// Profiles without credentials; those travel in `secrets`, if at all.
+    #[serde(default)]
+    servers: Vec<ServerProfile>,
+    #[serde(default)]
+    secrets: Option<EncryptedSecrets>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsExportSummary {
+    path: String,
+    preferences: usize,
+    shortcuts: usize,
+    servers: usize,
+    /// `None` when secrets were left out.
+    secrets: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsImportSummary {
+    preferences: usize,
+    shortcuts: usize,
+    servers: usize,
+    secrets: usize,
+    /// The file has secrets, but no passphrase was given, so they were not imported.
+    secrets_skipped: bool,
+    /// Parts that could not be applied, e.g. a shortcut taken by another app.
+    warnings: Vec<String>,
+}
+
+fn base64_engine() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<aead::LessSafeKey, String> {
+    let iterations = NonZeroU32::new(iterations)
+        .filter(|iterations| iterations.get() <= MAX_KDF_ITERATIONS)
+        .ok_or_else(|| format!("Unsupported key derivation cost: {iterations}"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    aead::UnboundKey::new(&aead::AES_256_GCM, &key)
+        .map(aead::LessSafeKey::new)
+        .map_err(|_| "Failed to set up encryption".to_string())
+}
+
+fn encrypt_secrets(
+    values: &BTreeMap<String, BTreeMap<String, String>>,
+    passphrase: &str,
+) -> Result<EncryptedSecrets, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    getrandom::fill(&mut salt)
+        .and_then(|()| getrandom::fill(&mut nonce))
+        .map_err(|err| format!("Failed to generate a key: {err}"))?;
+    let key = derive_key(passphrase, &salt, KDF_ITERATIONS)?;
+    let mut data = serde_json::to_vec(values).map_err(|err| err.to_string())?;
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(AAD),
+        &mut data,
+    )
+    .map_err(|_| "Failed to encrypt secrets".to_string())?;
+    Ok(EncryptedSecrets {
+        kdf: KDF_NAME.to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: base64_engine().encode(salt),
+        cipher: CIPHER_NAME.to_string(),
+        nonce: base64_engine().encode(nonce),
+        data: base64_engine().encode(data),
+    })
+}
+
+fn decrypt_secrets(
+    sealed: &EncryptedSecrets,
+    passphrase: &str,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, String> {
+    if sealed.kdf != KDF_NAME || sealed.cipher != CIPHER_NAME {
+        return Err(format!(
+            "Unsupported secrets encryption: {} / {}",
+            sealed.kdf, sealed.cipher
+        ));
+    }
+    let decode = |field: &str| {
+        base64_engine()
+            .decode(field)
+            .map_err(|_| "The secrets in this file are corrupted".to_string())
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = aead::Nonce::try_assume_unique_for_key(&decode(&sealed.nonce)?)
+        .map_err(|_| "The secrets in this file are corrupted".to_string())?;
+    let mut data = decode(&sealed.data)?;
+    let key = derive_key(passphrase, &salt, sealed.iterations)?;
+    let plaintext = key
+        .open_in_place(nonce, aead::Aad::from(AAD), &mut data)
+        .map_err(|_| "Wrong passphrase, or the secrets in this file are corrupted".to_string())?;
+    serde_json::from_slice(plaintext)
+        .map_err(|_| "The secrets in this file are corrupted".to_string())
+}
+
+fn build_bundle(
+    app: &tauri::AppHandle,
+    include_secrets: bool,
+    passphrase: Option<&str>,
+) -> Result<(SettingsBundle, Option<usize>), String> {
+    let mut preferences = match settings::all_settings(app) {
+        Value::Object(values) => values,
+        _ => Map::new(),
+    };
+    preferences.retain(|key, _| !is_machine_only(key));
+    let mut servers = servers::saved_profiles(&app.state::<ServerProfilesState>());
+    for profile in &mut servers {
+        profile.has_credentials = false;
+    }
+    let (secrets, secret_count) = if include_secrets {
+        let passphrase = passphrase
+            .filter(|passphrase| passphrase.chars().count() >= MIN_PASSPHRASE_LEN)
+            .ok_or_else(|| {
+                format!("Exporting secrets needs a passphrase of at least {MIN_PASSPHRASE_LEN} characters")
+            })?;
+        let values = secrets::export_all(app)?;
+        let count = values.values().map(BTreeMap::len).sum();
+        (Some(encrypt_secrets(&values, passphrase)?), Some(count))
+    } else {
+        (None, None)
+    };
+    let bundle = SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        app_version: app.package_info().version.to_string(),
+        preferences,
+        shortcuts: shortcuts::saved_bindings(app),
+        servers,
+        secrets,
+    };
+    Ok((bundle, secret_count))
+}
+
+fn read_bundle(path: &Path) -> Result<SettingsBundle, String> {
+    let raw = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let bundle: SettingsBundle =
+        serde_json::from_str(&raw).map_err(|_| "Not an OpenChamber settings file".to_string())?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("Not an OpenChamber settings file".to_string());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "The settings file was made by a newer OpenChamber ({})",
+            bundle.app_version
+        ));
+    }
+    Ok(bundle)
+}
+
+fn apply_bundle(
+    app: &tauri::AppHandle,
+    bundle: SettingsBundle,
+    passphrase: Option<&str>,
+) -> Result<SettingsImportSummary, String> {
+    let mut summary = SettingsImportSummary::default();
+    // Decrypt before changing anything, so a wrong passphrase leaves no half import.
+    let secret_values = match (&bundle.secrets, passphrase) {
+        (Some(sealed), Some(passphrase)) => Some(decrypt_secrets(sealed, passphrase)?),
+        (Some(_), None) => {
+            summary.secrets_skipped = true;
+            None
+        }
+        (None, _) => None,
+    };
+
+    for (key, value) in bundle.preferences {
+        if is_machine_only(&key) {
+            summary.warnings.push(format!(
+                "Setting {key} was not imported; change it in the app"
+            ));
+            continue;
+        }
+        match settings::set_setting(app, &key, value, None) {
+            Ok(()) => summary.preferences += 1,
+            Err(err) => summary.warnings.push(format!("Setting {key}: {err}")),
+        }
+    }
+    for (namespace, values) in secret_values.unwrap_or_default() {
+        for (key, value) in values {
+            match secrets::set_secret(app, Some(&namespace), &key, &value) {
+                Ok(()) => summary.secrets += 1,
+                Err(err) => summary.warnings.push(format!("Secret {key}: {err}")),
+            }
+        }
+    }
+    // After the secrets, so profiles pick up their imported credentials.
+    summary.servers =
+        servers::import_profiles(app, &app.state::<ServerProfilesState>(), bundle.servers)?;
+    for (action, accelerator) in bundle.shortcuts {
+        match shortcuts::bind(app, action, &accelerator) {
+            Ok(()) => summary.shortcuts += 1,
+            Err(err) => summary.warnings.push(err),
+        }
+    }
+    Ok(summary)
+}
+
+/// Writes preferences, server profiles and global shortcuts to one file for moving
+/// to another machine. Secrets are left out unless `include_secrets` is set, in
+/// which case they are encrypted with `passphrase`.
+#[tauri::command]
+pub(crate) async fn desktop_export_settings(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    include_secrets: bool,
+    passphrase: Option<String>,
+) -> Result<SettingsExportSummary, String> {
+    crate::origin::require_local(&webview, "export settings")?;
+    let target = fs_ops::check_new(&PathPolicy::for_app(&app), "export settings", &path)
+        .map_err(|err| err.to_string())?;
+    let task_app = app.clone();
+    let task_target = target.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let (bundle, secrets) = build_bundle(&task_app, include_secrets, passphrase.as_deref())?;
+        let raw = serde_json::to_string_pretty(&bundle).map_err(|err| err.to_string())?;
+        fs::write(&task_target, raw).map_err(|err| format!("{}: {err}", task_target.display()))?;
+        Ok(SettingsExportSummary {
+            path: task_target.to_string_lossy().to_string(),
+            preferences: bundle.preferences.len(),
+            shortcuts: bundle.shortcuts.len(),
+            servers: bundle.servers.len(),
+            secrets,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    let category = if include_secrets {
+        AuditCategory::Secret
+    } else {
+        AuditCategory::FileWrite
+    };
+    audit::record(
+        &app,
+        category,
+        "export settings",
+        target.to_string_lossy(),
+        &result,
+    );
+    result
+}
+
+/// Applies a file written by [`desktop_export_settings`] on top of the current
+/// settings. Secrets are only imported when `passphrase` is given; a wrong one
+/// fails the import before anything is changed.
+#[tauri::command]
+pub(crate) async fn desktop_import_settings(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<SettingsImportSummary, String> {
+    crate::origin::require_local(&webview, "import settings")?;
+    let source =
+        path_policy::check(&app, "import settings", &path).map_err(|err| err.to_string())?;
+    let task_app = app.clone();
+    let task_source = source.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let bundle = read_bundle(&task_source)?;
+        apply_bundle(&task_app, bundle, passphrase.as_deref())
+    })
+    .await
+    .map_err(|err| err.to_string())
+    .and_then(|result| result);
+    let category = if result.as_ref().is_ok_and(|summary| summary.secrets > 0) {
+        AuditCategory::Secret
+    } else {
+        AuditCategory::FileWrite
+    };
+    audit::record(
+        &app,
+        category,
+        "import settings",
+        source.to_string_lossy(),
+        &result,
+    );
+    if let Ok(summary) = &result {
+        log::info!(
+            "[settings] imported {} preferences, {} servers, {} shortcuts, {} secrets",
+            summary.preferences,
+            summary.servers,
+            summary.shortcuts,
+            summary.secrets
+        );
+    }
+    result
+}
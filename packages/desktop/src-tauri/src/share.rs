@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+#[cfg(any(target_os = "macos", windows))]
+use tokio::sync::oneshot;
+
+use crate::i18n;
+
+/// One thing to share, as sent by the web UI.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ShareItem {
+    Text {
+        text: String,
+    },
+    Url {
+        url: String,
+    },
+    /// An absolute path, e.g. an exported transcript or a diagnostics zip.
+    File {
+        path: String,
+    },
+}
+
+/// Where the share button sits, in CSS pixels relative to the webview. The macOS
+/// picker points at it; Windows always centers its share UI.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ShareAnchor {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ShareOutcome {
+    /// The native share UI was shown.
+    Shown,
+    /// No share UI on this platform; the items were copied to the clipboard instead.
+    Copied,
+}
+
+/// Items split by kind, validated.
+#[derive(Clone, Debug, Default)]
+struct ShareContent {
+    texts: Vec<String>,
+    urls: Vec<String>,
+    files: Vec<String>,
+}
+
+impl ShareContent {
+    fn parse(items: Vec<ShareItem>) -> Result<Self, String> {
+        let mut content = Self::default();
+        for item in items {
+            match item {
+                ShareItem::Text { text } if !text.trim().is_empty() => content.texts.push(text),
+                ShareItem::Text { .. } => {}
+                ShareItem::Url { url } => {
+                    let parsed = url::Url::parse(url.trim())
+                        .map_err(|err| format!("Invalid URL {url}: {err}"))?;
+                    content.urls.push(parsed.to_string());
+                }
+                ShareItem::File { path } => {
+                    if !Path::new(&path).is_absolute() || !Path::new(&path).is_file() {
+                        return Err(format!("Not an existing file: {path}"));
+                    }
+                    content.files.push(path);
+                }
+            }
+        }
+        if content.texts.is_empty() && content.urls.is_empty() && content.files.is_empty() {
+            return Err("Nothing to share".to_string());
+        }
+        Ok(content)
+    }
+
+    /// Everything as lines of text, for the clipboard fallback.
+    fn plain_text(&self) -> String {
+        self.texts
+            .iter()
+            .chain(&self.urls)
+            .chain(&self.files)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(target_os = "macos")]
+thread_local! {
+    /// The picker is not retained while it is on screen; keep the latest alive.
+    static ACTIVE_PICKER: std::cell::RefCell<Option<objc2::rc::Retained<objc2_app_kit::NSSharingServicePicker>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Shows `NSSharingServicePicker` next to `anchor` in the window's web view.
+#[cfg(target_os = "macos")]
+async fn show_native(
+    window: &tauri::WebviewWindow,
+    content: ShareContent,
+    anchor: Option<ShareAnchor>,
+) -> Result<bool, String> {
+    let (done, shown) = oneshot::channel();
+    window
+        .with_webview(move |platform| {
+            use objc2::{rc::Retained, runtime::AnyObject, AllocAnyThread};
+            use objc2_app_kit::NSSharingServicePicker;
+            use objc2_foundation::{
+                MainThreadMarker, NSArray, NSObject, NSPoint, NSRect, NSRectEdge, NSSize, NSString,
+                NSURL,
+            };
+            use objc2_web_kit::WKWebView;
+
+            if MainThreadMarker::new().is_none() {
+                return;
+            }
+            // SAFETY: Tauri hands out the live WKWebView on the main thread.
+            let webview = unsafe { &*(platform.inner() as *const WKWebView) };
+
+            let mut objects: Vec<Retained<NSObject>> = Vec::new();
+            for text in &content.texts {
+                objects.push(Retained::into_super(NSString::from_str(text)));
+            }
+            for url in &content.urls {
+                if let Some(url) = NSURL::URLWithString(&NSString::from_str(url)) {
+                    objects.push(Retained::into_super(url));
+                }
+            }
+            for path in &content.files {
+                objects.push(Retained::into_super(NSURL::fileURLWithPath(
+                    &NSString::from_str(path),
+                )));
+            }
+            let refs: Vec<&AnyObject> = objects.iter().map(|object| &***object).collect();
+            let items = NSArray::from_slice(&refs);
+            // SAFETY: the picker accepts strings and URLs as items.
+            let picker = unsafe {
+                NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &items)
+            };
+
+            // WKWebView is flipped, so CSS coordinates map onto its bounds directly.
+            let rect = match anchor {
+                Some(anchor) => NSRect::new(
+                    NSPoint::new(anchor.x, anchor.y),
+                    NSSize::new(anchor.width.max(1.0), anchor.height.max(1.0)),
+                ),
+                None => {
+                    let bounds = webview.bounds();
+                    NSRect::new(
+                        NSPoint::new(bounds.size.width / 2.0, 0.0),
+                        NSSize::new(1.0, 1.0),
+                    )
+                }
+            };
+            picker.showRelativeToRect_ofView_preferredEdge(rect, webview, NSRectEdge::MaxY);
+            ACTIVE_PICKER.with(|active| active.replace(Some(picker)));
+            let _ = done.send(true);
+        })
+        .map_err(|err| err.to_string())?;
+    shown
+        .await
+        .map_err(|_| "The share picker could not be shown".to_string())
+}
+
+#[cfg(windows)]
+thread_local! {
+    /// The handler of the last share; each window's manager keeps it until removed.
+    static DATA_REQUESTED: std::cell::RefCell<
+        Option<(windows::ApplicationModel::DataTransfer::DataTransferManager, i64)>,
+    > = const { std::cell::RefCell::new(None) };
+}
+
+/// Shows the Windows share UI through `DataTransferManager`, answering its
+/// `DataRequested` event with the items.
+#[cfg(windows)]
+async fn show_native(
+    window: &tauri::WebviewWindow,
+    content: ShareContent,
+    _anchor: Option<ShareAnchor>,
+) -> Result<bool, String> {
+    use windows::{
+        core::{factory, Interface, HSTRING},
+        ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager},
+        Foundation::{TypedEventHandler, Uri},
+        Storage::{IStorageItem, StorageFile},
+        Win32::{Foundation::HWND, UI::Shell::IDataTransferManagerInterop},
+    };
+    use windows_collections::IIterable;
+
+    // Missing before Windows 10 2004, where the share UI still exists.
+    if !DataTransferManager::IsSupported().unwrap_or(true) {
+        return Ok(false);
+    }
+
+    let files = content.files;
+    // The share UI shows a single link; further URLs travel as text.
+    let mut urls = content.urls.into_iter();
+    let link = urls.next();
+    let text = content
+        .texts
+        .into_iter()
+        .chain(urls)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let hwnd = window.hwnd().map_err(|err| err.to_string())?.0 as isize;
+    let (done, shown) = oneshot::channel();
+    window
+        .run_on_main_thread(move || {
+            let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+            let result = (|| -> windows::core::Result<()> {
+                let interop = factory::<DataTransferManager, IDataTransferManagerInterop>()?;
+                // SAFETY: `hwnd` is the live top-level window owned by this thread.
+                let manager: DataTransferManager = unsafe { interop.GetForWindow(hwnd) }?;
+                DATA_REQUESTED.with(|active| {
+                    if let Some((manager, token)) = active.take() {
+                        let _ = manager.RemoveDataRequested(token);
+                    }
+                });
+                let token = manager.DataRequested(&TypedEventHandler::new(
+                    move |_, args: windows::core::Ref<DataRequestedEventArgs>| {
+                        let data = args.ok()?.Request()?.Data()?;
+                        data.Properties()?.SetTitle(&HSTRING::from("OpenChamber"))?;
+                        if !text.is_empty() {
+                            data.SetText(&HSTRING::from(text.as_str()))?;
+                        }
+                        if let Some(link) = &link {
+                            data.SetWebLink(&Uri::CreateUri(&HSTRING::from(link.as_str()))?)?;
+                        }
+                        if !files.is_empty() {
+                            // Quick lookups of files the user just produced; WinRT
+                            // objects cannot be resolved ahead on another thread.
+                            let items = files
+                                .iter()
+                                .map(|path| {
+                                    StorageFile::GetFileFromPathAsync(&HSTRING::from(
+                                        path.as_str(),
+                                    ))?
+                                    .get()?
+                                    .cast::<IStorageItem>()
+                                    .map(Some)
+                                })
+                                .collect::<windows::core::Result<Vec<_>>>()?;
+                            data.SetStorageItemsReadOnly(&IIterable::from(items))?;
+                        }
+                        Ok(())
+                    },
+                ))?;
+                DATA_REQUESTED.with(|active| active.replace(Some((manager, token))));
+                // SAFETY: as above.
+                unsafe { interop.ShowShareUIForWindow(hwnd) }
+            })();
+            let _ = done.send(result.map_err(|err| err.to_string()));
+        })
+        .map_err(|err| err.to_string())?;
+    shown
+        .await
+        .map_err(|_| "The share UI could not be shown".to_string())??;
+    Ok(true)
+}
+
+/// Linux has no share API.
+#[cfg(not(any(target_os = "macos", windows)))]
+async fn show_native(
+    _window: &tauri::WebviewWindow,
+    _content: ShareContent,
+    _anchor: Option<ShareAnchor>,
+) -> Result<bool, String> {
+    Ok(false)
+}
+
+fn copy_to_clipboard(app: &tauri::AppHandle, content: &ShareContent) -> Result<(), String> {
+    use tauri_plugin_notification::NotificationExt;
+
+    app.clipboard()
+        .write_text(content.plain_text())
+        .map_err(|err| err.to_string())?;
+    let _ = app
+        .notification()
+        .builder()
+        .title(i18n::t(app, "share.copiedTitle"))
+        .body(i18n::t(app, "share.copiedBody"))
+        .show();
+    Ok(())
+}
+
+/// Shares text, URLs and files through the OS share UI of the calling window. On
+/// platforms without one, copies them to the clipboard and says so in a
+/// notification.
+#[tauri::command]
+pub(crate) async fn desktop_share(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    items: Vec<ShareItem>,
+    anchor: Option<ShareAnchor>,
+) -> Result<ShareOutcome, String> {
+    let content = ShareContent::parse(items)?;
+    if show_native(&window, content.clone(), anchor).await? {
+        return Ok(ShareOutcome::Shown);
+    }
+    // Clipboard access must stay off the main thread; see `clipboard.rs`.
+    copy_to_clipboard(&app, &content)?;
+    log::info!("[share] no share UI; copied to the clipboard");
+    Ok(ShareOutcome::Copied)
+}
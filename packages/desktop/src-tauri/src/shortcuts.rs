@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::{
+    events::MenuAction, menu::MENU_ACCELERATORS, read_desktop_setting_from_disk,
+    tray::show_main_window, window_registry, write_desktop_setting_to_disk,
+};
+
+const GLOBAL_SHORTCUTS_KEY: &str = "desktopGlobalShortcuts";
+
+/// Actions that can be bound to a system-wide shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ShortcutAction {
+    /// Show and focus the main window from anywhere.
+    SummonWindow,
+    /// Summon the window and start a new session.
+    NewSession,
+    /// Hide the window to the tray if it is focused, otherwise summon it.
+    ToggleWindow,
+}
+
+impl ShortcutAction {
+    fn run<R: tauri::Runtime>(self, app: &tauri::AppHandle<R>) {
+        match self {
+            Self::SummonWindow => show_main_window(app),
+            Self::NewSession => {
+                window_registry::show_action_target(app);
+                window_registry::dispatch_menu_action(app, MenuAction::NewSession);
+            }
+            Self::ToggleWindow => {
+                let Some(window) = app.get_webview_window("main") else {
+                    return;
+                };
+                let frontmost =
+                    window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false);
+                if frontmost {
+                    let _ = window.hide();
+                } else {
+                    show_main_window(app);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShortcutBinding {
+    action: ShortcutAction,
+    accelerator: String,
+    registered: bool,
+}
+
+struct Binding {
+    accelerator: String,
+    shortcut: Shortcut,
+    registered: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct GlobalShortcutsState {
+    bindings: Mutex<BTreeMap<ShortcutAction, Binding>>,
+}
+
+fn parse_shortcut(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator.trim())
+        .map_err(|err| format!("Invalid shortcut '{accelerator}': {err}"))
+}
+
+fn menu_conflict(shortcut: &Shortcut) -> Option<&'static str> {
+    MENU_ACCELERATORS
+        .iter()
+        .copied()
+        .find(|accelerator| Shortcut::from_str(accelerator).ok().as_ref() == Some(shortcut))
+}
+
+fn persist(bindings: &BTreeMap<ShortcutAction, Binding>) {
+    let stored: BTreeMap<ShortcutAction, String> = bindings
+        .iter()
+        .map(|(action, binding)| (*action, binding.accelerator.clone()))
+        .collect();
+    let value = serde_json::to_value(stored).unwrap_or(serde_json::Value::Null);
+    if let Err(err) = write_desktop_setting_to_disk(GLOBAL_SHORTCUTS_KEY, value) {
+        log::warn!("[shortcuts] failed to persist bindings: {err}");
+    }
+}
+
+/// Called by the global shortcut plugin for every registered shortcut.
+pub(crate) fn handle_shortcut<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    shortcut: &Shortcut,
+    event: ShortcutEvent,
+) {
+    if event.state != ShortcutState::Pressed {
+        return;
+    }
+    let Some(state) = app.try_state::<GlobalShortcutsState>() else {
+        return;
+    };
+    let action = state
+        .bindings
+        .lock()
+        .expect("global shortcuts mutex")
+        .iter()
+        .find(|(_, binding)| binding.shortcut == *shortcut)
+        .map(|(action, _)| *action);
+    if let Some(action) = action {
+        log::info!("[shortcuts] {action:?}");
+        action.run(app);
+    }
+}
+
+/// Registers the bindings saved in settings. Failures (e.g. a shortcut owned by
+/// another app) are logged and reported as `registered: false` by the list command.
+pub(crate) fn register_saved_shortcuts<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let saved = read_desktop_setting_from_disk(GLOBAL_SHORTCUTS_KEY)
+        .and_then(|value| serde_json::from_value::<BTreeMap<ShortcutAction, String>>(value).ok())
+        .unwrap_or_default();
+
+    let state = app.state::<GlobalShortcutsState>();
+    let mut bindings = state.bindings.lock().expect("global shortcuts mutex");
+    for (action, accelerator) in saved {
+        let shortcut = match parse_shortcut(&accelerator) {
+            Ok(shortcut) => shortcut,
+            Err(err) => {
+                log::warn!("[shortcuts] {err}");
+                continue;
+            }
+        };
+        let registered = match app.global_shortcut().register(shortcut) {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("[shortcuts] failed to register {accelerator}: {err}");
+                false
+            }
+        };
+        bindings.insert(
+            action,
+            Binding {
+                accelerator,
+                shortcut,
+                registered,
+            },
+        );
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_shortcut_list(
+    state: tauri::State<'_, GlobalShortcutsState>,
+) -> Result<Vec<ShortcutBinding>, String> {
+    Ok(state
+        .bindings
+        .lock()
+        .expect("global shortcuts mutex")
+        .iter()
+        .map(|(action, binding)| ShortcutBinding {
+            action: *action,
+            accelerator: binding.accelerator.clone(),
+            registered: binding.registered,
+        })
+        .collect())
+}
+
+/// Accelerators of the current bindings, for settings export.
+pub(crate) fn saved_bindings<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> BTreeMap<ShortcutAction, String> {
+    app.state::<GlobalShortcutsState>()
+        .bindings
+        .lock()
+        .expect("global shortcuts mutex")
+        .iter()
+        .map(|(action, binding)| (*action, binding.accelerator.clone()))
+        .collect()
+}
+
+/// Binds `accelerator` to `action`, replacing any previous binding for that action.
+pub(crate) fn bind<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    action: ShortcutAction,
+    accelerator: &str,
+) -> Result<(), String> {
+    let state = app.state::<GlobalShortcutsState>();
+    let accelerator = accelerator.trim().to_string();
+    let shortcut = parse_shortcut(&accelerator)?;
+
+    if let Some(existing) = menu_conflict(&shortcut) {
+        return Err(format!(
+            "'{accelerator}' conflicts with the menu shortcut {existing}"
+        ));
+    }
+
+    let mut bindings = state.bindings.lock().expect("global shortcuts mutex");
+    if let Some((other, _)) = bindings
+        .iter()
+        .find(|(other, binding)| **other != action && binding.shortcut == shortcut)
+    {
+        return Err(format!("'{accelerator}' is already bound to {other:?}"));
+    }
+
+    let global = app.global_shortcut();
+    if let Some(previous) = bindings.get(&action) {
+        if previous.shortcut == shortcut && previous.registered {
+            return Ok(());
+        }
+        if previous.registered {
+            let _ = global.unregister(previous.shortcut);
+        }
+    }
+
+    if let Err(err) = global.register(shortcut) {
+        bindings.remove(&action);
+        persist(&bindings);
+        return Err(format!(
+            "'{accelerator}' could not be registered; it may be in use by another application ({err})"
+        ));
+    }
+
+    bindings.insert(
+        action,
+        Binding {
+            accelerator,
+            shortcut,
+            registered: true,
+        },
+    );
+    persist(&bindings);
+    Ok(())
+}
+
+/// Binds `accelerator` to `action`, replacing any previous binding for that action.
+#[tauri::command]
+pub(crate) fn desktop_shortcut_register(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    action: ShortcutAction,
+    accelerator: String,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "register global shortcuts")?;
+    bind(&app, action, &accelerator)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_shortcut_unregister(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, GlobalShortcutsState>,
+    action: ShortcutAction,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "unregister global shortcuts")?;
+    let mut bindings = state.bindings.lock().expect("global shortcuts mutex");
+    let Some(binding) = bindings.remove(&action) else {
+        return Ok(false);
+    };
+    if binding.registered {
+        app.global_shortcut()
+            .unregister(binding.shortcut)
+            .map_err(|err| err.to_string())?;
+    }
+    persist(&bindings);
+    Ok(true)
+}
@@ -0,0 +1,218 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::settings;
+
+/// Settings-store key holding a [`SidecarEnvSettings`] object. Read on every
+/// sidecar spawn, so changes apply on the next (re)start.
+pub(crate) const SIDECAR_ENV_KEY: &str = "sidecar.env";
+
+/// Variables the desktop shell sets itself, whose overrides would break the
+/// sidecar, and variables that load code into it or the shells it starts.
+const RESERVED_VARIABLES: &[&str] = &[
+    "PATH",
+    "OPENCHAMBER_HOST",
+    "OPENCHAMBER_DIST_DIR",
+    "OPENCHAMBER_DESKTOP_NOTIFY",
+    "OPENCHAMBER_UI_PASSWORD",
+    "NODE_OPTIONS",
+    "NODE_PATH",
+    "BUN_OPTIONS",
+    "BASH_ENV",
+    "ENV",
+    "PATHEXT",
+    "COMSPEC",
+];
+/// Dynamic loader variables such as `LD_PRELOAD` and `DYLD_INSERT_LIBRARIES`.
+const RESERVED_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SidecarEnvSettings {
+    variables: BTreeMap<String, String>,
+    /// Appended to `PATH`, after the system and built-in toolchain locations.
+    path_entries: Vec<String>,
+    cwd: Option<String>,
+}
+
+/// User customizations for the sidecar process, validated and `~`-expanded.
+#[derive(Debug, Default)]
+pub(crate) struct SidecarEnv {
+    pub(crate) variables: Vec<(String, String)>,
+    pub(crate) path_entries: Vec<String>,
+    pub(crate) cwd: Option<PathBuf>,
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .ok()
+        .map(|home| home.trim().to_string())
+        .filter(|home| !home.is_empty())
+}
+
+pub(crate) fn expand_home(raw: &str) -> String {
+    let raw = raw.trim();
+    match (raw.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{home}{rest}")
+        }
+        _ => raw.to_string(),
+    }
+}
+
+fn is_reserved(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    RESERVED_VARIABLES.contains(&upper.as_str())
+        || RESERVED_PREFIXES
+            .iter()
+            .any(|prefix| upper.starts_with(prefix))
+}
+
+/// Drops malformed and reserved variable names.
+fn filter_variables(variables: BTreeMap<String, String>) -> Vec<(String, String)> {
+    variables
+        .into_iter()
+        .filter(|(name, _)| {
+            let valid = !name.is_empty() && !name.contains(['=', '\0']);
+            let reserved = is_reserved(name);
+            if !valid || reserved {
+                log::warn!("[sidecar] ignoring environment variable {name:?}");
+            }
+            valid && !reserved
+        })
+        .collect()
+}
+
+pub(crate) fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> SidecarEnv {
+    let settings: SidecarEnvSettings = settings::get_setting(app, SIDECAR_ENV_KEY)
+        .and_then(|value| match serde_json::from_value(value) {
+            Ok(settings) => Some(settings),
+            Err(err) => {
+                log::warn!("[sidecar] ignoring invalid {SIDECAR_ENV_KEY} setting: {err}");
+                None
+            }
+        })
+        .unwrap_or_default();
+
+    let variables = filter_variables(settings.variables);
+
+    let path_entries = settings
+        .path_entries
+        .iter()
+        .map(|entry| expand_home(entry))
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    let cwd = settings
+        .cwd
+        .as_deref()
+        .map(expand_home)
+        .filter(|cwd| !cwd.is_empty())
+        .map(PathBuf::from)
+        .filter(|cwd| {
+            let is_dir = cwd.is_dir();
+            if !is_dir {
+                log::warn!(
+                    "[sidecar] ignoring working directory {}: not a directory",
+                    cwd.display()
+                );
+            }
+            is_dir
+        });
+
+    SidecarEnv {
+        variables,
+        path_entries,
+        cwd,
+    }
+}
+
+/// Newest `~/.nvm/versions/node/<version>/bin`, by numeric version order.
+fn nvm_bin(home: &str) -> Option<String> {
+    let versions = std::fs::read_dir(format!("{home}/.nvm/versions/node")).ok()?;
+    versions
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let parts: Vec<u64> = name
+                .trim_start_matches('v')
+                .split('.')
+                .map(|part| part.parse().ok())
+                .collect::<Option<_>>()?;
+            Some((parts, entry.path().join("bin")))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, bin)| bin.to_string_lossy().to_string())
+}
+
+/// Bin and shim directories of version managers and toolchains that GUI launches
+/// do not inherit from the login shell. Active-shell variables win over defaults.
+pub(crate) fn toolchain_dirs(home: &str) -> Vec<String> {
+    let env_dir = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+    let mut dirs = Vec::new();
+    dirs.extend(env_dir("NVM_BIN").or_else(|| nvm_bin(home)));
+    dirs.push(format!(
+        "{}/shims",
+        env_dir("ASDF_DATA_DIR").unwrap_or_else(|| format!("{home}/.asdf"))
+    ));
+    dirs.push(format!("{home}/.local/share/mise/shims"));
+    dirs.push(format!("{home}/.volta/bin"));
+    dirs.extend(env_dir("PNPM_HOME"));
+    dirs.push(format!("{home}/.deno/bin"));
+    dirs.push(format!("{home}/go/bin"));
+    dirs
+}
+
+/// Replaces the `sidecar.env` setting (`null` clears it) after the user confirms;
+/// it applies at the next sidecar start.
+#[tauri::command]
+pub(crate) async fn desktop_set_sidecar_env(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    env: serde_json::Value,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change the server environment")?;
+    if !env.is_null() {
+        let parsed = serde_json::from_value::<SidecarEnvSettings>(env.clone())
+            .map_err(|err| format!("Invalid {SIDECAR_ENV_KEY} setting: {err}"))?;
+        if let Some(name) = parsed.variables.keys().find(|name| is_reserved(name)) {
+            return Err(format!("{name} cannot be set for the server"));
+        }
+    }
+    settings::set_protected_setting(&app, &webview, SIDECAR_ENV_KEY, env).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_variables_drops_reserved_and_malformed_names() {
+        let variables = [
+            "FOO",
+            "path",
+            "OPENCHAMBER_HOST",
+            "NODE_OPTIONS",
+            "LD_PRELOAD",
+            "ld_library_path",
+            "DYLD_INSERT_LIBRARIES",
+            "BASH_ENV",
+            "",
+            "A=B",
+            "NODE_ENV",
+        ]
+        .into_iter()
+        .map(|name| (name.to_string(), "value".to_string()))
+        .collect();
+        let kept: Vec<String> = filter_variables(variables)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(kept, ["FOO", "NODE_ENV"]);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::Manager;
+
+use crate::profiles;
+
+pub(crate) const SIDECAR_LOG_FILE_NAME: &str = "sidecar.log";
+const SIDECAR_LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+pub(crate) const SIDECAR_LOG_MAX_ROTATED: usize = 3;
+
+#[derive(Clone, Copy)]
+pub(crate) enum SidecarStream {
+    Stdout,
+    Stderr,
+}
+
+impl SidecarStream {
+    fn as_str(self) -> &'static str {
+        match self {
+            SidecarStream::Stdout => "stdout",
+            SidecarStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Size-rotated log file: `sidecar.log`, `sidecar.log.1` … `sidecar.log.N`.
+struct SidecarLogFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl SidecarLogFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file,
+            written,
+        })
+    }
+
+    fn write_line(&mut self, stream: SidecarStream, line: &str) -> io::Result<()> {
+        if self.written >= SIDECAR_LOG_MAX_BYTES {
+            self.rotate()?;
+        }
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+        let entry = format!("{timestamp} [{}] {line}\n", stream.as_str());
+        self.file.write_all(entry.as_bytes())?;
+        self.written = self.written.saturating_add(entry.len() as u64);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for idx in (1..SIDECAR_LOG_MAX_ROTATED).rev() {
+            let from = rotated_path(&self.path, idx);
+            if from.exists() {
+                let _ = fs::rename(&from, rotated_path(&self.path, idx + 1));
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+}
+
+pub(crate) fn rotated_path(path: &Path, idx: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{idx}"));
+    PathBuf::from(name)
+}
+
+#[derive(Default)]
+pub(crate) struct SidecarLogState {
+    file: Mutex<Option<SidecarLogFile>>,
+}
+
+pub(crate) fn sidecar_log_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(SIDECAR_LOG_FILE_NAME))
+}
+
+/// Forwards one line of sidecar output to the app log and appends it to `sidecar.log`.
+pub(crate) fn record_sidecar_output<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    stream: SidecarStream,
+    line: &str,
+) {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.is_empty() {
+        return;
+    }
+
+    match stream {
+        SidecarStream::Stdout => log::info!(target: "sidecar", "[sidecar] {line}"),
+        SidecarStream::Stderr => log::warn!(target: "sidecar", "[sidecar] {line}"),
+    }
+
+    let Some(state) = app.try_state::<SidecarLogState>() else {
+        return;
+    };
+    let mut guard = state.file.lock().expect("sidecar log mutex");
+    if guard.is_none() {
+        let Some(path) = sidecar_log_path(app) else {
+            return;
+        };
+        match SidecarLogFile::open(path) {
+            Ok(file) => *guard = Some(file),
+            Err(err) => {
+                log::warn!("[sidecar] failed to open {SIDECAR_LOG_FILE_NAME}: {err}");
+                return;
+            }
+        }
+    }
+    if let Some(file) = guard.as_mut() {
+        if let Err(err) = file.write_line(stream, line) {
+            log::warn!("[sidecar] failed to write {SIDECAR_LOG_FILE_NAME}: {err}");
+            *guard = None;
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::Manager;
+
+use crate::settings;
+
+/// Settings-store key holding `{ "enabled": bool, "languages": ["en-US", …] }`.
+pub(crate) const SPELLCHECK_KEY: &str = "spellcheck";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SpellcheckConfig {
+    enabled: bool,
+    /// Dictionaries to check against, most preferred first; empty follows the OS.
+    languages: Vec<String>,
+}
+
+impl Default for SpellcheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            languages: Vec::new(),
+        }
+    }
+}
+
+fn parse_config(value: &Value) -> SpellcheckConfig {
+    serde_json::from_value(value.clone()).unwrap_or_default()
+}
+
+/// `None` until the user configures spellcheck, so each engine keeps its own
+/// default (WebKitGTK ships with checking off, WebKit and WebView2 with it on).
+fn stored_config<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<SpellcheckConfig> {
+    settings::get_setting(app, SPELLCHECK_KEY).map(|value| parse_config(&value))
+}
+
+/// Disabling also turns off WebKit's autocorrect and autocapitalize, which
+/// otherwise rewrite code identifiers as they are typed. The attributes go on the
+/// root element, so every field without its own setting inherits them.
+fn page_script(enabled: bool) -> String {
+    format!(
+        r#"(function(on){{try{{var r=document.documentElement;if(!r)return;
+var attrs={{spellcheck:'false',autocorrect:'off',autocapitalize:'off'}};
+Object.keys(attrs).forEach(function(k){{if(on)r.removeAttribute(k);else r.setAttribute(k,attrs[k]);}});
+}}catch(_e){{}}}})({enabled});"#
+    )
+}
+
+/// WebKitGTK checks only when asked to, against the languages set on the web
+/// context, which expects POSIX locale names such as `en_US`.
+#[cfg(target_os = "linux")]
+fn apply_native<R: tauri::Runtime>(webview: &tauri::Webview<R>, config: &SpellcheckConfig) {
+    use webkit2gtk::{WebContextExt, WebViewExt};
+
+    let enabled = config.enabled;
+    let languages: Vec<String> = if config.languages.is_empty() {
+        sys_locale::get_locale().into_iter().collect()
+    } else {
+        config.languages.clone()
+    }
+    .iter()
+    .map(|language| language.replace('-', "_"))
+    .collect();
+    let result = webview.with_webview(move |platform| {
+        let Some(context) = platform.inner().context() else {
+            return;
+        };
+        context.set_spell_checking_enabled(enabled);
+        if enabled && !languages.is_empty() {
+            let languages: Vec<&str> = languages.iter().map(String::as_str).collect();
+            context.set_spell_checking_languages(&languages);
+        }
+    });
+    if let Err(err) = result {
+        log::warn!("[spellcheck] failed to configure webview: {err}");
+    }
+}
+
+/// WebKit checks against the app-wide `NSSpellChecker`, which supports a single
+/// language; the first one listed wins, and none restores automatic detection.
+#[cfg(target_os = "macos")]
+fn apply_native<R: tauri::Runtime>(webview: &tauri::Webview<R>, config: &SpellcheckConfig) {
+    let language = config
+        .languages
+        .first()
+        .map(|language| language.replace('-', "_"));
+    let result = webview.app_handle().run_on_main_thread(move || {
+        use objc2_app_kit::NSSpellChecker;
+        use objc2_foundation::NSString;
+
+        let checker = NSSpellChecker::sharedSpellChecker();
+        match language {
+            Some(language) => {
+                checker.setAutomaticallyIdentifiesLanguages(false);
+                if !checker.setLanguage(&NSString::from_str(&language)) {
+                    log::warn!("[spellcheck] no dictionary for {language}");
+                }
+            }
+            None => checker.setAutomaticallyIdentifiesLanguages(true),
+        }
+    });
+    if let Err(err) = result {
+        log::warn!("[spellcheck] failed to configure spell checker: {err}");
+    }
+}
+
+/// WebView2 checks against the languages enabled in Windows settings and offers
+/// no API to choose them; only the page attributes apply.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn apply_native<R: tauri::Runtime>(_webview: &tauri::Webview<R>, _config: &SpellcheckConfig) {}
+
+/// Configures a freshly created window before its first page loads.
+pub(crate) fn restore_spellcheck<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
+    if let Some(config) = stored_config(window.app_handle()) {
+        apply_native(window.as_ref(), &config);
+    }
+}
+
+/// Page-load hook: a new document starts without the root attributes.
+pub(crate) fn inject<R: tauri::Runtime>(webview: &tauri::Webview<R>) {
+    if let Some(config) = stored_config(webview.app_handle()) {
+        let _ = webview.eval(page_script(config.enabled));
+    }
+}
+
+/// Settings side effect: applies the new configuration to every open window.
+pub(crate) fn apply_spellcheck_setting<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    value: &Value,
+) {
+    let config = parse_config(value);
+    for window in app.webview_windows().into_values() {
+        apply_native(window.as_ref(), &config);
+        let _ = window.eval(page_script(config.enabled));
+    }
+}
+
+/// Turns spellcheck on or off in every window and picks its dictionaries, given
+/// as BCP 47 tags (`en-US`). Omitted or empty `languages` follow the OS.
+#[tauri::command]
+pub(crate) fn desktop_set_spellcheck(
+    app: tauri::AppHandle,
+    enabled: bool,
+    languages: Option<Vec<String>>,
+) -> Result<(), String> {
+    let languages: Vec<String> = languages
+        .unwrap_or_default()
+        .iter()
+        .map(|language| language.trim().to_string())
+        .filter(|language| !language.is_empty())
+        .collect();
+    if let Some(invalid) = languages.iter().find(|language| {
+        !language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }) {
+        return Err(format!("Invalid language tag: {invalid}"));
+    }
+    settings::set_setting(
+        &app,
+        SPELLCHECK_KEY,
+        json!({ "enabled": enabled, "languages": languages }),
+        None,
+    )
+}
@@ -0,0 +1,150 @@
+use std::sync::Mutex;
+
+use base64::Engine;
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{i18n, profiles};
+
+pub(crate) const SPLASH_LABEL: &str = "splash";
+const SPLASH_WIDTH: f64 = 360.0;
+const SPLASH_HEIGHT: f64 = 220.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BootStage {
+    ResolvingResources,
+    StartingServer,
+    WaitingForHealth,
+    LoadingUi,
+}
+
+impl BootStage {
+    const ALL: [BootStage; 4] = [
+        BootStage::ResolvingResources,
+        BootStage::StartingServer,
+        BootStage::WaitingForHealth,
+        BootStage::LoadingUi,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|stage| *stage == self)
+            .unwrap_or(0)
+    }
+
+    fn label_key(self) -> &'static str {
+        match self {
+            BootStage::ResolvingResources => "splash.stage.resolvingResources",
+            BootStage::StartingServer => "splash.stage.startingServer",
+            BootStage::WaitingForHealth => "splash.stage.waitingForHealth",
+            BootStage::LoadingUi => "splash.stage.loadingUi",
+        }
+    }
+}
+
+/// Current boot stage, re-applied whenever the splash page (re)loads.
+pub(crate) struct SplashState {
+    stage: Mutex<BootStage>,
+}
+
+impl Default for SplashState {
+    fn default() -> Self {
+        Self {
+            stage: Mutex::new(BootStage::ResolvingResources),
+        }
+    }
+}
+
+fn stage_script(stage: BootStage) -> String {
+    format!(
+        "window.__openchamberBootStage={};if(window.__openchamberSetBootStage)window.__openchamberSetBootStage();",
+        stage.index()
+    )
+}
+
+fn splash_html<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> String {
+    let items: String = BootStage::ALL
+        .iter()
+        .map(|stage| format!("<li>{}</li>", i18n::t(app, stage.label_key())))
+        .collect();
+    let title = profiles::window_title();
+    format!(
+        r#"<!doctype html>
+<html><head><meta charset="utf-8"><style>
+:root {{ color-scheme: light dark; }}
+body {{ margin: 0; height: 100vh; display: flex; flex-direction: column; justify-content: center;
+  padding: 0 32px; box-sizing: border-box; font: 13px -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif;
+  background: #f5f5f4; color: #1c1917; cursor: default; user-select: none; }}
+@media (prefers-color-scheme: dark) {{ body {{ background: #1c1917; color: #e7e5e4; }} }}
+h1 {{ font-size: 17px; font-weight: 600; margin: 0 0 16px; }}
+ol {{ list-style: none; margin: 0; padding: 0; }}
+li {{ padding: 3px 0 3px 18px; position: relative; opacity: .4; }}
+li::before {{ content: ""; position: absolute; left: 2px; top: 8px; width: 7px; height: 7px; border-radius: 50%; background: currentColor; }}
+li.done {{ opacity: .7; }}
+li.active {{ opacity: 1; font-weight: 500; }}
+li.active::before {{ animation: pulse 1s ease-in-out infinite alternate; }}
+@keyframes pulse {{ from {{ opacity: .3; }} to {{ opacity: 1; }} }}
+</style></head>
+<body><h1>{title}</h1><ol id="stages">{items}</ol>
+<script>
+window.__openchamberSetBootStage = function() {{
+  var current = window.__openchamberBootStage || 0;
+  var items = document.querySelectorAll('#stages li');
+  for (var i = 0; i < items.length; i++) {{
+    items[i].className = i < current ? 'done' : i === current ? 'active' : '';
+  }}
+}};
+window.__openchamberSetBootStage();
+</script></body></html>"#
+    )
+}
+
+/// Opens the boot status window. Called synchronously from `setup` so something is
+/// on screen while the local server starts.
+pub(crate) fn show<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let html = splash_html(app);
+    let data_url = format!(
+        "data:text/html;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(html)
+    );
+    let Ok(url) = url::Url::parse(&data_url) else {
+        return;
+    };
+    let result = WebviewWindowBuilder::new(app, SPLASH_LABEL, WebviewUrl::External(url))
+        .title(profiles::window_title())
+        .inner_size(SPLASH_WIDTH, SPLASH_HEIGHT)
+        .resizable(false)
+        .maximizable(false)
+        .decorations(false)
+        .skip_taskbar(true)
+        .center()
+        .on_page_load(|webview, _payload| {
+            if let Some(state) = webview.try_state::<SplashState>() {
+                let stage = *state.stage.lock().expect("splash stage mutex");
+                let _ = webview.eval(stage_script(stage));
+            }
+        })
+        .build();
+    if let Err(err) = result {
+        log::warn!("[splash] failed to create splash window: {err}");
+    }
+}
+
+/// Advances the splash to `stage`. A no-op once the splash is closed, e.g. for
+/// sidecar restarts later in the session.
+pub(crate) fn set_stage<R: tauri::Runtime>(app: &tauri::AppHandle<R>, stage: BootStage) {
+    let Some(window) = app.get_webview_window(SPLASH_LABEL) else {
+        return;
+    };
+    if let Some(state) = app.try_state::<SplashState>() {
+        *state.stage.lock().expect("splash stage mutex") = stage;
+    }
+    log::info!("[splash] {stage:?}");
+    let _ = window.eval(stage_script(stage));
+}
+
+pub(crate) fn close<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = window.destroy();
+    }
+}
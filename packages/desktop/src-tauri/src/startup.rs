@@ -0,0 +1,142 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri::Manager;
+
+/// Milestones of a cold start, in the order they normally happen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StartupPhase {
+    /// The Tauri runtime is up and `setup` runs.
+    Setup,
+    /// The bundled web assets were found.
+    ResourcesResolved,
+    /// The sidecar process was started.
+    SidecarSpawned,
+    /// The sidecar answered its health check.
+    SidecarHealthy,
+    /// The main window was created, still hidden.
+    WindowCreated,
+    /// The main window finished loading its first page.
+    FirstPageLoaded,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartupMark {
+    phase: StartupPhase,
+    /// Since the process started.
+    at_ms: u64,
+    /// Since the previous mark, or the process start for the first.
+    took_ms: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StartupReport {
+    started_at: String,
+    marks: Vec<StartupMark>,
+    /// Set once the first page has loaded.
+    total_ms: Option<u64>,
+    /// The mark that took longest to reach.
+    slowest_phase: Option<StartupPhase>,
+}
+
+/// Timestamps of the first pass through each boot phase. Later sidecar restarts
+/// and navigations leave them alone.
+pub(crate) struct StartupState {
+    started: Instant,
+    started_at: chrono::DateTime<chrono::Utc>,
+    marks: Mutex<Vec<(StartupPhase, Duration)>>,
+}
+
+impl StartupState {
+    /// `started` should be taken as early in `main` as possible.
+    pub(crate) fn new(started: Instant) -> Self {
+        let started_at =
+            chrono::Utc::now() - chrono::Duration::from_std(started.elapsed()).unwrap_or_default();
+        Self {
+            started,
+            started_at,
+            marks: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn report(&self) -> StartupReport {
+        let marks = self.marks.lock().expect("startup marks mutex");
+        let mut previous = Duration::ZERO;
+        let marks: Vec<StartupMark> = marks
+            .iter()
+            .map(|(phase, at)| {
+                let mark = StartupMark {
+                    phase: *phase,
+                    at_ms: at.as_millis() as u64,
+                    took_ms: at.saturating_sub(previous).as_millis() as u64,
+                };
+                previous = *at;
+                mark
+            })
+            .collect();
+        StartupReport {
+            started_at: self.started_at.to_rfc3339(),
+            total_ms: marks
+                .iter()
+                .find(|mark| mark.phase == StartupPhase::FirstPageLoaded)
+                .map(|mark| mark.at_ms),
+            slowest_phase: marks
+                .iter()
+                .max_by_key(|mark| mark.took_ms)
+                .map(|mark| mark.phase),
+            marks,
+        }
+    }
+}
+
+/// Records `phase` the first time it is reached.
+pub(crate) fn mark<R: tauri::Runtime>(app: &tauri::AppHandle<R>, phase: StartupPhase) {
+    let Some(state) = app.try_state::<StartupState>() else {
+        return;
+    };
+    let elapsed = state.started.elapsed();
+    {
+        let mut marks = state.marks.lock().expect("startup marks mutex");
+        if marks.iter().any(|(seen, _)| *seen == phase) {
+            return;
+        }
+        marks.push((phase, elapsed));
+    }
+    log::debug!("[startup] {phase:?} at {}ms", elapsed.as_millis());
+    if phase == StartupPhase::FirstPageLoaded {
+        let report = state.report();
+        log::info!(
+            "[startup] ready in {}ms (slowest: {:?})",
+            elapsed.as_millis(),
+            report.slowest_phase
+        );
+    }
+}
+
+/// Whether `phase` has been reached during this launch.
+pub(crate) fn reached<R: tauri::Runtime>(app: &tauri::AppHandle<R>, phase: StartupPhase) -> bool {
+    app.try_state::<StartupState>().is_some_and(|state| {
+        state
+            .marks
+            .lock()
+            .expect("startup marks mutex")
+            .iter()
+            .any(|(seen, _)| *seen == phase)
+    })
+}
+
+pub(crate) fn report<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<StartupReport> {
+    app.try_state::<StartupState>().map(|state| state.report())
+}
+
+/// Timing of this launch's boot phases, for the cold-start breakdown.
+#[tauri::command]
+pub(crate) fn desktop_get_startup_report(app: tauri::AppHandle) -> Result<StartupReport, String> {
+    report(&app).ok_or_else(|| "Startup report unavailable".to_string())
+}
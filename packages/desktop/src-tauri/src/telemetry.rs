@@ -0,0 +1,379 @@
+use std::{fs, path::PathBuf, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+use tokio::sync::Notify;
+
+use crate::{profiles, proxy, safe_mode, settings};
+
+/// Settings-store key holding `{ "enabled": bool, "endpoint": string? }`. Off unless
+/// the user turns it on.
+pub(crate) const TELEMETRY_KEY: &str = "telemetry";
+const TELEMETRY_FILE_NAME: &str = "telemetry.json";
+/// Release builds set this; without an endpoint events are queued but never sent.
+const DEFAULT_ENDPOINT: Option<&str> = option_env!("OPENCHAMBER_TELEMETRY_ENDPOINT");
+const MAX_QUEUED_EVENTS: usize = 500;
+const MAX_BATCH_EVENTS: usize = 100;
+const UPLOAD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const UPLOAD_STARTUP_DELAY: Duration = Duration::from_secs(2 * 60);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct TelemetryConfig {
+    enabled: bool,
+    /// Overrides the built-in endpoint, e.g. for a self-hosted collector.
+    endpoint: Option<String>,
+}
+
+/// The only things ever recorded. Events carry no paths, names or content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TelemetryEventKind {
+    AppStarted,
+    /// The app started on a different version than last time.
+    UpdateApplied,
+    SidecarRestarted,
+}
+
+/// Events of one kind within one hour, counted rather than listed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueuedEvent {
+    event: TelemetryEventKind,
+    count: u32,
+    /// Start of the hour, in seconds since the Unix epoch.
+    hour: i64,
+    app_version: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TelemetryFile {
+    /// Random, and replaced whenever telemetry is turned off and on again.
+    install_id: String,
+    last_version: Option<String>,
+    queue: Vec<QueuedEvent>,
+}
+
+/// Exactly what one upload sends.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TelemetryBatch {
+    install_id: String,
+    os: &'static str,
+    arch: &'static str,
+    events: Vec<QueuedEvent>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TelemetryPreview {
+    enabled: bool,
+    /// `DO_NOT_TRACK` or `OPENCHAMBER_TELEMETRY=0` is set, which overrides the setting.
+    disabled_by_environment: bool,
+    endpoint: Option<String>,
+    queued: usize,
+    /// The next upload, byte for byte; `None` when nothing is queued.
+    next_batch: Option<TelemetryBatch>,
+}
+
+/// The queue is loaded lazily and only ever written while telemetry is on.
+#[derive(Default)]
+pub(crate) struct TelemetryState {
+    file: Mutex<Option<TelemetryFile>>,
+    /// Wakes a running upload so turning telemetry off cancels it.
+    kill: Notify,
+}
+
+fn load_config<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> TelemetryConfig {
+    settings::get_setting(app, TELEMETRY_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn disabled_by_environment() -> bool {
+    let set = |name: &str, values: &[&str]| {
+        std::env::var(name).is_ok_and(|value| values.contains(&value.trim()))
+    };
+    set("DO_NOT_TRACK", &["1", "true"]) || set("OPENCHAMBER_TELEMETRY", &["0", "false", "off"])
+}
+
+fn is_enabled<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    !safe_mode::is_enabled() && !disabled_by_environment() && load_config(app).enabled
+}
+
+/// Only `https` endpoints are used, except on loopback for testing a collector.
+fn endpoint(config: &TelemetryConfig) -> Option<String> {
+    let raw = config
+        .endpoint
+        .as_deref()
+        .map(str::trim)
+        .filter(|endpoint| !endpoint.is_empty())
+        .or(DEFAULT_ENDPOINT)?;
+    let url = url::Url::parse(raw).ok()?;
+    let loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    (url.scheme() == "https" || (url.scheme() == "http" && loopback)).then(|| url.to_string())
+}
+
+fn file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(TELEMETRY_FILE_NAME))
+}
+
+fn new_install_id() -> String {
+    let mut bytes = [0u8; 16];
+    if let Err(err) = getrandom::fill(&mut bytes) {
+        log::warn!("[telemetry] failed to generate an install id: {err}");
+    }
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn with_file<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut TelemetryFile) -> (T, bool),
+) -> Option<T> {
+    let state = app.try_state::<TelemetryState>()?;
+    let mut guard = state.file.lock().expect("telemetry mutex");
+    let file = guard.get_or_insert_with(|| {
+        file_path(app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str::<TelemetryFile>(&raw).ok())
+            .unwrap_or_default()
+    });
+    let mut changed = false;
+    if file.install_id.is_empty() {
+        file.install_id = new_install_id();
+        changed = true;
+    }
+
+    let (result, modified) = f(file);
+    if changed || modified {
+        if let Some(path) = file_path(app) {
+            let write = || -> anyhow::Result<()> {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, serde_json::to_string_pretty(file)?)?;
+                Ok(())
+            };
+            if let Err(err) = write() {
+                log::warn!("[telemetry] failed to write queue: {err}");
+            }
+        }
+    }
+    Some(result)
+}
+
+fn push_event(file: &mut TelemetryFile, event: TelemetryEventKind, app_version: &str) {
+    let hour = chrono::Utc::now().timestamp() / 3600 * 3600;
+    if let Some(last) = file
+        .queue
+        .iter_mut()
+        .rev()
+        .find(|queued| queued.event == event)
+    {
+        if last.hour == hour && last.app_version == app_version {
+            last.count = last.count.saturating_add(1);
+            return;
+        }
+    }
+    file.queue.push(QueuedEvent {
+        event,
+        count: 1,
+        hour,
+        app_version: app_version.to_string(),
+    });
+    let overflow = file.queue.len().saturating_sub(MAX_QUEUED_EVENTS);
+    file.queue.drain(..overflow);
+}
+
+/// Queues `event` for the next upload. Does nothing while telemetry is off.
+pub(crate) fn record<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: TelemetryEventKind) {
+    if !is_enabled(app) {
+        return;
+    }
+    let version = app.package_info().version.to_string();
+    with_file(app, |file| {
+        push_event(file, event, &version);
+        ((), true)
+    });
+}
+
+/// Records the app start, and an update when the version changed since last run.
+pub(crate) fn record_startup<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if !is_enabled(app) {
+        return;
+    }
+    let version = app.package_info().version.to_string();
+    with_file(app, |file| {
+        if file
+            .last_version
+            .as_deref()
+            .is_some_and(|last| last != version)
+        {
+            push_event(file, TelemetryEventKind::UpdateApplied, &version);
+        }
+        file.last_version = Some(version.clone());
+        push_event(file, TelemetryEventKind::AppStarted, &version);
+        ((), true)
+    });
+}
+
+fn next_batch<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<TelemetryBatch> {
+    with_file(app, |file| {
+        let batch = (!file.queue.is_empty()).then(|| TelemetryBatch {
+            install_id: file.install_id.clone(),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            events: file.queue.iter().take(MAX_BATCH_EVENTS).cloned().collect(),
+        });
+        (batch, false)
+    })
+    .flatten()
+}
+
+/// Takes uploaded counts off the queue. Events recorded during the upload may have
+/// been added to the sent entries, so counts are subtracted rather than entries
+/// removed.
+fn acknowledge<R: tauri::Runtime>(app: &tauri::AppHandle<R>, sent: &[QueuedEvent]) {
+    with_file(app, |file| {
+        for (queued, sent) in file.queue.iter_mut().zip(sent) {
+            queued.count = queued.count.saturating_sub(sent.count);
+        }
+        file.queue.retain(|queued| queued.count > 0);
+        ((), true)
+    });
+}
+
+async fn upload(
+    app: &tauri::AppHandle,
+    endpoint: &str,
+    batch: &TelemetryBatch,
+) -> Result<(), String> {
+    let client = proxy::load(app)
+        .apply(reqwest::Client::builder())
+        .timeout(UPLOAD_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response = client
+        .post(endpoint)
+        .json(batch)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Uploads the queue in batches every hour while telemetry is on, backing off
+/// exponentially after failures.
+pub(crate) fn spawn_uploader(app: tauri::AppHandle) {
+    if safe_mode::is_enabled() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut delay = UPLOAD_STARTUP_DELAY;
+        let mut retry_delay = MIN_RETRY_DELAY;
+        loop {
+            tokio::time::sleep(delay).await;
+            delay = UPLOAD_INTERVAL;
+            if !is_enabled(&app) {
+                continue;
+            }
+            let Some(endpoint) = endpoint(&load_config(&app)) else {
+                continue;
+            };
+            let Some(batch) = next_batch(&app) else {
+                continue;
+            };
+            let state = app.state::<TelemetryState>();
+            let result = tokio::select! {
+                result = upload(&app, &endpoint, &batch) => result,
+                () = state.kill.notified() => Err("telemetry was turned off".to_string()),
+            };
+            match result {
+                // Turning telemetry off mid-upload already dropped the queue.
+                Ok(()) if is_enabled(&app) => {
+                    acknowledge(&app, &batch.events);
+                    retry_delay = MIN_RETRY_DELAY;
+                    if next_batch(&app).is_some() {
+                        delay = MIN_RETRY_DELAY;
+                    }
+                }
+                Ok(()) => {}
+                Err(err) => {
+                    log::info!("[telemetry] upload failed: {err}; retrying in {retry_delay:?}");
+                    delay = retry_delay;
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+    });
+}
+
+/// Settings side effect for `telemetry`: turning it off cancels any upload and
+/// deletes the queue and install id straight away.
+pub(crate) fn apply_telemetry_setting<R: tauri::Runtime>(app: &tauri::AppHandle<R>, value: &Value) {
+    let enabled = value
+        .get("enabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if enabled {
+        return;
+    }
+    let Some(state) = app.try_state::<TelemetryState>() else {
+        return;
+    };
+    state.kill.notify_waiters();
+    *state.file.lock().expect("telemetry mutex") = None;
+    if let Some(path) = file_path(app) {
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("[telemetry] turned off; queue deleted"),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => log::warn!("[telemetry] failed to delete queue: {err}"),
+        }
+    }
+}
+
+/// Shows what telemetry would send next, so users can check before opting in or
+/// at any time after.
+#[tauri::command]
+pub(crate) fn desktop_telemetry_preview(app: tauri::AppHandle) -> TelemetryPreview {
+    let config = load_config(&app);
+    let enabled = is_enabled(&app);
+    TelemetryPreview {
+        enabled,
+        disabled_by_environment: disabled_by_environment(),
+        endpoint: endpoint(&config),
+        queued: if enabled {
+            with_file(&app, |file| (file.queue.len(), false)).unwrap_or(0)
+        } else {
+            0
+        },
+        next_batch: enabled.then(|| next_batch(&app)).flatten(),
+    }
+}
+
+/// Replaces the `telemetry` setting (`null` clears it) after the user confirms.
+#[tauri::command]
+pub(crate) async fn desktop_set_telemetry(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    telemetry: Value,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change telemetry")?;
+    if !telemetry.is_null() {
+        serde_json::from_value::<TelemetryConfig>(telemetry.clone())
+            .map_err(|err| format!("Invalid telemetry setting: {err}"))?;
+    }
+    settings::set_protected_setting(&app, &webview, TELEMETRY_KEY, telemetry).await
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use tauri::Manager;
+
+use crate::profiles;
+
+#[derive(Default)]
+struct WindowTitle {
+    /// As set by the web UI; empty until it sets one.
+    title: String,
+    edited: bool,
+}
+
+/// Per-window title and unsaved-work flag, kept so either can change on its own.
+#[derive(Default)]
+pub(crate) struct TitlebarState {
+    windows: Mutex<HashMap<String, WindowTitle>>,
+}
+
+fn window(app: &tauri::AppHandle, label: &str) -> Result<tauri::WebviewWindow, String> {
+    app.get_webview_window(label)
+        .ok_or_else(|| format!("No window labelled {label}"))
+}
+
+/// Native title for `title` as set by the web UI, e.g. "repo — main". Empty resets
+/// to the app name; named profiles keep their suffix either way.
+fn native_title(title: &str) -> String {
+    let title = title.trim();
+    match (title.is_empty(), profiles::active()) {
+        (true, _) => profiles::window_title(),
+        (false, Some(profile)) => format!("{title} ({profile})"),
+        (false, None) => title.to_string(),
+    }
+}
+
+/// macOS shows unsaved work as a dot in the close button; elsewhere the title
+/// gets a leading asterisk instead.
+fn apply(window: &tauri::WebviewWindow, state: &WindowTitle) -> Result<(), String> {
+    let title = native_title(&state.title);
+    #[cfg(target_os = "macos")]
+    {
+        let edited = state.edited;
+        let target = window.clone();
+        window
+            .run_on_main_thread(move || {
+                use objc2_app_kit::NSWindow;
+
+                let Ok(ns_window) = target.ns_window() else {
+                    return;
+                };
+                // SAFETY: Tauri returns a valid `NSWindow` pointer for the lifetime of the window.
+                unsafe { &*(ns_window as *const NSWindow) }.setDocumentEdited(edited);
+            })
+            .map_err(|err| err.to_string())?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let title = if state.edited {
+        format!("*{title}")
+    } else {
+        title
+    };
+    window.set_title(&title).map_err(|err| err.to_string())
+}
+
+fn update(
+    app: &tauri::AppHandle,
+    label: &str,
+    change: impl FnOnce(&mut WindowTitle),
+) -> Result<(), String> {
+    let window = window(app, label)?;
+    let state = app.state::<TitlebarState>();
+    let mut windows = state.windows.lock().expect("titlebar mutex");
+    let entry = windows.entry(label.to_string()).or_default();
+    change(entry);
+    apply(&window, entry)
+}
+
+/// Drops the state of a destroyed window.
+pub(crate) fn forget<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: &str) {
+    if let Some(state) = app.try_state::<TitlebarState>() {
+        state.windows.lock().expect("titlebar mutex").remove(label);
+    }
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_window_title(
+    app: tauri::AppHandle,
+    label: String,
+    title: String,
+) -> Result<(), String> {
+    update(&app, &label, |state| state.title = title)
+}
+
+/// Marks a window as holding unsent or unsaved work, e.g. a drafted prompt.
+#[tauri::command]
+pub(crate) fn desktop_set_document_edited(
+    app: tauri::AppHandle,
+    label: String,
+    edited: bool,
+) -> Result<(), String> {
+    update(&app, &label, |state| state.edited = edited)
+}
+
+/// Points the main window at the open repository folder, so macOS lists it as the
+/// window's document (Mission Control, the Window menu) and shows the proxy icon
+/// with drag support wherever the title is visible. `None` clears it. A no-op on
+/// other platforms.
+#[tauri::command]
+pub(crate) fn desktop_set_represented_file(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<(), String> {
+    let path = path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    if let Some(path) = &path {
+        if !Path::new(path).is_absolute() || !Path::new(path).exists() {
+            return Err(format!("Not an existing absolute path: {path}"));
+        }
+    }
+    let window = window(&app, "main")?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let target = window.clone();
+        window
+            .run_on_main_thread(move || {
+                use objc2_app_kit::NSWindow;
+                use objc2_foundation::{NSString, NSURL};
+
+                let Ok(ns_window) = target.ns_window() else {
+                    return;
+                };
+                // SAFETY: Tauri returns a valid `NSWindow` pointer for the lifetime of the window.
+                let ns_window = unsafe { &*(ns_window as *const NSWindow) };
+                let url = path
+                    .as_deref()
+                    .map(|path| NSURL::fileURLWithPath(&NSString::from_str(path)));
+                ns_window.setRepresentedURL(url.as_deref());
+            })
+            .map_err(|err| err.to_string())?;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (window, path);
+
+    Ok(())
+}
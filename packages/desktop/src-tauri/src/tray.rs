@@ -0,0 +1,230 @@
+use std::sync::Mutex;
+
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::Manager;
+
+use crate::{
+    events::{self, DesktopEvent, MenuAction},
+    i18n, read_desktop_setting_from_disk, window_registry, write_desktop_setting_to_disk,
+};
+
+const TRAY_ID: &str = "main";
+const TRAY_ITEM_NEW_SESSION_ID: &str = "tray_new_session";
+const TRAY_ITEM_OPEN_WINDOW_ID: &str = "tray_open_window";
+const TRAY_ITEM_CHECK_FOR_UPDATES_ID: &str = "tray_check_for_updates";
+const TRAY_ITEM_QUIT_ID: &str = "tray_quit";
+
+const BACKGROUND_MODE_SETTING_KEY: &str = "desktopRunInBackground";
+
+/// Tray items whose availability follows the menu context.
+struct TrayContextItems<R: tauri::Runtime> {
+    new_session: Mutex<MenuItem<R>>,
+}
+
+/// When enabled, closing the main window hides it to the tray and keeps the
+/// sidecar running instead of exiting the app.
+pub(crate) struct BackgroundModeState {
+    enabled: Mutex<bool>,
+}
+
+impl BackgroundModeState {
+    pub(crate) fn from_disk() -> Self {
+        let enabled = read_desktop_setting_from_disk(BACKGROUND_MODE_SETTING_KEY)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Self {
+            enabled: Mutex::new(enabled),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        *self.enabled.lock().expect("background mode mutex")
+    }
+}
+
+pub(crate) fn show_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+}
+
+fn build_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let new_session = MenuItem::with_id(
+        app,
+        TRAY_ITEM_NEW_SESSION_ID,
+        i18n::t(app, "tray.newSession"),
+        true,
+        None::<&str>,
+    )?;
+    let open_window = MenuItem::with_id(
+        app,
+        TRAY_ITEM_OPEN_WINDOW_ID,
+        i18n::t(app, "tray.openWindow"),
+        true,
+        None::<&str>,
+    )?;
+    let check_for_updates = MenuItem::with_id(
+        app,
+        TRAY_ITEM_CHECK_FOR_UPDATES_ID,
+        i18n::t(app, "tray.checkForUpdates"),
+        true,
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(
+        app,
+        TRAY_ITEM_QUIT_ID,
+        i18n::t_args(app, "tray.quit", &[("name", &app.package_info().name)]),
+        true,
+        None::<&str>,
+    )?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &new_session,
+            &open_window,
+            &PredefinedMenuItem::separator(app)?,
+            &check_for_updates,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    match app.try_state::<TrayContextItems<R>>() {
+        Some(items) => *items.new_session.lock().expect("tray items mutex") = new_session,
+        None => {
+            app.manage(TrayContextItems {
+                new_session: Mutex::new(new_session),
+            });
+        }
+    }
+    Ok(menu)
+}
+
+pub(crate) fn setup_tray<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+    let menu = build_tray_menu(app)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(app.package_info().name.clone())
+        .menu(&menu)
+        .show_menu_on_left_click(cfg!(target_os = "macos"))
+        .on_menu_event(|app, event| handle_tray_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Replaces the tray menu with a freshly built one, e.g. after a locale change.
+pub(crate) fn rebuild_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let has_workspace = app
+        .try_state::<TrayContextItems<R>>()
+        .map(|items| {
+            items
+                .new_session
+                .lock()
+                .expect("tray items mutex")
+                .is_enabled()
+                .unwrap_or(true)
+        })
+        .unwrap_or(true);
+    tray.set_menu(Some(build_tray_menu(app)?))?;
+    apply_menu_context(app, has_workspace);
+    Ok(())
+}
+
+/// Mirrors `menu::desktop_set_menu_context` for the tray menu.
+pub(crate) fn apply_menu_context<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    has_workspace: bool,
+) {
+    if let Some(items) = app.try_state::<TrayContextItems<R>>() {
+        let _ = items
+            .new_session
+            .lock()
+            .expect("tray items mutex")
+            .set_enabled(has_workspace);
+    }
+}
+
+fn handle_tray_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str) {
+    log::info!("[tray] click id={}", id);
+
+    match id {
+        TRAY_ITEM_NEW_SESSION_ID => {
+            window_registry::show_action_target(app);
+            window_registry::dispatch_menu_action(app, MenuAction::NewSession);
+        }
+        TRAY_ITEM_OPEN_WINDOW_ID => show_main_window(app),
+        TRAY_ITEM_CHECK_FOR_UPDATES_ID => {
+            show_main_window(app);
+            events::emit(app, DesktopEvent::CheckForUpdates);
+        }
+        TRAY_ITEM_QUIT_ID => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Intercepts the main window close when background mode is on.
+/// Returns `true` if the close was turned into a hide.
+pub(crate) fn hide_to_tray_on_close<R: tauri::Runtime>(
+    window: &tauri::Window<R>,
+    api: &tauri::CloseRequestApi,
+) -> bool {
+    if window.label() != "main" {
+        return false;
+    }
+    let enabled = window
+        .app_handle()
+        .try_state::<BackgroundModeState>()
+        .map(|state| state.is_enabled())
+        .unwrap_or(false);
+    if !enabled {
+        return false;
+    }
+
+    api.prevent_close();
+    let _ = window.hide();
+    log::info!("[tray] main window hidden; sidecar keeps running in background");
+    true
+}
+
+#[tauri::command]
+pub(crate) fn desktop_get_background_mode(
+    state: tauri::State<'_, BackgroundModeState>,
+) -> Result<bool, String> {
+    Ok(state.is_enabled())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_background_mode(
+    state: tauri::State<'_, BackgroundModeState>,
+    enabled: bool,
+) -> Result<(), String> {
+    *state.enabled.lock().expect("background mode mutex") = enabled;
+    write_desktop_setting_to_disk(
+        BACKGROUND_MODE_SETTING_KEY,
+        serde_json::Value::Bool(enabled),
+    )
+    .map_err(|err| err.to_string())
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    i18n, profiles, recents,
+};
+
+const TRUST_FILE_NAME: &str = "workspace-trust.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TrustLevel {
+    /// Terminals, git and the local server may run anything in the workspace.
+    Trusted,
+    /// Browsing only; nothing is executed on the workspace's behalf.
+    Restricted,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTrustEvent {
+    path: String,
+    level: TrustLevel,
+}
+
+/// Trust decisions keyed by normalized workspace path, loaded lazily from the app
+/// config dir. A decision covers everything below its folder.
+#[derive(Default)]
+pub(crate) struct TrustState {
+    entries: Mutex<Option<BTreeMap<String, TrustLevel>>>,
+    /// Held while a trust dialog is open, so concurrent requests ask only once.
+    prompting: Mutex<()>,
+}
+
+fn trust_file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(TRUST_FILE_NAME))
+}
+
+fn with_entries<R: tauri::Runtime, T>(
+    app: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut BTreeMap<String, TrustLevel>) -> (T, bool),
+) -> T {
+    let state = app.state::<TrustState>();
+    let (result, snapshot) = {
+        let mut guard = state.entries.lock().expect("trust mutex");
+        let entries = guard.get_or_insert_with(|| {
+            trust_file_path(app)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|raw| serde_json::from_str(&raw).ok())
+                .unwrap_or_default()
+        });
+        let (result, changed) = f(entries);
+        (result, changed.then(|| entries.clone()))
+    };
+
+    if let (Some(entries), Some(path)) = (snapshot, trust_file_path(app)) {
+        let write = || -> anyhow::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&entries)?)?;
+            Ok(())
+        };
+        if let Err(err) = write() {
+            log::warn!("[trust] failed to persist trust decisions: {err}");
+        }
+    }
+    result
+}
+
+/// The decision for `path` or the closest folder above it; `None` if the user
+/// has not been asked yet.
+pub(crate) fn trust_level<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    path: &str,
+) -> Option<TrustLevel> {
+    let path = recents::normalize_path(path)?;
+    with_entries(app, |entries| {
+        let level = Path::new(&path)
+            .ancestors()
+            .find_map(|ancestor| entries.get(ancestor.to_string_lossy().as_ref()).copied());
+        (level, false)
+    })
+}
+
+/// Records the decision for `path` and tells the frontend, which switches to
+/// restricted mode for such workspaces.
+pub(crate) fn set_trust_level<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    path: &str,
+    level: TrustLevel,
+) {
+    let Some(path) = recents::normalize_path(path) else {
+        return;
+    };
+    with_entries(app, |entries| {
+        let changed = entries.insert(path.clone(), level) != Some(level);
+        ((), changed)
+    });
+    log::info!("[trust] {path} is {level:?}");
+    events::emit(
+        app,
+        DesktopEvent::WorkspaceTrust(WorkspaceTrustEvent { path, level }),
+    );
+}
+
+/// Asks "Do you trust the authors of ...?". Dismissing the dialog counts as no.
+fn prompt(app: &tauri::AppHandle, path: &str) -> TrustLevel {
+    let trusted = app
+        .dialog()
+        .message(i18n::t_args(
+            app,
+            "dialog.workspaceTrust.message",
+            &[("path", path)],
+        ))
+        .title(i18n::t(app, "dialog.workspaceTrust.title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            i18n::t(app, "dialog.workspaceTrust.trust"),
+            i18n::t(app, "dialog.workspaceTrust.restricted"),
+        ))
+        .blocking_show();
+    if trusted {
+        TrustLevel::Trusted
+    } else {
+        TrustLevel::Restricted
+    }
+}
+
+/// The trust level of `path`, asking the user first if it was never decided.
+/// Blocks on the dialog; call from a blocking context.
+pub(crate) fn ensure_decided_blocking(app: &tauri::AppHandle, path: &str) -> TrustLevel {
+    let state = app.state::<TrustState>();
+    let _prompting = state.prompting.lock().expect("trust prompt mutex");
+    if let Some(level) = trust_level(app, path) {
+        return level;
+    }
+    let level = prompt(app, path);
+    set_trust_level(app, path, level);
+    level
+}
+
+/// [`ensure_decided_blocking`] for async callers.
+pub(crate) async fn ensure_decided(app: &tauri::AppHandle, path: &str) -> TrustLevel {
+    let dialog_app = app.clone();
+    let path = path.to_string();
+    tauri::async_runtime::spawn_blocking(move || ensure_decided_blocking(&dialog_app, &path))
+        .await
+        .unwrap_or(TrustLevel::Restricted)
+}
+
+/// Gate for native modules that execute something inside `path`.
+pub(crate) async fn require_trusted(app: &tauri::AppHandle, path: &str) -> Result<(), String> {
+    match ensure_decided(app, path).await {
+        TrustLevel::Trusted => Ok(()),
+        TrustLevel::Restricted => Err(format!("{path} is open in restricted mode")),
+    }
+}
+
+/// `None` until the user has decided.
+#[tauri::command]
+pub(crate) fn desktop_get_trust(app: tauri::AppHandle, path: String) -> Option<TrustLevel> {
+    trust_level(&app, &path)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_set_trust(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+    level: TrustLevel,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change workspace trust")?;
+    if path.trim().is_empty() {
+        return Err("Workspace path must not be empty".to_string());
+    }
+    set_trust_level(&app, &path, level);
+    Ok(())
+}
@@ -0,0 +1,446 @@
+use std::{collections::HashMap, path::Path, sync::Mutex, time::Duration};
+
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_shell::{
+    process::{CommandChild, CommandEvent},
+    ShellExt,
+};
+use ts_rs::TS;
+
+use crate::{
+    audit::{self, AuditCategory},
+    build_local_url,
+    events::{self, DesktopEvent},
+    pick_unused_port, sidecar_restart_backoff, wait_for_health,
+};
+
+const TUNNEL_HEALTH_INTERVAL: Duration = Duration::from_secs(15);
+const TUNNEL_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+// Consecutive failed health probes before the ssh process is recycled.
+const TUNNEL_MAX_FAILED_PROBES: u32 = 3;
+
+#[derive(Clone)]
+struct TunnelSpec {
+    host: String,
+    remote_port: u16,
+    identity: Option<String>,
+}
+
+struct TunnelEntry {
+    spec: TunnelSpec,
+    local_port: u16,
+    child: Option<CommandChild>,
+    /// Bumped on every (re)spawn so exits of replaced ssh processes are ignored.
+    generation: u64,
+    reconnect_attempts: u32,
+    last_error: Option<String>,
+}
+
+/// Local port forwards (`ssh -L`) to OpenChamber servers on remote machines, keyed by tunnel id.
+#[derive(Default)]
+pub(crate) struct TunnelState {
+    tunnels: Mutex<HashMap<String, TunnelEntry>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TunnelInfo {
+    id: String,
+    host: String,
+    remote_port: u16,
+    local_port: u16,
+    url: String,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TunnelStatusEvent {
+    id: String,
+    status: &'static str,
+    local_port: u16,
+    error: Option<String>,
+}
+
+fn emit_status<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    id: &str,
+    status: &'static str,
+    local_port: u16,
+    error: Option<String>,
+) {
+    events::emit(
+        app,
+        DesktopEvent::TunnelStatus(TunnelStatusEvent {
+            id: id.to_string(),
+            status,
+            local_port,
+            error,
+        }),
+    );
+}
+
+fn validate_spec(host: &str, identity: Option<&str>) -> Result<(), String> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err("SSH host must not be empty".to_string());
+    }
+    // Reject anything ssh could parse as an option or that would split into several args.
+    if host.starts_with('-') || host.chars().any(char::is_whitespace) {
+        return Err(format!("Invalid SSH host: {host}"));
+    }
+    if let Some(identity) = identity {
+        if !Path::new(identity).is_file() {
+            return Err(format!("Identity file not found: {identity}"));
+        }
+    }
+    Ok(())
+}
+
+fn ssh_args(spec: &TunnelSpec, local_port: u16) -> Vec<String> {
+    let mut args = vec![
+        "-N".to_string(),
+        "-L".to_string(),
+        format!("127.0.0.1:{local_port}:127.0.0.1:{}", spec.remote_port),
+        "-o".to_string(),
+        "ExitOnForwardFailure=yes".to_string(),
+        "-o".to_string(),
+        "ServerAliveInterval=15".to_string(),
+        "-o".to_string(),
+        "ServerAliveCountMax=3".to_string(),
+        // Never block on an interactive password or host key prompt; the app has no tty.
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+    ];
+    if let Some(identity) = spec.identity.as_deref() {
+        args.push("-i".to_string());
+        args.push(identity.to_string());
+    }
+    args.push("--".to_string());
+    args.push(spec.host.clone());
+    args
+}
+
+/// Spawns the ssh process for an already registered tunnel.
+fn connect(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let state = app.state::<TunnelState>();
+    let mut tunnels = state.tunnels.lock().expect("tunnel state mutex");
+    let entry = tunnels
+        .get_mut(id)
+        .ok_or_else(|| format!("Unknown tunnel: {id}"))?;
+
+    let (mut rx, child) = app
+        .shell()
+        .command("ssh")
+        .args(ssh_args(&entry.spec, entry.local_port))
+        .spawn()
+        .map_err(|err| format!("Failed to start ssh: {err}"))?;
+
+    entry.generation += 1;
+    entry.child = Some(child);
+    let generation = entry.generation;
+    drop(tunnels);
+
+    let app = app.clone();
+    let id = id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let mut exit_code = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stderr(bytes) => {
+                    let line = String::from_utf8_lossy(&bytes).trim().to_string();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    log::warn!("[tunnel] {id}: {line}");
+                    if let Some(entry) = app
+                        .state::<TunnelState>()
+                        .tunnels
+                        .lock()
+                        .expect("tunnel state mutex")
+                        .get_mut(&id)
+                    {
+                        entry.last_error = Some(line);
+                    }
+                }
+                CommandEvent::Error(error) => {
+                    log::warn!("[tunnel] {id} error: {error}");
+                }
+                CommandEvent::Terminated(payload) => {
+                    exit_code = payload.code;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        handle_tunnel_exit(&app, &id, generation, exit_code);
+    });
+
+    Ok(())
+}
+
+/// Reconnects with exponential backoff unless the tunnel was closed or already replaced.
+fn handle_tunnel_exit(app: &tauri::AppHandle, id: &str, generation: u64, code: Option<i32>) {
+    let state = app.state::<TunnelState>();
+    let mut tunnels = state.tunnels.lock().expect("tunnel state mutex");
+    let Some(entry) = tunnels.get_mut(id) else {
+        return;
+    };
+    if entry.generation != generation {
+        return;
+    }
+
+    entry.child = None;
+    entry.reconnect_attempts += 1;
+    let delay = sidecar_restart_backoff(entry.reconnect_attempts);
+    let local_port = entry.local_port;
+    let error = entry.last_error.clone();
+    drop(tunnels);
+
+    log::warn!(
+        "[tunnel] {id} exited code={code:?}; reconnecting in {}ms",
+        delay.as_millis()
+    );
+    emit_status(app, id, "reconnecting", local_port, error);
+
+    let app = app.clone();
+    let id = id.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let still_wanted = app
+            .state::<TunnelState>()
+            .tunnels
+            .lock()
+            .expect("tunnel state mutex")
+            .get(&id)
+            .is_some_and(|entry| entry.generation == generation && entry.child.is_none());
+        if !still_wanted {
+            return;
+        }
+        if let Err(err) = connect(&app, &id) {
+            log::error!("[tunnel] {id} reconnect failed: {err}");
+            handle_tunnel_exit(&app, &id, generation, None);
+        }
+    });
+}
+
+async fn probe_tunnel(client: &reqwest::Client, url: &str) -> bool {
+    let health_url = format!("{}/health", url.trim_end_matches('/'));
+    matches!(
+        client.get(&health_url).send().await,
+        Ok(resp) if resp.status().is_success()
+    )
+}
+
+/// Periodically probes the forwarded server and recycles the ssh process when the
+/// forward is up but no longer carries traffic (e.g. after a network change).
+fn spawn_health_monitor(app: tauri::AppHandle, id: String, url: String) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(client) = reqwest::Client::builder()
+            .no_proxy()
+            .timeout(TUNNEL_HEALTH_TIMEOUT)
+            .build()
+        else {
+            return;
+        };
+        let mut failed_probes = 0;
+        let mut healthy = true;
+
+        loop {
+            tokio::time::sleep(TUNNEL_HEALTH_INTERVAL).await;
+
+            let local_port = match app
+                .state::<TunnelState>()
+                .tunnels
+                .lock()
+                .expect("tunnel state mutex")
+                .get(&id)
+            {
+                Some(entry) => entry.local_port,
+                None => return,
+            };
+
+            if probe_tunnel(&client, &url).await {
+                failed_probes = 0;
+                if !healthy {
+                    healthy = true;
+                    if let Some(entry) = app
+                        .state::<TunnelState>()
+                        .tunnels
+                        .lock()
+                        .expect("tunnel state mutex")
+                        .get_mut(&id)
+                    {
+                        entry.reconnect_attempts = 0;
+                        entry.last_error = None;
+                    }
+                    log::info!("[tunnel] {id} reconnected");
+                    emit_status(&app, &id, "connected", local_port, None);
+                }
+                continue;
+            }
+
+            failed_probes += 1;
+            healthy = false;
+            if failed_probes < TUNNEL_MAX_FAILED_PROBES {
+                continue;
+            }
+            failed_probes = 0;
+
+            // Killing the child ends its event stream, which triggers the reconnect path.
+            let child = app
+                .state::<TunnelState>()
+                .tunnels
+                .lock()
+                .expect("tunnel state mutex")
+                .get_mut(&id)
+                .and_then(|entry| entry.child.take());
+            if let Some(child) = child {
+                log::warn!("[tunnel] {id} unhealthy; restarting ssh");
+                let _ = child.kill();
+            }
+        }
+    });
+}
+
+fn remove_tunnel<R: tauri::Runtime>(app: &tauri::AppHandle<R>, id: &str) -> bool {
+    let Some(state) = app.try_state::<TunnelState>() else {
+        return false;
+    };
+    let entry = state.tunnels.lock().expect("tunnel state mutex").remove(id);
+    let Some(mut entry) = entry else {
+        return false;
+    };
+    if let Some(child) = entry.child.take() {
+        let _ = child.kill();
+    }
+    emit_status(app, id, "closed", entry.local_port, None);
+    true
+}
+
+/// Best-effort teardown of every tunnel on app exit.
+pub(crate) fn close_all_tunnels<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<TunnelState>() else {
+        return;
+    };
+    let ids: Vec<String> = state
+        .tunnels
+        .lock()
+        .expect("tunnel state mutex")
+        .keys()
+        .cloned()
+        .collect();
+    for id in ids {
+        remove_tunnel(app, &id);
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_tunnel_open(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, TunnelState>,
+    host: String,
+    remote_port: u16,
+    identity: Option<String>,
+) -> Result<TunnelInfo, String> {
+    crate::origin::require_local(&webview, "open a tunnel")?;
+    let target = format!("{}:{remote_port}", host.trim());
+    let result = open_tunnel(&app, &state, host, remote_port, identity).await;
+    audit::record(
+        &app,
+        AuditCategory::Process,
+        "open ssh tunnel",
+        target,
+        &result,
+    );
+    result
+}
+
+async fn open_tunnel(
+    app: &tauri::AppHandle,
+    state: &TunnelState,
+    host: String,
+    remote_port: u16,
+    identity: Option<String>,
+) -> Result<TunnelInfo, String> {
+    let identity = identity
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    validate_spec(&host, identity.as_deref())?;
+
+    let spec = TunnelSpec {
+        host: host.trim().to_string(),
+        remote_port,
+        identity,
+    };
+    let local_port = pick_unused_port().map_err(|err| err.to_string())?;
+    let id = format!("tunnel-{local_port}");
+    let url = build_local_url(local_port);
+
+    state.tunnels.lock().expect("tunnel state mutex").insert(
+        id.clone(),
+        TunnelEntry {
+            spec: spec.clone(),
+            local_port,
+            child: None,
+            generation: 0,
+            reconnect_attempts: 0,
+            last_error: None,
+        },
+    );
+    emit_status(app, &id, "connecting", local_port, None);
+
+    if let Err(err) = connect(app, &id) {
+        remove_tunnel(app, &id);
+        return Err(err);
+    }
+
+    if wait_for_health(app, &url).await.is_none() {
+        let last_error = state
+            .tunnels
+            .lock()
+            .expect("tunnel state mutex")
+            .get(&id)
+            .and_then(|entry| entry.last_error.clone());
+        remove_tunnel(app, &id);
+        return Err(match last_error {
+            Some(err) => format!("SSH tunnel failed: {err}"),
+            None => "OpenChamber server did not respond through the tunnel".to_string(),
+        });
+    }
+
+    log::info!(
+        "[tunnel] {id} forwarding {url} -> {}:{}",
+        spec.host,
+        spec.remote_port
+    );
+    emit_status(app, &id, "connected", local_port, None);
+    spawn_health_monitor(app.clone(), id.clone(), url.clone());
+
+    Ok(TunnelInfo {
+        id,
+        host: spec.host,
+        remote_port,
+        local_port,
+        url,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn desktop_tunnel_close(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    tunnel_id: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "close a tunnel")?;
+    let closed = remove_tunnel(&app, &tunnel_id);
+    audit::record(
+        &app,
+        AuditCategory::Process,
+        "close ssh tunnel",
+        tunnel_id,
+        &Ok::<_, String>(closed),
+    );
+    Ok(closed)
+}
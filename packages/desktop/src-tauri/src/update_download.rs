@@ -0,0 +1,382 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use base64::Engine;
+use reqwest::header::{HeaderValue, ACCEPT, RANGE};
+use reqwest::StatusCode;
+use tauri::Manager;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    events::{self, DesktopEvent},
+    progress::{set_progress, TaskProgress},
+    update_patch, PendingUpdate, UpdateProgressEvent,
+};
+
+pub(crate) const UPDATE_DOWNLOAD_DIR: &str = "updates";
+const UPDATE_USER_AGENT: &str = concat!("openchamber-desktop/", env!("CARGO_PKG_VERSION"));
+
+struct DownloadedUpdate {
+    update: tauri_plugin_updater::Update,
+    path: PathBuf,
+}
+
+/// Tracks a staged update: the in-flight download (cancellable) and the
+/// verified package waiting to be installed.
+#[derive(Default)]
+pub(crate) struct UpdateDownloadState {
+    cancel: Mutex<Option<CancellationToken>>,
+    ready: Mutex<Option<DownloadedUpdate>>,
+    install_on_exit: Mutex<bool>,
+}
+
+pub(crate) enum DownloadError {
+    Cancelled,
+    Failed(String),
+}
+
+impl<E: std::fmt::Display> From<E> for DownloadError {
+    fn from(err: E) -> Self {
+        DownloadError::Failed(err.to_string())
+    }
+}
+
+fn package_file_name(update: &tauri_plugin_updater::Update) -> String {
+    let sanitized: String = format!("{}-{}", update.version, update.target)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{sanitized}.partial")
+}
+
+/// Removes packages left behind by downloads of other versions.
+fn remove_stale_packages(dir: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path != keep {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn verify_package_signature(
+    app: &tauri::AppHandle,
+    data: &[u8],
+    signature: &str,
+) -> Result<(), String> {
+    let pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|v| v.get("pubkey"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Updater public key is not configured".to_string())?;
+
+    let decode = |raw: &str| -> Result<String, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(raw.trim())
+            .map_err(|err| err.to_string())?;
+        String::from_utf8(bytes).map_err(|err| err.to_string())
+    };
+
+    let public_key = minisign_verify::PublicKey::decode(&decode(pubkey)?)
+        .map_err(|err| format!("Invalid updater public key: {err}"))?;
+    let signature = minisign_verify::Signature::decode(&decode(signature)?)
+        .map_err(|err| format!("Invalid update signature: {err}"))?;
+    public_key
+        .verify(data, &signature, true)
+        .map_err(|err| format!("Update signature verification failed: {err}"))
+}
+
+/// Where the package of `update` is downloaded to; other versions' packages are
+/// removed.
+fn package_path(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+) -> Result<PathBuf, DownloadError> {
+    let dir = app.path().app_cache_dir()?.join(UPDATE_DOWNLOAD_DIR);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(package_file_name(update));
+    remove_stale_packages(&dir, &path);
+    Ok(path)
+}
+
+/// A client honouring the updater's timeout and proxy.
+pub(crate) fn http_client(
+    update: &tauri_plugin_updater::Update,
+) -> Result<reqwest::Client, DownloadError> {
+    let mut client = reqwest::Client::builder().user_agent(UPDATE_USER_AGENT);
+    if let Some(timeout) = update.timeout {
+        client = client.timeout(timeout);
+    }
+    if let Some(proxy) = update.proxy.as_ref() {
+        client = client.proxy(reqwest::Proxy::all(proxy.as_str())?);
+    }
+    Ok(client.build()?)
+}
+
+/// Downloads the package to `path`, resuming a previous partial download of the
+/// same version when the server supports range requests.
+async fn download_package(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+    path: &Path,
+    token: &CancellationToken,
+) -> Result<(), DownloadError> {
+    let mut offset = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let client = http_client(update)?;
+
+    let mut headers = update.headers.clone();
+    if !headers.contains_key(ACCEPT) {
+        headers.insert(ACCEPT, HeaderValue::from_static("application/octet-stream"));
+    }
+    let mut request = client.get(update.download_url.clone()).headers(headers);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
+
+    let mut response = tokio::select! {
+        _ = token.cancelled() => return Err(DownloadError::Cancelled),
+        response = request.send() => response?,
+    };
+
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+        // The partial file already holds the whole package.
+        return Ok(());
+    }
+    if !status.is_success() {
+        return Err(DownloadError::Failed(format!(
+            "Download request failed with status: {status}"
+        )));
+    }
+
+    let mut file = if status == StatusCode::PARTIAL_CONTENT {
+        log::info!("[updater] resuming download at {offset} bytes");
+        fs::OpenOptions::new().append(true).open(path)?
+    } else {
+        offset = 0;
+        fs::File::create(path)?
+    };
+
+    let total = response.content_length().map(|len| len + offset);
+    events::emit(
+        app,
+        DesktopEvent::UpdateProgress(UpdateProgressEvent::Started {
+            content_length: total,
+        }),
+    );
+
+    let mut downloaded = offset;
+    loop {
+        let chunk = tokio::select! {
+            _ = token.cancelled() => {
+                let _ = file.flush();
+                return Err(DownloadError::Cancelled);
+            }
+            chunk = response.chunk() => chunk?,
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        file.write_all(&chunk)?;
+        downloaded = downloaded.saturating_add(chunk.len() as u64);
+        set_progress(app, TaskProgress::from_bytes(downloaded, total));
+        events::emit(
+            app,
+            DesktopEvent::UpdateProgress(UpdateProgressEvent::Progress {
+                chunk_length: chunk.len(),
+                downloaded,
+                total,
+            }),
+        );
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Checks the package at `path` against the release signature, deleting it on a
+/// mismatch so a corrupt partial is never resumed.
+fn verify_package(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+    path: &Path,
+) -> Result<(), DownloadError> {
+    let bytes = fs::read(path)?;
+    verify_package_signature(app, &bytes, &update.signature).map_err(|err| {
+        let _ = fs::remove_file(path);
+        DownloadError::Failed(err)
+    })
+}
+
+/// Fetches and verifies the package of `update`, from a patch against the
+/// installed version when one is published. Whenever the patch route fails,
+/// including a patched package failing the signature check, the full package is
+/// downloaded instead.
+async fn fetch_package(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+    token: &CancellationToken,
+) -> Result<PathBuf, DownloadError> {
+    let path = package_path(app, update)?;
+    // A partial full download is resumed rather than replaced by a patch.
+    if !path.exists() {
+        if let Some(patch) = update_patch::find_patch(app, update) {
+            let patched = update_patch::download_patched(app, update, &patch, &path, token)
+                .await
+                .and_then(|()| verify_package(app, update, &path));
+            match patched {
+                Ok(()) => return Ok(path),
+                Err(DownloadError::Cancelled) => return Err(DownloadError::Cancelled),
+                Err(DownloadError::Failed(err)) => {
+                    log::warn!("[updater] patch update failed, downloading full package: {err}");
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+    }
+    download_package(app, update, &path, token).await?;
+    verify_package(app, update, &path)?;
+    Ok(path)
+}
+
+/// Downloads and verifies the package of `update`, cancellable through
+/// `desktop_cancel_update_download`. Returns where the package was saved.
+pub(crate) async fn download_verified(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+) -> Result<PathBuf, String> {
+    let state = app.state::<UpdateDownloadState>();
+    let token = CancellationToken::new();
+    {
+        let mut guard = state.cancel.lock().expect("update download mutex");
+        if guard.is_some() {
+            return Err("Update download already in progress".to_string());
+        }
+        *guard = Some(token.clone());
+    }
+
+    set_progress(app, TaskProgress::Indeterminate);
+    let result = fetch_package(app, update, &token).await;
+    *state.cancel.lock().expect("update download mutex") = None;
+    set_progress(app, TaskProgress::None);
+
+    match result {
+        Ok(path) => {
+            events::emit(
+                app,
+                DesktopEvent::UpdateProgress(UpdateProgressEvent::Finished),
+            );
+            Ok(path)
+        }
+        Err(DownloadError::Cancelled) => {
+            log::info!("[updater] download cancelled");
+            events::emit(
+                app,
+                DesktopEvent::UpdateProgress(UpdateProgressEvent::Cancelled),
+            );
+            Err("Update download cancelled".to_string())
+        }
+        Err(DownloadError::Failed(err)) => Err(err),
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn desktop_download_update(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    pending: tauri::State<'_, PendingUpdate>,
+    state: tauri::State<'_, UpdateDownloadState>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "download updates")?;
+    let Some(update) = pending.0.lock().expect("pending update mutex").clone() else {
+        return Err("No pending update".to_string());
+    };
+    let path = download_verified(&app, &update).await?;
+    *state.ready.lock().expect("update download mutex") = Some(DownloadedUpdate { update, path });
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_cancel_update_download(
+    webview: tauri::Webview,
+    state: tauri::State<'_, UpdateDownloadState>,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "cancel update downloads")?;
+    Ok(
+        match state.cancel.lock().expect("update download mutex").as_ref() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+fn install_ready_update<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &UpdateDownloadState,
+) -> Result<(), String> {
+    let Some(ready) = state.ready.lock().expect("update download mutex").take() else {
+        return Err("No downloaded update".to_string());
+    };
+    crate::rollback::preserve_current(app);
+    let bytes = fs::read(&ready.path).map_err(|err| err.to_string())?;
+    update_patch::store_base(app, &ready.update, &bytes);
+    ready.update.install(bytes).map_err(|err| err.to_string())?;
+    let _ = fs::remove_file(&ready.path);
+    Ok(())
+}
+
+/// Installs the downloaded update now, or (with `defer_until_exit`) when the app quits.
+#[tauri::command]
+pub(crate) async fn desktop_install_update(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    state: tauri::State<'_, UpdateDownloadState>,
+    defer_until_exit: Option<bool>,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "install updates")?;
+    if defer_until_exit.unwrap_or(false) {
+        if state.ready.lock().expect("update download mutex").is_none() {
+            return Err("No downloaded update".to_string());
+        }
+        *state.install_on_exit.lock().expect("update download mutex") = true;
+        return Ok(());
+    }
+    // Saving the rollback snapshot archives the whole install.
+    tauri::async_runtime::spawn_blocking(move || {
+        install_ready_update(&app, &app.state::<UpdateDownloadState>())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+pub(crate) fn install_deferred_update<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<UpdateDownloadState>() else {
+        return;
+    };
+    if !std::mem::take(&mut *state.install_on_exit.lock().expect("update download mutex")) {
+        return;
+    }
+    log::info!("[updater] installing deferred update on exit");
+    if let Err(err) = install_ready_update(app, &state) {
+        log::error!("[updater] deferred install failed: {err}");
+    }
+}
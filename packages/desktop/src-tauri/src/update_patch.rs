@@ -0,0 +1,345 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    progress::{set_progress, TaskProgress},
+    update_download::{self, DownloadError},
+    UpdateProgressEvent,
+};
+
+/// Holds the package the running version was installed from; patches apply to it.
+const BASE_DIR_NAME: &str = "base";
+/// Patches are small; anything larger than this is not worth the risk.
+const MAX_PATCH_SIZE: u64 = 512 * 1024 * 1024;
+
+/// One entry of a platform's `patches` list in `latest.json`, next to its `url`
+/// and `signature`. The patch is a gzip-compressed bsdiff patch (the format of the
+/// `bsdiff` crate) turning the `from` version's package into this release's.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdatePatch {
+    from: String,
+    url: url::Url,
+    size: u64,
+    sha256: String,
+    base_sha256: String,
+    target_sha256: String,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdatePatchInfo {
+    from_version: String,
+    /// Download size in bytes.
+    size: u64,
+}
+
+fn base_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path().app_cache_dir().ok().map(|dir| {
+        dir.join(update_download::UPDATE_DOWNLOAD_DIR)
+            .join(BASE_DIR_NAME)
+    })
+}
+
+fn base_file_name(version: &str) -> String {
+    let sanitized: String = version
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("openchamber-{sanitized}.pkg")
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Keeps the package about to be installed, so the next update can be a patch
+/// against it. Only the newest package is kept.
+pub(crate) fn store_base<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    update: &tauri_plugin_updater::Update,
+    bytes: &[u8],
+) {
+    let Some(dir) = base_dir(app) else {
+        return;
+    };
+    let _ = fs::remove_dir_all(&dir);
+    let result = fs::create_dir_all(&dir)
+        .and_then(|()| fs::write(dir.join(base_file_name(&update.version)), bytes));
+    if let Err(err) = result {
+        log::warn!("[updater] failed to keep the package for patch updates: {err}");
+    }
+}
+
+/// The patch from the running version listed for this platform, if the package it
+/// applies to is still around.
+pub(crate) fn find_patch<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    update: &tauri_plugin_updater::Update,
+) -> Option<UpdatePatch> {
+    let current = app.package_info().version.to_string();
+    let platform = match tauri_plugin_updater::target() {
+        Some(target) => update.raw_json.get("platforms")?.get(target)?,
+        None => return None,
+    };
+    let patch = serde_json::from_value::<Vec<UpdatePatch>>(platform.get("patches")?.clone())
+        .map_err(|err| log::warn!("[updater] ignoring malformed patch list: {err}"))
+        .ok()?
+        .into_iter()
+        .find(|patch| patch.from == current)?;
+    let base = base_dir(app)?.join(base_file_name(&current));
+    (patch.size <= MAX_PATCH_SIZE && base.is_file()).then_some(patch)
+}
+
+pub(crate) fn patch_info(patch: &UpdatePatch) -> UpdatePatchInfo {
+    UpdatePatchInfo {
+        from_version: patch.from.clone(),
+        size: patch.size,
+    }
+}
+
+/// Reads a bsdiff offset: little-endian, sign bit in the top bit.
+fn read_offset(buf: &[u8]) -> i64 {
+    let raw = i64::from_le_bytes(buf.try_into().expect("8-byte offset"));
+    if raw & i64::MIN == 0 {
+        raw
+    } else {
+        -(raw & i64::MAX)
+    }
+}
+
+/// Applies a bsdiff patch: a sequence of `(add, copy, seek)` controls, each followed
+/// by `add` bytes added to the old data and `copy` bytes taken as-is.
+fn apply_bsdiff(old: &[u8], patch: &mut impl Read, new: &mut Vec<u8>) -> io::Result<()> {
+    let corrupt = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut old_pos: usize = 0;
+    loop {
+        let mut control = [0u8; 24];
+        let mut filled = 0;
+        while filled < control.len() {
+            match patch.read(&mut control[filled..])? {
+                0 if filled == 0 => return Ok(()),
+                0 => return Err(corrupt("Patch ends inside a control block")),
+                n => filled += n,
+            }
+        }
+        let add = usize::try_from(read_offset(&control[0..8]))
+            .map_err(|_| corrupt("Negative length in patch"))?;
+        let copy = usize::try_from(read_offset(&control[8..16]))
+            .map_err(|_| corrupt("Negative length in patch"))?;
+        let seek = read_offset(&control[16..24]);
+
+        let start = new.len();
+        let wanted = add
+            .checked_add(copy)
+            .ok_or_else(|| corrupt("Length overflow in patch"))?;
+        if patch.take(wanted as u64).read_to_end(new)? != wanted {
+            return Err(corrupt("Patch is truncated"));
+        }
+        let old_slice = old_pos
+            .checked_add(add)
+            .and_then(|end| old.get(old_pos..end))
+            .ok_or_else(|| corrupt("Patch reads past the old package"))?;
+        for (byte, base) in new[start..start + add].iter_mut().zip(old_slice) {
+            *byte = byte.wrapping_add(*base);
+        }
+        old_pos = i64::try_from(old_pos + add)
+            .ok()
+            .and_then(|pos| pos.checked_add(seek))
+            .and_then(|pos| usize::try_from(pos).ok())
+            .ok_or_else(|| corrupt("Patch seeks outside the old package"))?;
+    }
+}
+
+fn build_package(
+    base_path: &Path,
+    patch: &UpdatePatch,
+    patch_bytes: &[u8],
+    target: &Path,
+) -> Result<(), String> {
+    // Checked before anything parses the patch, so `apply_bsdiff` only ever sees
+    // bytes the release listed.
+    if sha256_hex(patch_bytes) != patch.sha256.to_ascii_lowercase() {
+        return Err("Patch checksum mismatch".to_string());
+    }
+    let base = fs::read(base_path).map_err(|err| err.to_string())?;
+    if sha256_hex(&base) != patch.base_sha256.to_ascii_lowercase() {
+        return Err("The installed package does not match the patch".to_string());
+    }
+    let mut package = Vec::with_capacity(base.len());
+    apply_bsdiff(&base, &mut GzDecoder::new(patch_bytes), &mut package)
+        .map_err(|err| format!("Failed to apply patch: {err}"))?;
+    if sha256_hex(&package) != patch.target_sha256.to_ascii_lowercase() {
+        return Err("Patched package checksum mismatch".to_string());
+    }
+    fs::write(target, package).map_err(|err| err.to_string())
+}
+
+/// Downloads `patch` and rebuilds the full package at `target` from it. The caller
+/// still verifies the package signature.
+pub(crate) async fn download_patched(
+    app: &tauri::AppHandle,
+    update: &tauri_plugin_updater::Update,
+    patch: &UpdatePatch,
+    target: &Path,
+    token: &CancellationToken,
+) -> Result<(), DownloadError> {
+    let current = app.package_info().version.to_string();
+    let base_path = base_dir(app)
+        .map(|dir| dir.join(base_file_name(&current)))
+        .ok_or_else(|| DownloadError::Failed("No cache directory".to_string()))?;
+
+    log::info!(
+        "[updater] downloading {} byte patch from {current} to {}",
+        patch.size,
+        update.version
+    );
+    let client = update_download::http_client(update)?;
+    let mut response = tokio::select! {
+        _ = token.cancelled() => return Err(DownloadError::Cancelled),
+        response = client.get(patch.url.clone()).send() => response?,
+    };
+    let status = response.status();
+    if !status.is_success() {
+        return Err(DownloadError::Failed(format!(
+            "Patch request failed with status: {status}"
+        )));
+    }
+
+    let total = Some(patch.size);
+    events::emit(
+        app,
+        DesktopEvent::UpdateProgress(UpdateProgressEvent::Started {
+            content_length: total,
+        }),
+    );
+    let mut bytes = Vec::with_capacity(patch.size as usize);
+    loop {
+        let chunk = tokio::select! {
+            _ = token.cancelled() => return Err(DownloadError::Cancelled),
+            chunk = response.chunk() => chunk?,
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > patch.size {
+            return Err(DownloadError::Failed(
+                "Patch is larger than advertised".to_string(),
+            ));
+        }
+        let downloaded = bytes.len() as u64;
+        set_progress(app, TaskProgress::from_bytes(downloaded, total));
+        events::emit(
+            app,
+            DesktopEvent::UpdateProgress(UpdateProgressEvent::Progress {
+                chunk_length: chunk.len(),
+                downloaded,
+                total,
+            }),
+        );
+    }
+
+    let patch = patch.clone();
+    let target = target.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || build_package(&base_path, &patch, &bytes, &target))
+        .await?
+        .map_err(DownloadError::Failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a bsdiff offset: magnitude with the sign in the top bit.
+    fn offset(value: i64) -> [u8; 8] {
+        let raw = if value < 0 {
+            value.unsigned_abs() as i64 | i64::MIN
+        } else {
+            value
+        };
+        raw.to_le_bytes()
+    }
+
+    fn control(add: i64, copy: i64, seek: i64) -> Vec<u8> {
+        [offset(add), offset(copy), offset(seek)].concat()
+    }
+
+    fn apply(old: &[u8], patch: &[u8]) -> io::Result<Vec<u8>> {
+        let mut new = Vec::new();
+        apply_bsdiff(old, &mut &patch[..], &mut new)?;
+        Ok(new)
+    }
+
+    #[test]
+    fn read_offset_decodes_sign_magnitude() {
+        assert_eq!(read_offset(&offset(5)), 5);
+        assert_eq!(read_offset(&offset(-5)), -5);
+        assert_eq!(read_offset(&offset(0)), 0);
+    }
+
+    #[test]
+    fn apply_bsdiff_adds_copies_and_seeks() {
+        let old = b"hello world";
+        let new = b"HELLO world!";
+        let mut patch = control(11, 1, 0);
+        patch.extend(new[..11].iter().zip(old).map(|(n, o)| n.wrapping_sub(*o)));
+        patch.push(b'!');
+        assert_eq!(apply(old, &patch).unwrap(), new);
+
+        // Seek back to the start and add "hello" again.
+        let mut patch = control(5, 0, -5);
+        patch.extend([0; 5]);
+        patch.extend(control(5, 0, 0));
+        patch.extend([0; 5]);
+        assert_eq!(apply(old, &patch).unwrap(), b"hellohello");
+
+        assert_eq!(apply(old, &[]).unwrap(), b"");
+    }
+
+    #[test]
+    fn apply_bsdiff_rejects_corrupt_patches() {
+        let old = b"hello";
+        let corrupt = |patch: &[u8]| apply(old, patch).unwrap_err().kind();
+
+        // Cut off inside a control block.
+        assert_eq!(corrupt(&control(1, 0, 0)[..10]), io::ErrorKind::InvalidData);
+        // Fewer bytes than the control promises.
+        assert_eq!(corrupt(&control(3, 2, 0)), io::ErrorKind::InvalidData);
+        // Negative lengths.
+        assert_eq!(corrupt(&control(-1, 0, 0)), io::ErrorKind::InvalidData);
+        // Adding past the end of the old data.
+        let mut past_end = control(6, 0, 0);
+        past_end.extend([0; 6]);
+        assert_eq!(corrupt(&past_end), io::ErrorKind::InvalidData);
+        // Seeking before the start of the old data.
+        let mut before_start = control(1, 0, -2);
+        before_start.push(0);
+        assert_eq!(corrupt(&before_start), io::ErrorKind::InvalidData);
+        // Huge lengths fail cleanly instead of overflowing or allocating.
+        assert_eq!(
+            corrupt(&control(i64::MAX, i64::MAX, 0)),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
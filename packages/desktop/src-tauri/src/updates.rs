@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    check_for_updates,
+    events::{self, DesktopEvent},
+    proxy, settings, PendingUpdate,
+};
+
+pub(crate) const UPDATE_CHANNEL_SETTING_KEY: &str = "desktopUpdateChannel";
+pub(crate) const UPDATE_CHECK_INTERVAL_SETTING_KEY: &str = "desktopUpdateCheckIntervalHours";
+/// Settings-store key holding [`UpdaterSettings`], for networks that cannot reach
+/// GitHub directly.
+pub(crate) const UPDATER_SETTINGS_KEY: &str = "updater";
+const DEFAULT_UPDATE_CHECK_INTERVAL_HOURS: u64 = 6;
+// Let startup (sidecar, window) settle before the first background check.
+const INITIAL_UPDATE_CHECK_DELAY: Duration = Duration::from_secs(60);
+// Re-read interval while background checks are disabled, so enabling them takes effect.
+const DISABLED_UPDATE_CHECK_POLL: Duration = Duration::from_secs(15 * 60);
+
+const STABLE_ENDPOINT: &str =
+    "https://github.com/btriapitsyn/openchamber/releases/latest/download/latest.json";
+const BETA_ENDPOINT: &str =
+    "https://github.com/btriapitsyn/openchamber/releases/download/beta/latest.json";
+const NIGHTLY_ENDPOINT: &str =
+    "https://github.com/btriapitsyn/openchamber/releases/download/nightly/latest.json";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl UpdateChannel {
+    pub(crate) const ALL: [UpdateChannel; 3] = [
+        UpdateChannel::Stable,
+        UpdateChannel::Beta,
+        UpdateChannel::Nightly,
+    ];
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "stable" => Some(UpdateChannel::Stable),
+            "beta" => Some(UpdateChannel::Beta),
+            "nightly" => Some(UpdateChannel::Nightly),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        }
+    }
+
+    /// `i18n` key of the channel's menu label.
+    pub(crate) fn label_key(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "updateChannel.stable",
+            UpdateChannel::Beta => "updateChannel.beta",
+            UpdateChannel::Nightly => "updateChannel.nightly",
+        }
+    }
+
+    pub(crate) fn endpoint(self) -> url::Url {
+        let raw = match self {
+            UpdateChannel::Stable => STABLE_ENDPOINT,
+            UpdateChannel::Beta => BETA_ENDPOINT,
+            UpdateChannel::Nightly => NIGHTLY_ENDPOINT,
+        };
+        url::Url::parse(raw).expect("valid update endpoint")
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct UpdaterSettings {
+    /// Mirror serving `latest.json` instead of GitHub. `{{channel}}` is replaced with
+    /// the channel name; the updater's `{{target}}`, `{{arch}}` and
+    /// `{{current_version}}` work too. Packages are still checked against the
+    /// bundled public key, so a mirror cannot alter them.
+    endpoint: Option<String>,
+    /// Proxy for update traffic only, overriding the app-wide `proxy` setting.
+    proxy: Option<String>,
+}
+
+fn read_updater_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> UpdaterSettings {
+    crate::settings::get_setting(app, UPDATER_SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+fn endpoint_for(settings: &UpdaterSettings, channel: UpdateChannel) -> Result<url::Url, String> {
+    match non_empty(settings.endpoint.as_deref()) {
+        Some(raw) => url::Url::parse(&raw.replace("{{channel}}", channel.as_str()))
+            .map_err(|err| format!("Invalid update mirror URL: {err}")),
+        None => Ok(channel.endpoint()),
+    }
+}
+
+fn parse_channel(value: &serde_json::Value) -> UpdateChannel {
+    value
+        .as_str()
+        .and_then(UpdateChannel::parse)
+        .unwrap_or_default()
+}
+
+pub(crate) fn update_channel<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> UpdateChannel {
+    settings::get_setting(app, UPDATE_CHANNEL_SETTING_KEY)
+        .map(|value| parse_channel(&value))
+        .unwrap_or_default()
+}
+
+/// Persists the channel; the settings side effect reflects it in the native menu.
+pub(crate) fn set_update_channel<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    channel: UpdateChannel,
+) {
+    if update_channel(app) == channel {
+        // A click on the checked item unchecks it; check it again.
+        crate::menu::sync_update_channel_menu(app, channel);
+        return;
+    }
+
+    log::info!("[updater] channel set to {}", channel.as_str());
+    if let Err(err) = settings::set_setting(
+        app,
+        UPDATE_CHANNEL_SETTING_KEY,
+        serde_json::Value::String(channel.as_str().to_string()),
+        None,
+    ) {
+        log::warn!("[updater] failed to persist update channel: {err}");
+    }
+}
+
+/// Settings side effect for the update channel.
+pub(crate) fn apply_update_channel_setting<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    value: &serde_json::Value,
+) {
+    crate::menu::sync_update_channel_menu(app, parse_channel(value));
+}
+
+/// Builds an updater that queries the endpoint of `channel`, or the configured
+/// mirror. Built per check, so settings changes apply to the next one.
+pub(crate) fn channel_updater<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    channel: UpdateChannel,
+) -> Result<tauri_plugin_updater::Updater, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let settings = read_updater_settings(app);
+    let mut builder = app
+        .updater_builder()
+        .endpoints(vec![endpoint_for(&settings, channel)?])
+        .map_err(|err| err.to_string())?;
+    let proxy = match non_empty(settings.proxy.as_deref()) {
+        Some(raw) => Some(proxy::parse_proxy_url(raw)?),
+        None => proxy::load(app).url().cloned(),
+    };
+    if let Some(url) = proxy {
+        builder = builder.proxy(url);
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+/// Settings side effect for `updater`: forgets an update found through the old
+/// endpoint or proxy, since its download would go the old way too.
+pub(crate) fn apply_updater_setting<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    value: &serde_json::Value,
+) {
+    let settings: UpdaterSettings = serde_json::from_value(value.clone()).unwrap_or_default();
+    // Report a bad mirror or proxy now rather than at the next check.
+    if let Err(err) = endpoint_for(&settings, UpdateChannel::Stable) {
+        log::warn!("[updater] {err}");
+    }
+    if let Some(Err(err)) = non_empty(settings.proxy.as_deref()).map(proxy::parse_proxy_url) {
+        log::warn!("[updater] {err}");
+    }
+    if let Some(pending) = app.try_state::<PendingUpdate>() {
+        *pending.0.lock().expect("pending update mutex") = None;
+    }
+}
+
+/// Background check interval; `0` disables automatic checks.
+fn read_update_check_interval_hours<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> u64 {
+    settings::get_setting(app, UPDATE_CHECK_INTERVAL_SETTING_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_UPDATE_CHECK_INTERVAL_HOURS)
+}
+
+/// Periodically checks for updates and emits `openchamber:update-available`
+/// once per newly discovered version.
+pub(crate) fn spawn_background_update_checks(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(INITIAL_UPDATE_CHECK_DELAY).await;
+
+        let mut last_notified_version: Option<String> = None;
+        loop {
+            let hours = read_update_check_interval_hours(&app);
+            if hours == 0 {
+                tokio::time::sleep(DISABLED_UPDATE_CHECK_POLL).await;
+                continue;
+            }
+
+            let channel = update_channel(&app);
+
+            if let Some(pending) = app.try_state::<PendingUpdate>() {
+                match check_for_updates(&app, &pending, channel).await {
+                    Ok(info) if info.available => {
+                        if info.version != last_notified_version {
+                            log::info!(
+                                "[updater] background check found {:?} on {}",
+                                info.version,
+                                channel.as_str()
+                            );
+                            last_notified_version = info.version.clone();
+                            events::emit(&app, DesktopEvent::UpdateAvailable(info));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => log::warn!("[updater] background check failed: {err}"),
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(hours.saturating_mul(60 * 60))).await;
+        }
+    });
+}
+
+/// Replaces the `updater` setting (`null` clears it) after the user confirms.
+#[tauri::command]
+pub(crate) async fn desktop_set_updater_settings(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    updater: serde_json::Value,
+) -> Result<(), String> {
+    crate::origin::require_local(&webview, "change the update source")?;
+    if !updater.is_null() {
+        let parsed = serde_json::from_value::<UpdaterSettings>(updater.clone())
+            .map_err(|err| format!("Invalid updater setting: {err}"))?;
+        endpoint_for(&parsed, UpdateChannel::Stable)?;
+        if let Some(raw) = non_empty(parsed.proxy.as_deref()) {
+            proxy::parse_proxy_url(raw)?;
+        }
+    }
+    settings::set_protected_setting(&app, &webview, UPDATER_SETTINGS_KEY, updater).await
+}
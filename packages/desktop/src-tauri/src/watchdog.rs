@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    kill_sidecar, local_sidecar, metrics, request_sidecar_restart, settings,
+};
+
+/// Settings-store key holding the limits; see `ResourceLimits`.
+const RESOURCE_LIMITS_KEY: &str = "sidecarResourceLimits";
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
+const RESOURCE_INCIDENT_HISTORY: usize = 10;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ResourceLimits {
+    enabled: bool,
+    /// Resident memory of the sidecar and its children, in MiB.
+    max_rss_mb: Option<u64>,
+    /// Summed CPU of the sidecar and its children; 100 is one busy core.
+    max_cpu_percent: Option<f32>,
+    /// Samples in a row over a limit before it counts, so short spikes pass.
+    consecutive_samples: u32,
+    /// Restart the sidecar once a limit is hit, instead of only warning.
+    restart: bool,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_rss_mb: Some(4096),
+            max_cpu_percent: None,
+            consecutive_samples: 6,
+            restart: false,
+        }
+    }
+}
+
+fn load_limits(app: &tauri::AppHandle) -> ResourceLimits {
+    settings::get_setting(app, RESOURCE_LIMITS_KEY)
+        .and_then(|value: Value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+enum ResourceKind {
+    Memory,
+    Cpu,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SidecarResourceIncident {
+    at: String,
+    kind: ResourceKind,
+    rss_bytes: u64,
+    cpu_percent: f32,
+    /// The limit that was exceeded, in bytes or percent.
+    limit: f64,
+    samples: u32,
+    restarted: bool,
+}
+
+/// Counts consecutive samples over each limit.
+#[derive(Default)]
+struct Streaks {
+    pid: Option<u32>,
+    memory: u32,
+    cpu: u32,
+}
+
+impl Streaks {
+    fn record(&mut self, limits: &ResourceLimits, usage: &metrics::ProcessUsage) {
+        let over_memory = limits
+            .max_rss_mb
+            .is_some_and(|limit| usage.rss_bytes > limit.saturating_mul(1024 * 1024));
+        let over_cpu = limits
+            .max_cpu_percent
+            .is_some_and(|limit| usage.cpu_percent > limit);
+        self.memory = if over_memory { self.memory + 1 } else { 0 };
+        self.cpu = if over_cpu { self.cpu + 1 } else { 0 };
+    }
+
+    /// The limit that has been exceeded long enough, memory first.
+    fn tripped(&self, limits: &ResourceLimits) -> Option<(ResourceKind, f64)> {
+        let needed = limits.consecutive_samples.max(1);
+        if self.memory >= needed {
+            let limit = limits.max_rss_mb.unwrap_or_default();
+            return Some((
+                ResourceKind::Memory,
+                limit.saturating_mul(1024 * 1024) as f64,
+            ));
+        }
+        if self.cpu >= needed {
+            return Some((
+                ResourceKind::Cpu,
+                f64::from(limits.max_cpu_percent.unwrap_or_default()),
+            ));
+        }
+        None
+    }
+}
+
+fn sidecar_pid(app: &tauri::AppHandle) -> Option<u32> {
+    local_sidecar(app)?
+        .child
+        .lock()
+        .expect("sidecar mutex")
+        .as_ref()
+        .map(|child| child.pid())
+}
+
+fn record_incident(app: &tauri::AppHandle, incident: SidecarResourceIncident) {
+    let Some(state) = local_sidecar(app) else {
+        return;
+    };
+    let mut incidents = state
+        .recent_resource_incidents
+        .lock()
+        .expect("sidecar incidents mutex");
+    if incidents.len() >= RESOURCE_INCIDENT_HISTORY {
+        incidents.remove(0);
+    }
+    incidents.push(incident);
+}
+
+/// Restarts the sidecar through the same path as a crash, after giving it the
+/// usual shutdown grace period.
+async fn restart_sidecar(app: &tauri::AppHandle) {
+    let kill_app = app.clone();
+    if let Err(err) = tauri::async_runtime::spawn_blocking(move || kill_sidecar(kill_app)).await {
+        log::warn!("[watchdog] failed to stop sidecar: {err}");
+        return;
+    }
+    request_sidecar_restart(app, None, None, false);
+}
+
+/// Samples the local sidecar's memory and CPU in the background and warns, or
+/// restarts it if configured to, once it stays over a limit.
+pub(crate) fn spawn_resource_watchdog(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut streaks = Streaks::default();
+
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+
+            let limits = load_limits(&app);
+            let pid = sidecar_pid(&app);
+            if !limits.enabled || pid.is_none() {
+                streaks = Streaks::default();
+                continue;
+            }
+            if streaks.pid != pid {
+                // A new sidecar starts with a clean record.
+                streaks = Streaks {
+                    pid,
+                    ..Streaks::default()
+                };
+            }
+
+            let sample_app = app.clone();
+            let usage = match tauri::async_runtime::spawn_blocking(move || {
+                metrics::sample_sidecar(&sample_app)
+            })
+            .await
+            {
+                Ok(Some(usage)) => usage,
+                Ok(None) => continue,
+                Err(err) => {
+                    log::warn!("[watchdog] sampling failed: {err}");
+                    continue;
+                }
+            };
+
+            streaks.record(&limits, &usage);
+            let Some((kind, limit)) = streaks.tripped(&limits) else {
+                continue;
+            };
+            let samples = streaks.memory.max(streaks.cpu);
+            streaks = Streaks {
+                pid,
+                ..Streaks::default()
+            };
+
+            log::warn!(
+                "[watchdog] sidecar over {kind:?} limit for {samples} samples (rss={} MiB, cpu={:.0}%, restart={})",
+                usage.rss_bytes / (1024 * 1024),
+                usage.cpu_percent,
+                limits.restart
+            );
+            let incident = SidecarResourceIncident {
+                at: chrono::Utc::now().to_rfc3339(),
+                kind,
+                rss_bytes: usage.rss_bytes,
+                cpu_percent: usage.cpu_percent,
+                limit,
+                samples,
+                restarted: limits.restart,
+            };
+            events::emit(&app, DesktopEvent::SidecarResourceWarning(incident.clone()));
+            record_incident(&app, incident);
+
+            if limits.restart {
+                restart_sidecar(&app).await;
+            }
+        }
+    });
+}
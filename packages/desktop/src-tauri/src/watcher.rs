@@ -0,0 +1,294 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{
+    event::{EventKind, ModifyKind},
+    RecommendedWatcher, RecursiveMode, Watcher,
+};
+use serde::Serialize;
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::{
+    events::{self, DesktopEvent},
+    file_index,
+    path_policy::{self, PathError, PathErrorKind},
+};
+
+// Quiet period after the last event before a batch is emitted.
+const FS_DEBOUNCE: Duration = Duration::from_millis(250);
+// Upper bound on how long a continuous burst (e.g. `npm install`) can delay a batch.
+const FS_MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+    Other,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+struct FsChange {
+    path: String,
+    relative_path: String,
+    kind: FsChangeKind,
+}
+
+#[derive(Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FsChangedEvent {
+    root: String,
+    changes: Vec<FsChange>,
+}
+
+/// Active recursive watches keyed by the root path passed to `desktop_watch_path`.
+#[derive(Default)]
+pub(crate) struct WatcherState {
+    // Dropping the watcher closes its channel, which stops the debounce thread.
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+fn change_kind(kind: &EventKind) -> Option<FsChangeKind> {
+    match kind {
+        EventKind::Access(_) => None,
+        EventKind::Create(_) => Some(FsChangeKind::Created),
+        EventKind::Remove(_) => Some(FsChangeKind::Removed),
+        EventKind::Modify(ModifyKind::Name(_)) => Some(FsChangeKind::Renamed),
+        EventKind::Modify(_) => Some(FsChangeKind::Modified),
+        EventKind::Any | EventKind::Other => Some(FsChangeKind::Other),
+    }
+}
+
+/// Filters out `.git` internals and anything the workspace's gitignore excludes.
+pub(crate) struct IgnoreFilter {
+    root: PathBuf,
+    repo: Option<git2::Repository>,
+}
+
+impl IgnoreFilter {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            repo: git2::Repository::discover(root).ok(),
+        }
+    }
+
+    pub(crate) fn is_ignored(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if relative.components().any(|c| c.as_os_str() == ".git") {
+            return true;
+        }
+        let Some(repo) = self.repo.as_ref() else {
+            return false;
+        };
+        let Some(workdir) = repo.workdir() else {
+            return false;
+        };
+        match path.strip_prefix(workdir) {
+            Ok(in_repo) if !in_repo.as_os_str().is_empty() => {
+                repo.is_path_ignored(in_repo).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn merge_change(pending: &mut HashMap<PathBuf, FsChangeKind>, path: PathBuf, kind: FsChangeKind) {
+    pending
+        .entry(path)
+        .and_modify(|existing| {
+            // A file created and then written within one batch is still just "created".
+            if !(*existing == FsChangeKind::Created && kind == FsChangeKind::Modified) {
+                *existing = kind;
+            }
+        })
+        .or_insert(kind);
+}
+
+fn flush<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    root: &Path,
+    pending: &mut HashMap<PathBuf, FsChangeKind>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    file_index::apply_changes(app, root, pending.keys());
+    let mut changes: Vec<FsChange> = pending
+        .drain()
+        .map(|(path, kind)| FsChange {
+            relative_path: path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string(),
+            path: path.to_string_lossy().to_string(),
+            kind,
+        })
+        .collect();
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    events::emit(
+        app,
+        DesktopEvent::FsChanged(FsChangedEvent {
+            root: root.to_string_lossy().to_string(),
+            changes,
+        }),
+    );
+}
+
+fn run_debouncer<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    root: PathBuf,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+) {
+    let filter = IgnoreFilter::new(&root);
+    let mut pending: HashMap<PathBuf, FsChangeKind> = HashMap::new();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let event = match rx.recv_timeout(FS_DEBOUNCE) {
+            Ok(event) => event,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                flush(&app, &root, &mut pending);
+                batch_started = None;
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        match event {
+            Ok(event) => {
+                if let Some(kind) = change_kind(&event.kind) {
+                    for path in event.paths {
+                        if !filter.is_ignored(&path) {
+                            merge_change(&mut pending, path, kind);
+                        }
+                    }
+                }
+            }
+            Err(err) => log::warn!("[watcher] {}: {err}", root.display()),
+        }
+
+        if !pending.is_empty() {
+            let started = *batch_started.get_or_insert_with(Instant::now);
+            if started.elapsed() >= FS_MAX_BATCH_DELAY {
+                flush(&app, &root, &mut pending);
+                batch_started = None;
+            }
+        }
+    }
+}
+
+/// Reports changes the app made itself right away instead of after the watcher's
+/// debounce. Paths in a watched workspace are reported against its root, others
+/// against their parent directory.
+pub(crate) fn emit_changes<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    changes: impl IntoIterator<Item = (PathBuf, FsChangeKind)>,
+) {
+    let roots: Vec<PathBuf> = app
+        .try_state::<WatcherState>()
+        .map(|state| {
+            state
+                .watchers
+                .lock()
+                .expect("watcher state mutex")
+                .keys()
+                .filter_map(|key| std::fs::canonicalize(key).ok())
+                .map(path_policy::strip_verbatim_prefix)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut batches: HashMap<PathBuf, HashMap<PathBuf, FsChangeKind>> = HashMap::new();
+    for (path, kind) in changes {
+        let root = roots
+            .iter()
+            .filter(|root| path.starts_with(root) && path != **root)
+            .max_by_key(|root| root.as_os_str().len())
+            .cloned()
+            .or_else(|| path.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| path.clone());
+        merge_change(batches.entry(root).or_default(), path, kind);
+    }
+    for (root, mut pending) in batches {
+        flush(app, &root, &mut pending);
+    }
+}
+
+/// Starts a recursive watch of `path` unless one is already running.
+pub(crate) fn watch(app: &tauri::AppHandle, path: &str) -> Result<(), PathError> {
+    // Watch the canonical path so event paths line up with the git workdir for ignore checks.
+    let watch_root = path_policy::check(app, "watch", path)?;
+    let root = PathBuf::from(path.trim());
+    if !watch_root.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a directory: {}", root.display()),
+        ));
+    }
+    let key = root.to_string_lossy().to_string();
+
+    let state = app.state::<WatcherState>();
+    let mut watchers = state.watchers.lock().expect("watcher state mutex");
+    if watchers.contains_key(&key) {
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|err| err.to_string())?;
+    watcher
+        .watch(&watch_root, RecursiveMode::Recursive)
+        .map_err(|err| err.to_string())?;
+
+    let thread_app = app.clone();
+    let thread_root = watch_root;
+    thread::Builder::new()
+        .name("fs-watcher".to_string())
+        .spawn(move || run_debouncer(thread_app, thread_root, rx))
+        .map_err(|err| err.to_string())?;
+
+    watchers.insert(key, watcher);
+    log::info!("[watcher] watching {}", root.display());
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn desktop_watch_path(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+) -> Result<(), PathError> {
+    crate::origin::require_local(&webview, "watch files")?;
+    watch(&app, &path)
+}
+
+#[tauri::command]
+pub(crate) fn desktop_unwatch_path(
+    webview: tauri::Webview,
+    state: tauri::State<'_, WatcherState>,
+    path: String,
+) -> Result<bool, String> {
+    crate::origin::require_local(&webview, "stop watching files")?;
+    let key = PathBuf::from(path.trim()).to_string_lossy().to_string();
+    let removed = state
+        .watchers
+        .lock()
+        .expect("watcher state mutex")
+        .remove(&key)
+        .is_some();
+    if removed {
+        log::info!("[watcher] stopped watching {key}");
+    }
+    Ok(removed)
+}
@@ -0,0 +1,184 @@
+use std::sync::{Mutex, OnceLock};
+
+use tauri::Manager;
+
+use crate::{
+    events::{self, DesktopEvent, MenuAction},
+    splash,
+};
+
+type ReopenHook = Box<dyn Fn() + Send + Sync>;
+
+struct Registry {
+    /// The window with keyboard focus; `None` while the app is in the background.
+    focused: Option<String>,
+    /// Window labels, most recently focused first.
+    recent: Vec<String>,
+    /// Actions waiting for a window to be created.
+    pending: Vec<MenuAction>,
+}
+
+/// Which app window the user is working in, so menu, tray and global-shortcut
+/// actions reach that window instead of always the main one.
+pub(crate) struct WindowRegistry {
+    inner: Mutex<Registry>,
+    reopen: OnceLock<ReopenHook>,
+}
+
+impl Default for WindowRegistry {
+    fn default() -> Self {
+        Self {
+            // The main window takes focus as it opens; assume so until told otherwise.
+            inner: Mutex::new(Registry {
+                focused: Some("main".to_string()),
+                recent: Vec::new(),
+                pending: Vec::new(),
+            }),
+            reopen: OnceLock::new(),
+        }
+    }
+}
+
+/// The splash screen never handles actions.
+fn is_app_window(label: &str) -> bool {
+    label != splash::SPLASH_LABEL
+}
+
+/// `WindowEvent::Focused` handler.
+pub(crate) fn handle_focus_changed<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    label: &str,
+    focused: bool,
+) {
+    if !is_app_window(label) {
+        return;
+    }
+    let Some(state) = app.try_state::<WindowRegistry>() else {
+        return;
+    };
+    let mut registry = state.inner.lock().expect("window registry mutex");
+    if focused {
+        registry.focused = Some(label.to_string());
+        registry.recent.retain(|recent| recent != label);
+        registry.recent.insert(0, label.to_string());
+    } else if registry.focused.as_deref() == Some(label) {
+        registry.focused = None;
+    }
+}
+
+/// `WindowEvent::Destroyed` handler.
+pub(crate) fn forget<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: &str) {
+    let Some(state) = app.try_state::<WindowRegistry>() else {
+        return;
+    };
+    let mut registry = state.inner.lock().expect("window registry mutex");
+    registry.recent.retain(|recent| recent != label);
+    if registry.focused.as_deref() == Some(label) {
+        registry.focused = None;
+    }
+}
+
+/// Whether any app window has keyboard focus.
+pub(crate) fn is_app_focused<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    app.try_state::<WindowRegistry>().is_some_and(|state| {
+        state
+            .inner
+            .lock()
+            .expect("window registry mutex")
+            .focused
+            .is_some()
+    })
+}
+
+/// How to bring back the main window once every window was closed. Set during
+/// setup, where the concrete app handle is at hand.
+pub(crate) fn set_reopen_hook<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    hook: impl Fn() + Send + Sync + 'static,
+) {
+    if let Some(state) = app.try_state::<WindowRegistry>() {
+        let _ = state.reopen.set(Box::new(hook));
+    }
+}
+
+/// The focused window, else the one focused last, else the main window or any
+/// other open one.
+pub(crate) fn action_target<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<String> {
+    let windows = app.webview_windows();
+    let recent = app.try_state::<WindowRegistry>().and_then(|state| {
+        let registry = state.inner.lock().expect("window registry mutex");
+        registry
+            .recent
+            .iter()
+            .find(|label| windows.contains_key(label.as_str()))
+            .cloned()
+    });
+    recent
+        .or_else(|| windows.contains_key("main").then(|| "main".to_string()))
+        .or_else(|| {
+            windows
+                .keys()
+                .find(|label| is_app_window(label.as_str()))
+                .cloned()
+        })
+}
+
+/// Shows and focuses the window [`action_target`] picks, for tray and global
+/// shortcut actions that arrive while the app is in the background.
+pub(crate) fn show_action_target<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(window) = action_target(app).and_then(|label| app.get_webview_window(&label)) else {
+        return;
+    };
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+}
+
+/// Sends a menu action to the window the user is working in. Without any window
+/// the action waits until the main window has been reopened and loaded.
+pub(crate) fn dispatch_menu_action<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    action: MenuAction,
+) {
+    if let Some(label) = action_target(app) {
+        events::emit_to(app, label.as_str(), DesktopEvent::MenuAction(action));
+        return;
+    }
+    let Some(state) = app.try_state::<WindowRegistry>() else {
+        return;
+    };
+    log::info!("[window-registry] no window open; queueing {action:?}");
+    let reopening = {
+        let mut registry = state.inner.lock().expect("window registry mutex");
+        registry.pending.push(action);
+        registry.pending.len() > 1
+    };
+    if !reopening {
+        if let Some(reopen) = state.reopen.get() {
+            reopen();
+        }
+    }
+}
+
+/// Main-window page-load hook for actions queued while no window was open.
+pub(crate) fn flush_pending_actions<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(state) = app.try_state::<WindowRegistry>() else {
+        return;
+    };
+    let pending = std::mem::take(&mut state.inner.lock().expect("window registry mutex").pending);
+    for action in pending {
+        events::emit_to(app, "main", DesktopEvent::MenuAction(action));
+    }
+}
+
+/// Drops queued actions after the main window could not be reopened.
+pub(crate) fn discard_pending_actions<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(state) = app.try_state::<WindowRegistry>() {
+        state
+            .inner
+            .lock()
+            .expect("window registry mutex")
+            .pending
+            .clear();
+    }
+}
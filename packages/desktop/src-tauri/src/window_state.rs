@@ -0,0 +1,220 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, PhysicalPosition, PhysicalSize};
+
+use crate::profiles;
+
+const WINDOW_STATE_FILE_NAME: &str = "window-state.json";
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+const MIN_WINDOW_WIDTH: u32 = 400;
+const MIN_WINDOW_HEIGHT: u32 = 300;
+// How much of the title bar must be on a monitor for the saved position to be trusted.
+const MIN_VISIBLE_PX: i32 = 64;
+
+/// Last known geometry of a window, in physical pixels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    fullscreen: bool,
+    monitor: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct WindowStateStore {
+    windows: Mutex<HashMap<String, WindowGeometry>>,
+    save_generation: Mutex<u64>,
+}
+
+impl WindowStateStore {
+    pub(crate) fn load<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Self {
+        let windows = state_file_path(app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| serde_json::from_str::<HashMap<String, WindowGeometry>>(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            windows: Mutex::new(windows),
+            save_generation: Mutex::new(0),
+        }
+    }
+}
+
+fn state_file_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(profiles::scoped)
+        .map(|dir| dir.join(WINDOW_STATE_FILE_NAME))
+}
+
+fn write_to_disk<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> anyhow::Result<()> {
+    let Some(store) = app.try_state::<WindowStateStore>() else {
+        return Ok(());
+    };
+    let Some(path) = state_file_path(app) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let snapshot = store.windows.lock().expect("window state mutex").clone();
+    fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+    Ok(())
+}
+
+/// Captures the current geometry of `window` into the store.
+fn capture<R: tauri::Runtime>(window: &tauri::Window<R>) {
+    let Some(store) = window.app_handle().try_state::<WindowStateStore>() else {
+        return;
+    };
+    // Minimized windows report bogus positions (e.g. -32000 on Windows).
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    let mut windows = store.windows.lock().expect("window state mutex");
+    let label = window.label().to_string();
+
+    // Keep the restored (un-maximized) bounds so un-maximizing after relaunch
+    // returns to the size the user chose.
+    if maximized || fullscreen {
+        if let Some(existing) = windows.get_mut(&label) {
+            existing.maximized = maximized;
+            existing.fullscreen = fullscreen;
+            existing.monitor = monitor;
+            return;
+        }
+    }
+
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    windows.insert(
+        label,
+        WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized,
+            fullscreen,
+            monitor,
+        },
+    );
+}
+
+/// Saves immediately; used when the window is about to close.
+pub(crate) fn save_now<R: tauri::Runtime>(window: &tauri::Window<R>) {
+    capture(window);
+    if let Err(err) = write_to_disk(window.app_handle()) {
+        log::warn!("[window-state] failed to save: {err}");
+    }
+}
+
+/// Debounced save for bursts of move/resize events.
+pub(crate) fn schedule_save<R: tauri::Runtime>(window: &tauri::Window<R>) {
+    let app = window.app_handle().clone();
+    let Some(store) = app.try_state::<WindowStateStore>() else {
+        return;
+    };
+    capture(window);
+
+    let generation = {
+        let mut guard = store.save_generation.lock().expect("window state mutex");
+        *guard = guard.wrapping_add(1);
+        *guard
+    };
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        let Some(store) = app.try_state::<WindowStateStore>() else {
+            return;
+        };
+        if *store.save_generation.lock().expect("window state mutex") != generation {
+            return;
+        }
+        if let Err(err) = write_to_disk(&app) {
+            log::warn!("[window-state] failed to save: {err}");
+        }
+    });
+}
+
+fn is_visible_on(geometry: &WindowGeometry, monitor: &tauri::Monitor) -> bool {
+    let area = monitor.position();
+    let size = monitor.size();
+    let right = area.x.saturating_add(size.width as i32);
+    let bottom = area.y.saturating_add(size.height as i32);
+
+    // The top edge (title bar) must be reachable so the window can be dragged.
+    let visible_left = geometry.x.max(area.x);
+    let visible_right = geometry.x.saturating_add(geometry.width as i32).min(right);
+    visible_right - visible_left >= MIN_VISIBLE_PX && geometry.y >= area.y && geometry.y < bottom
+}
+
+/// Applies the saved geometry to a freshly built (still hidden) window.
+/// Falls back to the default centered placement when the saved monitor is gone.
+pub(crate) fn restore<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
+    let Some(store) = window.app_handle().try_state::<WindowStateStore>() else {
+        return;
+    };
+    let Some(geometry) = store
+        .windows
+        .lock()
+        .expect("window state mutex")
+        .get(window.label())
+        .cloned()
+    else {
+        return;
+    };
+
+    let monitors = window.available_monitors().unwrap_or_default();
+    let target = monitors
+        .iter()
+        .find(|m| geometry.monitor.is_some() && m.name() == geometry.monitor.as_ref())
+        .filter(|m| is_visible_on(&geometry, m))
+        .or_else(|| monitors.iter().find(|m| is_visible_on(&geometry, m)));
+
+    let width = geometry.width.max(MIN_WINDOW_WIDTH);
+    let height = geometry.height.max(MIN_WINDOW_HEIGHT);
+
+    match target {
+        Some(monitor) => {
+            let max = monitor.size();
+            let _ = window.set_size(PhysicalSize::new(
+                width.min(max.width),
+                height.min(max.height),
+            ));
+            let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        }
+        None => {
+            log::info!("[window-state] saved monitor unavailable; centering window");
+            if let Ok(Some(monitor)) = window.primary_monitor() {
+                let max = monitor.size();
+                let _ = window.set_size(PhysicalSize::new(
+                    width.min(max.width),
+                    height.min(max.height),
+                ));
+            }
+            let _ = window.center();
+        }
+    }
+
+    if geometry.fullscreen {
+        let _ = window.set_fullscreen(true);
+    } else if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
@@ -0,0 +1,65 @@
+use objc2_app_kit::NSWindow;
+use objc2_foundation::MainThreadMarker;
+use tauri::Manager;
+
+use crate::{profiles, settings};
+
+/// Settings-store key; `false` opts out of macOS native window tabbing. Read at
+/// startup, so a change applies on the next launch.
+pub(crate) const NATIVE_WINDOW_TABS_KEY: &str = "nativeWindowTabs";
+
+pub(crate) fn enabled<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    settings::get_setting(app, NATIVE_WINDOW_TABS_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true)
+}
+
+/// Windows only merge into tabs with windows of the same profile.
+fn tabbing_identifier<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> String {
+    format!(
+        "{}.{}",
+        app.config().identifier,
+        profiles::active().unwrap_or("default")
+    )
+}
+
+/// Lets AppKit offer tabbing (and the Window menu's tab items) only when enabled.
+/// Must run on the main thread, i.e. from `setup`.
+pub(crate) fn configure<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(mtm) = MainThreadMarker::new() {
+        NSWindow::setAllowsAutomaticWindowTabbing(enabled(app), mtm);
+    }
+}
+
+pub(crate) fn apply<'a, R: tauri::Runtime, M: Manager<R>>(
+    app: &tauri::AppHandle<R>,
+    builder: tauri::WebviewWindowBuilder<'a, R, M>,
+) -> tauri::WebviewWindowBuilder<'a, R, M> {
+    if enabled(app) {
+        builder.tabbing_identifier(&tabbing_identifier(app))
+    } else {
+        builder
+    }
+}
+
+/// Switches tabs in the focused window's tab group. Menu events arrive on the
+/// main thread, which AppKit requires.
+pub(crate) fn select_adjacent_tab<R: tauri::Runtime>(app: &tauri::AppHandle<R>, next: bool) {
+    let Some(window) = app
+        .webview_windows()
+        .into_values()
+        .find(|window| window.is_focused().unwrap_or(false))
+    else {
+        return;
+    };
+    let Ok(ns_window) = window.ns_window() else {
+        return;
+    };
+    // SAFETY: `ns_window` is the live NSWindow backing `window`; both actions accept a nil sender.
+    let ns_window = unsafe { &*(ns_window as *const NSWindow) };
+    if next {
+        ns_window.selectNextTab(None);
+    } else {
+        ns_window.selectPreviousTab(None);
+    }
+}
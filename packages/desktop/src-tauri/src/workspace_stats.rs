@@ -0,0 +1,343 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+};
+
+use serde::Serialize;
+
+use crate::{
+    path_policy::{self, PathError, PathErrorKind},
+    watcher::IgnoreFilter,
+};
+
+/// Entries kept in each "largest" list.
+const TOP_ENTRIES: usize = 20;
+/// Extensions listed, by total size.
+const TOP_EXTENSIONS: usize = 50;
+/// Directories deeper than this below the root are summed but not listed.
+const MAX_LISTED_DIR_DEPTH: usize = 3;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SizedEntry {
+    /// Relative to the workspace root, with `/` separators.
+    path: String,
+    size: u64,
+    /// Excluded by the workspace's gitignore.
+    ignored: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExtensionStats {
+    extension: String,
+    files: u64,
+    size: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceStats {
+    root: String,
+    /// Files the gitignore doesn't exclude, i.e. what an indexer would read.
+    file_count: u64,
+    total_size: u64,
+    ignored_file_count: u64,
+    ignored_size: u64,
+    /// Size of `.git`, counted in neither total.
+    git_dir_size: u64,
+    largest_files: Vec<SizedEntry>,
+    largest_directories: Vec<SizedEntry>,
+    /// Non-ignored files by extension, largest total first; `""` for none.
+    extensions: Vec<ExtensionStats>,
+    /// Entries that could not be read, e.g. for lack of permission.
+    unreadable: u64,
+}
+
+/// What one worker found; merged into `WorkspaceStats` at the end.
+#[derive(Default)]
+struct Tally {
+    file_count: u64,
+    total_size: u64,
+    ignored_file_count: u64,
+    ignored_size: u64,
+    git_dir_size: u64,
+    largest_files: BinaryHeap<Reverse<(u64, String, bool)>>,
+    directories: Vec<(u64, String, bool)>,
+    extensions: HashMap<String, ExtensionStats>,
+    unreadable: u64,
+}
+
+impl Tally {
+    fn add_file(&mut self, relative: &str, size: u64, ignored: bool) {
+        if ignored {
+            self.ignored_file_count += 1;
+            self.ignored_size += size;
+        } else {
+            self.file_count += 1;
+            self.total_size += size;
+            let extension = Path::new(relative)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let stats = self.extensions.entry(extension.clone()).or_default();
+            stats.extension = extension;
+            stats.files += 1;
+            stats.size += size;
+        }
+        push_capped(
+            &mut self.largest_files,
+            (size, relative.to_string(), ignored),
+        );
+    }
+
+    fn merge(&mut self, other: Tally) {
+        self.file_count += other.file_count;
+        self.total_size += other.total_size;
+        self.ignored_file_count += other.ignored_file_count;
+        self.ignored_size += other.ignored_size;
+        self.git_dir_size += other.git_dir_size;
+        self.unreadable += other.unreadable;
+        for Reverse(entry) in other.largest_files {
+            push_capped(&mut self.largest_files, entry);
+        }
+        self.directories.extend(other.directories);
+        for (extension, stats) in other.extensions {
+            let merged = self.extensions.entry(extension).or_default();
+            merged.extension = stats.extension;
+            merged.files += stats.files;
+            merged.size += stats.size;
+        }
+    }
+}
+
+fn push_capped(heap: &mut BinaryHeap<Reverse<(u64, String, bool)>>, entry: (u64, String, bool)) {
+    if heap.len() < TOP_ENTRIES {
+        heap.push(Reverse(entry));
+    } else if heap
+        .peek()
+        .is_some_and(|Reverse(smallest)| entry.0 > smallest.0)
+    {
+        heap.pop();
+        heap.push(Reverse(entry));
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Plain total size below `dir`, for `.git`.
+fn tree_size(dir: &Path, unreadable: &mut u64) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        *unreadable += 1;
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => tree_size(&entry.path(), unreadable),
+            Ok(file_type) if file_type.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Total size below `dir`, recorded into `tally`. Once a directory is ignored,
+/// everything below it is too, so the gitignore is no longer consulted.
+fn walk(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    ignored: bool,
+    filter: &IgnoreFilter,
+    tally: &mut Tally,
+) -> u64 {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            tally.unreadable += 1;
+            return 0;
+        }
+    };
+    let mut size = 0;
+    for entry in entries {
+        let Ok(entry) = entry else {
+            tally.unreadable += 1;
+            continue;
+        };
+        let path = entry.path();
+        // Symlinks are not followed, so nothing is counted twice.
+        let Ok(file_type) = entry.file_type() else {
+            tally.unreadable += 1;
+            continue;
+        };
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() && entry.file_name() == ".git" {
+            tally.git_dir_size += tree_size(&path, &mut tally.unreadable);
+            continue;
+        }
+        let entry_ignored = ignored || filter.is_ignored(&path);
+        if file_type.is_dir() {
+            let dir_size = walk(root, &path, depth + 1, entry_ignored, filter, tally);
+            if depth < MAX_LISTED_DIR_DEPTH {
+                tally
+                    .directories
+                    .push((dir_size, relative_path(root, &path), entry_ignored));
+            }
+            size += dir_size;
+        } else if let Ok(metadata) = entry.metadata() {
+            let file_size = metadata.len();
+            tally.add_file(&relative_path(root, &path), file_size, entry_ignored);
+            size += file_size;
+        } else {
+            tally.unreadable += 1;
+        }
+    }
+    size
+}
+
+/// Walks `root` with one worker per core, each taking top-level entries from a
+/// shared queue, so one huge directory doesn't hold up the rest.
+fn collect(root: &Path) -> Tally {
+    let mut tally = Tally::default();
+    let top_level: Vec<PathBuf> = match fs::read_dir(root) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => {
+            tally.unreadable += 1;
+            return tally;
+        }
+    };
+    let queue = Mutex::new(top_level);
+    let workers = thread::available_parallelism().map_or(4, |count| count.get());
+
+    let partials: Vec<Tally> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    // `git2::Repository` can't be shared between threads.
+                    let filter = IgnoreFilter::new(root);
+                    let mut tally = Tally::default();
+                    loop {
+                        let Some(path) = queue.lock().expect("workspace stats queue").pop() else {
+                            break;
+                        };
+                        walk_top_level(root, &path, &filter, &mut tally);
+                    }
+                    tally
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    });
+    for partial in partials {
+        tally.merge(partial);
+    }
+    tally
+}
+
+fn walk_top_level(root: &Path, path: &Path, filter: &IgnoreFilter, tally: &mut Tally) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        tally.unreadable += 1;
+        return;
+    };
+    if metadata.file_type().is_symlink() {
+        return;
+    }
+    if metadata.is_dir() && path.file_name().is_some_and(|name| name == ".git") {
+        tally.git_dir_size += tree_size(path, &mut tally.unreadable);
+        return;
+    }
+    let ignored = filter.is_ignored(path);
+    if metadata.is_dir() {
+        let size = walk(root, path, 1, ignored, filter, tally);
+        tally
+            .directories
+            .push((size, relative_path(root, path), ignored));
+    } else {
+        tally.add_file(&relative_path(root, path), metadata.len(), ignored);
+    }
+}
+
+fn workspace_stats(root: &Path) -> WorkspaceStats {
+    let tally = collect(root);
+
+    let largest_files = tally
+        .largest_files
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size, path, ignored))| SizedEntry {
+            path,
+            size,
+            ignored,
+        })
+        .collect();
+    let mut directories = tally.directories;
+    directories.sort_by_key(|entry| Reverse(entry.0));
+    let largest_directories = directories
+        .into_iter()
+        .take(TOP_ENTRIES)
+        .map(|(size, path, ignored)| SizedEntry {
+            path,
+            size,
+            ignored,
+        })
+        .collect();
+    let mut extensions: Vec<ExtensionStats> = tally.extensions.into_values().collect();
+    extensions.sort_by_key(|stats| Reverse(stats.size));
+    extensions.truncate(TOP_EXTENSIONS);
+
+    WorkspaceStats {
+        root: root.to_string_lossy().to_string(),
+        file_count: tally.file_count,
+        total_size: tally.total_size,
+        ignored_file_count: tally.ignored_file_count,
+        ignored_size: tally.ignored_size,
+        git_dir_size: tally.git_dir_size,
+        largest_files,
+        largest_directories,
+        extensions,
+        unreadable: tally.unreadable,
+    }
+}
+
+/// Disk usage of a workspace, split by what its gitignore excludes, so the UI can
+/// warn before indexing a huge repository and point at what takes the space.
+#[tauri::command]
+pub(crate) async fn desktop_workspace_stats(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    path: String,
+) -> Result<WorkspaceStats, PathError> {
+    crate::origin::require_local(&webview, "read workspace stats")?;
+    let root = path_policy::check(&app, "workspace stats", &path)?;
+    if !root.is_dir() {
+        return Err(PathError::new(
+            PathErrorKind::InvalidPath,
+            format!("Not a directory: {}", path.trim()),
+        ));
+    }
+    let started = std::time::Instant::now();
+    let stats = tauri::async_runtime::spawn_blocking(move || workspace_stats(&root))
+        .await
+        .map_err(|err| PathError::new(PathErrorKind::Io, err.to_string()))?;
+    log::info!(
+        "[workspace-stats] {} files, {} bytes in {:?}",
+        stats.file_count + stats.ignored_file_count,
+        stats.total_size + stats.ignored_size,
+        started.elapsed()
+    );
+    Ok(stats)
+}
@@ -0,0 +1,111 @@
+use serde_json::{Map, Value};
+use tauri::Manager;
+
+use crate::settings;
+
+/// Settings-store key holding `{ "<window label>": factor }`.
+pub(crate) const ZOOM_FACTORS_KEY: &str = "zoomFactors";
+
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+const ZOOM_STEP: f64 = 0.1;
+
+fn clamp_zoom(factor: f64) -> f64 {
+    if !factor.is_finite() {
+        return 1.0;
+    }
+    // Round to the step so repeated menu presses land on tidy values.
+    ((factor / ZOOM_STEP).round() * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+fn stored_factors<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Map<String, Value> {
+    match settings::get_setting(app, ZOOM_FACTORS_KEY) {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn zoom_for<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: &str) -> f64 {
+    stored_factors(app)
+        .get(label)
+        .and_then(Value::as_f64)
+        .map(clamp_zoom)
+        .unwrap_or(1.0)
+}
+
+/// Persists the factor; the settings side effect applies it to the window.
+fn store_zoom<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    label: &str,
+    factor: f64,
+) -> Result<f64, String> {
+    let factor = clamp_zoom(factor);
+    let mut factors = stored_factors(app);
+    factors.insert(label.to_string(), Value::from(factor));
+    settings::set_setting(app, ZOOM_FACTORS_KEY, Value::Object(factors), None)?;
+    Ok(factor)
+}
+
+/// Applies stored zoom factors to every open window they name.
+pub(crate) fn apply_zoom_factors<R: tauri::Runtime>(app: &tauri::AppHandle<R>, value: &Value) {
+    let Value::Object(factors) = value else {
+        return;
+    };
+    for (label, factor) in factors {
+        let (Some(window), Some(factor)) = (app.get_webview_window(label), factor.as_f64()) else {
+            continue;
+        };
+        if let Err(err) = window.set_zoom(clamp_zoom(factor)) {
+            log::warn!("[zoom] failed to set zoom on {label}: {err}");
+        }
+    }
+}
+
+/// Re-applies a window's saved zoom right after it is created.
+pub(crate) fn restore_zoom<R: tauri::Runtime>(window: &tauri::WebviewWindow<R>) {
+    let factor = zoom_for(window.app_handle(), window.label());
+    if factor != 1.0 {
+        let _ = window.set_zoom(factor);
+    }
+}
+
+/// Adjusts the focused window's zoom; `delta` of `None` resets to 100%.
+fn step_zoom<R: tauri::Runtime>(app: &tauri::AppHandle<R>, delta: Option<f64>) {
+    let label = app
+        .webview_windows()
+        .into_iter()
+        .find(|(_, window)| window.is_focused().unwrap_or(false))
+        .map(|(label, _)| label)
+        .unwrap_or_else(|| "main".to_string());
+    let factor = match delta {
+        Some(delta) => zoom_for(app, &label) + delta,
+        None => 1.0,
+    };
+    if let Err(err) = store_zoom(app, &label, factor) {
+        log::warn!("[zoom] failed to persist zoom: {err}");
+    }
+}
+
+pub(crate) fn zoom_in<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    step_zoom(app, Some(ZOOM_STEP));
+}
+
+pub(crate) fn zoom_out<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    step_zoom(app, Some(-ZOOM_STEP));
+}
+
+pub(crate) fn reset_zoom<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    step_zoom(app, None);
+}
+
+/// Sets the zoom of the calling window (or `label`), returning the applied factor.
+#[tauri::command]
+pub(crate) fn desktop_set_zoom(
+    app: tauri::AppHandle,
+    webview: tauri::Webview,
+    factor: f64,
+    label: Option<String>,
+) -> Result<f64, String> {
+    let label = label.unwrap_or_else(|| webview.label().to_string());
+    store_zoom(&app, &label, factor)
+}
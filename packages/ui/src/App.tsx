@@ -25,6 +25,7 @@ import { useFontPreferences } from '@/hooks/useFontPreferences';
 import { CODE_FONT_OPTION_MAP, DEFAULT_MONO_FONT, DEFAULT_UI_FONT, UI_FONT_OPTION_MAP } from '@/lib/fontOptions';
 import { ConfigUpdateOverlay } from '@/components/ui/ConfigUpdateOverlay';
 import { AboutDialog } from '@/components/ui/AboutDialog';
+import { DesktopReconnectBanner } from '@/components/desktop/DesktopReconnectBanner';
 import { RuntimeAPIProvider } from '@/contexts/RuntimeAPIProvider';
 import { registerRuntimeAPIs } from '@/contexts/runtimeAPIRegistry';
 import { useUIStore } from '@/stores/useUIStore';
@@ -291,6 +292,7 @@ function App({ apis }: AppProps) {
             <div className="h-full text-foreground bg-background">
               <MainLayout />
               <Toaster />
+              <DesktopReconnectBanner />
               <ConfigUpdateOverlay />
               <AboutDialogWrapper />
               {showMemoryDebug && (
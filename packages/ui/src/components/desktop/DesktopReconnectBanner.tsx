@@ -0,0 +1,76 @@
+import React from 'react';
+import { Button } from '@/components/ui/button';
+import type { DesktopEvent } from '@/lib/desktop-events/DesktopEvent';
+import type { ReconnectBannerEvent } from '@/lib/desktop-events/ReconnectBannerEvent';
+
+const RECONNECT_BANNER_EVENT: DesktopEvent['event'] = 'openchamber:reconnect-banner';
+
+type TauriEventApi = {
+  listen?: (
+    event: string,
+    handler: (evt: { payload?: unknown }) => void
+  ) => Promise<() => void>;
+};
+
+type TauriGlobal = {
+  event?: TauriEventApi;
+};
+
+/** Shown by the desktop shell while the remote server it is connected to is down. */
+export const DesktopReconnectBanner: React.FC = () => {
+  const [banner, setBanner] = React.useState<ReconnectBannerEvent | null>(null);
+
+  React.useEffect(() => {
+    if (typeof window === 'undefined') return;
+    const tauri = (window as unknown as { __TAURI__?: TauriGlobal }).__TAURI__;
+    const listen = tauri?.event?.listen;
+    if (typeof listen !== 'function') return;
+
+    let unlisten: null | (() => void | Promise<void>) = null;
+    let disposed = false;
+
+    listen(RECONNECT_BANNER_EVENT, (evt) => {
+      const payload = evt?.payload as ReconnectBannerEvent | undefined;
+      if (!payload || typeof payload.visible !== 'boolean') return;
+      setBanner(payload.visible ? payload : null);
+    })
+      .then((fn) => {
+        if (disposed) {
+          void fn();
+          return;
+        }
+        unlisten = fn;
+      })
+      .catch(() => {
+        // ignore
+      });
+
+    return () => {
+      disposed = true;
+      try {
+        void unlisten?.();
+      } catch {
+        // ignore
+      }
+    };
+  }, []);
+
+  if (!banner) return null;
+
+  return (
+    <div
+      role="alert"
+      className="fixed inset-x-0 top-0 z-50 flex items-center justify-center gap-3 bg-destructive px-3 py-2 typography-ui-label text-white"
+    >
+      <span>{banner.message}</span>
+      <Button
+        size="sm"
+        variant="outline"
+        className="h-6 border-white bg-transparent px-2.5 text-white hover:bg-white/10 hover:text-white"
+        onClick={() => window.location.reload()}
+      >
+        {banner.reconnectLabel}
+      </Button>
+    </div>
+  );
+};
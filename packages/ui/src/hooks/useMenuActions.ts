@@ -9,9 +9,11 @@ import { isTauriShell } from '@/lib/desktop';
 import { useFileSystemAccess } from '@/hooks/useFileSystemAccess';
 import { createWorktreeSession } from '@/lib/worktreeSessionCreator';
 import { showOpenCodeStatus } from '@/lib/openCodeStatus';
+import type { DesktopEvent } from '@/lib/desktop-events/DesktopEvent';
+import type { MenuAction } from '@/lib/desktop-events/MenuAction';
 
-const MENU_ACTION_EVENT = 'openchamber:menu-action';
-const CHECK_FOR_UPDATES_EVENT = 'openchamber:check-for-updates';
+const MENU_ACTION_EVENT: DesktopEvent['event'] = 'openchamber:menu-action';
+const CHECK_FOR_UPDATES_EVENT: DesktopEvent['event'] = 'openchamber:check-for-updates';
 
 type TauriEventApi = {
   listen?: (
@@ -24,25 +26,6 @@ type TauriGlobal = {
   event?: TauriEventApi;
 };
 
-type MenuAction =
-  | 'about'
-  | 'settings'
-  | 'command-palette'
-  | 'new-session'
-  | 'new-worktree-session'
-  | 'change-workspace'
-  | 'open-git-tab'
-  | 'open-diff-tab'
-  | 'open-files-tab'
-  | 'open-terminal-tab'
-  | 'theme-light'
-  | 'theme-dark'
-  | 'theme-system'
-  | 'toggle-sidebar'
-  | 'toggle-memory-debug'
-  | 'help-dialog'
-  | 'download-logs';
-
 export const useMenuActions = (
   onToggleMemoryDebug?: () => void
 ) => {
@@ -199,17 +182,6 @@ export const useMenuActions = (
     ]
   );
 
-  React.useEffect(() => {
-    const handleMenuAction = (event: Event) => {
-      const action = (event as CustomEvent<MenuAction>).detail;
-      if (!action) return;
-      handleAction(action);
-    };
-
-    window.addEventListener(MENU_ACTION_EVENT, handleMenuAction);
-    return () => window.removeEventListener(MENU_ACTION_EVENT, handleMenuAction);
-  }, [handleAction]);
-
   React.useEffect(() => {
     if (typeof window === 'undefined') return;
     const tauri = (window as unknown as { __TAURI__?: TauriGlobal }).__TAURI__;
@@ -219,7 +191,7 @@ export const useMenuActions = (
     let unlistenMenu: null | (() => void | Promise<void>) = null;
     let unlistenUpdate: null | (() => void | Promise<void>) = null;
 
-    listen('openchamber:menu-action', (evt) => {
+    listen(MENU_ACTION_EVENT, (evt) => {
       const action = evt?.payload;
       if (typeof action !== 'string') return;
       handleAction(action as MenuAction);
@@ -231,7 +203,7 @@ export const useMenuActions = (
         // ignore
       });
 
-    listen('openchamber:check-for-updates', () => {
+    listen(CHECK_FOR_UPDATES_EVENT, () => {
       window.dispatchEvent(new Event(CHECK_FOR_UPDATES_EVENT));
     })
       .then((fn) => {
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ArchiveOperation } from "./ArchiveOperation";
+
+export type ArchiveProgressEvent = { archive: string, operation: ArchiveOperation, 
+/**
+ * The entry being read or written.
+ */
+entry: string | null, 
+/**
+ * Bytes so far, of the uncompressed contents except when extracting a
+ * `.tar.gz`, which is only measured by how much of the archive was read.
+ */
+processed: number, total: number | null, 
+/**
+ * Sent once at the end, whether the operation succeeded or not.
+ */
+done: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BackupInfo } from "./BackupInfo";
+
+/**
+ * Emitted as `openchamber:backup` after every backup, successful or not.
+ */
+export type BackupEvent = { backup: BackupInfo | null, error: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BackupReason } from "./BackupReason";
+
+export type BackupInfo = { path: string, 
+/**
+ * Milliseconds since the Unix epoch.
+ */
+createdAt: number, size: number, reason: BackupReason | null, };
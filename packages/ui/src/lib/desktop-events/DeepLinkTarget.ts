@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Navigation target decoded from an `openchamber://` URL.
+ */
+export type DeepLinkTarget = { "kind": "session", id: string, } | { "kind": "workspace", path: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MissingDependency } from "./MissingDependency";
+
+export type DependencyMissingEvent = { missing: Array<MissingDependency>, searchedPath: Array<string>, };
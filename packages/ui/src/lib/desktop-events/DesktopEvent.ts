@@ -0,0 +1,45 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ArchiveProgressEvent } from "./ArchiveProgressEvent";
+import type { BackupEvent } from "./BackupEvent";
+import type { DeepLinkTarget } from "./DeepLinkTarget";
+import type { DependencyMissingEvent } from "./DependencyMissingEvent";
+import type { DesktopUpdateInfo } from "./DesktopUpdateInfo";
+import type { FilesDroppedEvent } from "./FilesDroppedEvent";
+import type { FsChangedEvent } from "./FsChangedEvent";
+import type { GitStatusSummary } from "./GitStatusSummary";
+import type { GitTransferEvent } from "./GitTransferEvent";
+import type { HealthProgressEvent } from "./HealthProgressEvent";
+import type { IdleSnapshot } from "./IdleSnapshot";
+import type { LanAccessInfo } from "./LanAccessInfo";
+import type { LaunchAction } from "./LaunchAction";
+import type { MenuAction } from "./MenuAction";
+import type { NetworkInfo } from "./NetworkInfo";
+import type { OpenWorkspaceEvent } from "./OpenWorkspaceEvent";
+import type { OsThemeChangedEvent } from "./OsThemeChangedEvent";
+import type { PowerInfo } from "./PowerInfo";
+import type { ProcessMetrics } from "./ProcessMetrics";
+import type { QuitCheckEvent } from "./QuitCheckEvent";
+import type { ReconnectBannerEvent } from "./ReconnectBannerEvent";
+import type { SearchResultsEvent } from "./SearchResultsEvent";
+import type { ServerConnectedEvent } from "./ServerConnectedEvent";
+import type { ServerHealthEvent } from "./ServerHealthEvent";
+import type { SettingsChangedEvent } from "./SettingsChangedEvent";
+import type { SidecarCrashedEvent } from "./SidecarCrashedEvent";
+import type { SidecarPortDecision } from "./SidecarPortDecision";
+import type { SidecarResourceIncident } from "./SidecarResourceIncident";
+import type { SleepPreventionEvent } from "./SleepPreventionEvent";
+import type { TunnelStatusEvent } from "./TunnelStatusEvent";
+import type { UpdateChannel } from "./UpdateChannel";
+import type { UpdateProgressEvent } from "./UpdateProgressEvent";
+import type { VersionMismatch } from "./VersionMismatch";
+import type { WorkspaceTrustEvent } from "./WorkspaceTrustEvent";
+import type { WorktreePruneReport } from "./WorktreePruneReport";
+
+/**
+ * Every event the desktop shell sends to the frontend, keyed by its Tauri event
+ * name. `cargo test` exports the TypeScript side to `packages/ui`, so listeners
+ * can narrow the payload with `Extract<DesktopEvent, { event: ... }>`.
+ *
+ * PTY output is not listed: it goes to a per-session event name.
+ */
+export type DesktopEvent = { "event": "openchamber:menu-action", "payload": MenuAction } | { "event": "openchamber:check-for-updates" } | { "event": "openchamber:update-available", "payload": DesktopUpdateInfo } | { "event": "openchamber:update-progress", "payload": UpdateProgressEvent } | { "event": "openchamber:update-channel-changed", "payload": UpdateChannel } | { "event": "openchamber:health-progress", "payload": HealthProgressEvent } | { "event": "openchamber:server-info", "payload": SidecarPortDecision } | { "event": "openchamber:server-health", "payload": ServerHealthEvent } | { "event": "openchamber:reconnect-banner", "payload": ReconnectBannerEvent } | { "event": "openchamber:server-connected", "payload": ServerConnectedEvent } | { "event": "openchamber:version-mismatch", "payload": VersionMismatch } | { "event": "openchamber:sidecar-crashed", "payload": SidecarCrashedEvent } | { "event": "openchamber:sidecar-restarted", "payload": string } | { "event": "openchamber:sidecar-resource-warning", "payload": SidecarResourceIncident } | { "event": "openchamber:lan-access", "payload": LanAccessInfo } | { "event": "openchamber:tunnel-status", "payload": TunnelStatusEvent } | { "event": "openchamber:dependency-missing", "payload": DependencyMissingEvent } | { "event": "openchamber:deep-link", "payload": DeepLinkTarget } | { "event": "openchamber:launch-action", "payload": LaunchAction } | { "event": "openchamber:workspace-trust", "payload": WorkspaceTrustEvent } | { "event": "openchamber:open-workspace", "payload": OpenWorkspaceEvent } | { "event": "openchamber:files-dropped", "payload": FilesDroppedEvent } | { "event": "openchamber:fs-changed", "payload": FsChangedEvent } | { "event": "openchamber:search-results", "payload": SearchResultsEvent } | { "event": "openchamber:archive-progress", "payload": ArchiveProgressEvent } | { "event": "openchamber:backup", "payload": BackupEvent } | { "event": "openchamber:git-status", "payload": GitStatusSummary } | { "event": "openchamber:git-transfer", "payload": GitTransferEvent } | { "event": "openchamber:worktree-prune", "payload": WorktreePruneReport } | { "event": "openchamber:settings-changed", "payload": SettingsChangedEvent } | { "event": "openchamber:os-theme-changed", "payload": OsThemeChangedEvent } | { "event": "openchamber:quit-check", "payload": QuitCheckEvent } | { "event": "openchamber:sleep-prevention", "payload": SleepPreventionEvent } | { "event": "openchamber:idle-state", "payload": IdleSnapshot } | { "event": "openchamber:power", "payload": PowerInfo } | { "event": "openchamber:network", "payload": NetworkInfo } | { "event": "openchamber:process-metrics", "payload": ProcessMetrics };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UpdateChannel } from "./UpdateChannel";
+import type { UpdatePatchInfo } from "./UpdatePatchInfo";
+
+export type DesktopUpdateInfo = { available: boolean, channel: UpdateChannel, currentVersion: string, version: string | null, body: string | null, date: string | null, 
+/**
+ * Set when the update can be downloaded as a patch against this version.
+ */
+patch: UpdatePatchInfo | null, };
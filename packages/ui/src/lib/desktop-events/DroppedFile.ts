@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContentEncoding } from "./ContentEncoding";
+
+export type DroppedFile = { path: string, name: string, 
+/**
+ * Path relative to the active workspace, when the file lives inside it.
+ */
+relativePath: string | null, isDir: boolean, size: number, content: string | null, encoding: ContentEncoding | null, 
+/**
+ * Why `content` is missing for a regular file (too large, unreadable, ...).
+ */
+skippedReason: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DroppedFile } from "./DroppedFile";
+
+export type FilesDroppedEvent = { files: Array<DroppedFile>, workspace: string | null, x: number, y: number, 
+/**
+ * More paths were dropped than `MAX_DROPPED_FILES`; the rest were ignored.
+ */
+truncated: boolean, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FsChangeKind } from "./FsChangeKind";
+
+export type FsChange = { path: string, relativePath: string, kind: FsChangeKind, };
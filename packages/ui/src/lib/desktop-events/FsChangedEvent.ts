@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FsChange } from "./FsChange";
+
+export type FsChangedEvent = { root: string, changes: Array<FsChange>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GitStatusSummary = { repoPath: string, branch: string | null, detached: boolean, upstream: string | null, ahead: number, behind: number, staged: number, unstaged: number, untracked: number, conflicted: number, hasStaged: boolean, hasUnstaged: boolean, hasUntracked: boolean, hasConflicts: boolean, 
+/**
+ * Tracked changes exist (staged, unstaged or conflicted); untracked files alone don't count.
+ */
+isDirty: boolean, };
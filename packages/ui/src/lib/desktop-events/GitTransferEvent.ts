@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GitTransferOperation } from "./GitTransferOperation";
+
+/**
+ * Progress of a fetch, pull or push, as git reports it on stderr.
+ */
+export type GitTransferEvent = { repo: string, operation: GitTransferOperation, 
+/**
+ * git's label for the current step, e.g. `Receiving objects`.
+ */
+phase: string | null, percent: number | null, current: number | null, total: number | null, 
+/**
+ * Sent once at the end, whether the transfer succeeded or not.
+ */
+done: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthStage } from "./HealthStage";
+
+/**
+ * Emitted as `openchamber:health-progress` whenever a startup probe changes stage.
+ */
+export type HealthProgressEvent = { url: string, stage: HealthStage, elapsedMs: number, timeoutMs: number, serverVersion: string | null, openCodeReady: boolean | null, openCodeError: string | null, };
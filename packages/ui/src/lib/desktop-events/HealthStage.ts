@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How far a startup `/health` probe has got.
+ */
+export type HealthStage = "waiting" | "starting" | "ready" | "timed-out";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type IdleSnapshot = { userIdle: boolean, windowHidden: boolean, 
+/**
+ * Seconds since the last input anywhere on the system; `None` where the
+ * platform does not tell.
+ */
+idleSecs: number | null, 
+/**
+ * Background polling is slowed down.
+ */
+throttled: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LanAccessInfo = { enabled: boolean, 
+/**
+ * Address other devices should open, e.g. `http://192.168.1.20:57123`.
+ */
+url: string | null, token: string | null, bindHost: string | null, warning: string | null, };
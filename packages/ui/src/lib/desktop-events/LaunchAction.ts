@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Something the frontend should do on behalf of the command line.
+ */
+export type LaunchAction = { "kind": "openWorkspace", path: string, } | { "kind": "newSession" };
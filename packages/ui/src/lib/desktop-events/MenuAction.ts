@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Commands from the native menu, tray and global shortcuts.
+ */
+export type MenuAction = "about" | "settings" | "command-palette" | "new-session" | "new-worktree-session" | "change-workspace" | "open-git-tab" | "open-diff-tab" | "open-files-tab" | "open-terminal-tab" | "theme-light" | "theme-dark" | "theme-system" | "toggle-sidebar" | "find" | "toggle-memory-debug" | "help-dialog" | "download-logs";
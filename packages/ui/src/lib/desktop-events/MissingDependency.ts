@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type MissingDependency = { name: string, 
+/**
+ * Whether the local server cannot work without it.
+ */
+required: boolean, guidance: string, };
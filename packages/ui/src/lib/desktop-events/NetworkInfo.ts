@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NetworkInfo = { online: boolean, 
+/**
+ * `None` when the platform does not report whether the connection is metered.
+ */
+metered: boolean | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PowerStatus } from "./PowerStatus";
+
+export type PowerInfo = { status: PowerStatus, 
+/**
+ * `None` on machines without a battery.
+ */
+batteryPercent: number | null, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProcessUsage } from "./ProcessUsage";
+
+export type ProcessMetrics = { app: ProcessUsage, 
+/**
+ * `None` on macOS, where WebKit's helpers are XPC services outside our
+ * process tree.
+ */
+webview: ProcessUsage | null, 
+/**
+ * The local server and everything it started. `None` without a sidecar.
+ */
+sidecar: ProcessUsage | null, totalMemoryBytes: number, cpuCount: number, };
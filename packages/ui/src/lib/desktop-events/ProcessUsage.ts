@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProcessUsage = { processCount: number, rssBytes: number, 
+/**
+ * Summed over processes; 100 is one fully busy core.
+ */
+cpuPercent: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sent to the main window as `openchamber:reconnect-banner` when a remote server
+ * goes down, and with `visible: false` once it answers again or is left.
+ */
+export type ReconnectBannerEvent = { url: string, visible: boolean, message: string, 
+/**
+ * Label of the button that reloads the window.
+ */
+reconnectLabel: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchRange } from "./SearchRange";
+
+export type SearchMatch = { 
+/**
+ * Relative to the workspace, with `/` separators.
+ */
+path: string, 
+/**
+ * 1-based.
+ */
+line: number, 
+/**
+ * 1-based, in UTF-16 code units.
+ */
+column: number, preview: string, ranges: Array<SearchRange>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A match range within `SearchMatch::preview`, in UTF-16 code units so it
+ * indexes JS strings.
+ */
+export type SearchRange = { start: number, end: number, };
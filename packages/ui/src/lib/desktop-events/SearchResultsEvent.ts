@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchMatch } from "./SearchMatch";
+
+/**
+ * A batch of results for `search_id`. The last event of a search has `done` set,
+ * also when it was cancelled.
+ */
+export type SearchResultsEvent = { searchId: string, matches: Array<SearchMatch>, done: boolean, cancelled: boolean, 
+/**
+ * The match limit was reached before every file was searched.
+ */
+truncated: boolean, filesSearched: number, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ServerHealthStatus } from "./ServerHealthStatus";
+
+export type ServerHealthEvent = { url: string, status: ServerHealthStatus, latencyMs: number | null, consecutiveFailures: number, isRemote: boolean, };
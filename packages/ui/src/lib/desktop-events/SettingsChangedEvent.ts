@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type SettingsChangedEvent = { key: string, value: JsonValue, 
+/**
+ * Label of the webview that made the change, so it can skip its own echo.
+ */
+source: string | null, };
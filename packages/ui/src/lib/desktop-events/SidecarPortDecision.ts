@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SidecarPortSource } from "./SidecarPortSource";
+import type { SkippedPort } from "./SkippedPort";
+
+/**
+ * How the local server port was chosen, emitted as `openchamber:server-info`.
+ */
+export type SidecarPortDecision = { url: string, port: number, source: SidecarPortSource, preferredPort: number | null, 
+/**
+ * An already running OpenChamber server was reused instead of spawning one.
+ */
+adopted: boolean, skipped: Array<SkippedPort>, };
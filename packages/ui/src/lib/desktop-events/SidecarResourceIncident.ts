@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ResourceKind } from "./ResourceKind";
+
+export type SidecarResourceIncident = { at: string, kind: ResourceKind, rssBytes: number, cpuPercent: number, 
+/**
+ * The limit that was exceeded, in bytes or percent.
+ */
+limit: number, samples: number, restarted: boolean, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StaleReason } from "./StaleReason";
+
+export type StaleWorktree = { path: string, branch: string | null, reasons: Array<StaleReason>, 
+/**
+ * Why the worktree stays despite being stale, e.g. uncommitted changes or a
+ * failed removal.
+ */
+keptBecause: string | null, removed: boolean, };
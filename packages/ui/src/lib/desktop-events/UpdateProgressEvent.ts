@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateProgressEvent = { "event": "Started", "data": { contentLength: number | null, } } | { "event": "Progress", "data": { chunkLength: number, downloaded: number, total: number | null, } } | { "event": "Finished" } | { "event": "Cancelled" };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type VersionMismatch = { url: string, serverVersion: string | null, serverApiVersion: number, desktopVersion: string, supportedApiVersions: [number, number], };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StaleWorktree } from "./StaleWorktree";
+
+export type WorktreePruneReport = { repo: string, dryRun: boolean, 
+/**
+ * The branch merges were checked against.
+ */
+baseBranch: string | null, worktrees: Array<StaleWorktree>, };
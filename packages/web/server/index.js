@@ -30,6 +30,8 @@ const MODELS_METADATA_CACHE_TTL = 5 * 60 * 1000;
 const CLIENT_RELOAD_DELAY_MS = 800;
 const OPEN_CODE_READY_GRACE_MS = 12000;
 const LONG_REQUEST_TIMEOUT_MS = 4 * 60 * 1000;
+// Bumped on breaking changes to the HTTP API; desktop clients check it via /version.
+const SERVER_API_VERSION = 1;
 const fsPromises = fs.promises;
 const DEFAULT_FILE_SEARCH_LIMIT = 60;
 const MAX_FILE_SEARCH_LIMIT = 400;
@@ -3719,6 +3721,14 @@ async function main(options = {}) {
     });
   });
 
+  app.get("/version", async (req, res) => {
+    const { getCurrentVersion } = await import("./lib/package-manager.js");
+    res.json({
+      version: getCurrentVersion(),
+      apiVersion: SERVER_API_VERSION,
+    });
+  });
+
   app.use((req, res, next) => {
     if (
       req.path.startsWith("/api/config/agents") ||